@@ -0,0 +1,261 @@
+//! Compares the core collections with their std counterparts. Run with
+//! `cargo bench --features bench`, or pass a filter like `-- dequeue` to run
+//! one group only.
+
+use std::{
+    collections::{BTreeSet, LinkedList, VecDeque},
+    hint::black_box,
+};
+
+use collections_rust::{BinaryTree, Dequeue, Queue, Vector};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rand::{rngs::SmallRng, seq::SliceRandom, SeedableRng};
+
+/// Number of values stored in each benchmark, from fitting in L1 to well
+/// past the last level cache.
+const SIZES: [usize; 3] = [1_000, 100_000, 1_000_000];
+
+/// Values `0..len` in an order that's the same on every run.
+fn shuffled(len: usize) -> Vec<u64> {
+    let mut values: Vec<u64> = (0..len as u64).collect();
+    values.shuffle(&mut SmallRng::seed_from_u64(len as u64));
+
+    values
+}
+
+fn vector(c: &mut Criterion) {
+    let mut group = c.benchmark_group("vector");
+
+    for len in SIZES {
+        group.throughput(Throughput::Elements(len as u64));
+
+        group.bench_with_input(BenchmarkId::new("Vector/push", len), &len, |b, &len| {
+            b.iter(|| {
+                let mut vector = Vector::new();
+                (0..len as u64).for_each(|value| vector.push(value));
+                vector
+            });
+        });
+
+        group.bench_with_input(BenchmarkId::new("Vec/push", len), &len, |b, &len| {
+            b.iter(|| {
+                let mut vec = Vec::new();
+                (0..len as u64).for_each(|value| vec.push(value));
+                vec
+            });
+        });
+
+        let mut vector = Vector::new();
+        (0..len as u64).for_each(|value| vector.push(value));
+        let vec: Vec<u64> = (0..len as u64).collect();
+
+        group.bench_with_input(BenchmarkId::new("Vector/sum", len), &vector, |b, vector| {
+            b.iter(|| black_box(vector).iter().sum::<u64>());
+        });
+
+        group.bench_with_input(BenchmarkId::new("Vec/sum", len), &vec, |b, vec| {
+            b.iter(|| black_box(vec).iter().sum::<u64>());
+        });
+    }
+
+    group.finish();
+}
+
+fn dequeue(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dequeue");
+
+    for len in SIZES {
+        group.throughput(Throughput::Elements(len as u64));
+
+        // Fill from both ends, then empty from the front
+        group.bench_with_input(
+            BenchmarkId::new("Dequeue/push_pop", len),
+            &len,
+            |b, &len| {
+                b.iter(|| {
+                    let mut dequeue = Dequeue::new();
+                    for value in 0..len as u64 / 2 {
+                        dequeue.push_back(value);
+                        dequeue.push_front(value);
+                    }
+                    while let Some(value) = dequeue.pop_front() {
+                        black_box(value);
+                    }
+                });
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("LinkedList/push_pop", len),
+            &len,
+            |b, &len| {
+                b.iter(|| {
+                    let mut list = LinkedList::new();
+                    for value in 0..len as u64 / 2 {
+                        list.push_back(value);
+                        list.push_front(value);
+                    }
+                    while let Some(value) = list.pop_front() {
+                        black_box(value);
+                    }
+                });
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("VecDeque/push_pop", len),
+            &len,
+            |b, &len| {
+                b.iter(|| {
+                    let mut deque = VecDeque::new();
+                    for value in 0..len as u64 / 2 {
+                        deque.push_back(value);
+                        deque.push_front(value);
+                    }
+                    while let Some(value) = deque.pop_front() {
+                        black_box(value);
+                    }
+                });
+            },
+        );
+
+        let dequeue: Dequeue<u64> = (0..len as u64).collect();
+        let list: LinkedList<u64> = (0..len as u64).collect();
+        let deque: VecDeque<u64> = (0..len as u64).collect();
+
+        group.bench_with_input(
+            BenchmarkId::new("Dequeue/sum", len),
+            &dequeue,
+            |b, dequeue| {
+                b.iter(|| black_box(dequeue).iter().sum::<u64>());
+            },
+        );
+
+        group.bench_with_input(BenchmarkId::new("LinkedList/sum", len), &list, |b, list| {
+            b.iter(|| black_box(list).iter().sum::<u64>());
+        });
+
+        group.bench_with_input(BenchmarkId::new("VecDeque/sum", len), &deque, |b, deque| {
+            b.iter(|| black_box(deque).iter().sum::<u64>());
+        });
+    }
+
+    group.finish();
+}
+
+fn queue(c: &mut Criterion) {
+    let mut group = c.benchmark_group("queue");
+
+    for len in SIZES {
+        group.throughput(Throughput::Elements(len as u64));
+
+        // Keeps a short backlog, like a producer slightly ahead of its
+        // consumer
+        group.bench_with_input(
+            BenchmarkId::new("Queue/throughput", len),
+            &len,
+            |b, &len| {
+                b.iter(|| {
+                    let mut queue = Queue::new();
+                    for value in 0..len as u64 {
+                        queue.append(value);
+                        if value % 4 != 0 {
+                            black_box(queue.pop());
+                        }
+                    }
+                    while let Some(value) = queue.pop() {
+                        black_box(value);
+                    }
+                });
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("VecDeque/throughput", len),
+            &len,
+            |b, &len| {
+                b.iter(|| {
+                    let mut deque = VecDeque::new();
+                    for value in 0..len as u64 {
+                        deque.push_back(value);
+                        if value % 4 != 0 {
+                            black_box(deque.pop_front());
+                        }
+                    }
+                    while let Some(value) = deque.pop_front() {
+                        black_box(value);
+                    }
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn binary_tree(c: &mut Criterion) {
+    let mut group = c.benchmark_group("binary_tree");
+
+    for len in SIZES {
+        let values = shuffled(len);
+        group.throughput(Throughput::Elements(len as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("BinaryTree/insert", len),
+            &values,
+            |b, values| {
+                b.iter(|| {
+                    let mut tree = BinaryTree::new();
+                    values.iter().for_each(|&value| {
+                        tree.insert(value);
+                    });
+                    tree
+                });
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("BTreeSet/insert", len),
+            &values,
+            |b, values| {
+                b.iter(|| {
+                    let mut set = BTreeSet::new();
+                    values.iter().for_each(|&value| {
+                        set.insert(value);
+                    });
+                    set
+                });
+            },
+        );
+
+        let mut tree = BinaryTree::new();
+        values.iter().for_each(|&value| {
+            tree.insert(value);
+        });
+        let set: BTreeSet<u64> = values.iter().copied().collect();
+
+        // Half of the lookups miss
+        let lookups: Vec<u64> = values.iter().map(|value| value * 2).collect();
+
+        group.bench_with_input(
+            BenchmarkId::new("BinaryTree/contains", len),
+            &lookups,
+            |b, lookups| {
+                b.iter(|| lookups.iter().filter(|value| tree.contains(value)).count());
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("BTreeSet/contains", len),
+            &lookups,
+            |b, lookups| {
+                b.iter(|| lookups.iter().filter(|value| set.contains(value)).count());
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, vector, dequeue, queue, binary_tree);
+criterion_main!(benches);
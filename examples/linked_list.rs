@@ -0,0 +1,12 @@
+use collections_rust::LinkedList;
+
+fn main() {
+    let mut list = LinkedList::new();
+
+    list.append(3);
+    list.append(5);
+    list.append(7);
+    list.prepend(1);
+
+    println!("{list}");
+}
@@ -0,0 +1,78 @@
+#![no_main]
+
+//! Replays random operations on a `BinaryTree` and on a `BTreeSet` and
+//! checks that both always hold the same values and that the tree keeps
+//! its invariants.
+
+use std::collections::BTreeSet;
+
+use arbitrary::Arbitrary;
+use collections_rust::BinaryTree;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    Insert(u8),
+    Remove(u8),
+    Contains(u8),
+    RemoveRange(u8, u8),
+    SplitOffAndAppend(u8),
+    RetainEven,
+    Rebalance,
+    Append(BinaryTree<u8>),
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    initial: BinaryTree<u8>,
+    ops: Vec<Op>,
+}
+
+fuzz_target!(|input: Input| {
+    let Input {
+        initial: mut tree,
+        ops,
+    } = input;
+    let mut model: BTreeSet<u8> = tree.iter().copied().collect();
+
+    for op in ops {
+        match op {
+            Op::Insert(value) => assert_eq!(tree.insert(value), model.insert(value)),
+            Op::Remove(value) => assert_eq!(tree.remove(&value), model.remove(&value)),
+            Op::Contains(value) => assert_eq!(tree.contains(&value), model.contains(&value)),
+            Op::RemoveRange(a, b) => {
+                let range = a.min(b)..a.max(b);
+                let removed = model.range(range.clone()).count();
+                model.retain(|value| !range.contains(value));
+
+                assert_eq!(tree.remove_range(range), removed);
+            }
+            Op::SplitOffAndAppend(value) => {
+                let greater = tree.split_off(&value);
+
+                assert!(greater.iter().eq(model.range(value..)));
+                assert!(tree.iter().eq(model.range(..value)));
+                assert_eq!(greater.check_invariants(), Ok(()));
+
+                tree.append(greater);
+            }
+            Op::RetainEven => {
+                tree.retain(|value| value % 2 == 0);
+                model.retain(|value| value % 2 == 0);
+            }
+            Op::Rebalance => {
+                tree.rebalance();
+                assert!(tree.is_balanced());
+            }
+            Op::Append(other) => {
+                model.extend(other.iter().copied());
+                tree.append(other);
+            }
+        }
+
+        assert_eq!(tree.check_invariants(), Ok(()));
+        assert_eq!(tree.size(), model.len());
+        assert!(tree.iter().eq(model.iter()));
+        assert!(tree.iter().rev().eq(model.iter().rev()));
+    }
+});
@@ -0,0 +1,121 @@
+#![no_main]
+
+//! Replays random operations on a `Dequeue` and on a `VecDeque`, moving
+//! nodes around with cursors, and checks that both always hold the same
+//! values in both directions.
+
+use std::collections::VecDeque;
+
+use arbitrary::Arbitrary;
+use collections_rust::{traits::List, Dequeue};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    PushFront(u8),
+    PushBack(u8),
+    PopFront,
+    PopBack,
+    Insert(usize, u8),
+    Remove(usize),
+    Splice(usize, Dequeue<u8>),
+    SplitBefore(usize),
+    SplitAfter(usize),
+    RemoveCurrent(usize),
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    initial: Dequeue<u8>,
+    ops: Vec<Op>,
+}
+
+fuzz_target!(|input: Input| {
+    let Input {
+        initial: mut dequeue,
+        ops,
+    } = input;
+    let mut model: VecDeque<u8> = dequeue.iter().copied().collect();
+
+    for op in ops {
+        let len = model.len();
+
+        match op {
+            Op::PushFront(value) => {
+                dequeue.push_front(value);
+                model.push_front(value);
+            }
+            Op::PushBack(value) => {
+                dequeue.push_back(value);
+                model.push_back(value);
+            }
+            Op::PopFront => assert_eq!(dequeue.pop_front(), model.pop_front()),
+            Op::PopBack => assert_eq!(dequeue.pop_back(), model.pop_back()),
+            Op::Insert(index, value) => {
+                let index = index % (len + 1);
+                List::insert(&mut dequeue, index, value);
+                model.insert(index, value);
+            }
+            Op::Remove(index) => {
+                if len > 0 {
+                    let index = index % len;
+                    assert_eq!(Some(List::remove(&mut dequeue, index)), model.remove(index));
+                }
+            }
+            Op::Splice(index, other) => {
+                // Splicing before the ghost element appends at the back
+                let index = index % (len + 1);
+                for (offset, value) in other.iter().enumerate() {
+                    model.insert(index + offset, *value);
+                }
+
+                let mut cursor = dequeue.cursor_mut();
+                if index < len {
+                    (0..=index).for_each(|_| cursor.move_next());
+                }
+                cursor.splice_before(other);
+            }
+            Op::SplitBefore(index) => {
+                if len > 0 {
+                    let index = index % len;
+
+                    let mut cursor = dequeue.cursor_mut();
+                    (0..=index).for_each(|_| cursor.move_next());
+                    let front = cursor.split_before();
+
+                    assert!(front.iter().eq(model.range(..index)));
+
+                    // Put it back through the ghost element
+                    dequeue.cursor_mut().splice_after(front);
+                }
+            }
+            Op::SplitAfter(index) => {
+                if len > 0 {
+                    let index = index % len;
+
+                    let mut cursor = dequeue.cursor_mut();
+                    (0..=index).for_each(|_| cursor.move_next());
+                    let back = cursor.split_after();
+
+                    assert!(back.iter().eq(model.range(index + 1..)));
+
+                    dequeue.cursor_mut().splice_before(back);
+                }
+            }
+            Op::RemoveCurrent(index) => {
+                if len > 0 {
+                    let index = index % len;
+
+                    let mut cursor = dequeue.cursor_mut();
+                    (0..=index).for_each(|_| cursor.move_next());
+                    assert_eq!(cursor.index(), Some(index));
+                    assert_eq!(cursor.remove_current(), model.remove(index));
+                }
+            }
+        }
+
+        assert_eq!(dequeue.len(), model.len());
+        assert!(dequeue.iter().eq(model.iter()));
+        assert!(dequeue.iter().rev().eq(model.iter().rev()));
+    }
+});
@@ -0,0 +1,83 @@
+#![no_main]
+
+//! Replays random operations on a `Vector` and on a `Vec` and checks that
+//! both always hold the same values.
+
+use arbitrary::Arbitrary;
+use collections_rust::Vector;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    Push(u16),
+    Pop,
+    Insert(usize, u16),
+    Remove(usize),
+    Reserve(u8),
+    Swap(usize, usize),
+    DrainBack(u8),
+    DrainRange(usize, usize),
+    Drain,
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    initial: Vector<u16>,
+    ops: Vec<Op>,
+}
+
+fuzz_target!(|input: Input| {
+    let Input { mut initial, ops } = input;
+    let mut model: Vec<u16> = initial.iter().copied().collect();
+
+    for op in ops {
+        let vector = &mut initial;
+
+        match op {
+            Op::Push(value) => {
+                vector.push(value);
+                model.push(value);
+            }
+            Op::Pop => assert_eq!(vector.pop(), model.pop()),
+            Op::Insert(index, value) => {
+                let index = index % (model.len() + 1);
+                vector.insert(index, value);
+                model.insert(index, value);
+            }
+            Op::Remove(index) => {
+                if !model.is_empty() {
+                    let index = index % model.len();
+                    assert_eq!(vector.remove(index), model.remove(index));
+                }
+            }
+            Op::Reserve(additional) => {
+                assert!(vector.try_reserve(additional as usize).is_ok());
+            }
+            Op::Swap(a, b) => {
+                if !model.is_empty() {
+                    let (a, b) = (a % model.len(), b % model.len());
+                    vector.swap(a, b);
+                    model.swap(a, b);
+                }
+            }
+            Op::DrainBack(count) => {
+                // Dropping a partially consumed drain must still empty it
+                let count = count as usize;
+                let drained: Vec<u16> = vector.drain(..).rev().take(count).collect();
+                let expected: Vec<u16> = model.drain(..).rev().take(count).collect();
+                assert_eq!(drained, expected);
+            }
+            Op::DrainRange(start, end) => {
+                let start = start % (model.len() + 1);
+                let end = start + end % (model.len() - start + 1);
+                assert!(vector.drain(start..end).eq(model.drain(start..end)));
+            }
+            Op::Drain => assert!(vector.drain(..).eq(model.drain(..))),
+        }
+
+        assert_eq!(initial.len(), model.len());
+        assert_eq!(&initial[..], &model[..]);
+    }
+
+    assert!(initial.into_iter().eq(model));
+});
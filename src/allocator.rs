@@ -0,0 +1,252 @@
+use std::{
+    alloc::{self, Layout},
+    ptr::{self, NonNull},
+};
+
+#[cfg(any(feature = "binary_tree", feature = "deque", feature = "queue"))]
+use crate::try_reserve_error::TryReserveError;
+
+/// Source of memory for the nodes of the linked collections and the buffer
+/// of [`crate::Vector`], like [`crate::Dequeue::new_in`], so they can be
+/// backed by pools or arenas. It's a stable stand-in for the unstable
+/// `std::alloc::Allocator`, and [`Global`] forwards to the global allocator.
+///
+/// The collections only ask for layouts of non-zero size and never move
+/// nodes or buffers between two allocators that aren't clones of each
+/// other, so implementations don't need to deal with either.
+///
+/// # Safety
+///
+/// Blocks returned by [`Allocator::allocate`] must be valid for reads and
+/// writes of `layout.size()` bytes, aligned to `layout.align()` and not
+/// overlap other live blocks, until they're passed to
+/// [`Allocator::deallocate`] or the allocator is dropped. Moving the
+/// allocator must not invalidate them, and a clone must behave like the
+/// same allocator, freeing the blocks of the original and the other way
+/// around, as [`crate::BinaryTree::split_off`] moves nodes to a clone.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::{alloc::Layout, cell::Cell, ptr::NonNull};
+///
+/// use collections_rust::{Allocator, Dequeue, Global};
+///
+/// /// Counts the live blocks.
+/// #[derive(Default)]
+/// struct Counting(Cell<usize>);
+///
+/// unsafe impl Allocator for Counting {
+///     fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+///         self.0.set(self.0.get() + 1);
+///         Global.allocate(layout)
+///     }
+///
+///     unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+///         self.0.set(self.0.get() - 1);
+///         Global.deallocate(ptr, layout)
+///     }
+/// }
+///
+/// let counting = Counting::default();
+/// let mut dequeue = Dequeue::new_in(&counting);
+///
+/// dequeue.extend([1, 2, 3]);
+/// assert_eq!(counting.0.get(), 3);
+///
+/// dequeue.pop_front();
+/// assert_eq!(counting.0.get(), 2);
+///
+/// drop(dequeue);
+/// assert_eq!(counting.0.get(), 0);
+/// ```
+pub unsafe trait Allocator {
+    /// Allocates a block of memory for `layout`, which never has zero size.
+    /// Returns `None` if there's no memory left.
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>>;
+
+    /// Frees a block of memory.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by [`Allocator::allocate`] of this
+    /// allocator or a clone of it for the same `layout`, and not be freed
+    /// already.
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+
+    /// Moves a block to one of `new_layout`, keeping the contents that fit,
+    /// and frees the old one. Returns `None` and leaves the old block alone
+    /// if there's no memory left. The default allocates a new block and
+    /// copies the contents, allocators that can resize in place should
+    /// override it.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by this allocator for `old_layout`,
+    /// and `new_layout` must have the same alignment.
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Option<NonNull<u8>> {
+        let new_ptr = self.allocate(new_layout)?;
+
+        let len = old_layout.size().min(new_layout.size());
+        ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), len);
+        self.deallocate(ptr, old_layout);
+
+        Some(new_ptr)
+    }
+}
+
+/// The global allocator, registered with `#[global_allocator]` or the
+/// system one by default. Default allocator of every collection.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Global;
+
+unsafe impl Allocator for Global {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        NonNull::new(unsafe { alloc::alloc(layout) })
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        alloc::dealloc(ptr.as_ptr(), layout);
+    }
+
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Option<NonNull<u8>> {
+        NonNull::new(alloc::realloc(ptr.as_ptr(), old_layout, new_layout.size()))
+    }
+}
+
+unsafe impl<A: Allocator + ?Sized> Allocator for &A {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        (**self).allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        (**self).deallocate(ptr, layout);
+    }
+
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Option<NonNull<u8>> {
+        (**self).reallocate(ptr, old_layout, new_layout)
+    }
+}
+
+/// Moves `value` to memory from `alloc`, returning an error instead of
+/// aborting if there's none.
+#[cfg(any(feature = "binary_tree", feature = "deque", feature = "queue"))]
+pub(crate) fn try_new_in<T, A: Allocator>(
+    value: T,
+    alloc: &A,
+) -> Result<NonNull<T>, TryReserveError> {
+    let layout = Layout::new::<T>();
+
+    let slot = if layout.size() == 0 {
+        NonNull::dangling()
+    } else {
+        alloc
+            .allocate(layout)
+            .ok_or(TryReserveError::AllocError { layout })?
+            .cast::<T>()
+    };
+
+    unsafe { slot.write(value) };
+
+    Ok(slot)
+}
+
+/// Moves `value` to memory from `alloc` like [`Box::new`] does with the
+/// global allocator.
+#[cfg(any(feature = "binary_tree", feature = "deque", feature = "queue"))]
+pub(crate) fn new_in<T, A: Allocator>(value: T, alloc: &A) -> NonNull<T> {
+    try_new_in(value, alloc).unwrap_or_else(|_| alloc::handle_alloc_error(Layout::new::<T>()))
+}
+
+/// Moves the value out of `ptr` and frees its memory, like dereferencing
+/// the result of [`Box::from_raw`] does with the global allocator.
+///
+/// # Safety
+///
+/// `ptr` must come from [`new_in`] or [`try_new_in`] with the same
+/// allocator, and it's no longer valid afterwards.
+#[cfg(any(feature = "binary_tree", feature = "deque", feature = "queue"))]
+pub(crate) unsafe fn take_in<T, A: Allocator>(ptr: NonNull<T>, alloc: &A) -> T {
+    let value = ptr.read();
+    let layout = Layout::new::<T>();
+
+    if layout.size() != 0 {
+        alloc.deallocate(ptr.cast(), layout);
+    }
+
+    value
+}
+
+#[cfg(all(
+    test,
+    any(feature = "binary_tree", feature = "deque", feature = "queue")
+))]
+mod tests {
+    use std::{alloc::Layout, cell::RefCell, ptr::NonNull};
+
+    use super::{new_in, take_in, try_new_in, Allocator, Global};
+
+    /// Allocator that fails after a number of allocations and records the
+    /// layouts it hands out.
+    struct Limited {
+        left: RefCell<usize>,
+        live: RefCell<Vec<Layout>>,
+    }
+
+    unsafe impl Allocator for Limited {
+        fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+            let mut left = self.left.borrow_mut();
+            *left = left.checked_sub(1)?;
+            self.live.borrow_mut().push(layout);
+
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            let mut live = self.live.borrow_mut();
+            let index = live.iter().position(|live| *live == layout).unwrap();
+            live.swap_remove(index);
+
+            Global.deallocate(ptr, layout);
+        }
+    }
+
+    #[test]
+    fn test_new_and_take() {
+        let limited = Limited {
+            left: RefCell::new(2),
+            live: RefCell::new(Vec::new()),
+        };
+
+        let a = new_in(String::from("a"), &limited);
+        let b = try_new_in(7u64, &limited).unwrap();
+        assert!(try_new_in(8u64, &limited).is_err());
+
+        // Zero sized values don't need memory
+        let unit = try_new_in((), &limited).unwrap();
+        assert_eq!(limited.live.borrow().len(), 2);
+
+        unsafe {
+            take_in(unit, &limited);
+            assert_eq!(take_in(b, &limited), 7);
+            assert_eq!(take_in(a, &limited), "a");
+        }
+
+        assert!(limited.live.borrow().is_empty());
+    }
+}
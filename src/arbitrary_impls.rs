@@ -0,0 +1,255 @@
+//! [`arbitrary::Arbitrary`] implementations, so fuzzers can build the
+//! collections straight out of their raw input. Every collection is built
+//! from an arbitrary sequence of values through its public insertion
+//! methods, which exercises the same code paths as regular use.
+//!
+//! Collections of small integers like [`crate::FixedBitSet`] or
+//! [`crate::SparseSet`] are left out, since arbitrary values would make
+//! them allocate gigabytes.
+
+#[cfg(any(feature = "concurrent_hash_map", feature = "index_map"))]
+use std::hash::{BuildHasher, Hash};
+#[cfg(feature = "interval_map")]
+use std::ops::Range;
+
+// Nothing is left to implement if the feature is enabled without any
+// collection
+#[allow(unused_imports)]
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+#[allow(unused_imports)]
+use crate::*;
+
+/// Implements `Arbitrary` for collections that implement `FromIterator`,
+/// collecting an arbitrary number of arbitrary items.
+macro_rules! impl_arbitrary_from_iter {
+    ($(#[$cfg:meta] impl<$($param:ident $(: $bound:ident $(+ $bounds:ident)*)?),*> for $ty:ty { $item:ty })*) => {$(
+        #[$cfg]
+        impl<'a, $($param: Arbitrary<'a> $(+ $bound $(+ $bounds)*)?),*> Arbitrary<'a> for $ty {
+            fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+                u.arbitrary_iter::<$item>()?.collect()
+            }
+
+            fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+                u.arbitrary_take_rest_iter::<$item>()?.collect()
+            }
+        }
+    )*};
+}
+
+impl_arbitrary_from_iter! {
+    #[cfg(feature = "deque")]
+    impl<T> for Dequeue<T> { T }
+    #[cfg(feature = "array_deque")]
+    impl<T> for ArrayDeque<T> { T }
+    #[cfg(feature = "list")]
+    impl<T> for List<T> { T }
+    #[cfg(feature = "linked_list")]
+    impl<T> for LinkedList<T> { T }
+    #[cfg(feature = "cow_vector")]
+    impl<T> for CowVector<T> { T }
+    #[cfg(feature = "persistent_vec")]
+    impl<T: Clone> for PersistentVec<T> { T }
+    #[cfg(feature = "sorted_vec")]
+    impl<T: Ord> for SortedVec<T> { T }
+    #[cfg(feature = "binary_heap")]
+    impl<T: Ord> for BinaryHeap<T> { T }
+    #[cfg(feature = "min_max_heap")]
+    impl<T: Ord> for MinMaxHeap<T> { T }
+    #[cfg(feature = "binary_multiset")]
+    impl<T: Ord> for BinaryMultiSet<T> { T }
+    #[cfg(feature = "btree_set")]
+    impl<T: Ord> for BTreeSet<T> { T }
+    #[cfg(feature = "scapegoat_tree")]
+    impl<T: Ord> for ScapegoatTree<T> { T }
+    #[cfg(feature = "skip_set")]
+    impl<T: Ord> for SkipSet<T> { T }
+    #[cfg(feature = "splay_tree")]
+    impl<T: Ord> for SplayTree<T> { T }
+    #[cfg(feature = "treap")]
+    impl<T: Ord> for Treap<T> { T }
+    #[cfg(feature = "persistent_tree")]
+    impl<T: Ord + Clone> for PersistentTree<T> { T }
+    #[cfg(feature = "counter")]
+    impl<T: Hash + Eq> for Counter<T> { T }
+    #[cfg(feature = "hash_set")]
+    impl<T: Hash + Eq> for HashSet<T> { T }
+    #[cfg(feature = "btree_map")]
+    impl<K: Ord, V> for BTreeMap<K, V> { (K, V) }
+    #[cfg(feature = "concurrent_skip_map")]
+    impl<K: Ord, V> for ConcurrentSkipMap<K, V> { (K, V) }
+    #[cfg(feature = "skip_map")]
+    impl<K: Ord, V> for SkipMap<K, V> { (K, V) }
+    #[cfg(feature = "tree_map")]
+    impl<K: Ord, V> for TreeMap<K, V> { (K, V) }
+}
+
+#[cfg(feature = "index_map")]
+impl<'a, K, V, S> Arbitrary<'a> for IndexMap<K, V, S>
+where
+    K: Arbitrary<'a> + Hash + Eq,
+    V: Arbitrary<'a>,
+    S: BuildHasher + Default,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.arbitrary_iter::<(K, V)>()?.collect()
+    }
+}
+
+#[cfg(feature = "concurrent_hash_map")]
+impl<'a, K, V, S> Arbitrary<'a> for ConcurrentHashMap<K, V, S>
+where
+    K: Arbitrary<'a> + Hash + Eq,
+    V: Arbitrary<'a>,
+    S: BuildHasher + Clone + Default,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.arbitrary_iter::<(K, V)>()?.collect()
+    }
+}
+
+#[cfg(feature = "interval_map")]
+impl<'a, K, V> Arbitrary<'a> for IntervalMap<K, V>
+where
+    K: Arbitrary<'a> + Ord + Clone,
+    V: Arbitrary<'a> + Clone + PartialEq,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.arbitrary_iter::<(Range<K>, V)>()?.collect()
+    }
+}
+
+#[cfg(feature = "circular_buffer")]
+impl<'a, T: Arbitrary<'a>, const N: usize> Arbitrary<'a> for CircularBuffer<T, N> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.arbitrary_iter::<T>()?.collect()
+    }
+}
+
+#[cfg(feature = "bit_vec")]
+impl<'a> Arbitrary<'a> for BitVec {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.arbitrary_iter::<bool>()?.collect()
+    }
+}
+
+#[cfg(feature = "vector")]
+impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for Vector<T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut vector = Vector::new();
+
+        for value in u.arbitrary_iter()? {
+            vector.push(value?);
+        }
+
+        Ok(vector)
+    }
+}
+
+#[cfg(feature = "queue")]
+impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for Queue<T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut queue = Queue::new();
+
+        for value in u.arbitrary_iter()? {
+            queue.append(value?);
+        }
+
+        Ok(queue)
+    }
+}
+
+#[cfg(feature = "binary_tree")]
+impl<'a, T: Arbitrary<'a> + Ord> Arbitrary<'a> for BinaryTree<T> {
+    /// Builds the tree by inserting the values in the order they come, so
+    /// fuzzers can reach unbalanced shapes too.
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut tree = BinaryTree::new();
+
+        for value in u.arbitrary_iter()? {
+            tree.insert(value?);
+        }
+
+        Ok(tree)
+    }
+}
+
+#[cfg(all(
+    test,
+    any(
+        feature = "binary_tree",
+        feature = "deque",
+        feature = "tree_map",
+        feature = "vector"
+    )
+))]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    #[cfg(feature = "binary_tree")]
+    use crate::BinaryTree;
+    #[cfg(feature = "deque")]
+    use crate::Dequeue;
+    #[cfg(feature = "tree_map")]
+    use crate::TreeMap;
+    #[cfg(feature = "vector")]
+    use crate::Vector;
+
+    /// Odd bytes tell the collections to keep reading values.
+    #[cfg(any(feature = "deque", feature = "vector"))]
+    fn sequence_data() -> Vec<u8> {
+        (1..=255).step_by(2).collect()
+    }
+
+    /// Values in descending order, repeated.
+    #[cfg(any(feature = "binary_tree", feature = "tree_map"))]
+    fn sorted_data() -> Vec<u8> {
+        (0..=255).rev().cycle().take(1024).collect()
+    }
+
+    #[test]
+    #[cfg(feature = "vector")]
+    fn test_vector() {
+        let data = sequence_data();
+
+        let vector = Vector::<u16>::arbitrary(&mut Unstructured::new(&data)).unwrap();
+        let vec = Vec::<u16>::arbitrary(&mut Unstructured::new(&data)).unwrap();
+
+        assert!(!vector.is_empty());
+        assert!(vector == vec);
+    }
+
+    #[test]
+    #[cfg(feature = "deque")]
+    fn test_dequeue() {
+        let data = sequence_data();
+
+        let dequeue = Dequeue::<u16>::arbitrary(&mut Unstructured::new(&data)).unwrap();
+        let vec = Vec::<u16>::arbitrary(&mut Unstructured::new(&data)).unwrap();
+
+        assert!(!dequeue.is_empty());
+        assert!(dequeue == vec);
+
+        // Every other byte is a value
+        let rest = Dequeue::<u8>::arbitrary_take_rest(Unstructured::new(&data)).unwrap();
+        assert!(rest.iter().eq(data.iter().skip(1).step_by(2)));
+    }
+
+    #[test]
+    #[cfg(feature = "binary_tree")]
+    fn test_binary_tree() {
+        let data = sorted_data();
+
+        let tree = BinaryTree::<u8>::arbitrary(&mut Unstructured::new(&data)).unwrap();
+        assert!(tree.check_invariants().is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "tree_map")]
+    fn test_tree_map() {
+        let data = sorted_data();
+
+        let map = TreeMap::<u8, u8>::arbitrary_take_rest(Unstructured::new(&data)).unwrap();
+        assert!(map.iter().map(|(key, _)| key).is_sorted());
+    }
+}
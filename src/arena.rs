@@ -0,0 +1,187 @@
+use std::{
+    alloc,
+    mem::{self, MaybeUninit},
+    ptr,
+};
+
+use crate::try_reserve_error::{self, TryReserveError};
+
+/// Pool of values allocated in fixed-size chunks. Values are written into
+/// consecutive slots of the last chunk, which keeps values allocated together
+/// close in memory, and slots released with `free` are reused before touching
+/// a new chunk. Releasing all the chunks at once with `clear` doesn't depend
+/// on the number of values.
+///
+/// The arena never drops values by itself, whoever allocates them must read
+/// them back with `free` or drop them in place before calling `clear`.
+pub(crate) struct Arena<T> {
+    chunks: Vec<ptr::NonNull<[MaybeUninit<T>]>>,
+    chunk_capacity: usize,
+    /// Number of slots taken from the last chunk.
+    used: usize,
+    free: Vec<ptr::NonNull<T>>,
+}
+
+impl<T> Arena<T> {
+    /// Creates an empty arena that allocates `chunk_capacity` slots at once.
+    /// Doesn't allocate memory until the first value is allocated.
+    pub(crate) fn new(chunk_capacity: usize) -> Self {
+        assert!(chunk_capacity > 0, "Arena chunks can't be empty");
+
+        Self {
+            chunks: Vec::new(),
+            chunk_capacity,
+            used: 0,
+            free: Vec::new(),
+        }
+    }
+
+    /// Returns the number of slots allocated at once.
+    pub(crate) fn chunk_capacity(&self) -> usize {
+        self.chunk_capacity
+    }
+
+    /// Returns the number of bytes taken by the chunks and the free list,
+    /// whether the slots are in use or not.
+    pub(crate) fn heap_size(&self) -> usize {
+        self.chunks.len() * self.chunk_capacity * mem::size_of::<T>()
+            + self.chunks.capacity() * mem::size_of::<ptr::NonNull<[MaybeUninit<T>]>>()
+            + self.free.capacity() * mem::size_of::<ptr::NonNull<T>>()
+    }
+
+    /// Returns the number of chunks currently allocated.
+    #[cfg(test)]
+    pub(crate) fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Moves `value` into a free slot and returns a pointer to it.
+    pub(crate) fn alloc(&mut self, value: T) -> ptr::NonNull<T> {
+        let slot = match self.free.pop() {
+            Some(slot) => slot,
+
+            None => {
+                if self.chunks.is_empty() || self.used == self.chunk_capacity {
+                    let chunk = Box::<[T]>::new_uninit_slice(self.chunk_capacity);
+                    self.chunks.push(ptr::NonNull::from(Box::leak(chunk)));
+                    self.used = 0;
+                }
+
+                let chunk = self.chunks.last().unwrap().cast::<T>();
+                self.used += 1;
+
+                unsafe { chunk.add(self.used - 1) }
+            }
+        };
+
+        unsafe { slot.write(value) };
+
+        slot
+    }
+
+    /// Like `alloc`, but returns an error instead of aborting if a new chunk
+    /// is needed and can't be allocated. `value` is dropped in that case.
+    pub(crate) fn try_alloc(&mut self, value: T) -> Result<ptr::NonNull<T>, TryReserveError> {
+        if self.free.is_empty() && (self.chunks.is_empty() || self.used == self.chunk_capacity) {
+            // Make room for the chunk pointer first, so the chunk can't leak.
+            // The error reports the size of the pointer, not what the list
+            // of chunks asked for, which isn't available
+            self.chunks
+                .try_reserve(1)
+                .map_err(|_| TryReserveError::AllocError {
+                    layout: alloc::Layout::new::<ptr::NonNull<[MaybeUninit<T>]>>(),
+                })?;
+
+            let layout = alloc::Layout::array::<MaybeUninit<T>>(self.chunk_capacity)
+                .map_err(|_| TryReserveError::CapacityOverflow)?;
+            let chunk = try_reserve_error::try_alloc(layout)?.cast::<MaybeUninit<T>>();
+
+            self.chunks.push(ptr::NonNull::slice_from_raw_parts(
+                chunk,
+                self.chunk_capacity,
+            ));
+            self.used = 0;
+        }
+
+        // There's a free slot now, so this doesn't allocate
+        Ok(self.alloc(value))
+    }
+
+    /// Moves the value out of `slot` and makes the slot available again.
+    /// `slot` must have been returned by `alloc` and not freed since.
+    pub(crate) unsafe fn free(&mut self, slot: ptr::NonNull<T>) -> T {
+        self.free.push(slot);
+
+        slot.read()
+    }
+
+    /// Deallocates all the chunks. Values still stored in the arena are
+    /// forgotten, not dropped.
+    pub(crate) fn clear(&mut self) {
+        for chunk in self.chunks.drain(..) {
+            unsafe { drop(Box::from_raw(chunk.as_ptr())) };
+        }
+
+        self.used = 0;
+        self.free.clear();
+    }
+}
+
+impl<T> Drop for Arena<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Arena;
+
+    #[test]
+    fn test_alloc_free() {
+        let mut arena = Arena::new(2);
+
+        let a = arena.alloc(String::from("a"));
+        let b = arena.alloc(String::from("b"));
+        let c = arena.alloc(String::from("c"));
+
+        assert_eq!(arena.chunks.len(), 2);
+
+        unsafe {
+            assert_eq!(b.as_ptr(), a.as_ptr().add(1));
+            assert_eq!(arena.free(b), "b");
+
+            // Freed slots are reused first
+            let d = arena.alloc(String::from("d"));
+            assert_eq!(d, b);
+            assert_eq!(arena.chunks.len(), 2);
+
+            assert_eq!(arena.free(a), "a");
+            assert_eq!(arena.free(c), "c");
+            assert_eq!(arena.free(d), "d");
+        }
+
+        arena.clear();
+        assert!(arena.chunks.is_empty());
+
+        let e = arena.alloc(String::from("e"));
+        unsafe { assert_eq!(arena.free(e), "e") };
+    }
+
+    #[test]
+    fn test_try_alloc() {
+        let mut arena = Arena::new(2);
+
+        let slots: Vec<_> = (0..5).map(|i| arena.try_alloc(i).unwrap()).collect();
+        assert_eq!(arena.chunks.len(), 3);
+
+        for (i, slot) in slots.into_iter().enumerate() {
+            unsafe { assert_eq!(arena.free(slot), i) };
+        }
+
+        // Zero sized values take no memory but still get slots
+        let mut units = Arena::new(4);
+        let unit = units.try_alloc(()).unwrap();
+        unsafe { units.free(unit) };
+    }
+}
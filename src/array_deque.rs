@@ -0,0 +1,514 @@
+use std::{
+    mem::{self, MaybeUninit},
+    ops::{Index, IndexMut},
+    ptr, slice,
+};
+
+use crate::{
+    heap_size::{self, HeapSize},
+    vector::Buffer,
+};
+
+/// Double-ended queue stored in a growable circular buffer. The values
+/// start at index `head` of the buffer and wrap around to the beginning when
+/// they reach its end, so pushing and popping at both ends is O(1) amortized
+/// without moving any values. Being contiguous in memory (in at most two
+/// parts) it's faster than the linked [`crate::Dequeue`] for most workloads.
+pub struct ArrayDeque<T> {
+    buf: Buffer<T>,
+    /// Index in the buffer of the first value.
+    head: usize,
+    len: usize,
+}
+
+impl<T> ArrayDeque<T> {
+    /// Creates an empty deque. Doesn't allocate until the first push.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::ArrayDeque;
+    ///
+    /// let deque: ArrayDeque<i32> = ArrayDeque::new();
+    ///
+    /// assert!(deque.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            buf: Buffer::new(),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of values in the deque.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the deque contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of values the deque can hold without growing.
+    pub fn capacity(&self) -> usize {
+        self.buf.cap()
+    }
+
+    /// Removes all the values from the deque, keeping the buffer.
+    pub fn clear(&mut self) {
+        while self.pop_back().is_some() {}
+        self.head = 0;
+    }
+
+    /// Returns the index in the buffer of the logical `index`.
+    fn physical(&self, index: usize) -> usize {
+        let until_end = self.buf.cap() - self.head;
+
+        if index < until_end {
+            self.head + index
+        } else {
+            index - until_end
+        }
+    }
+
+    /// Doubles the capacity of the buffer. If the values wrapped around, the
+    /// part at the beginning of the old buffer is moved right after its end,
+    /// which always fits because the new buffer is at least twice as big.
+    fn grow(&mut self) {
+        let old_cap = self.buf.cap();
+        self.buf.grow();
+
+        if self.head + self.len > old_cap {
+            let wrapped = self.head + self.len - old_cap;
+
+            unsafe {
+                ptr::copy_nonoverlapping(self.buf.ptr(), self.buf.ptr().add(old_cap), wrapped);
+            }
+        }
+    }
+
+    /// Appends `value` to the back of the deque.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::new();
+    ///
+    /// deque.push_back(1);
+    /// deque.push_back(2);
+    ///
+    /// assert_eq!(deque.back(), Some(&2));
+    /// ```
+    pub fn push_back(&mut self, value: T) {
+        if self.len == self.buf.cap() {
+            self.grow();
+        }
+
+        unsafe {
+            ptr::write(self.buf.ptr().add(self.physical(self.len)), value);
+        }
+
+        self.len += 1;
+    }
+
+    /// Prepends `value` to the front of the deque.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::new();
+    ///
+    /// deque.push_front(1);
+    /// deque.push_front(2);
+    ///
+    /// assert_eq!(deque.front(), Some(&2));
+    /// ```
+    pub fn push_front(&mut self, value: T) {
+        if self.len == self.buf.cap() {
+            self.grow();
+        }
+
+        self.head = match self.head {
+            0 => self.buf.cap() - 1,
+            head => head - 1,
+        };
+
+        unsafe {
+            ptr::write(self.buf.ptr().add(self.head), value);
+        }
+
+        self.len += 1;
+    }
+
+    /// Removes and returns the value at the back of the deque.
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.len -= 1;
+
+        unsafe { Some(ptr::read(self.buf.ptr().add(self.physical(self.len)))) }
+    }
+
+    /// Removes and returns the value at the front of the deque.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::ArrayDeque;
+    ///
+    /// let mut deque: ArrayDeque<i32> = [1, 2].into_iter().collect();
+    ///
+    /// assert_eq!(deque.pop_front(), Some(1));
+    /// assert_eq!(deque.pop_front(), Some(2));
+    /// assert_eq!(deque.pop_front(), None);
+    /// ```
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let value = unsafe { ptr::read(self.buf.ptr().add(self.head)) };
+
+        self.head = self.physical(1);
+        self.len -= 1;
+
+        Some(value)
+    }
+
+    /// Returns a reference to the value at `index`, counting from the front.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+
+        unsafe { Some(&*self.buf.ptr().add(self.physical(index))) }
+    }
+
+    /// Returns a mutable reference to the value at `index`, counting from the
+    /// front.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+
+        unsafe { Some(&mut *self.buf.ptr().add(self.physical(index))) }
+    }
+
+    /// Returns the value at the front of the deque.
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Returns a mutable reference to the value at the front of the deque.
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    /// Returns the value at the back of the deque.
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    /// Returns a mutable reference to the value at the back of the deque.
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.len.checked_sub(1)?)
+    }
+
+    /// Returns the ranges of the buffer that contain the values, in order.
+    /// The second one is empty unless the values wrap around.
+    fn ranges(&self) -> ((usize, usize), (usize, usize)) {
+        let until_end = self.buf.cap() - self.head;
+
+        if self.len <= until_end {
+            ((self.head, self.len), (0, 0))
+        } else {
+            ((self.head, until_end), (0, self.len - until_end))
+        }
+    }
+
+    /// Returns the values as two slices, the first one starting at the front
+    /// of the deque. The second one is empty if the values are contiguous.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::new();
+    ///
+    /// deque.push_back(2);
+    /// deque.push_back(3);
+    /// deque.push_front(1);
+    ///
+    /// let (front, back) = deque.as_slices();
+    /// assert_eq!([front, back].concat(), [1, 2, 3]);
+    /// ```
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let ((start, len), (_, wrapped)) = self.ranges();
+
+        unsafe {
+            (
+                slice::from_raw_parts(self.buf.ptr().add(start), len),
+                slice::from_raw_parts(self.buf.ptr(), wrapped),
+            )
+        }
+    }
+
+    /// Returns the values as two mutable slices, the first one starting at
+    /// the front of the deque.
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let ((start, len), (_, wrapped)) = self.ranges();
+
+        unsafe {
+            (
+                slice::from_raw_parts_mut(self.buf.ptr().add(start), len),
+                slice::from_raw_parts_mut(self.buf.ptr(), wrapped),
+            )
+        }
+    }
+
+    /// Moves the values so they are contiguous in the buffer, starting at
+    /// its beginning, and returns them as a single slice. Takes O(capacity)
+    /// time if the values wrap around, O(1) otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::new();
+    ///
+    /// deque.push_back(2);
+    /// deque.push_back(3);
+    /// deque.push_front(1);
+    ///
+    /// deque.make_contiguous().sort_by(|a, b| b.cmp(a));
+    ///
+    /// assert_eq!(deque.as_slices(), (&[3, 2, 1][..], &[][..]));
+    /// ```
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        if self.len > self.buf.cap() - self.head {
+            if mem::size_of::<T>() == 0 {
+                self.head = 0;
+            } else {
+                // The free slots are uninitialized, but rotating them as
+                // `MaybeUninit` only moves bytes around.
+                unsafe {
+                    let slots = slice::from_raw_parts_mut(
+                        self.buf.ptr() as *mut MaybeUninit<T>,
+                        self.buf.cap(),
+                    );
+
+                    slots.rotate_left(self.head);
+                }
+
+                self.head = 0;
+            }
+        }
+
+        self.as_mut_slices().0
+    }
+
+    /// Returns an iterator over the values from front to back.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::ArrayDeque;
+    ///
+    /// let deque: ArrayDeque<i32> = [1, 2, 3].into_iter().collect();
+    ///
+    /// assert!(deque.iter().eq([1, 2, 3].iter()));
+    /// assert!(deque.iter().rev().eq([3, 2, 1].iter()));
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (front, back) = self.as_slices();
+
+        Iter {
+            front: front.iter(),
+            back: back.iter(),
+        }
+    }
+}
+
+impl<T> Drop for ArrayDeque<T> {
+    fn drop(&mut self) {
+        while self.pop_back().is_some() {}
+    }
+}
+
+impl<T> Default for ArrayDeque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Index<usize> for ArrayDeque<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("Index out of bounds")
+    }
+}
+
+impl<T> IndexMut<usize> for ArrayDeque<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("Index out of bounds")
+    }
+}
+
+impl<T> Extend<T> for ArrayDeque<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for ArrayDeque<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut deque = Self::new();
+        deque.extend(iter);
+
+        deque
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ArrayDeque<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the values of an [`ArrayDeque`] from front to back.
+pub struct Iter<'a, T> {
+    front: slice::Iter<'a, T>,
+    back: slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<T: HeapSize> HeapSize for ArrayDeque<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.buf.cap() * mem::size_of::<T>() + heap_size::children(self.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::ArrayDeque;
+
+    #[test]
+    fn test_against_vec_deque() {
+        let mut deque = ArrayDeque::new();
+        let mut expected = VecDeque::new();
+
+        // Pseudo random sequence of operations on both ends
+        for step in 0..5_000u32 {
+            match (step * 7_919) % 7 {
+                0..=2 => {
+                    deque.push_back(step);
+                    expected.push_back(step);
+                }
+                3 | 4 => {
+                    deque.push_front(step);
+                    expected.push_front(step);
+                }
+                5 => assert_eq!(deque.pop_front(), expected.pop_front()),
+                _ => assert_eq!(deque.pop_back(), expected.pop_back()),
+            }
+
+            assert_eq!(deque.len(), expected.len());
+            assert_eq!(deque.front(), expected.front());
+            assert_eq!(deque.back(), expected.back());
+        }
+
+        assert!(deque.iter().eq(expected.iter()));
+        assert!(deque.iter().rev().eq(expected.iter().rev()));
+        assert_eq!(deque[10], expected[10]);
+
+        deque[10] = 0;
+        *deque.front_mut().unwrap() = 1;
+        *deque.back_mut().unwrap() = 2;
+        assert_eq!(deque.get(10), Some(&0));
+        assert_eq!(deque.get(deque.len()), None);
+    }
+
+    #[test]
+    fn test_make_contiguous() {
+        let mut deque: ArrayDeque<String> = ArrayDeque::new();
+
+        for value in 0..6 {
+            deque.push_back(value.to_string());
+        }
+
+        for value in 6..8 {
+            deque.push_front(value.to_string());
+        }
+
+        assert_eq!(deque.capacity(), 8);
+        assert!(!deque.as_slices().1.is_empty());
+
+        let values = deque.make_contiguous();
+        assert_eq!(values, ["7", "6", "0", "1", "2", "3", "4", "5"]);
+        assert!(deque.as_slices().1.is_empty());
+
+        deque.pop_front();
+        deque.push_back(String::from("8"));
+        deque.as_mut_slices().1[0].push('!');
+        assert_eq!(deque.back().unwrap(), "8!");
+
+        deque.clear();
+        assert!(deque.is_empty());
+        assert_eq!(deque.capacity(), 8);
+        assert!(deque.make_contiguous().is_empty());
+    }
+
+    #[test]
+    fn test_zero_sized() {
+        let mut deque = ArrayDeque::new();
+
+        for _ in 0..100 {
+            deque.push_front(());
+            deque.push_back(());
+        }
+
+        assert_eq!(deque.len(), 200);
+        assert_eq!(deque.make_contiguous().len(), 200);
+        assert_eq!(deque.iter().count(), 200);
+        assert_eq!(deque.pop_back(), Some(()));
+    }
+}
@@ -0,0 +1,318 @@
+use std::slice;
+
+use crate::{heap_size::HeapSize, vector::Vector};
+
+/// Priority queue implemented as a max-heap stored in a [`Vector`]. The
+/// greatest value is always at index 0 and every value is greater than or
+/// equal to its children, which are at indices `2 * i + 1` and `2 * i + 2`.
+pub struct BinaryHeap<T> {
+    data: Vector<T>,
+}
+
+impl<T> BinaryHeap<T> {
+    /// Creates an empty heap.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryHeap;
+    ///
+    /// let heap: BinaryHeap<i32> = BinaryHeap::new();
+    ///
+    /// assert!(heap.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            data: Vector::new(),
+        }
+    }
+
+    /// Returns the number of values in the heap.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns `true` if the heap contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Removes all the values from the heap.
+    pub fn clear(&mut self) {
+        while self.data.pop().is_some() {}
+    }
+
+    /// Returns the greatest value without removing it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryHeap;
+    ///
+    /// let heap: BinaryHeap<i32> = [1, 5, 2].into_iter().collect();
+    ///
+    /// assert_eq!(heap.peek(), Some(&5));
+    /// ```
+    pub fn peek(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    /// Returns an iterator over the values in the heap in arbitrary order.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    /// Consumes the heap and returns the underlying vector, in heap order.
+    pub fn into_vector(self) -> Vector<T> {
+        self.data
+    }
+}
+
+impl<T: Ord> BinaryHeap<T> {
+    /// Moves the value at `index` up while it's greater than its parent.
+    fn sift_up(&mut self, mut index: usize) {
+        while index > 0 {
+            let parent = (index - 1) / 2;
+
+            if self.data[index] <= self.data[parent] {
+                break;
+            }
+
+            self.data.swap(index, parent);
+            index = parent;
+        }
+    }
+
+    /// Moves the value at `index` down while it's smaller than one of its
+    /// children, considering only the first `len` values.
+    fn sift_down(&mut self, mut index: usize, len: usize) {
+        loop {
+            let left = 2 * index + 1;
+            let right = left + 1;
+            let mut greatest = index;
+
+            if left < len && self.data[left] > self.data[greatest] {
+                greatest = left;
+            }
+
+            if right < len && self.data[right] > self.data[greatest] {
+                greatest = right;
+            }
+
+            if greatest == index {
+                break;
+            }
+
+            self.data.swap(index, greatest);
+            index = greatest;
+        }
+    }
+
+    /// Restores the heap property of the whole vector by sifting down every
+    /// value that has children, starting from the last one. Runs in O(n).
+    fn heapify(&mut self) {
+        let len = self.data.len();
+
+        for index in (0..len / 2).rev() {
+            self.sift_down(index, len);
+        }
+    }
+
+    /// Builds a heap out of the values of `vector` in O(n).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::{BinaryHeap, Vector};
+    ///
+    /// let mut vector = Vector::new();
+    /// vector.push(1);
+    /// vector.push(3);
+    /// vector.push(2);
+    ///
+    /// let heap = BinaryHeap::from_vector(vector);
+    ///
+    /// assert_eq!(heap.peek(), Some(&3));
+    /// ```
+    pub fn from_vector(data: Vector<T>) -> Self {
+        let mut heap = Self { data };
+        heap.heapify();
+
+        heap
+    }
+
+    /// Pushes `value` into the heap in O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new();
+    ///
+    /// heap.push(1);
+    /// heap.push(3);
+    ///
+    /// assert_eq!(heap.peek(), Some(&3));
+    /// assert_eq!(heap.len(), 2);
+    /// ```
+    pub fn push(&mut self, value: T) {
+        self.data.push(value);
+        self.sift_up(self.data.len() - 1);
+    }
+
+    /// Removes and returns the greatest value in O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryHeap;
+    ///
+    /// let mut heap: BinaryHeap<i32> = [1, 3, 2].into_iter().collect();
+    ///
+    /// assert_eq!(heap.pop(), Some(3));
+    /// assert_eq!(heap.pop(), Some(2));
+    /// assert_eq!(heap.pop(), Some(1));
+    /// assert_eq!(heap.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        let last = self.data.len().checked_sub(1)?;
+
+        self.data.swap(0, last);
+        let greatest = self.data.pop();
+        self.sift_down(0, last);
+
+        greatest
+    }
+
+    /// Consumes the heap and returns its values in ascending order. Sorts
+    /// in place (heapsort), without allocating.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryHeap;
+    ///
+    /// let heap: BinaryHeap<i32> = [4, 1, 3, 2].into_iter().collect();
+    ///
+    /// assert_eq!(*heap.into_sorted_vector(), [1, 2, 3, 4]);
+    /// ```
+    pub fn into_sorted_vector(mut self) -> Vector<T> {
+        for end in (1..self.data.len()).rev() {
+            self.data.swap(0, end);
+            self.sift_down(0, end);
+        }
+
+        self.data
+    }
+}
+
+impl<T> Default for BinaryHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> Extend<T> for BinaryHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for BinaryHeap<T> {
+    /// Collects the values first and then builds the heap in O(n).
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut data = Vector::new();
+
+        for value in iter {
+            data.push(value);
+        }
+
+        Self::from_vector(data)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a BinaryHeap<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: HeapSize> HeapSize for BinaryHeap<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.data.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BinaryHeap;
+
+    fn assert_heap<T: Ord>(heap: &BinaryHeap<T>) {
+        for index in 1..heap.len() {
+            assert!(heap.data[index] <= heap.data[(index - 1) / 2]);
+        }
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let mut heap = BinaryHeap::new();
+
+        for value in [5, 1, 8, 3, 9, 2, 8, 7] {
+            heap.push(value);
+            assert_heap(&heap);
+        }
+
+        assert_eq!(heap.len(), 8);
+        assert_eq!(heap.peek(), Some(&9));
+
+        let mut popped = Vec::new();
+
+        while let Some(value) = heap.pop() {
+            assert_heap(&heap);
+            popped.push(value);
+        }
+
+        assert_eq!(popped, [9, 8, 8, 7, 5, 3, 2, 1]);
+        assert_eq!(heap.peek(), None);
+    }
+
+    #[test]
+    fn test_heapify_and_sort() {
+        let values: Vec<_> = (0..1_000).map(|value| (value * 7_919) % 1_000).collect();
+        let mut heap: BinaryHeap<_> = values.iter().copied().collect();
+
+        assert_heap(&heap);
+        assert_eq!(heap.iter().count(), 1_000);
+
+        heap.extend([2_000, -1]);
+        assert_heap(&heap);
+        assert_eq!(heap.peek(), Some(&2_000));
+
+        let sorted = heap.into_sorted_vector();
+        assert_eq!(sorted.len(), 1_002);
+        assert!(sorted.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn test_owned_values() {
+        let mut heap: BinaryHeap<String> = ["pear", "apple", "fig"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        heap.push(String::from("zucchini"));
+        assert_eq!(heap.pop().unwrap(), "zucchini");
+
+        heap.clear();
+        assert!(heap.is_empty());
+
+        heap.push(String::from("kiwi"));
+        assert_eq!(heap.into_vector().len(), 1);
+    }
+}
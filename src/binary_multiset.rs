@@ -0,0 +1,370 @@
+use crate::{
+    binary_tree::{self, BinaryTree},
+    heap_size::HeapSize,
+};
+
+/// Value stored in the underlying tree together with the number of times it
+/// has been inserted.
+struct Counted<T> {
+    value: T,
+    count: usize,
+}
+
+/// Sorted multiset backed by a [`BinaryTree`]. Duplicates don't take extra
+/// nodes, each node stores a value and how many copies of it there are.
+pub struct BinaryMultiSet<T> {
+    tree: BinaryTree<Counted<T>>,
+    len: usize,
+}
+
+impl<T> BinaryMultiSet<T> {
+    /// Creates an empty multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryMultiSet;
+    ///
+    /// let set: BinaryMultiSet<i32> = BinaryMultiSet::new();
+    ///
+    /// assert!(set.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            tree: BinaryTree::new(),
+            len: 0,
+        }
+    }
+
+    /// Returns the number of values in the multiset, counting duplicates.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryMultiSet;
+    ///
+    /// let set: BinaryMultiSet<i32> = [1, 1, 2].into_iter().collect();
+    ///
+    /// assert_eq!(set.len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of distinct values in the multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryMultiSet;
+    ///
+    /// let set: BinaryMultiSet<i32> = [1, 1, 2].into_iter().collect();
+    ///
+    /// assert_eq!(set.distinct_len(), 2);
+    /// ```
+    pub fn distinct_len(&self) -> usize {
+        self.tree.size()
+    }
+
+    /// Returns `true` if the multiset contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all the values from the multiset.
+    pub fn clear(&mut self) {
+        self.tree.clear();
+        self.len = 0;
+    }
+
+    /// Returns an iterator over the values in ascending order. Each value is
+    /// yielded as many times as it has been inserted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryMultiSet;
+    ///
+    /// let set: BinaryMultiSet<i32> = [2, 1, 2].into_iter().collect();
+    ///
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), vec![&1, &2, &2]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            counts: self.counts(),
+            current: None,
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over the distinct values in ascending order
+    /// together with their counts.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryMultiSet;
+    ///
+    /// let set: BinaryMultiSet<i32> = [2, 1, 2].into_iter().collect();
+    ///
+    /// assert_eq!(set.counts().collect::<Vec<_>>(), vec![(&1, 1), (&2, 2)]);
+    /// ```
+    pub fn counts(&self) -> Counts<'_, T> {
+        Counts(self.tree.iter())
+    }
+}
+
+impl<T: Ord> BinaryMultiSet<T> {
+    /// Inserts `value` into the multiset and returns how many copies of it
+    /// there are after the insertion.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryMultiSet;
+    ///
+    /// let mut set = BinaryMultiSet::new();
+    ///
+    /// assert_eq!(set.insert(1), 1);
+    /// assert_eq!(set.insert(1), 2);
+    /// ```
+    pub fn insert(&mut self, value: T) -> usize {
+        let counted = self.tree.get_or_insert_with_by(
+            value,
+            |value, node| value.cmp(&node.value),
+            |value| Counted { value, count: 0 },
+        );
+
+        counted.count += 1;
+        self.len += 1;
+
+        counted.count
+    }
+
+    /// Returns how many copies of `value` the multiset contains.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryMultiSet;
+    ///
+    /// let set: BinaryMultiSet<i32> = [1, 1, 2].into_iter().collect();
+    ///
+    /// assert_eq!(set.count_of(&1), 2);
+    /// assert_eq!(set.count_of(&3), 0);
+    /// ```
+    pub fn count_of(&self, value: &T) -> usize {
+        self.tree
+            .get_by(|node| value.cmp(&node.value))
+            .map_or(0, |counted| counted.count)
+    }
+
+    /// Returns `true` if the multiset contains at least one copy of `value`.
+    pub fn contains(&self, value: &T) -> bool {
+        self.count_of(value) > 0
+    }
+
+    /// Removes one copy of `value` and returns `true` unless the `value` is
+    /// not present.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryMultiSet;
+    ///
+    /// let mut set: BinaryMultiSet<i32> = [1, 1].into_iter().collect();
+    ///
+    /// assert!(set.remove_one(&1));
+    /// assert_eq!(set.count_of(&1), 1);
+    /// assert!(set.remove_one(&1));
+    /// assert!(!set.remove_one(&1));
+    /// ```
+    pub fn remove_one(&mut self, value: &T) -> bool {
+        let Some(counted) = self.tree.get_mut_by(|node| value.cmp(&node.value)) else {
+            return false;
+        };
+
+        if counted.count > 1 {
+            counted.count -= 1;
+        } else {
+            self.tree.remove_by(|node| value.cmp(&node.value));
+        }
+
+        self.len -= 1;
+
+        true
+    }
+
+    /// Removes every copy of `value` and returns how many were removed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryMultiSet;
+    ///
+    /// let mut set: BinaryMultiSet<i32> = [1, 1, 2].into_iter().collect();
+    ///
+    /// assert_eq!(set.remove_all(&1), 2);
+    /// assert_eq!(set.remove_all(&1), 0);
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn remove_all(&mut self, value: &T) -> usize {
+        let removed = self
+            .tree
+            .remove_by(|node| value.cmp(&node.value))
+            .map_or(0, |counted| counted.count);
+
+        self.len -= removed;
+
+        removed
+    }
+}
+
+impl<T> Default for BinaryMultiSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> Extend<T> for BinaryMultiSet<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for BinaryMultiSet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+
+        set
+    }
+}
+
+/// Iterator over the distinct values of a [`BinaryMultiSet`] and their counts.
+pub struct Counts<'a, T>(binary_tree::Iter<'a, Counted<T>>);
+
+impl<'a, T> Iterator for Counts<'a, T> {
+    type Item = (&'a T, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|counted| (&counted.value, counted.count))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+/// Iterator over the values of a [`BinaryMultiSet`], duplicates included.
+pub struct Iter<'a, T> {
+    counts: Counts<'a, T>,
+    current: Option<(&'a T, usize)>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (value, remaining) = match self.current.take() {
+            Some(current) => current,
+            None => self.counts.next()?,
+        };
+
+        if remaining > 1 {
+            self.current = Some((value, remaining - 1));
+        }
+
+        self.len -= 1;
+
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<T: HeapSize> HeapSize for Counted<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.value.heap_size_of_children()
+    }
+}
+
+impl<T: HeapSize> HeapSize for BinaryMultiSet<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.tree.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BinaryMultiSet;
+
+    #[test]
+    fn test_insert_and_count() {
+        let mut set = BinaryMultiSet::new();
+
+        for value in [5, 3, 5, 8, 3, 5] {
+            set.insert(value);
+        }
+
+        assert_eq!(set.len(), 6);
+        assert_eq!(set.distinct_len(), 3);
+        assert_eq!(set.count_of(&5), 3);
+        assert_eq!(set.count_of(&3), 2);
+        assert_eq!(set.count_of(&8), 1);
+        assert_eq!(set.count_of(&1), 0);
+        assert_eq!(
+            set.iter().copied().collect::<Vec<_>>(),
+            vec![3, 3, 5, 5, 5, 8]
+        );
+        assert_eq!(set.iter().len(), 6);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut set: BinaryMultiSet<i32> = [5, 3, 5, 8, 3, 5].into_iter().collect();
+
+        assert!(set.remove_one(&5));
+        assert_eq!(set.count_of(&5), 2);
+        assert_eq!(set.len(), 5);
+
+        assert!(set.remove_one(&8));
+        assert!(!set.contains(&8));
+        assert!(!set.remove_one(&8));
+        assert_eq!(set.distinct_len(), 2);
+
+        assert_eq!(set.remove_all(&5), 2);
+        assert_eq!(set.remove_all(&5), 0);
+        assert_eq!(set.counts().collect::<Vec<_>>(), vec![(&3, 2)]);
+        assert_eq!(set.len(), 2);
+
+        set.clear();
+        assert!(set.is_empty());
+        assert_eq!(set.iter().next(), None);
+    }
+
+    #[test]
+    fn test_owned_values() {
+        let mut set = BinaryMultiSet::new();
+
+        for word in ["b", "a", "b", "c", "a", "b"] {
+            set.insert(String::from(word));
+        }
+
+        assert_eq!(set.count_of(&String::from("b")), 3);
+        assert_eq!(set.remove_all(&String::from("a")), 2);
+        assert!(set.remove_one(&String::from("c")));
+        assert_eq!(set.iter().cloned().collect::<Vec<_>>(), vec!["b", "b", "b"]);
+    }
+}
@@ -1,102 +1,307 @@
-use std::{marker, ptr};
+use std::{
+    cmp::Ordering,
+    fmt,
+    iter::Peekable,
+    marker, mem,
+    ops::{Bound, RangeBounds},
+    ptr,
+};
+
+#[cfg(feature = "stats")]
+use crate::stats::Stats;
+use crate::{
+    allocator::{self, Allocator, Global},
+    arena::Arena,
+    compare::{Compare, Natural},
+    drop_guard,
+    heap_size::{self, HeapSize},
+    stats::Counters,
+    traits,
+    try_reserve_error::TryReserveError,
+};
 
 /// Binary tree node.
 struct Node<T> {
     left: Link<T>,
     right: Link<T>,
+    parent: Link<T>,
+    /// Number of nodes in the subtree rooted at this node, itself included.
+    size: usize,
     value: T,
 }
 
 /// Rusty pointer to a node.
 type Link<T> = Option<ptr::NonNull<Node<T>>>;
 
-/// Main binary tree struct.
-pub struct BinaryTree<T> {
+/// Result of descending the tree looking for a value.
+enum Search<T> {
+    /// The node that contains the value.
+    Found(ptr::NonNull<Node<T>>),
+
+    /// The value is not present. It belongs to the left (`Ordering::Less`) or
+    /// right (`Ordering::Greater`) child of the given parent, or to the root if
+    /// there's no parent.
+    Vacant(Link<T>, Ordering),
+}
+
+/// Creates a [`BinaryTree`] containing the arguments, inserted in the order
+/// they're written. Repeated values are only stored once.
+///
+/// # Examples
+///
+/// ```rust
+/// use collections_rust::binary_tree;
+///
+/// let tree = binary_tree! {4, 2, 6, 2};
+///
+/// assert_eq!(tree.size(), 3);
+/// assert!(tree.iter().eq(&[2, 4, 6]));
+/// ```
+#[macro_export]
+macro_rules! binary_tree {
+    ($($value:expr),* $(,)?) => {{
+        // Not mutated when the macro is called without values
+        #[allow(unused_mut)]
+        let mut tree = $crate::BinaryTree::new();
+        $(tree.insert($value);)*
+        tree
+    }};
+}
+
+/// Main binary tree struct. Values are ordered by the comparator `C`, which
+/// defaults to the [`Ord`] implementation of `T`. See [`Compare`].
+pub struct BinaryTree<T, C = Natural, A: Allocator = Global> {
     root: Link<T>,
     size: usize,
-    value_inserted: bool,
-    value_removed: bool,
+    comparator: C,
+    /// Where the nodes are allocated if the tree was created with an arena,
+    /// otherwise each node is a separate allocation of `alloc`.
+    arena: Option<Arena<Node<T>>>,
+    alloc: A,
+    stats: Counters,
     _marker: marker::PhantomData<T>,
 }
 
+// SAFETY: The raw node links are what make the tree `!Send`. Every node is
+// uniquely owned by the tree, whether it lives in its own allocation or in the
+// arena, and no link is shared with another tree or handed out past a borrow
+// of `self`, so moving the tree moves the values, the comparator and the
+// allocator along with it. That's sound as long as all of them are `Send`.
+unsafe impl<T: Send, C: Send, A: Allocator + Send> Send for BinaryTree<T, C, A> {}
+
 impl<T> Node<T> {
-    /// Allocates a new node and returns a `ptr::NonNull` to the node.
-    unsafe fn new_non_null(value: T, right: Link<T>, left: Link<T>) -> ptr::NonNull<Node<T>> {
-        ptr::NonNull::new_unchecked(Box::into_raw(Box::new(Node { right, left, value })))
+    /// Returns a node without links. See [`BinaryTree::new_node`].
+    fn leaf(value: T) -> Self {
+        Node {
+            right: None,
+            left: None,
+            parent: None,
+            size: 1,
+            value,
+        }
+    }
+
+    /// Returns the number of nodes in the given subtree.
+    unsafe fn size_of(link: Link<T>) -> usize {
+        link.map_or(0, |node| (*node.as_ptr()).size)
+    }
+
+    /// Recomputes the subtree size of `node` from the sizes of its children,
+    /// which must be up to date. Every function that relinks nodes calls this
+    /// bottom-up on the nodes whose subtrees changed.
+    unsafe fn update_size(node: ptr::NonNull<Node<T>>) {
+        (*node.as_ptr()).size =
+            1 + Node::size_of((*node.as_ptr()).left) + Node::size_of((*node.as_ptr()).right);
+    }
+
+    /// Adds `delta` nodes to the subtree sizes of `node` and its ancestors.
+    unsafe fn update_sizes_upwards(mut node: Link<T>, delta: isize) {
+        while let Some(current) = node {
+            (*current.as_ptr()).size = (*current.as_ptr()).size.wrapping_add_signed(delta);
+            node = (*current.as_ptr()).parent;
+        }
+    }
+
+    /// Makes `child` the left child of `node` and `node` the parent of `child`.
+    unsafe fn set_left(node: ptr::NonNull<Node<T>>, child: Link<T>) {
+        (*node.as_ptr()).left = child;
+
+        if let Some(child) = child {
+            (*child.as_ptr()).parent = Some(node);
+        }
+    }
+
+    /// Makes `child` the right child of `node` and `node` the parent of `child`.
+    unsafe fn set_right(node: ptr::NonNull<Node<T>>, child: Link<T>) {
+        (*node.as_ptr()).right = child;
+
+        if let Some(child) = child {
+            (*child.as_ptr()).parent = Some(node);
+        }
+    }
+
+    /// Returns the node that contains the minimum value of the given subtree.
+    unsafe fn leftmost(mut node: ptr::NonNull<Node<T>>) -> ptr::NonNull<Node<T>> {
+        while let Some(left) = (*node.as_ptr()).left {
+            node = left;
+        }
+
+        node
+    }
+
+    /// Returns the node that contains the maximum value of the given subtree.
+    unsafe fn rightmost(mut node: ptr::NonNull<Node<T>>) -> ptr::NonNull<Node<T>> {
+        while let Some(right) = (*node.as_ptr()).right {
+            node = right;
+        }
+
+        node
+    }
+
+    /// Returns the inorder successor of `node`. If it has a right subtree,
+    /// the successor is the minimum of that subtree. Otherwise it's the first
+    /// ancestor reached from its left subtree. Walking the whole tree with
+    /// this function visits every edge twice, so it's O(1) amortized.
+    unsafe fn successor(mut node: ptr::NonNull<Node<T>>) -> Link<T> {
+        if let Some(right) = (*node.as_ptr()).right {
+            return Some(Node::leftmost(right));
+        }
+
+        while let Some(parent) = (*node.as_ptr()).parent {
+            if (*parent.as_ptr()).left == Some(node) {
+                return Some(parent);
+            }
+
+            node = parent;
+        }
+
+        None
+    }
+
+    /// Returns the inorder predecessor of `node`, mirroring `successor`.
+    unsafe fn predecessor(mut node: ptr::NonNull<Node<T>>) -> Link<T> {
+        if let Some(left) = (*node.as_ptr()).left {
+            return Some(Node::rightmost(left));
+        }
+
+        while let Some(parent) = (*node.as_ptr()).parent {
+            if (*parent.as_ptr()).right == Some(node) {
+                return Some(parent);
+            }
+
+            node = parent;
+        }
+
+        None
     }
 }
 
-impl<T: Ord> BinaryTree<T> {
+impl<T> BinaryTree<T> {
     /// Creates a new binary tree. Doesn't allocate memory until first value
     /// is inserted.
-    pub fn new() -> Self {
-        Self {
-            size: 0,
-            root: None,
-            value_inserted: false,
-            value_removed: false,
-            _marker: marker::PhantomData,
-        }
+    pub const fn new() -> Self {
+        Self::new_with_comparator(Natural)
     }
 
-    /// Returns the current number of elements in the tree.
+    /// Creates a new binary tree whose nodes are allocated out of an arena
+    /// owned by the tree, `chunk_capacity` nodes at a time. Nodes allocated
+    /// together are close in memory, the slots of removed nodes are reused
+    /// and [`BinaryTree::clear`] or dropping the tree deallocates all the
+    /// chunks at once instead of node by node. Values still have to be
+    /// dropped one by one unless they don't need [`Drop`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_capacity` is 0.
     ///
     /// # Examples
     ///
     /// ```rust
     /// use collections_rust::BinaryTree;
     ///
-    /// let mut tree = BinaryTree::new();
+    /// let mut tree = BinaryTree::new_with_arena(64);
     ///
-    /// tree.insert(1);
-    /// assert_eq!(tree.size(), 1);
+    /// for value in 0..100 {
+    ///     tree.insert(value);
+    /// }
+    ///
+    /// tree.remove(&50);
+    /// assert_eq!(tree.size(), 99);
+    ///
+    /// tree.clear();
+    /// assert!(tree.is_empty());
     /// ```
-    pub fn size(&self) -> usize {
-        self.size
+    pub fn new_with_arena(chunk_capacity: usize) -> Self {
+        Self::new_with_comparator_and_arena(Natural, chunk_capacity)
     }
+}
 
-    /// Returns `true` if the tree contains no values.
+impl<T, C> BinaryTree<T, C> {
+    /// Creates a new binary tree that orders its values with `comparator`
+    /// instead of their [`Ord`] implementation. Values that are not [`Ord`]
+    /// can be stored this way.
     ///
     /// # Examples
     ///
     /// ```rust
     /// use collections_rust::BinaryTree;
     ///
-    /// let mut tree = BinaryTree::new();
+    /// let mut tree = BinaryTree::new_with_comparator(|a: &String, b: &String| {
+    ///     a.to_lowercase().cmp(&b.to_lowercase())
+    /// });
     ///
-    /// tree.insert(1);
-    /// assert!(!tree.is_empty());
+    /// tree.insert(String::from("Hello"));
     ///
-    /// tree.remove(&1);
-    /// assert!(tree.is_empty());
+    /// assert!(!tree.insert(String::from("HELLO")));
+    /// assert!(tree.contains(&String::from("hello")));
     /// ```
-    pub fn is_empty(&self) -> bool {
-        self.size == 0
+    pub const fn new_with_comparator(comparator: C) -> Self {
+        Self::new_with_comparator_in(comparator, Global)
     }
 
-    /// Recursive function for inserting nodes in the tree. The funciton allways
-    /// returns a node to the caller, either the current node or the new inserted
-    /// node.
-    unsafe fn insert_recursively(&mut self, mut current: Link<T>, value: T) -> Link<T> {
-        if let Some(node) = current {
-            if value < (*node.as_ptr()).value {
-                (*node.as_ptr()).left = self.insert_recursively((*node.as_ptr()).left, value);
-            } else if value > (*node.as_ptr()).value {
-                (*node.as_ptr()).right = self.insert_recursively((*node.as_ptr()).right, value);
-            } else {
-                self.value_inserted = false;
-            }
-        } else {
-            current = Some(Node::new_non_null(value, None, None));
-            self.value_inserted = true;
-            self.size += 1;
+    /// Creates a new binary tree ordered by `comparator` whose nodes are
+    /// allocated out of an arena owned by the tree, see
+    /// [`BinaryTree::new_with_arena`].
+    pub fn new_with_comparator_and_arena(comparator: C, chunk_capacity: usize) -> Self {
+        let mut tree = Self::new_with_comparator(comparator);
+        tree.arena = Some(Arena::new(chunk_capacity));
+
+        tree
+    }
+}
+
+impl<T, A: Allocator> BinaryTree<T, Natural, A> {
+    /// Creates a new binary tree whose nodes are allocated by `alloc`. See
+    /// [`Allocator`] for an example. Like with [`BinaryTree::new`], nothing
+    /// is allocated until the first value is inserted.
+    pub const fn new_in(alloc: A) -> Self {
+        Self::new_with_comparator_in(Natural, alloc)
+    }
+}
+
+impl<T, C, A: Allocator> BinaryTree<T, C, A> {
+    /// Creates a new binary tree ordered by `comparator` whose nodes are
+    /// allocated by `alloc`, see [`BinaryTree::new_in`].
+    pub const fn new_with_comparator_in(comparator: C, alloc: A) -> Self {
+        Self {
+            size: 0,
+            root: None,
+            comparator,
+            arena: None,
+            alloc,
+            stats: Counters::new(),
+            _marker: marker::PhantomData,
         }
+    }
 
-        current
+    /// Returns the allocator of the tree.
+    pub fn allocator(&self) -> &A {
+        &self.alloc
     }
 
-    /// Adds the given `value` to the tree and returns `true` unless it is
-    /// already present.
+    /// Returns the counters of the tree, see [`Stats`]. `pointer_walks`
+    /// counts the links followed while looking values up, so it grows with
+    /// the height of the tree.
     ///
     /// # Examples
     ///
@@ -105,35 +310,68 @@ impl<T: Ord> BinaryTree<T> {
     ///
     /// let mut tree = BinaryTree::new();
     ///
-    /// tree.insert(1);
-    /// assert!(tree.contains(&1));
+    /// // Sorted insertions degenerate into a list
+    /// for value in 0..4 {
+    ///     tree.insert(value);
+    /// }
+    ///
+    /// let before = tree.stats().pointer_walks;
+    /// assert!(tree.contains(&3));
+    /// assert_eq!(tree.stats().pointer_walks - before, 3);
+    /// assert_eq!(tree.stats().allocations, 4);
     /// ```
-    pub fn insert(&mut self, value: T) -> bool {
-        unsafe {
-            self.root = self.insert_recursively(self.root, value);
-        }
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> Stats {
+        self.stats.get()
+    }
 
-        self.value_inserted
+    /// Allocates a new node with the given children, in the arena if the tree
+    /// has one, and returns a `ptr::NonNull` to the node.
+    unsafe fn new_node(
+        &mut self,
+        value: T,
+        right: Link<T>,
+        left: Link<T>,
+    ) -> ptr::NonNull<Node<T>> {
+        let node = match &mut self.arena {
+            Some(arena) => arena.alloc(Node::leaf(value)),
+            None => allocator::new_in(Node::leaf(value), &self.alloc),
+        };
+        self.stats.record_allocation();
+
+        Node::set_left(node, left);
+        Node::set_right(node, right);
+        Node::update_size(node);
+
+        node
     }
 
-    /// Returns `true` if the node that contains `value` can be located.
-    unsafe fn search(&self, current: Link<T>, value: &T) -> bool {
-        match current {
-            None => false,
+    /// Allocates a new node without children like `new_node`, but returns an
+    /// error instead of aborting if there's no memory.
+    unsafe fn try_new_leaf(&mut self, value: T) -> Result<ptr::NonNull<Node<T>>, TryReserveError> {
+        let node = match &mut self.arena {
+            Some(arena) => arena.try_alloc(Node::leaf(value)),
+            None => allocator::try_new_in(Node::leaf(value), &self.alloc),
+        }?;
+        self.stats.record_allocation();
 
-            Some(node) => {
-                if value < &(*node.as_ptr()).value {
-                    self.search((*node.as_ptr()).left, value)
-                } else if value > &(*node.as_ptr()).value {
-                    self.search((*node.as_ptr()).right, value)
-                } else {
-                    true
-                }
-            }
+        Ok(node)
+    }
+
+    /// Deallocates `node` and returns its contents.
+    unsafe fn free_node(&mut self, node: ptr::NonNull<Node<T>>) -> Node<T> {
+        match &mut self.arena {
+            Some(arena) => arena.free(node),
+            None => allocator::take_in(node, &self.alloc),
         }
     }
 
-    /// Returns `true` if `value` is present in the tree.
+    /// Returns a reference to the comparator that orders the values.
+    pub fn comparator(&self) -> &C {
+        &self.comparator
+    }
+
+    /// Returns the current number of elements in the tree.
     ///
     /// # Examples
     ///
@@ -143,100 +381,33 @@ impl<T: Ord> BinaryTree<T> {
     /// let mut tree = BinaryTree::new();
     ///
     /// tree.insert(1);
-    /// assert!(tree.contains(&1));
-    /// assert!(!tree.contains(&2));
+    /// assert_eq!(tree.size(), 1);
     /// ```
-    pub fn contains(&self, value: &T) -> bool {
-        unsafe { self.search(self.root, value) }
-    }
-
-    /// Returns a pointer to the parent node of the node that contains the
-    /// minimum value in the given subtree. Used for searching inorder successors.
-    unsafe fn min_value_parent_node(&self, node: ptr::NonNull<Node<T>>) -> Link<T> {
-        match (*node.as_ptr()).left {
-            None => None,
-
-            Some(subnode) => match (*subnode.as_ptr()).left {
-                None => Some(node),
-                Some(_) => self.min_value_parent_node(subnode),
-            },
-        }
+    pub fn size(&self) -> usize {
+        self.size
     }
 
-    /// Performs the binary tree node removal algorithm:
+    /// Returns `true` if the tree contains no values.
     ///
-    /// - Find the node that contains `value`.
+    /// # Examples
     ///
-    /// - If the node only has one child, deallocate the node and make the parent
-    /// point to the child.
+    /// ```rust
+    /// use collections_rust::BinaryTree;
     ///
-    /// - If the node has no children just make the parent point to any of its
-    /// non-existent children (set the `Link<T>` to `None`).
+    /// let mut tree = BinaryTree::new();
     ///
-    /// - If the node has two children, locate the inorder successor of the
-    /// current node in the right subtree, swap the values, deallocate the
-    /// successor and make the successor parent point to `None`. The case where
-    /// the inorder successor parent is the root node has to be considered.
-    ///
-    /// Just like `insert_recursively`, a `Link<T>` will allways be returned
-    /// to the caller. This simplifies the amount of cases we have to deal with.
-    unsafe fn remove_recursively(&mut self, current: Link<T>, value: &T) -> Link<T> {
-        // Not found
-        if current.is_none() {
-            self.value_removed = false;
-            return None;
-        }
-
-        // Search node
-        let node = current.unwrap();
-
-        if value < &(*node.as_ptr()).value {
-            (*node.as_ptr()).left = self.remove_recursively((*node.as_ptr()).left, value);
-            return current;
-        }
-        if value > &(*node.as_ptr()).value {
-            (*node.as_ptr()).right = self.remove_recursively((*node.as_ptr()).right, value);
-            return current;
-        }
-
-        // Found
-        self.value_removed = true;
-        self.size -= 1;
-
-        // Node has only one child or none
-        let mut replacement_node = None;
-        if (*node.as_ptr()).left.is_none() {
-            replacement_node = Some((*node.as_ptr()).right);
-        } else if (*node.as_ptr()).right.is_none() {
-            replacement_node = Some((*node.as_ptr()).left);
-        }
-        if replacement_node.is_some() {
-            drop(Box::from_raw(node.as_ptr()));
-            return replacement_node.unwrap();
-        }
-
-        // Node has two children
-        let node_to_be_dropped;
-
-        if let Some(parent) = self.min_value_parent_node((*node.as_ptr()).right.unwrap()) {
-            node_to_be_dropped = (*parent.as_ptr()).left.unwrap();
-            let left = ptr::read(node_to_be_dropped.as_ptr());
-            (*node.as_ptr()).value = left.value;
-            (*parent.as_ptr()).left = left.right;
-        } else {
-            node_to_be_dropped = (*node.as_ptr()).right.unwrap();
-            let right = ptr::read(node_to_be_dropped.as_ptr());
-            (*node.as_ptr()).value = right.value;
-            (*node.as_ptr()).right = right.right;
-        }
-
-        drop(Box::from_raw(node_to_be_dropped.as_ptr()));
-
-        current
+    /// tree.insert(1);
+    /// assert!(!tree.is_empty());
+    ///
+    /// tree.remove(&1);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
     }
 
-    /// Removes the `value` from the tree and returns `true` unless the `value`
-    /// is not present.
+    /// Removes all the values from the tree and deallocates the nodes. The
+    /// tree can be reused afterwards.
     ///
     /// # Examples
     ///
@@ -247,208 +418,4892 @@ impl<T: Ord> BinaryTree<T> {
     ///
     /// tree.insert(1);
     /// tree.insert(2);
-    /// tree.insert(3);
-    ///
-    /// tree.remove(&2);
+    /// tree.clear();
     ///
-    /// assert!(!tree.contains(&2));
+    /// assert!(tree.is_empty());
+    /// assert!(!tree.contains(&1));
     /// ```
-    pub fn remove(&mut self, value: &T) -> bool {
+    pub fn clear(&mut self) {
+        // Emptied first, so the tree is still valid if dropping a value panics
+        let root = self.root.take();
+        self.size = 0;
+
         unsafe {
-            self.root = self.remove_recursively(self.root, value);
+            self.drop_all(root);
         }
-
-        self.value_removed
     }
 }
 
-impl<T> BinaryTree<T> {
-    /// Drop the left subtree, drop the right subtree and then drop the root.
-    unsafe fn drop_recursively(&mut self, current: Link<T>) {
-        if let Some(node) = current {
-            self.drop_recursively((*node.as_ptr()).left);
-            self.drop_recursively((*node.as_ptr()).right);
-            drop(Box::from_raw(node.as_ptr()));
+impl<T, C, A: Allocator> BinaryTree<T, C, A> {
+    /// Descends from the root guided by `cmp`, which compares the searched
+    /// value with the value of each visited node. This allows lookups that
+    /// only compare part of `T`, like a key or a wrapped value.
+    unsafe fn search_by<F: FnMut(&T) -> Ordering>(&self, mut cmp: F) -> Search<T> {
+        let mut parent = None;
+        let mut side = Ordering::Equal;
+        let mut current = self.root;
+        let mut links = 0;
+
+        while let Some(node) = current {
+            side = cmp(&(*node.as_ptr()).value);
+
+            current = match side {
+                Ordering::Less => (*node.as_ptr()).left,
+                Ordering::Greater => (*node.as_ptr()).right,
+                Ordering::Equal => {
+                    self.stats.record_walk(links);
+                    return Search::Found(node);
+                }
+            };
+
+            parent = Some(node);
+            links += 1;
         }
+
+        self.stats.record_walk(links.saturating_sub(1));
+
+        Search::Vacant(parent, side)
     }
-}
 
-impl<T> Drop for BinaryTree<T> {
-    fn drop(&mut self) {
-        unsafe { self.drop_recursively(self.root) }
+    /// Allocates a new node for `value` and attaches it where a previous call
+    /// to `search_by` ended.
+    unsafe fn insert_at(
+        &mut self,
+        parent: Link<T>,
+        side: Ordering,
+        value: T,
+    ) -> ptr::NonNull<Node<T>> {
+        let node = self.new_node(value, None, None);
+        self.attach(parent, side, node);
+
+        node
     }
-}
 
-pub struct Iter<'a, T> {
-    values: Vec<&'a T>,
-    current_index: usize,
-}
+    /// Links the new leaf `node` as the child of `parent` on `side`, or as the
+    /// root if `parent` is `None`.
+    unsafe fn attach(&mut self, parent: Link<T>, side: Ordering, node: ptr::NonNull<Node<T>>) {
+        match parent {
+            None => self.set_root(Some(node)),
+            Some(parent) if side == Ordering::Less => Node::set_left(parent, Some(node)),
+            Some(parent) => Node::set_right(parent, Some(node)),
+        }
 
-impl<T> BinaryTree<T> {
-    /// Fills `values` vector using inorder traversal.
-    unsafe fn push_values_inorder(&self, current: Link<T>, values: &mut Vec<&T>) {
-        if let Some(node) = current {
-            self.push_values_inorder((*node.as_ptr()).left, values);
-            values.push(&(*node.as_ptr()).value);
-            self.push_values_inorder((*node.as_ptr()).right, values);
+        Node::update_sizes_upwards(parent, 1);
+        self.size += 1;
+        self.stats.record_len(self.size);
+    }
+
+    /// Returns the value for which `cmp` returns `Ordering::Equal`. `cmp` must
+    /// be consistent with the order of the values in the tree.
+    #[cfg(any(feature = "binary_multiset", feature = "tree_map"))]
+    pub(crate) fn get_by<F: FnMut(&T) -> Ordering>(&self, cmp: F) -> Option<&T> {
+        unsafe {
+            match self.search_by(cmp) {
+                Search::Found(node) => Some(&(*node.as_ptr()).value),
+                Search::Vacant(..) => None,
+            }
         }
     }
 
-    /// Returns an iterator over the values contained in the tree.
-    ///
-    /// # Examples
-    ///
-    /// ```rust
-    /// use collections_rust::BinaryTree;
-    ///
-    /// let mut tree = BinaryTree::new();
-    ///
-    /// tree.insert(3);
-    /// tree.insert(1);
-    /// tree.insert(2);
-    ///
-    /// let mut expected = 1;
-    /// for value in tree.iter() {
-    ///     assert_eq!(value, &expected);
-    ///     expected += 1;
-    /// }
-    /// ```
-    pub fn iter(&self) -> Iter<T> {
-        let mut values = Vec::with_capacity(self.size);
+    /// Mutable version of `get_by`. The value must not be modified in a way
+    /// that changes its order.
+    #[cfg(any(feature = "binary_multiset", feature = "tree_map"))]
+    pub(crate) fn get_mut_by<F: FnMut(&T) -> Ordering>(&mut self, cmp: F) -> Option<&mut T> {
+        unsafe {
+            match self.search_by(cmp) {
+                Search::Found(node) => Some(&mut (*node.as_ptr()).value),
+                Search::Vacant(..) => None,
+            }
+        }
+    }
+
+    /// Returns the value that compares equal to `key` according to `cmp`,
+    /// inserting the one built by `make` from the `key` if it's not present.
+    /// Only one descent is needed in both cases. The value returned by `make`
+    /// must compare equal to `key`.
+    #[cfg(any(feature = "binary_multiset", feature = "tree_map"))]
+    pub(crate) fn get_or_insert_with_by<K, F, M>(&mut self, key: K, mut cmp: F, make: M) -> &mut T
+    where
+        F: FnMut(&K, &T) -> Ordering,
+        M: FnOnce(K) -> T,
+    {
+        match self.slot_by(|value| cmp(&key, value)) {
+            Slot::Occupied(slot) => slot.into_mut(),
+            Slot::Vacant(slot) => slot.insert(make(key)),
+        }
+    }
 
+    /// Locates the position of the value for which `cmp` returns
+    /// `Ordering::Equal`. The returned slot can then read, replace or remove
+    /// the value, or insert a new one if it's vacant, without descending
+    /// again.
+    #[cfg(any(feature = "binary_multiset", feature = "tree_map"))]
+    pub(crate) fn slot_by<F: FnMut(&T) -> Ordering>(&mut self, cmp: F) -> Slot<'_, T, C, A> {
         unsafe {
-            self.push_values_inorder(self.root, &mut values);
+            match self.search_by(cmp) {
+                Search::Found(node) => Slot::Occupied(OccupiedSlot { tree: self, node }),
+                Search::Vacant(parent, side) => Slot::Vacant(VacantSlot {
+                    tree: self,
+                    parent,
+                    side,
+                }),
+            }
         }
+    }
 
-        Iter {
-            values,
-            current_index: 0,
+    /// Removes and returns the value for which `cmp` returns
+    /// `Ordering::Equal`.
+    #[cfg(any(feature = "binary_multiset", feature = "tree_map"))]
+    pub(crate) fn remove_by<F: FnMut(&T) -> Ordering>(&mut self, cmp: F) -> Option<T> {
+        unsafe {
+            match self.search_by(cmp) {
+                Search::Found(node) => Some(self.remove_node(node).0),
+                Search::Vacant(..) => None,
+            }
         }
     }
 }
 
-impl<'a, T> Iterator for Iter<'a, T> {
-    type Item = &'a T;
+/// Position in the tree returned by `BinaryTree::slot_by`.
+#[cfg(any(feature = "binary_multiset", feature = "tree_map"))]
+pub(crate) enum Slot<'a, T, C, A: Allocator = Global> {
+    Occupied(OccupiedSlot<'a, T, C, A>),
+    Vacant(VacantSlot<'a, T, C, A>),
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.current_index == self.values.len() {
-            return None;
-        }
+/// Node that contains the searched value.
+#[cfg(any(feature = "binary_multiset", feature = "tree_map"))]
+pub(crate) struct OccupiedSlot<'a, T, C, A: Allocator = Global> {
+    // Only read to remove the value, which the multiset never does
+    #[cfg_attr(not(feature = "tree_map"), allow(dead_code))]
+    tree: &'a mut BinaryTree<T, C, A>,
+    node: ptr::NonNull<Node<T>>,
+}
 
-        let value = self.values[self.current_index];
+/// Place where the searched value would be attached.
+#[cfg(any(feature = "binary_multiset", feature = "tree_map"))]
+pub(crate) struct VacantSlot<'a, T, C, A: Allocator = Global> {
+    tree: &'a mut BinaryTree<T, C, A>,
+    parent: Link<T>,
+    side: Ordering,
+}
 
-        self.current_index += 1;
+#[cfg(any(feature = "binary_multiset", feature = "tree_map"))]
+impl<'a, T, C, A: Allocator> OccupiedSlot<'a, T, C, A> {
+    #[cfg(feature = "tree_map")]
+    pub(crate) fn get(&self) -> &T {
+        unsafe { &(*self.node.as_ptr()).value }
+    }
 
-        Some(value)
+    #[cfg(feature = "tree_map")]
+    pub(crate) fn get_mut(&mut self) -> &mut T {
+        unsafe { &mut (*self.node.as_ptr()).value }
     }
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        let remaining = self.values.len() - self.current_index;
+    pub(crate) fn into_mut(self) -> &'a mut T {
+        unsafe { &mut (*self.node.as_ptr()).value }
+    }
 
-        (remaining, Some(remaining))
+    #[cfg(feature = "tree_map")]
+    pub(crate) fn remove(self) -> T {
+        unsafe { self.tree.remove_node(self.node).0 }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::BinaryTree;
+#[cfg(any(feature = "binary_multiset", feature = "tree_map"))]
+impl<'a, T, C, A: Allocator> VacantSlot<'a, T, C, A> {
+    /// Inserts `value`, which must belong to this position in the order.
+    pub(crate) fn insert(self, value: T) -> &'a mut T {
+        unsafe {
+            let node = self.tree.insert_at(self.parent, self.side, value);
 
-    fn tree_values() -> Vec<i32> {
-        vec![20, 10, 30, 5, 15, 16, 14, 25, 21, 35, 40, 50]
+            &mut (*node.as_ptr()).value
+        }
     }
+}
 
-    #[test]
-    fn test_insert() {
-        let numbers = tree_values();
+impl<T, C: Default> Default for BinaryTree<T, C> {
+    fn default() -> Self {
+        Self::new_with_comparator(C::default())
+    }
+}
 
-        let mut tree = BinaryTree::new();
+impl<T, C, A: Allocator> traits::Collection for BinaryTree<T, C, A> {
+    fn len(&self) -> usize {
+        self.size()
+    }
+}
 
-        tree.insert(numbers[0]);
-        assert!(tree.contains(&numbers[0]));
+impl<T, C: Compare<T>, A: Allocator> traits::Set<T> for BinaryTree<T, C, A> {
+    fn insert(&mut self, value: T) -> bool {
+        BinaryTree::insert(self, value)
+    }
 
-        tree.insert(numbers[1]);
-        assert!(tree.contains(&numbers[1]));
+    fn contains(&self, value: &T) -> bool {
+        BinaryTree::contains(self, value)
+    }
 
-        tree.insert(numbers[2]);
-        assert!(tree.contains(&numbers[2]));
+    fn remove(&mut self, value: &T) -> bool {
+        BinaryTree::remove(self, value)
+    }
+}
+
+impl<T, C: Compare<T>, A: Allocator> traits::SortedSet<T> for BinaryTree<T, C, A> {
+    fn first(&self) -> Option<&T> {
+        self.iter().next()
+    }
+
+    fn last(&self) -> Option<&T> {
+        self.iter().next_back()
+    }
+}
+
+impl<T: HeapSize, C, A: Allocator> HeapSize for BinaryTree<T, C, A> {
+    fn heap_size_of_children(&self) -> usize {
+        let nodes = match &self.arena {
+            Some(arena) => arena.heap_size(),
+            None => self.size * mem::size_of::<Node<T>>(),
+        };
+
+        nodes + heap_size::children(self.iter())
+    }
+}
+
+impl<T, C: Compare<T>, A: Allocator> BinaryTree<T, C, A> {
+    /// Descends from the root looking for the node that contains `value`.
+    unsafe fn find_node(&self, value: &T) -> Link<T> {
+        match self.search_by(|node_value| self.comparator.compare(value, node_value)) {
+            Search::Found(node) => Some(node),
+            Search::Vacant(..) => None,
+        }
+    }
+
+    /// Returns the node that contains the smallest value greater than or
+    /// equal to `value`.
+    unsafe fn lower_bound_node(&self, value: &T) -> Link<T> {
+        let mut candidate = None;
+        let mut current = self.root;
+
+        while let Some(node) = current {
+            match self.comparator.compare(value, &(*node.as_ptr()).value) {
+                Ordering::Less => {
+                    candidate = current;
+                    current = (*node.as_ptr()).left;
+                }
+                Ordering::Greater => current = (*node.as_ptr()).right,
+                Ordering::Equal => return current,
+            }
+        }
+
+        candidate
+    }
+
+    /// Adds the given `value` to the tree and returns `true` unless it is
+    /// already present.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// tree.insert(1);
+    /// assert!(tree.contains(&1));
+    /// ```
+    pub fn insert(&mut self, value: T) -> bool {
+        unsafe {
+            match self.search_by(|node_value| self.comparator.compare(&value, node_value)) {
+                Search::Found(_) => false,
+
+                Search::Vacant(parent, side) => {
+                    self.insert_at(parent, side, value);
+                    true
+                }
+            }
+        }
+    }
+
+    /// Like [`BinaryTree::insert`], but returns an error instead of aborting
+    /// if the node can't be allocated. `value` is dropped in that case.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryReserveError`] if the allocator fails, and leaves the
+    /// tree unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// assert_eq!(tree.try_insert(1), Ok(true));
+    /// assert_eq!(tree.try_insert(1), Ok(false));
+    /// ```
+    pub fn try_insert(&mut self, value: T) -> Result<bool, TryReserveError> {
+        unsafe {
+            match self.search_by(|node_value| self.comparator.compare(&value, node_value)) {
+                Search::Found(_) => Ok(false),
+
+                Search::Vacant(parent, side) => {
+                    let node = self.try_new_leaf(value)?;
+                    self.attach(parent, side, node);
+
+                    Ok(true)
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if `value` is present in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// tree.insert(1);
+    /// assert!(tree.contains(&1));
+    /// assert!(!tree.contains(&2));
+    /// ```
+    pub fn contains(&self, value: &T) -> bool {
+        unsafe { self.find_node(value).is_some() }
+    }
+
+    /// Returns the smallest value in the tree that is greater than `value`,
+    /// whether `value` itself is present or not. When `value` is found, its
+    /// successor is reached through the parent links without searching again.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// tree.insert(10);
+    /// tree.insert(20);
+    /// tree.insert(30);
+    ///
+    /// assert_eq!(tree.next_after(&10), Some(&20));
+    /// assert_eq!(tree.next_after(&25), Some(&30));
+    /// assert_eq!(tree.next_after(&30), None);
+    /// ```
+    pub fn next_after(&self, value: &T) -> Option<&T> {
+        unsafe {
+            let mut candidate = None;
+            let mut current = self.root;
+
+            while let Some(node) = current {
+                match self.comparator.compare(value, &(*node.as_ptr()).value) {
+                    Ordering::Less => {
+                        candidate = current;
+                        current = (*node.as_ptr()).left;
+                    }
+                    Ordering::Greater => current = (*node.as_ptr()).right,
+                    Ordering::Equal => {
+                        candidate = Node::successor(node);
+                        break;
+                    }
+                }
+            }
+
+            candidate.map(|node| &(*node.as_ptr()).value)
+        }
+    }
+
+    /// Returns the number of edges between the root and the node that
+    /// contains `value`, or `None` if `value` is not present in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// tree.insert(2);
+    /// tree.insert(1);
+    /// tree.insert(3);
+    /// tree.insert(4);
+    ///
+    /// assert_eq!(tree.depth_of(&2), Some(0));
+    /// assert_eq!(tree.depth_of(&3), Some(1));
+    /// assert_eq!(tree.depth_of(&4), Some(2));
+    /// assert_eq!(tree.depth_of(&5), None);
+    /// ```
+    pub fn depth_of(&self, value: &T) -> Option<usize> {
+        let mut current = self.root;
+        let mut depth = 0;
+
+        unsafe {
+            while let Some(node) = current {
+                current = match self.comparator.compare(value, &(*node.as_ptr()).value) {
+                    Ordering::Less => (*node.as_ptr()).left,
+                    Ordering::Greater => (*node.as_ptr()).right,
+                    Ordering::Equal => return Some(depth),
+                };
+
+                depth += 1;
+            }
+        }
+
+        None
+    }
+
+    /// Returns the deepest node that has both `a` and `b` in its subtree,
+    /// which is the first node where the searches for `a` and `b` take
+    /// different directions. Both values must be present in the tree.
+    unsafe fn common_ancestor_node(&self, a: &T, b: &T) -> Link<T> {
+        let mut current = self.root;
+
+        while let Some(node) = current {
+            let value = &(*node.as_ptr()).value;
+
+            current = match (
+                self.comparator.compare(a, value),
+                self.comparator.compare(b, value),
+            ) {
+                (Ordering::Less, Ordering::Less) => (*node.as_ptr()).left,
+                (Ordering::Greater, Ordering::Greater) => (*node.as_ptr()).right,
+                _ => return Some(node),
+            };
+        }
+
+        None
+    }
+
+    /// Returns the value of the lowest common ancestor of `a` and `b`, the
+    /// deepest node that has both values in its subtree (a node is in its
+    /// own subtree). Returns `None` unless both values are present. Runs in
+    /// O(h) where h is the height of the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// for value in [4, 2, 6, 1, 3, 5] {
+    ///     tree.insert(value);
+    /// }
+    ///
+    /// assert_eq!(tree.lowest_common_ancestor(&1, &3), Some(&2));
+    /// assert_eq!(tree.lowest_common_ancestor(&3, &5), Some(&4));
+    /// assert_eq!(tree.lowest_common_ancestor(&6, &5), Some(&6));
+    /// assert_eq!(tree.lowest_common_ancestor(&1, &7), None);
+    /// ```
+    pub fn lowest_common_ancestor(&self, a: &T, b: &T) -> Option<&T> {
+        unsafe {
+            if self.find_node(a).is_none() || self.find_node(b).is_none() {
+                return None;
+            }
+
+            self.common_ancestor_node(a, b)
+                .map(|node| &(*node.as_ptr()).value)
+        }
+    }
+
+    /// Returns the values on the path between the nodes of `a` and `b`, both
+    /// included: up from `a` to their lowest common ancestor and then down to
+    /// `b`. Returns `None` unless both values are present. Runs in O(h)
+    /// where h is the height of the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// for value in [4, 2, 6, 1, 3, 5] {
+    ///     tree.insert(value);
+    /// }
+    ///
+    /// assert_eq!(tree.path_between(&1, &5), Some(vec![&1, &2, &4, &6, &5]));
+    /// assert_eq!(tree.path_between(&3, &3), Some(vec![&3]));
+    /// assert_eq!(tree.path_between(&3, &7), None);
+    /// ```
+    pub fn path_between(&self, a: &T, b: &T) -> Option<Vec<&T>> {
+        unsafe {
+            let (Some(a_node), Some(b_node)) = (self.find_node(a), self.find_node(b)) else {
+                return None;
+            };
+
+            let ancestor = self.common_ancestor_node(a, b)?;
+            let mut path = Vec::new();
+
+            // Up from `a`, then up from `b` reversed
+            for (mut current, reversed) in [(a_node, false), (b_node, true)] {
+                let start = path.len();
+
+                while current != ancestor {
+                    path.push(&(*current.as_ptr()).value);
+                    current = (*current.as_ptr()).parent.unwrap();
+                }
+
+                if reversed {
+                    path[start..].reverse();
+                } else {
+                    path.push(&(*ancestor.as_ptr()).value);
+                }
+            }
+
+            Some(path)
+        }
+    }
+
+    /// Returns a pointer to the parent node of the node that contains the
+    /// minimum value in the given subtree. Used for searching inorder successors.
+    unsafe fn min_value_parent_node(&self, node: ptr::NonNull<Node<T>>) -> Link<T> {
+        let mut parent = None;
+        let mut current = node;
+
+        while let Some(left) = (*current.as_ptr()).left {
+            parent = Some(current);
+            current = left;
+        }
+
+        parent
+    }
+
+    /// Removes the `value` from the tree and returns `true` unless the `value`
+    /// is not present.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// tree.insert(1);
+    /// tree.insert(2);
+    /// tree.insert(3);
+    ///
+    /// tree.remove(&2);
+    ///
+    /// assert!(!tree.contains(&2));
+    /// ```
+    pub fn remove(&mut self, value: &T) -> bool {
+        unsafe {
+            match self.find_node(value) {
+                None => false,
+
+                Some(node) => {
+                    self.remove_node(node);
+                    true
+                }
+            }
+        }
+    }
+
+    /// Joins two subtrees where all the values in `left` are less than all
+    /// the values in `right`. The minimum value of `right` becomes the root of
+    /// the joined subtree.
+    unsafe fn join(&self, left: Link<T>, right: Link<T>) -> Link<T> {
+        let (Some(_), Some(right_root)) = (left, right) else {
+            return left.or(right);
+        };
+
+        if let Some(parent) = self.min_value_parent_node(right_root) {
+            let min = (*parent.as_ptr()).left.unwrap();
+            Node::set_left(parent, (*min.as_ptr()).right);
+
+            // Every node between the old parent of `min` and the root of
+            // `right` has lost `min` from its subtree.
+            let mut current = parent;
+            Node::update_size(current);
+
+            while current != right_root {
+                current = (*current.as_ptr()).parent.unwrap();
+                Node::update_size(current);
+            }
+
+            Node::set_left(min, left);
+            Node::set_right(min, right);
+            Node::update_size(min);
+
+            Some(min)
+        } else {
+            Node::set_left(right_root, left);
+            Node::update_size(right_root);
+
+            right
+        }
+    }
+
+    /// Removes the values for which `keep` returns `false`. The nodes are
+    /// collected in order by following the parent links and the kept ones are
+    /// relinked into a balanced tree with `link_balanced`, so this works on
+    /// degenerate trees without recursing on their height. `keep` is called
+    /// in order and removed values are handed to `removed` in ascending
+    /// order. Neither of them may panic, nodes are freed halfway through.
+    unsafe fn filter_nodes<F, R>(&mut self, keep: &mut F, removed: &mut R)
+    where
+        F: FnMut(&T) -> bool,
+        R: FnMut(T),
+    {
+        let mut nodes = Vec::with_capacity(self.size);
+        let mut current = self.root.map(|root| Node::leftmost(root));
+
+        while let Some(node) = current {
+            current = Node::successor(node);
+            nodes.push(node);
+        }
+
+        // Nodes can only be freed once the traversal no longer needs their
+        // parent links
+        nodes.retain(|&node| {
+            if keep(&(*node.as_ptr()).value) {
+                return true;
+            }
+
+            let node = self.free_node(node);
+            self.size -= 1;
+            removed(node.value);
+
+            false
+        });
+
+        self.set_root(Self::link_balanced(&nodes));
+    }
+
+    /// Retains only the values for which `keep` returns `true`. `keep` is
+    /// called once per value in ascending order before any of them is
+    /// removed, so the tree is left untouched if it panics. The remaining
+    /// values are relinked into a balanced tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// for value in 0..10 {
+    ///     tree.insert(value);
+    /// }
+    ///
+    /// tree.retain(|value| value % 2 == 0);
+    ///
+    /// assert_eq!(tree.size(), 5);
+    /// assert!(tree.iter().eq([0, 2, 4, 6, 8].iter()));
+    /// ```
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut keep: F) {
+        // The removed values are dropped once the tree is valid again
+        drop(self.extract_if(|value| !keep(value)));
+    }
+
+    /// Like [`BinaryTree::retain`], but stops calling `keep` at the first
+    /// error it returns. The values checked before it are removed or kept as
+    /// `keep` said, the value that failed and the ones after it are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by `keep`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// for value in 1..=5 {
+    ///     tree.insert(value);
+    /// }
+    ///
+    /// let result = tree.try_retain(|&value| match value {
+    ///     4 => Err("too big"),
+    ///     _ => Ok(value % 2 == 1),
+    /// });
+    ///
+    /// assert_eq!(result, Err("too big"));
+    /// assert!(tree.iter().eq([1, 3, 4, 5].iter()));
+    /// ```
+    pub fn try_retain<E, F>(&mut self, mut keep: F) -> Result<(), E>
+    where
+        F: FnMut(&T) -> Result<bool, E>,
+    {
+        let mut result = Ok(());
+
+        self.retain(|value| match result {
+            Ok(()) => keep(value).unwrap_or_else(|err| {
+                result = Err(err);
+                true
+            }),
+            Err(_) => true,
+        });
+
+        result
+    }
+
+    /// Inserts the values of `iter` until it yields an error. The values
+    /// before the error stay in the tree.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error yielded by `iter`.
+    pub fn try_extend<E, I>(&mut self, iter: I) -> Result<(), E>
+    where
+        I: IntoIterator<Item = Result<T, E>>,
+    {
+        for value in iter {
+            self.insert(value?);
+        }
+
+        Ok(())
+    }
+
+    /// Removes the values for which `extract` returns `true` and returns an
+    /// iterator over them in ascending order. The values are removed when this
+    /// method is called, even if the returned iterator is never consumed.
+    /// Like with [`BinaryTree::retain`], the tree is left untouched if
+    /// `extract` panics.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// for value in 0..10 {
+    ///     tree.insert(value);
+    /// }
+    ///
+    /// let odds: Vec<_> = tree.extract_if(|value| value % 2 != 0).collect();
+    ///
+    /// assert_eq!(odds, [1, 3, 5, 7, 9]);
+    /// assert!(tree.iter().eq([0, 2, 4, 6, 8].iter()));
+    /// ```
+    pub fn extract_if<F: FnMut(&T) -> bool>(&mut self, extract: F) -> ExtractIf<T> {
+        let extracted: Vec<bool> = self.iter().map(extract).collect();
+        let mut values = Vec::with_capacity(extracted.iter().filter(|&&extract| extract).count());
+        let mut extracted = extracted.into_iter();
+
+        unsafe {
+            self.filter_nodes(&mut |_| !extracted.next().unwrap(), &mut |value| {
+                values.push(value)
+            });
+        }
+
+        ExtractIf {
+            values: values.into_iter(),
+        }
+    }
+
+    /// Returns the nodes on the search path of the point where `goes_left`
+    /// changes from `true` to `false`, from the root down, along with the
+    /// answer of `goes_left` for each of them. See `split_by_path`.
+    unsafe fn split_path<F>(&self, mut goes_left: F) -> Vec<(ptr::NonNull<Node<T>>, bool)>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut path = Vec::new();
+        let mut current = self.root;
+
+        while let Some(node) = current {
+            let left = goes_left(&(*node.as_ptr()).value);
+            path.push((node, left));
+
+            current = match left {
+                true => (*node.as_ptr()).right,
+                false => (*node.as_ptr()).left,
+            };
+        }
+
+        path
+    }
+
+    /// Splits the given subtree like `split_subtree`, with the answers
+    /// recorded by `split_path` instead of calling user code while nodes are
+    /// being relinked. The subtree can also be the right part of a previous
+    /// split at a point that's not after this one: splitting keeps the
+    /// ancestors of every node that stays on the same side, so its search
+    /// path is what's left of the recorded one.
+    unsafe fn split_by_path(
+        &self,
+        current: Link<T>,
+        path: Vec<(ptr::NonNull<Node<T>>, bool)>,
+    ) -> (Link<T>, Link<T>) {
+        let mut path = path.into_iter();
+
+        self.split_subtree(current, |value| {
+            let (_, left) = path
+                .by_ref()
+                .find(|(node, _)| ptr::eq(&(*node.as_ptr()).value, value))
+                .unwrap();
+
+            left
+        })
+    }
+
+    /// Splits the given subtree into one subtree with the values for which
+    /// `goes_left` returns `true` and another one with the rest. `goes_left`
+    /// must return `true` for a prefix of the values in ascending order, like
+    /// "less than `value`". Only the nodes in the search path of the split
+    /// point are relinked. The path is walked from the top down, each node is
+    /// attached below the last node that went to the same side, so this
+    /// doesn't recurse on the height of the subtree.
+    unsafe fn split_subtree<F>(&self, current: Link<T>, mut goes_left: F) -> (Link<T>, Link<T>)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let (mut left_root, mut right_root) = (None, None);
+
+        // Nodes whose right (left) link receives the next node that goes to
+        // the left (right) subtree
+        let (mut left_tail, mut right_tail) = (None, None);
+
+        let mut current = current;
+
+        while let Some(node) = current {
+            if goes_left(&(*node.as_ptr()).value) {
+                current = (*node.as_ptr()).right;
+
+                match left_tail {
+                    Some(tail) => Node::set_right(tail, Some(node)),
+                    None => left_root = Some(node),
+                }
+
+                left_tail = Some(node);
+            } else {
+                current = (*node.as_ptr()).left;
+
+                match right_tail {
+                    Some(tail) => Node::set_left(tail, Some(node)),
+                    None => right_root = Some(node),
+                }
+
+                right_tail = Some(node);
+            }
+        }
+
+        // The tails still point to the part of the path that went to the
+        // other side
+        if let Some(tail) = left_tail {
+            (*tail.as_ptr()).right = None;
+        }
+
+        if let Some(tail) = right_tail {
+            (*tail.as_ptr()).left = None;
+        }
+
+        for root in [left_root, right_root].into_iter().flatten() {
+            (*root.as_ptr()).parent = None;
+        }
+
+        // Only the relinked nodes changed size, and they are the ancestors of
+        // the tails
+        for tail in [left_tail, right_tail] {
+            let mut node = tail;
+
+            while let Some(current) = node {
+                Node::update_size(current);
+                node = (*current.as_ptr()).parent;
+            }
+        }
+
+        (left_root, right_root)
+    }
+
+    /// Removes all the values within `range` and returns how many were
+    /// removed. The tree is split at both ends of the range, the middle part
+    /// is dropped as a whole and the remaining parts are joined back, so only
+    /// the search paths of the bounds are relinked instead of removing values
+    /// one by one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// for value in 1..=10 {
+    ///     tree.insert(value);
+    /// }
+    ///
+    /// assert_eq!(tree.remove_range(3..8), 5);
+    /// assert!(tree.iter().eq([1, 2, 8, 9, 10].iter()));
+    ///
+    /// assert_eq!(tree.remove_range(9..), 2);
+    /// assert!(tree.iter().eq([1, 2, 8].iter()));
+    /// ```
+    pub fn remove_range<R: RangeBounds<T>>(&mut self, range: R) -> usize {
+        // Nothing to remove, and the end split point would come before the
+        // start one, which `split_by_path` doesn't support
+        let inverted = match (range.start_bound(), range.end_bound()) {
+            (Bound::Included(start), Bound::Included(end) | Bound::Excluded(end))
+            | (Bound::Excluded(start), Bound::Included(end)) => {
+                self.comparator.compare(start, end) == Ordering::Greater
+            }
+            (Bound::Excluded(start), Bound::Excluded(end)) => {
+                self.comparator.compare(start, end) != Ordering::Less
+            }
+            _ => false,
+        };
+
+        if inverted {
+            return 0;
+        }
+
+        unsafe {
+            // The comparator runs for both bounds before any node is relinked,
+            // so the tree is left untouched if it panics
+            let start = self.split_path(|value| match range.start_bound() {
+                Bound::Included(start) => self.comparator.compare(value, start) == Ordering::Less,
+                Bound::Excluded(start) => {
+                    self.comparator.compare(value, start) != Ordering::Greater
+                }
+                Bound::Unbounded => false,
+            });
+
+            let end = self.split_path(|value| match range.end_bound() {
+                Bound::Included(end) => self.comparator.compare(value, end) != Ordering::Greater,
+                Bound::Excluded(end) => self.comparator.compare(value, end) == Ordering::Less,
+                Bound::Unbounded => true,
+            });
+
+            let (left, rest) = self.split_by_path(self.root, start);
+            let (middle, right) = self.split_by_path(rest, end);
+
+            let removed = Node::size_of(middle);
+
+            let root = self.join(left, right);
+            self.set_root(root);
+            self.size -= removed;
+
+            // Dropped once the tree is valid again, in case a value panics
+            self.drop_iteratively(middle);
+
+            removed
+        }
+    }
+}
+
+impl<T, C: Compare<T>, A: Allocator> BinaryTree<T, C, A> {
+    /// Splits the tree in two at the given `value`. After this call `self`
+    /// contains the values less than `value` and the returned tree contains
+    /// the values greater than or equal to `value`. Only the nodes in the
+    /// search path of `value` are visited, so this takes O(h). If the tree
+    /// has an arena, the returned tree gets its own arena and the values are
+    /// moved there, which takes O(m) where m is the size of the returned tree.
+    /// The returned tree allocates with a clone of the allocator of `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// for value in [3, 1, 4, 5, 2] {
+    ///     tree.insert(value);
+    /// }
+    ///
+    /// let greater = tree.split_off(&3);
+    ///
+    /// assert!(tree.iter().eq([1, 2].iter()));
+    /// assert!(greater.iter().eq([3, 4, 5].iter()));
+    /// ```
+    pub fn split_off(&mut self, value: &T) -> BinaryTree<T, C, A>
+    where
+        C: Clone,
+        A: Clone,
+    {
+        let mut other =
+            BinaryTree::new_with_comparator_in(self.comparator.clone(), self.alloc.clone());
+
+        unsafe {
+            let (less, greater_or_equal) = self.split_subtree(self.root, |node_value| {
+                self.comparator.compare(node_value, value) == Ordering::Less
+            });
+
+            self.set_root(less);
+
+            match &self.arena {
+                None => {
+                    other.set_root(greater_or_equal);
+                    other.size = Node::size_of(other.root);
+                }
+
+                Some(arena) => {
+                    other.arena = Some(Arena::new(arena.chunk_capacity()));
+
+                    let mut values = Vec::with_capacity(Node::size_of(greater_or_equal));
+                    self.take_values_inorder(greater_or_equal, &mut values);
+
+                    let len = values.len();
+                    other.extend_sorted(values.into_iter(), len);
+                }
+            }
+        }
+
+        self.size -= other.size;
+
+        other
+    }
+
+    /// Moves all the values of `other` into `self`. Values already present in
+    /// `self` are kept and their duplicates in `other` are dropped.
+    ///
+    /// When `other` is small compared to `self` its values are inserted one by
+    /// one. Otherwise both trees are merged in O(n + m) by walking their values
+    /// in order and rebuilding a balanced tree out of the merged sequence.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut a = BinaryTree::new();
+    /// a.insert(1);
+    /// a.insert(3);
+    ///
+    /// let mut b = BinaryTree::new();
+    /// b.insert(2);
+    /// b.insert(3);
+    ///
+    /// a.append(b);
+    ///
+    /// assert!(a.iter().eq([1, 2, 3].iter()));
+    /// ```
+    pub fn append(&mut self, mut other: BinaryTree<T, C, A>) {
+        if other.is_empty() {
+            return;
+        }
+
+        // The nodes of `other` can be moved along with its arena, but not
+        // into the arena of `self`.
+        if self.is_empty() && self.arena.is_none() {
+            *self = other;
+            return;
+        }
+
+        let total = self.size + other.size;
+
+        if other.size * (total.ilog2() as usize) < total {
+            for value in other.take_sorted_vec() {
+                self.insert(value);
+            }
+
+            return;
+        }
+
+        let mut a = self.take_sorted_vec().into_iter().peekable();
+        let mut b = other.take_sorted_vec().into_iter().peekable();
+
+        let mut merged = Vec::with_capacity(total);
+
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            match self.comparator.compare(x, y) {
+                Ordering::Less => merged.push(a.next().unwrap()),
+                Ordering::Greater => merged.push(b.next().unwrap()),
+                Ordering::Equal => {
+                    b.next();
+                    merged.push(a.next().unwrap());
+                }
+            }
+        }
+
+        merged.extend(a);
+        merged.extend(b);
+
+        let len = merged.len();
+
+        self.extend_sorted(merged.into_iter(), len);
+    }
+}
+
+/// Iterator over the values removed by [`BinaryTree::extract_if`].
+pub struct ExtractIf<T> {
+    values: std::vec::IntoIter<T>,
+}
+
+impl<T> Iterator for ExtractIf<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.values.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+
+impl<T, C, A: Allocator> BinaryTree<T, C, A> {
+    /// Makes `root` the root of the tree, the root has no parent.
+    unsafe fn set_root(&mut self, root: Link<T>) {
+        self.root = root;
+
+        if let Some(root) = root {
+            (*root.as_ptr()).parent = None;
+        }
+    }
+
+    /// Points the link of `parent` that currently points to `child` (or the
+    /// root if there's no parent) to `replacement`.
+    unsafe fn replace_child(
+        &mut self,
+        parent: Link<T>,
+        child: ptr::NonNull<Node<T>>,
+        replacement: Link<T>,
+    ) {
+        match parent {
+            None => self.set_root(replacement),
+
+            Some(parent) => {
+                if (*parent.as_ptr()).left == Some(child) {
+                    Node::set_left(parent, replacement);
+                } else {
+                    Node::set_right(parent, replacement);
+                }
+            }
+        }
+    }
+
+    /// Returns the value stored in the root.
+    #[cfg(feature = "splay_tree")]
+    pub(crate) fn root_value(&self) -> Option<&T> {
+        self.root.map(|root| unsafe { &(*root.as_ptr()).value })
+    }
+
+    /// Rotates `node` above its parent, which becomes its child. The order
+    /// of the values doesn't change and the sizes of both nodes are updated.
+    #[cfg(feature = "splay_tree")]
+    unsafe fn rotate_up(&mut self, node: ptr::NonNull<Node<T>>) {
+        let parent = (*node.as_ptr()).parent.unwrap();
+        let grandparent = (*parent.as_ptr()).parent;
+
+        if (*parent.as_ptr()).left == Some(node) {
+            Node::set_left(parent, (*node.as_ptr()).right);
+            Node::set_right(node, Some(parent));
+        } else {
+            Node::set_right(parent, (*node.as_ptr()).left);
+            Node::set_left(node, Some(parent));
+        }
+
+        Node::update_size(parent);
+        Node::update_size(node);
+        self.replace_child(grandparent, parent, Some(node));
+    }
+
+    /// Moves `node` to the root with the splay tree rotations. When the node
+    /// and its parent are children on the same side the parent is rotated
+    /// first (zig-zig), otherwise the node is rotated twice (zig-zag). This
+    /// roughly halves the depth of every node on the path, which is what
+    /// makes splay operations amortized O(log n).
+    #[cfg(feature = "splay_tree")]
+    unsafe fn splay_node(&mut self, node: ptr::NonNull<Node<T>>) {
+        while let Some(parent) = (*node.as_ptr()).parent {
+            if let Some(grandparent) = (*parent.as_ptr()).parent {
+                let node_is_left = (*parent.as_ptr()).left == Some(node);
+                let parent_is_left = (*grandparent.as_ptr()).left == Some(parent);
+
+                if node_is_left == parent_is_left {
+                    self.rotate_up(parent);
+                } else {
+                    self.rotate_up(node);
+                }
+            }
+
+            self.rotate_up(node);
+        }
+    }
+
+    /// Performs the binary tree node removal algorithm:
+    ///
+    /// - If the node only has one child, deallocate the node and make the parent
+    ///   point to the child.
+    ///
+    /// - If the node has no children just make the parent point to any of its
+    ///   non-existent children (set the `Link<T>` to `None`).
+    ///
+    /// - If the node has two children, locate the inorder successor of the
+    ///   node in the right subtree, move its value into the node, make the
+    ///   successor parent point to the successor right child and deallocate the
+    ///   successor. The case where the successor parent is the node itself has
+    ///   to be considered.
+    ///
+    /// Returns the removed value and the node that contains the successor of
+    /// the removed value after the removal.
+    unsafe fn remove_node(&mut self, node: ptr::NonNull<Node<T>>) -> (T, Link<T>) {
+        let (left, right) = ((*node.as_ptr()).left, (*node.as_ptr()).right);
+
+        self.size -= 1;
+
+        // Node has only one child or none
+        let (Some(_), Some(right)) = (left, right) else {
+            let successor = Node::successor(node);
+            Node::update_sizes_upwards((*node.as_ptr()).parent, -1);
+            self.replace_child((*node.as_ptr()).parent, node, left.or(right));
+
+            return (self.free_node(node).value, successor);
+        };
+
+        // Node has two children
+        let successor = Node::leftmost(right);
+        Node::update_sizes_upwards((*successor.as_ptr()).parent, -1);
+
+        if successor == right {
+            Node::set_right(node, (*successor.as_ptr()).right);
+        } else {
+            Node::set_left(
+                (*successor.as_ptr()).parent.unwrap(),
+                (*successor.as_ptr()).right,
+            );
+        }
+
+        let successor = self.free_node(successor);
+
+        (
+            mem::replace(&mut (*node.as_ptr()).value, successor.value),
+            Some(node),
+        )
+    }
+
+    /// Drops the given subtree without recursion. While the current node has
+    /// a left child, the tree is rotated to the right so that the left child
+    /// becomes the current node. Once there's no left child, the current node
+    /// is dropped and its right child becomes the current node. Every rotation
+    /// moves one node out of the left spine, so this runs in O(n) with no
+    /// extra memory regardless of the shape of the tree. If dropping a value
+    /// panics the rest of the subtree is still dropped.
+    unsafe fn drop_iteratively(&mut self, mut current: Link<T>) {
+        drop_guard::drop_each(&mut || {
+            while let Some(node) = current {
+                match (*node.as_ptr()).left {
+                    Some(left) => {
+                        (*node.as_ptr()).left = (*left.as_ptr()).right;
+                        (*left.as_ptr()).right = Some(node);
+                        current = Some(left);
+                    }
+
+                    None => {
+                        current = (*node.as_ptr()).right;
+                        return Some(self.free_node(node));
+                    }
+                }
+            }
+
+            None
+        });
+    }
+
+    /// Drops the subtree of `root`, which must contain all the nodes of the
+    /// tree, without updating the root or the size. With an arena there's no
+    /// need to free the nodes one by one, the values are dropped in place
+    /// following the parent links (only if they need it) and then all the
+    /// chunks are deallocated together.
+    unsafe fn drop_all(&mut self, root: Link<T>) {
+        let Some(arena) = &mut self.arena else {
+            self.drop_iteratively(root);
+            return;
+        };
+
+        if mem::needs_drop::<T>() {
+            let mut current = root.map(|root| Node::leftmost(root));
+
+            drop_guard::drop_each(&mut || {
+                let node = current?;
+                current = Node::successor(node);
+
+                Some(ptr::read(&(*node.as_ptr()).value))
+            });
+        }
+
+        arena.clear();
+    }
+}
+
+#[cfg(feature = "splay_tree")]
+impl<T, C: Compare<T>, A: Allocator> BinaryTree<T, C, A> {
+    /// Searches `value` and moves it to the root. If it's not present, the
+    /// last node visited by the search is moved to the root instead. Returns
+    /// `true` if the value was found.
+    pub(crate) fn splay(&mut self, value: &T) -> bool {
+        unsafe {
+            match self.search_by(|node| self.comparator.compare(value, node)) {
+                Search::Found(node) => {
+                    self.splay_node(node);
+                    true
+                }
+
+                Search::Vacant(parent, _) => {
+                    if let Some(parent) = parent {
+                        self.splay_node(parent);
+                    }
+                    false
+                }
+            }
+        }
+    }
+
+    /// Inserts `value` and moves it to the root, or moves the equal value
+    /// already in the tree to the root. Returns `true` if `value` was
+    /// inserted.
+    pub(crate) fn insert_and_splay(&mut self, value: T) -> bool {
+        unsafe {
+            match self.search_by(|node| self.comparator.compare(&value, node)) {
+                Search::Found(node) => {
+                    self.splay_node(node);
+                    false
+                }
+
+                Search::Vacant(parent, side) => {
+                    let node = self.insert_at(parent, side, value);
+                    self.splay_node(node);
+                    true
+                }
+            }
+        }
+    }
+}
+
+impl<T, C, A: Allocator> BinaryTree<T, C, A> {
+    /// Fills an empty tree with a perfectly balanced tree built out of `len`
+    /// values yielded in ascending order by `values`. Ordering and uniqueness
+    /// are not checked, the caller must guarantee them.
+    fn extend_sorted<I: Iterator<Item = T>>(&mut self, mut values: I, len: usize) {
+        debug_assert!(self.is_empty());
+
+        unsafe {
+            self.root = self.build_balanced(&mut values, len);
+        }
+
+        self.size = len;
+        self.stats.record_len(len);
+    }
+
+    /// Builds the left subtree with the first half of the values, then the
+    /// root, then the right subtree with the remaining values. Each node is
+    /// allocated in order so `values` is consumed exactly once.
+    unsafe fn build_balanced<I: Iterator<Item = T>>(
+        &mut self,
+        values: &mut I,
+        len: usize,
+    ) -> Link<T> {
+        if len == 0 {
+            return None;
+        }
+
+        let left = self.build_balanced(values, len / 2);
+        let value = values.next().expect("Not enough values to build the tree");
+        let right = self.build_balanced(values, len - len / 2 - 1);
+
+        Some(self.new_node(value, right, left))
+    }
+
+    /// Moves the values of the given subtree into `values` using inorder
+    /// traversal and deallocates the nodes. Left children are rotated up like
+    /// in `drop_iteratively`, so each node is freed as soon as its value is
+    /// taken without recursing on the height of the subtree.
+    unsafe fn take_values_inorder(&mut self, mut current: Link<T>, values: &mut Vec<T>) {
+        while let Some(node) = current {
+            match (*node.as_ptr()).left {
+                Some(left) => {
+                    (*node.as_ptr()).left = (*left.as_ptr()).right;
+                    (*left.as_ptr()).right = Some(node);
+                    current = Some(left);
+                }
+
+                None => {
+                    let Node { right, value, .. } = self.free_node(node);
+                    values.push(value);
+                    current = right;
+                }
+            }
+        }
+    }
+
+    /// Empties the tree and returns its values in ascending order.
+    fn take_sorted_vec(&mut self) -> Vec<T> {
+        let mut values = Vec::with_capacity(self.size);
+
+        unsafe {
+            self.take_values_inorder(self.root, &mut values);
+        }
+
+        self.root = None;
+        self.size = 0;
+
+        values
+    }
+}
+
+impl<T, C, A: Allocator> BinaryTree<T, C, A> {
+    /// Consumes the tree and builds a balanced tree with the values returned
+    /// by `f`, which is called once per value in ascending order. When `f`
+    /// is strictly monotonic (increasing or decreasing) the new tree is built
+    /// in O(n) without comparing values more than once, otherwise the mapped
+    /// values are sorted in O(n log n) and duplicates are discarded (the
+    /// first one is kept). The new tree uses an arena with the same chunk
+    /// capacity if this tree used one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// for value in [2, 1, 3] {
+    ///     tree.insert(value);
+    /// }
+    ///
+    /// let names = tree.map(|value| format!("id-{value}"));
+    /// assert!(names.iter().eq(["id-1", "id-2", "id-3"].iter()));
+    ///
+    /// // Not monotonic, values are sorted and deduplicated
+    /// let parity = names.map(|name| name.ends_with(['1', '3']));
+    /// assert!(parity.iter().eq([false, true].iter()));
+    /// ```
+    pub fn map<U: Ord, F: FnMut(T) -> U>(mut self, f: F) -> BinaryTree<U> {
+        let mut tree = match &self.arena {
+            Some(arena) => BinaryTree::new_with_arena(arena.chunk_capacity()),
+            None => BinaryTree::new(),
+        };
+
+        let mut values: Vec<U> = self.take_sorted_vec().into_iter().map(f).collect();
+
+        if values.windows(2).any(|pair| pair[0] >= pair[1]) {
+            if values.windows(2).all(|pair| pair[0] > pair[1]) {
+                values.reverse();
+            } else {
+                values.sort();
+                values.dedup();
+            }
+        }
+
+        let len = values.len();
+        tree.extend_sorted(values.into_iter(), len);
+
+        tree
+    }
+}
+
+impl<T, C, A: Allocator> Drop for BinaryTree<T, C, A> {
+    fn drop(&mut self) {
+        unsafe { self.drop_all(self.root) }
+    }
+}
+
+/// Iterator over the values of the tree in ascending order. Moves from node
+/// to node following the parent links, so it doesn't allocate.
+pub struct Iter<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    marker: marker::PhantomData<&'a T>,
+}
+
+impl<T, C, A: Allocator> BinaryTree<T, C, A> {
+    /// Returns an iterator over the values contained in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// tree.insert(3);
+    /// tree.insert(1);
+    /// tree.insert(2);
+    ///
+    /// let mut expected = 1;
+    /// for value in tree.iter() {
+    ///     assert_eq!(value, &expected);
+    ///     expected += 1;
+    /// }
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        unsafe {
+            Iter {
+                front: self.root.map(|root| Node::leftmost(root)),
+                back: self.root.map(|root| Node::rightmost(root)),
+                len: self.size,
+                marker: marker::PhantomData,
+            }
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.front.map(|node| unsafe {
+            self.len -= 1;
+            self.front = Node::successor(node);
+            &(*node.as_ptr()).value
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.back.map(|node| unsafe {
+            self.len -= 1;
+            self.back = Node::predecessor(node);
+            &(*node.as_ptr()).value
+        })
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// Iterator over the values of the tree in ascending order together with the
+/// depth of their nodes. See [`BinaryTree::iter_with_depth`].
+pub struct IterWithDepth<'a, T> {
+    next: Link<T>,
+    depth: usize,
+    len: usize,
+    marker: marker::PhantomData<&'a T>,
+}
+
+impl<T, C, A: Allocator> BinaryTree<T, C, A> {
+    /// Returns an iterator over the values in ascending order, each one
+    /// paired with the number of edges between the root and its node. The
+    /// depth is updated while moving from one node to the next, so the whole
+    /// iteration runs in O(n).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// for value in [2, 1, 4, 3] {
+    ///     tree.insert(value);
+    /// }
+    ///
+    /// let dump: Vec<String> = tree
+    ///     .iter_with_depth()
+    ///     .map(|(value, depth)| format!("{}{value}", "  ".repeat(depth)))
+    ///     .collect();
+    ///
+    /// assert_eq!(dump, vec!["  1", "2", "    3", "  4"]);
+    /// ```
+    pub fn iter_with_depth(&self) -> IterWithDepth<'_, T> {
+        let mut iter = IterWithDepth {
+            next: self.root,
+            depth: 0,
+            len: self.size,
+            marker: marker::PhantomData,
+        };
+
+        unsafe {
+            while let Some(left) = iter.next.and_then(|node| (*node.as_ptr()).left) {
+                iter.next = Some(left);
+                iter.depth += 1;
+            }
+        }
+
+        iter
+    }
+}
+
+impl<'a, T> Iterator for IterWithDepth<'a, T> {
+    type Item = (&'a T, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next?;
+        let item = unsafe { (&(*node.as_ptr()).value, self.depth) };
+
+        self.len -= 1;
+
+        // Same steps as `Node::successor`, counting the levels.
+        unsafe {
+            if let Some(mut current) = (*node.as_ptr()).right {
+                self.depth += 1;
+
+                while let Some(left) = (*current.as_ptr()).left {
+                    current = left;
+                    self.depth += 1;
+                }
+
+                self.next = Some(current);
+            } else {
+                let mut child = node;
+                self.next = None;
+
+                while let Some(parent) = (*child.as_ptr()).parent {
+                    self.depth -= 1;
+
+                    if (*parent.as_ptr()).left == Some(child) {
+                        self.next = Some(parent);
+                        break;
+                    }
+
+                    child = parent;
+                }
+            }
+        }
+
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterWithDepth<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// Iterator over the values of the tree that fall within a range, in
+/// ascending order. See [`BinaryTree::range`].
+pub struct Range<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    marker: marker::PhantomData<&'a T>,
+}
+
+impl<T, C, A: Allocator> BinaryTree<T, C, A> {
+    /// Returns an iterator over the values within `range`, where `cmp`
+    /// compares a bound with the value of a node. Both ends of the range are
+    /// located with one descent each, then the iterator moves through the
+    /// parent links.
+    pub(crate) fn range_by<K, R, F>(&self, range: R, mut cmp: F) -> Range<'_, T>
+    where
+        R: RangeBounds<K>,
+        F: FnMut(&K, &T) -> Ordering,
+    {
+        unsafe {
+            // First node that is not before the start bound.
+            let mut front = None;
+            let mut current = self.root;
+
+            while let Some(node) = current {
+                let after_start = match range.start_bound() {
+                    Bound::Included(start) => {
+                        cmp(start, &(*node.as_ptr()).value) != Ordering::Greater
+                    }
+                    Bound::Excluded(start) => cmp(start, &(*node.as_ptr()).value) == Ordering::Less,
+                    Bound::Unbounded => true,
+                };
+
+                if after_start {
+                    front = current;
+                    current = (*node.as_ptr()).left;
+                } else {
+                    current = (*node.as_ptr()).right;
+                }
+            }
+
+            // Last node that is not after the end bound.
+            let mut back = None;
+            let mut current = self.root;
+
+            while let Some(node) = current {
+                let before_end = match range.end_bound() {
+                    Bound::Included(end) => cmp(end, &(*node.as_ptr()).value) != Ordering::Less,
+                    Bound::Excluded(end) => cmp(end, &(*node.as_ptr()).value) == Ordering::Greater,
+                    Bound::Unbounded => true,
+                };
+
+                if before_end {
+                    back = current;
+                    current = (*node.as_ptr()).right;
+                } else {
+                    current = (*node.as_ptr()).left;
+                }
+            }
+
+            // If `back` comes before `front` the range is empty, and that
+            // happens exactly when `back` is also before the start bound.
+            let empty = match (front, back) {
+                (Some(_), Some(back)) => match range.start_bound() {
+                    Bound::Included(start) => {
+                        cmp(start, &(*back.as_ptr()).value) == Ordering::Greater
+                    }
+                    Bound::Excluded(start) => cmp(start, &(*back.as_ptr()).value) != Ordering::Less,
+                    Bound::Unbounded => false,
+                },
+                _ => true,
+            };
+
+            if empty {
+                (front, back) = (None, None);
+            }
+
+            Range {
+                front,
+                back,
+                marker: marker::PhantomData,
+            }
+        }
+    }
+}
+
+impl<T, C: Compare<T>, A: Allocator> BinaryTree<T, C, A> {
+    /// Returns an iterator over the values within `range` in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// for value in 1..=10 {
+    ///     tree.insert(value);
+    /// }
+    ///
+    /// assert!(tree.range(3..6).eq([3, 4, 5].iter()));
+    /// assert!(tree.range(8..).eq([8, 9, 10].iter()));
+    /// assert_eq!(tree.range(20..).next(), None);
+    /// ```
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Range<'_, T> {
+        self.range_by(range, |bound, value| self.comparator.compare(bound, value))
+    }
+}
+
+impl<'a, T> Iterator for Range<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.map(|node| unsafe {
+            if self.front == self.back {
+                (self.front, self.back) = (None, None);
+            } else {
+                self.front = Node::successor(node);
+            }
+
+            &(*node.as_ptr()).value
+        })
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Range<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.map(|node| unsafe {
+            if self.front == self.back {
+                (self.front, self.back) = (None, None);
+            } else {
+                self.back = Node::predecessor(node);
+            }
+
+            &(*node.as_ptr()).value
+        })
+    }
+}
+
+/// Cursor over the values of a tree in ascending order. Just like the
+/// [`crate::Dequeue`] cursors, there's a "ghost" position between the maximum
+/// and the minimum values where the cursor doesn't point to any value.
+pub struct TreeCursor<'a, T, C = Natural, A: Allocator = Global> {
+    current: Link<T>,
+    tree: &'a BinaryTree<T, C, A>,
+}
+
+/// Cursor that can also modify or remove the value it points to. See
+/// [`TreeCursor`].
+pub struct TreeCursorMut<'a, T, C = Natural, A: Allocator = Global> {
+    current: Link<T>,
+    tree: &'a mut BinaryTree<T, C, A>,
+}
+
+impl<T, C, A: Allocator> BinaryTree<T, C, A> {
+    /// Returns a cursor positioned at the ghost position.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// tree.insert(1);
+    /// tree.insert(2);
+    ///
+    /// let mut cursor = tree.cursor();
+    /// assert_eq!(cursor.current(), None);
+    ///
+    /// cursor.move_next();
+    /// assert_eq!(cursor.current(), Some(&1));
+    /// ```
+    pub fn cursor(&self) -> TreeCursor<'_, T, C, A> {
+        TreeCursor {
+            current: None,
+            tree: self,
+        }
+    }
+
+    /// Returns a mutable cursor positioned at the ghost position.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// tree.insert(1);
+    /// tree.insert(2);
+    ///
+    /// let mut cursor = tree.cursor_mut();
+    /// cursor.move_prev();
+    /// assert_eq!(cursor.remove_current(), Some(2));
+    ///
+    /// assert!(tree.iter().eq([1].iter()));
+    /// ```
+    pub fn cursor_mut(&mut self) -> TreeCursorMut<'_, T, C, A> {
+        TreeCursorMut {
+            current: None,
+            tree: self,
+        }
+    }
+}
+
+impl<'a, T, C, A: Allocator> TreeCursor<'a, T, C, A> {
+    /// Moves the cursor to the next value. If the cursor is at the ghost
+    /// position it moves to the minimum value, and if it's at the maximum
+    /// value it moves to the ghost position.
+    pub fn move_next(&mut self) {
+        unsafe {
+            self.current = match self.current {
+                Some(node) => Node::successor(node),
+                None => self.tree.root.map(|root| Node::leftmost(root)),
+            }
+        }
+    }
+
+    /// Moves the cursor to the previous value, see [`TreeCursor::move_next`].
+    pub fn move_prev(&mut self) {
+        unsafe {
+            self.current = match self.current {
+                Some(node) => Node::predecessor(node),
+                None => self.tree.root.map(|root| Node::rightmost(root)),
+            }
+        }
+    }
+
+    /// Returns the value the cursor points to, or `None` at the ghost
+    /// position.
+    pub fn current(&self) -> Option<&'a T> {
+        unsafe { self.current.map(|node| &(*node.as_ptr()).value) }
+    }
+
+    /// Returns the value after the current one without moving the cursor.
+    pub fn peek_next(&self) -> Option<&'a T> {
+        let mut cursor = TreeCursor {
+            current: self.current,
+            tree: self.tree,
+        };
+        cursor.move_next();
+
+        cursor.current()
+    }
+
+    /// Returns the value before the current one without moving the cursor.
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        let mut cursor = TreeCursor {
+            current: self.current,
+            tree: self.tree,
+        };
+        cursor.move_prev();
+
+        cursor.current()
+    }
+}
+
+impl<'a, T, C: Compare<T>, A: Allocator> TreeCursor<'a, T, C, A> {
+    /// Moves the cursor to `value` and returns `true` if it's present.
+    /// Otherwise the cursor is moved to the smallest value greater than
+    /// `value`, or to the ghost position if there's no such value, and `false`
+    /// is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// tree.insert(10);
+    /// tree.insert(20);
+    ///
+    /// let mut cursor = tree.cursor();
+    ///
+    /// assert!(!cursor.seek(&15));
+    /// assert_eq!(cursor.current(), Some(&20));
+    ///
+    /// assert!(cursor.seek(&10));
+    /// assert_eq!(cursor.current(), Some(&10));
+    /// ```
+    pub fn seek(&mut self, value: &T) -> bool {
+        unsafe {
+            self.current = self.tree.lower_bound_node(value);
+        }
+
+        self.current()
+            .is_some_and(|current| self.tree.comparator.compare(current, value) == Ordering::Equal)
+    }
+}
+
+impl<'a, T, C, A: Allocator> TreeCursorMut<'a, T, C, A> {
+    /// Returns a read-only cursor at the same position.
+    fn as_cursor(&self) -> TreeCursor<'_, T, C, A> {
+        TreeCursor {
+            current: self.current,
+            tree: self.tree,
+        }
+    }
+
+    /// Moves the cursor to the next value, see [`TreeCursor::move_next`].
+    pub fn move_next(&mut self) {
+        let mut cursor = self.as_cursor();
+        cursor.move_next();
+        self.current = cursor.current;
+    }
+
+    /// Moves the cursor to the previous value, see [`TreeCursor::move_next`].
+    pub fn move_prev(&mut self) {
+        let mut cursor = self.as_cursor();
+        cursor.move_prev();
+        self.current = cursor.current;
+    }
+
+    /// Returns a mutable reference to the value the cursor points to, or
+    /// `None` at the ghost position. The value must not be modified in a way
+    /// that changes its ordering relative to the other values in the tree,
+    /// otherwise the tree is left in an unspecified (but memory safe) state.
+    pub fn current(&mut self) -> Option<&mut T> {
+        unsafe { self.current.map(|node| &mut (*node.as_ptr()).value) }
+    }
+
+    /// Returns the value after the current one without moving the cursor.
+    pub fn peek_next(&self) -> Option<&T> {
+        self.as_cursor().peek_next()
+    }
+
+    /// Returns the value before the current one without moving the cursor.
+    pub fn peek_prev(&self) -> Option<&T> {
+        self.as_cursor().peek_prev()
+    }
+
+    /// Removes the value the cursor points to and returns it. The cursor is
+    /// moved to the next value. If the cursor is at the ghost position it
+    /// returns `None` and does nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// for value in 1..=5 {
+    ///     tree.insert(value);
+    /// }
+    ///
+    /// let mut cursor = tree.cursor_mut();
+    /// cursor.move_next();
+    ///
+    /// while let Some(value) = cursor.current() {
+    ///     if *value % 2 == 0 {
+    ///         cursor.remove_current();
+    ///     } else {
+    ///         cursor.move_next();
+    ///     }
+    /// }
+    ///
+    /// assert!(tree.iter().eq([1, 3, 5].iter()));
+    /// ```
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current?;
+
+        unsafe {
+            let (value, successor) = self.tree.remove_node(node);
+            self.current = successor;
+
+            Some(value)
+        }
+    }
+}
+
+impl<'a, T, C: Compare<T>, A: Allocator> TreeCursorMut<'a, T, C, A> {
+    /// Moves the cursor to `value`, see [`TreeCursor::seek`].
+    pub fn seek(&mut self, value: &T) -> bool {
+        let mut cursor = self.as_cursor();
+        let found = cursor.seek(value);
+        self.current = cursor.current;
+
+        found
+    }
+}
+
+/// Lazy iterator over the values present in either of two trees. See
+/// [`BinaryTree::union`].
+pub struct Union<'a, T, C = Natural> {
+    a: Peekable<Iter<'a, T>>,
+    b: Peekable<Iter<'a, T>>,
+    comparator: &'a C,
+}
+
+/// Lazy iterator over the values present in both trees. See
+/// [`BinaryTree::intersection`].
+pub struct Intersection<'a, T, C = Natural> {
+    a: Peekable<Iter<'a, T>>,
+    b: Peekable<Iter<'a, T>>,
+    comparator: &'a C,
+}
+
+/// Lazy iterator over the values present in the first tree but not in the
+/// second one. See [`BinaryTree::difference`].
+pub struct Difference<'a, T, C = Natural> {
+    a: Peekable<Iter<'a, T>>,
+    b: Peekable<Iter<'a, T>>,
+    comparator: &'a C,
+}
+
+/// Lazy iterator over the values present in exactly one of two trees. See
+/// [`BinaryTree::symmetric_difference`].
+pub struct SymmetricDifference<'a, T, C = Natural> {
+    a: Peekable<Iter<'a, T>>,
+    b: Peekable<Iter<'a, T>>,
+    comparator: &'a C,
+}
+
+/// Lazy iterator over the values present in exactly one of two trees, tagged
+/// with the tree they come from. See [`BinaryTree::diff`].
+pub struct Diff<'a, T, C = Natural> {
+    a: Peekable<Iter<'a, T>>,
+    b: Peekable<Iter<'a, T>>,
+    comparator: &'a C,
+}
+
+/// Value yielded by [`BinaryTree::diff`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffItem<'a, T> {
+    /// The value is present in the tree `diff` was called on but not in the
+    /// other one.
+    OnlyInSelf(&'a T),
+    /// The value is present in the other tree but not in the tree `diff` was
+    /// called on.
+    OnlyInOther(&'a T),
+}
+
+impl<T, C: Compare<T>, A: Allocator> BinaryTree<T, C, A> {
+    /// Returns an iterator over the values present in `self` or `other`, in
+    /// ascending order and without duplicates.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut a = BinaryTree::new();
+    /// a.insert(1);
+    /// a.insert(2);
+    ///
+    /// let mut b = BinaryTree::new();
+    /// b.insert(2);
+    /// b.insert(3);
+    ///
+    /// let union: Vec<_> = a.union(&b).cloned().collect();
+    /// assert_eq!(union, [1, 2, 3]);
+    /// ```
+    pub fn union<'a>(&'a self, other: &'a BinaryTree<T, C, A>) -> Union<'a, T, C> {
+        Union {
+            a: self.iter().peekable(),
+            b: other.iter().peekable(),
+            comparator: &self.comparator,
+        }
+    }
+
+    /// Returns an iterator over the values present in both `self` and `other`,
+    /// in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut a = BinaryTree::new();
+    /// a.insert(1);
+    /// a.insert(2);
+    ///
+    /// let mut b = BinaryTree::new();
+    /// b.insert(2);
+    /// b.insert(3);
+    ///
+    /// let intersection: Vec<_> = a.intersection(&b).cloned().collect();
+    /// assert_eq!(intersection, [2]);
+    /// ```
+    pub fn intersection<'a>(&'a self, other: &'a BinaryTree<T, C, A>) -> Intersection<'a, T, C> {
+        Intersection {
+            a: self.iter().peekable(),
+            b: other.iter().peekable(),
+            comparator: &self.comparator,
+        }
+    }
+
+    /// Returns an iterator over the values present in `self` but not in
+    /// `other`, in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut a = BinaryTree::new();
+    /// a.insert(1);
+    /// a.insert(2);
+    ///
+    /// let mut b = BinaryTree::new();
+    /// b.insert(2);
+    /// b.insert(3);
+    ///
+    /// let difference: Vec<_> = a.difference(&b).cloned().collect();
+    /// assert_eq!(difference, [1]);
+    /// ```
+    pub fn difference<'a>(&'a self, other: &'a BinaryTree<T, C, A>) -> Difference<'a, T, C> {
+        Difference {
+            a: self.iter().peekable(),
+            b: other.iter().peekable(),
+            comparator: &self.comparator,
+        }
+    }
+
+    /// Returns an iterator over the values present in `self` or `other` but
+    /// not in both, in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut a = BinaryTree::new();
+    /// a.insert(1);
+    /// a.insert(2);
+    ///
+    /// let mut b = BinaryTree::new();
+    /// b.insert(2);
+    /// b.insert(3);
+    ///
+    /// let symmetric_difference: Vec<_> = a.symmetric_difference(&b).cloned().collect();
+    /// assert_eq!(symmetric_difference, [1, 3]);
+    /// ```
+    pub fn symmetric_difference<'a>(
+        &'a self,
+        other: &'a BinaryTree<T, C, A>,
+    ) -> SymmetricDifference<'a, T, C> {
+        SymmetricDifference {
+            a: self.iter().peekable(),
+            b: other.iter().peekable(),
+            comparator: &self.comparator,
+        }
+    }
+
+    /// Returns an iterator over the values present in `self` or `other` but
+    /// not in both, in ascending order, telling which tree each one comes
+    /// from. Both sides of the difference are computed in a single walk
+    /// over the trees, which is useful to reconcile two sets.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::{BinaryTree, DiffItem};
+    ///
+    /// let mut local = BinaryTree::new();
+    /// local.insert(1);
+    /// local.insert(2);
+    ///
+    /// let mut remote = BinaryTree::new();
+    /// remote.insert(2);
+    /// remote.insert(3);
+    ///
+    /// let (mut upload, mut download) = (Vec::new(), Vec::new());
+    ///
+    /// for item in local.diff(&remote) {
+    ///     match item {
+    ///         DiffItem::OnlyInSelf(value) => upload.push(*value),
+    ///         DiffItem::OnlyInOther(value) => download.push(*value),
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(upload, [1]);
+    /// assert_eq!(download, [3]);
+    /// ```
+    pub fn diff<'a>(&'a self, other: &'a BinaryTree<T, C, A>) -> Diff<'a, T, C> {
+        Diff {
+            a: self.iter().peekable(),
+            b: other.iter().peekable(),
+            comparator: &self.comparator,
+        }
+    }
+}
+
+impl<'a, T, C: Compare<T>> Iterator for Union<'a, T, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match (self.a.peek().copied(), self.b.peek().copied()) {
+            (None, None) => None,
+            (Some(_), None) => self.a.next(),
+            (None, Some(_)) => self.b.next(),
+
+            (Some(a), Some(b)) => match self.comparator.compare(a, b) {
+                Ordering::Less => self.a.next(),
+                Ordering::Greater => self.b.next(),
+                Ordering::Equal => {
+                    self.b.next();
+                    self.a.next()
+                }
+            },
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (a, _) = self.a.size_hint();
+        let (b, _) = self.b.size_hint();
+
+        (a.max(b), Some(a + b))
+    }
+}
+
+impl<'a, T, C: Compare<T>> Iterator for Intersection<'a, T, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let a = *self.a.peek()?;
+            let b = *self.b.peek()?;
+
+            match self.comparator.compare(a, b) {
+                Ordering::Less => {
+                    self.a.next();
+                }
+                Ordering::Greater => {
+                    self.b.next();
+                }
+                Ordering::Equal => {
+                    self.b.next();
+                    return self.a.next();
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (a, _) = self.a.size_hint();
+        let (b, _) = self.b.size_hint();
+
+        (0, Some(a.min(b)))
+    }
+}
+
+impl<'a, T, C: Compare<T>> Iterator for Difference<'a, T, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let a = *self.a.peek()?;
+
+            let Some(b) = self.b.peek().copied() else {
+                return self.a.next();
+            };
+
+            match self.comparator.compare(a, b) {
+                Ordering::Less => return self.a.next(),
+                Ordering::Greater => {
+                    self.b.next();
+                }
+                Ordering::Equal => {
+                    self.a.next();
+                    self.b.next();
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (a, _) = self.a.size_hint();
+        let (b, _) = self.b.size_hint();
+
+        (a.saturating_sub(b), Some(a))
+    }
+}
+
+impl<'a, T, C: Compare<T>> Iterator for SymmetricDifference<'a, T, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match (self.a.peek().copied(), self.b.peek().copied()) {
+                (None, None) => return None,
+                (Some(_), None) => return self.a.next(),
+                (None, Some(_)) => return self.b.next(),
+
+                (Some(a), Some(b)) => match self.comparator.compare(a, b) {
+                    Ordering::Less => return self.a.next(),
+                    Ordering::Greater => return self.b.next(),
+                    Ordering::Equal => {
+                        self.a.next();
+                        self.b.next();
+                    }
+                },
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (a, _) = self.a.size_hint();
+        let (b, _) = self.b.size_hint();
+
+        (0, Some(a + b))
+    }
+}
+
+impl<'a, T, C: Compare<T>> Iterator for Diff<'a, T, C> {
+    type Item = DiffItem<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match (self.a.peek().copied(), self.b.peek().copied()) {
+                (None, None) => return None,
+                (Some(_), None) => return self.a.next().map(DiffItem::OnlyInSelf),
+                (None, Some(_)) => return self.b.next().map(DiffItem::OnlyInOther),
+
+                (Some(a), Some(b)) => match self.comparator.compare(a, b) {
+                    Ordering::Less => return self.a.next().map(DiffItem::OnlyInSelf),
+                    Ordering::Greater => return self.b.next().map(DiffItem::OnlyInOther),
+                    Ordering::Equal => {
+                        self.a.next();
+                        self.b.next();
+                    }
+                },
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (a, _) = self.a.size_hint();
+        let (b, _) = self.b.size_hint();
+
+        (0, Some(a + b))
+    }
+}
+
+impl<T, C, A: Allocator> BinaryTree<T, C, A> {
+    /// Returns the first node of the given subtree in postorder, the one
+    /// reached by going left whenever possible and right otherwise.
+    unsafe fn first_postorder(mut node: ptr::NonNull<Node<T>>) -> ptr::NonNull<Node<T>> {
+        while let Some(child) = (*node.as_ptr()).left.or((*node.as_ptr()).right) {
+            node = child;
+        }
+
+        node
+    }
+
+    /// Returns the number of levels of the tree and whether both children of
+    /// every node differ in height by at most one. Nodes are visited in
+    /// postorder following the parent links and the heights of the subtrees
+    /// whose parent hasn't been visited yet are kept on a stack, so this
+    /// doesn't recurse on the height of the tree.
+    fn height_and_balance(&self) -> (usize, bool) {
+        let Some(root) = self.root else {
+            return (0, true);
+        };
+
+        let mut heights: Vec<usize> = Vec::new();
+        let mut balanced = true;
+
+        unsafe {
+            let mut node = Self::first_postorder(root);
+
+            loop {
+                // The right subtree was visited last, so its height is on top
+                let right = (*node.as_ptr()).right.map_or(0, |_| heights.pop().unwrap());
+                let left = (*node.as_ptr()).left.map_or(0, |_| heights.pop().unwrap());
+
+                balanced &= left.abs_diff(right) <= 1;
+                heights.push(1 + left.max(right));
+
+                let Some(parent) = (*node.as_ptr()).parent else {
+                    break;
+                };
+
+                node = match (*parent.as_ptr()).right {
+                    Some(right) if (*parent.as_ptr()).left == Some(node) => {
+                        Self::first_postorder(right)
+                    }
+                    _ => parent,
+                };
+            }
+        }
+
+        (heights.pop().unwrap(), balanced)
+    }
+
+    /// Returns the number of levels in the tree, which is the number of nodes
+    /// in the longest path from the root to a leaf. An empty tree has height
+    /// 0 and a tree with only one value has height 1.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// assert_eq!(tree.height(), 0);
+    ///
+    /// tree.insert(2);
+    /// tree.insert(1);
+    /// tree.insert(3);
+    /// assert_eq!(tree.height(), 2);
+    ///
+    /// tree.insert(4);
+    /// assert_eq!(tree.height(), 3);
+    /// ```
+    pub fn height(&self) -> usize {
+        self.height_and_balance().0
+    }
+
+    /// Returns `true` if, for every node in the tree, the heights of its left
+    /// and right subtrees differ by at most one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// tree.insert(1);
+    /// tree.insert(2);
+    /// assert!(tree.is_balanced());
+    ///
+    /// tree.insert(3);
+    /// assert!(!tree.is_balanced());
+    /// ```
+    pub fn is_balanced(&self) -> bool {
+        self.height_and_balance().1
+    }
+
+    /// Rebuilds the tree into a perfectly balanced shape, with height
+    /// `⌈log2(n + 1)⌉`. The existing nodes are relinked in place (nothing is
+    /// allocated or moved), which takes O(n) time and O(n) extra memory for
+    /// the list of nodes. Useful for trees that degraded after inserting
+    /// values in ascending or descending order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// for value in 0..15 {
+    ///     tree.insert(value);
+    /// }
+    /// assert_eq!(tree.height(), 15);
+    ///
+    /// tree.rebalance();
+    /// assert_eq!(tree.height(), 4);
+    /// assert!(tree.is_balanced());
+    /// assert!(tree.iter().copied().eq(0..15));
+    /// ```
+    pub fn rebalance(&mut self) {
+        let mut nodes = Vec::with_capacity(self.size);
+
+        unsafe {
+            let mut current = self.root.map(|root| Node::leftmost(root));
+
+            while let Some(node) = current {
+                current = Node::successor(node);
+                nodes.push(node);
+            }
+
+            self.set_root(Self::link_balanced(&nodes));
+        }
+    }
+
+    /// Links the given nodes, sorted in inorder, into a balanced subtree the
+    /// same way `build_balanced` does and returns its root.
+    unsafe fn link_balanced(nodes: &[ptr::NonNull<Node<T>>]) -> Link<T> {
+        if nodes.is_empty() {
+            return None;
+        }
+
+        let middle = nodes.len() / 2;
+        let (node, left, right) = (nodes[middle], &nodes[..middle], &nodes[middle + 1..]);
+
+        Node::set_left(node, Self::link_balanced(left));
+        Node::set_right(node, Self::link_balanced(right));
+        Node::update_size(node);
+
+        Some(node)
+    }
+}
+
+impl<T, C: Compare<T>, A: Allocator> BinaryTree<T, C, A> {
+    /// Verifies the structure of the tree: values are in strictly ascending
+    /// order according to the comparator, every child points back to its
+    /// parent, every node stores the size of its subtree and the tree stores
+    /// the number of nodes. Returns the first violation found.
+    ///
+    /// The public API always keeps these invariants, this is meant for tests
+    /// and debugging, for example after a comparator that is not a total
+    /// order has been used. Runs in O(n) without recursion.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// for value in [2, 1, 3] {
+    ///     tree.insert(value);
+    /// }
+    ///
+    /// assert_eq!(tree.check_invariants(), Ok(()));
+    /// ```
+    pub fn check_invariants(&self) -> Result<(), InvariantError> {
+        unsafe {
+            // Inorder traversal with an explicit stack that only follows the
+            // child links, the parent links can't be trusted yet.
+            let mut stack = Vec::new();
+            let mut current = self.root;
+            let mut previous: Option<ptr::NonNull<Node<T>>> = None;
+            let mut index = 0;
+
+            loop {
+                while let Some(node) = current {
+                    // Stops cycles from looping forever.
+                    if index + stack.len() >= self.size {
+                        return Err(InvariantError::Size {
+                            stored: self.size,
+                            actual: None,
+                        });
+                    }
+
+                    stack.push(node);
+                    current = (*node.as_ptr()).left;
+                }
+
+                let Some(node) = stack.pop() else {
+                    break;
+                };
+
+                if let Some(previous) = previous {
+                    let ordering = self
+                        .comparator
+                        .compare(&(*previous.as_ptr()).value, &(*node.as_ptr()).value);
+
+                    if ordering != Ordering::Less {
+                        return Err(InvariantError::Unordered { index });
+                    }
+                }
+
+                let (left, right) = ((*node.as_ptr()).left, (*node.as_ptr()).right);
+
+                let parent_links_ok = [left, right]
+                    .into_iter()
+                    .flatten()
+                    .all(|child| (*child.as_ptr()).parent == Some(node));
+
+                if !parent_links_ok
+                    || (Some(node) == self.root && (*node.as_ptr()).parent.is_some())
+                {
+                    return Err(InvariantError::ParentLink { index });
+                }
+
+                // Checking each node against its children is enough, sizes
+                // are then correct by induction from the leaves.
+                let expected = 1 + Node::size_of(left) + Node::size_of(right);
+
+                if (*node.as_ptr()).size != expected {
+                    return Err(InvariantError::SubtreeSize {
+                        index,
+                        stored: (*node.as_ptr()).size,
+                        expected,
+                    });
+                }
+
+                previous = Some(node);
+                current = right;
+                index += 1;
+            }
+
+            if index != self.size {
+                return Err(InvariantError::Size {
+                    stored: self.size,
+                    actual: Some(index),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Violation of the structure of a [`BinaryTree`] found by
+/// [`BinaryTree::check_invariants`]. Nodes are identified by the position of
+/// their value in ascending order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvariantError {
+    /// The value at `index` is not greater than the previous value.
+    Unordered { index: usize },
+    /// A child of the node at `index` doesn't point back to it, or the node
+    /// is the root and has a parent.
+    ParentLink { index: usize },
+    /// The subtree size stored in the node at `index` doesn't match the sizes
+    /// of its children.
+    SubtreeSize {
+        index: usize,
+        stored: usize,
+        expected: usize,
+    },
+    /// The size of the tree doesn't match the number of nodes. `actual` is
+    /// `None` if there are more nodes than expected, which includes cycles.
+    Size {
+        stored: usize,
+        actual: Option<usize>,
+    },
+}
+
+impl fmt::Display for InvariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unordered { index } => {
+                write!(f, "value {index} is not greater than the previous value")
+            }
+            Self::ParentLink { index } => {
+                write!(f, "wrong parent link at the node of value {index}")
+            }
+            Self::SubtreeSize {
+                index,
+                stored,
+                expected,
+            } => write!(
+                f,
+                "node of value {index} stores subtree size {stored}, expected {expected}"
+            ),
+            Self::Size {
+                stored,
+                actual: Some(actual),
+            } => write!(f, "tree stores size {stored} but has {actual} nodes"),
+            Self::Size {
+                stored,
+                actual: None,
+            } => {
+                write!(f, "tree stores size {stored} but has more nodes")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvariantError {}
+
+impl<T: fmt::Debug, C, A: Allocator> fmt::Debug for BinaryTree<T, C, A> {
+    /// Formats the values of the tree as a set in ascending order.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T: fmt::Debug, C, A: Allocator> BinaryTree<T, C, A> {
+    /// Writes the address of the root node, then one line per node in
+    /// preorder with its address, value, links and subtree size. Missing
+    /// links are written as null pointers, `0x0`. Unlike
+    /// [`BinaryTree::to_ascii`], this shows the actual pointers, which is
+    /// meant for seeing how rotations and removals rewire the nodes. The
+    /// format may change at any time.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `w` if writing fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// tree.insert(2);
+    /// tree.insert(1);
+    ///
+    /// let mut layout = String::new();
+    /// tree.dump_layout(&mut layout).unwrap();
+    ///
+    /// // Something like:
+    /// // root 0x5581e0a31b10, size 2
+    /// // 0x5581e0a31b10: 2, parent 0x0, left 0x5581e0a31b50, right 0x0, size 2
+    /// // 0x5581e0a31b50: 1, parent 0x5581e0a31b10, left 0x0, right 0x0, size 1
+    /// assert_eq!(layout.lines().count(), 3);
+    /// assert!(layout.lines().nth(1).unwrap().contains(": 2, parent 0x0, left 0x"));
+    /// ```
+    pub fn dump_layout(&self, mut w: impl fmt::Write) -> fmt::Result {
+        writeln!(w, "root {:p}, size {}", as_ptr(self.root), self.size)?;
+
+        // Right children are pushed first so left subtrees come out first
+        let mut stack: Vec<_> = self.root.into_iter().collect();
+
+        while let Some(node) = stack.pop() {
+            let node = unsafe { node.as_ref() };
+            writeln!(
+                w,
+                "{:p}: {:?}, parent {:p}, left {:p}, right {:p}, size {}",
+                node,
+                node.value,
+                as_ptr(node.parent),
+                as_ptr(node.left),
+                as_ptr(node.right),
+                node.size
+            )?;
+            stack.extend(node.right);
+            stack.extend(node.left);
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the address `link` points to, null if there's no node.
+fn as_ptr<T>(link: Link<T>) -> *const Node<T> {
+    link.map_or(ptr::null(), |node| node.as_ptr())
+}
+
+/// Pending work on the explicit stack of [`BinaryTree::to_dot`].
+enum DotStep<T> {
+    /// Writes the given child of the node with the given id, or a point if
+    /// the child is missing. The root has no parent.
+    Child(Link<T>, Option<usize>),
+
+    /// Writes the edge from the first id to the second one.
+    Edge(usize, usize),
+}
+
+impl<T: fmt::Display, C, A: Allocator> BinaryTree<T, C, A> {
+    /// Returns a [Graphviz](https://graphviz.org) description of the shape of
+    /// the tree in the DOT language. Render it with `dot -Tpng`, for example.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// tree.insert(2);
+    /// tree.insert(1);
+    /// tree.insert(3);
+    ///
+    /// let dot = tree.to_dot();
+    ///
+    /// assert!(dot.starts_with("digraph BinaryTree {"));
+    /// assert!(dot.contains("n0 [label=\"2\"];"));
+    /// assert!(dot.contains("n0 -> n1;"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph BinaryTree {\n");
+
+        // Ids are assigned in preorder. A missing child is drawn as a point so
+        // that left and right children can be told apart, and the edge to a
+        // node is written after its subtree.
+        let mut stack = Vec::new();
+        let mut next_id = 0;
+
+        if self.root.is_some() {
+            stack.push(DotStep::Child(self.root, None));
+        }
+
+        while let Some(step) = stack.pop() {
+            let (link, parent) = match step {
+                DotStep::Child(link, parent) => (link, parent),
+
+                DotStep::Edge(from, to) => {
+                    dot.push_str(&format!("    n{from} -> n{to};\n"));
+                    continue;
+                }
+            };
+
+            let id = next_id;
+            next_id += 1;
+
+            if let Some(parent) = parent {
+                stack.push(DotStep::Edge(parent, id));
+            }
+
+            let Some(node) = link else {
+                dot.push_str(&format!("    n{id} [shape=point];\n"));
+                continue;
+            };
+
+            unsafe {
+                let label = (*node.as_ptr()).value.to_string();
+                let label = label.replace('\\', "\\\\").replace('"', "\\\"");
+                dot.push_str(&format!("    n{id} [label=\"{label}\"];\n"));
+
+                let (left, right) = ((*node.as_ptr()).left, (*node.as_ptr()).right);
+
+                if left.is_some() || right.is_some() {
+                    stack.push(DotStep::Child(right, Some(id)));
+                    stack.push(DotStep::Child(left, Some(id)));
+                }
+            }
+        }
+
+        dot.push_str("}\n");
+
+        dot
+    }
+
+    /// Pushes the children of `node` on the stack of `to_ascii`, so that the
+    /// left one is written first. Nothing is pushed for leaves, but if only
+    /// one child is present the other one is written as `(none)` so that
+    /// left and right can be told apart.
+    unsafe fn push_ascii_children(
+        node: ptr::NonNull<Node<T>>,
+        prefix: String,
+        stack: &mut Vec<(Link<T>, String, bool)>,
+    ) {
+        let (left, right) = ((*node.as_ptr()).left, (*node.as_ptr()).right);
+
+        if left.is_some() || right.is_some() {
+            stack.push((right, prefix.clone(), true));
+            stack.push((left, prefix, false));
+        }
+    }
+
+    /// Returns a drawing of the shape of the tree made of ASCII characters,
+    /// with one value per line. Children are indented below their parent, the
+    /// left child first.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// for value in [20, 10, 30, 15] {
+    ///     tree.insert(value);
+    /// }
+    ///
+    /// let expected = "\
+    /// 20
+    /// |-- 10
+    /// |   |-- (none)
+    /// |   `-- 15
+    /// `-- 30
+    /// ";
+    ///
+    /// assert_eq!(tree.to_ascii(), expected);
+    /// ```
+    pub fn to_ascii(&self) -> String {
+        let mut ascii = String::new();
+
+        let Some(root) = self.root else {
+            return ascii;
+        };
+
+        // Nodes still to be written, each one with the branches of its
+        // ancestors that still have children to be written and whether it's
+        // the last child of its parent
+        let mut stack = Vec::new();
+
+        unsafe {
+            ascii.push_str(&format!("{}\n", (*root.as_ptr()).value));
+            Self::push_ascii_children(root, String::new(), &mut stack);
+
+            while let Some((node, prefix, is_last)) = stack.pop() {
+                ascii.push_str(&prefix);
+                ascii.push_str(if is_last { "`-- " } else { "|-- " });
+
+                let Some(node) = node else {
+                    ascii.push_str("(none)\n");
+                    continue;
+                };
+
+                ascii.push_str(&format!("{}\n", (*node.as_ptr()).value));
+
+                let prefix = format!("{prefix}{}", if is_last { "    " } else { "|   " });
+                Self::push_ascii_children(node, prefix, &mut stack);
+            }
+        }
+
+        ascii
+    }
+
+    /// Prints the drawing returned by [`BinaryTree::to_ascii`] to the
+    /// standard output.
+    pub fn print_ascii(&self) {
+        print!("{}", self.to_ascii());
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<T> Node<T> {
+    /// Returns the node that contains the value at position `index` (0 based)
+    /// in ascending order within the given subtree. The subtree sizes tell
+    /// which child contains it, so this is a single descent.
+    unsafe fn select(mut node: ptr::NonNull<Node<T>>, mut index: usize) -> ptr::NonNull<Node<T>> {
+        loop {
+            let left_size = Node::size_of((*node.as_ptr()).left);
+
+            node = match index.cmp(&left_size) {
+                Ordering::Less => (*node.as_ptr()).left.unwrap(),
+                Ordering::Equal => return node,
+                Ordering::Greater => {
+                    index -= left_size + 1;
+                    (*node.as_ptr()).right.unwrap()
+                }
+            };
+        }
+    }
+}
+
+/// Infinite iterator over values of the tree picked uniformly at random. See
+/// [`BinaryTree::iter_random`].
+#[cfg(feature = "rand")]
+pub struct RandomIter<'a, T, R: ?Sized> {
+    root: Link<T>,
+    rng: &'a mut R,
+    marker: marker::PhantomData<&'a T>,
+}
+
+#[cfg(feature = "rand")]
+impl<T, C, A: Allocator> BinaryTree<T, C, A> {
+    /// Returns a value of the tree picked uniformly at random, or `None` if
+    /// the tree is empty. Every node knows the size of its subtree, so this
+    /// takes O(h). Requires the `rand` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// use rand::{rngs::SmallRng, SeedableRng};
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// let mut rng = SmallRng::seed_from_u64(0);
+    ///
+    /// assert_eq!(tree.choose(&mut rng), None);
+    ///
+    /// for value in 1..=10 {
+    ///     tree.insert(value);
+    /// }
+    ///
+    /// let value = tree.choose(&mut rng).unwrap();
+    /// assert!((1..=10).contains(value));
+    /// ```
+    pub fn choose<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Option<&T> {
+        let root = self.root?;
+
+        unsafe {
+            let node = Node::select(root, rng.random_range(0..self.size));
+
+            Some(&(*node.as_ptr()).value)
+        }
+    }
+
+    /// Returns an iterator that yields values of the tree picked uniformly at
+    /// random, independently of each other, so values can repeat. The
+    /// iterator never ends unless the tree is empty. Requires the `rand`
+    /// feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// use rand::{rngs::SmallRng, SeedableRng};
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// let mut rng = SmallRng::seed_from_u64(0);
+    ///
+    /// for value in 1..=10 {
+    ///     tree.insert(value);
+    /// }
+    ///
+    /// let sample: Vec<_> = tree.iter_random(&mut rng).take(5).collect();
+    ///
+    /// assert_eq!(sample.len(), 5);
+    /// assert!(sample.iter().all(|value| tree.contains(value)));
+    /// ```
+    pub fn iter_random<'a, R: rand::Rng + ?Sized>(
+        &'a self,
+        rng: &'a mut R,
+    ) -> RandomIter<'a, T, R> {
+        RandomIter {
+            root: self.root,
+            rng,
+            marker: marker::PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<'a, T, R: rand::Rng + ?Sized> Iterator for RandomIter<'a, T, R> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let root = self.root?;
+
+        unsafe {
+            let index = self.rng.random_range(0..(*root.as_ptr()).size);
+
+            Some(&(*Node::select(root, index).as_ptr()).value)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.root {
+            None => (0, Some(0)),
+            Some(_) => (usize::MAX, None),
+        }
+    }
+}
+
+/// Parallel iterator over the values of the tree. See
+/// [`BinaryTree::par_iter`].
+#[cfg(feature = "rayon")]
+pub struct ParIter<'a, T> {
+    root: Link<T>,
+    marker: marker::PhantomData<&'a T>,
+}
+
+/// Part of the tree processed by one rayon job: the node itself plus, if
+/// requested, its left and right subtrees. Splitting detaches the left
+/// subtree first and then the right one, so every part is a contiguous run
+/// of values in ascending order.
+#[cfg(feature = "rayon")]
+struct SubtreeProducer<'a, T> {
+    node: Link<T>,
+    with_left: bool,
+    with_right: bool,
+    marker: marker::PhantomData<&'a T>,
+}
+
+// The iterators only hand out shared references to the values.
+#[cfg(feature = "rayon")]
+unsafe impl<'a, T: Sync> Send for ParIter<'a, T> {}
+
+#[cfg(feature = "rayon")]
+unsafe impl<'a, T: Sync> Send for SubtreeProducer<'a, T> {}
+
+#[cfg(feature = "rayon")]
+impl<T: Sync, C, A: Allocator> BinaryTree<T, C, A> {
+    /// Returns a [rayon](https://docs.rs/rayon) parallel iterator over the
+    /// values of the tree. The work is split by subtrees, order-sensitive
+    /// operations like `collect` keep the ascending order. Requires the
+    /// `rayon` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    ///
+    /// use rayon::prelude::*;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// for value in [4, 2, 6, 1, 3, 5, 7] {
+    ///     tree.insert(value);
+    /// }
+    ///
+    /// let squares: Vec<i32> = tree.par_iter().map(|value| value * value).collect();
+    ///
+    /// assert_eq!(squares, [1, 4, 9, 16, 25, 36, 49]);
+    /// ```
+    pub fn par_iter(&self) -> ParIter<'_, T> {
+        ParIter {
+            root: self.root,
+            marker: marker::PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'a, T: Sync> rayon::iter::ParallelIterator for ParIter<'a, T> {
+    type Item = &'a T;
+
+    fn drive_unindexed<Co>(self, consumer: Co) -> Co::Result
+    where
+        Co: rayon::iter::plumbing::UnindexedConsumer<Self::Item>,
+    {
+        let producer = SubtreeProducer {
+            node: self.root,
+            with_left: true,
+            with_right: true,
+            marker: marker::PhantomData,
+        };
+
+        rayon::iter::plumbing::bridge_unindexed(producer, consumer)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'a, T: Sync> rayon::iter::plumbing::UnindexedProducer for SubtreeProducer<'a, T> {
+    type Item = &'a T;
+
+    fn split(self) -> (Self, Option<Self>) {
+        let Some(node) = self.node else {
+            return (self, None);
+        };
+
+        let subtree = |child| SubtreeProducer {
+            node: child,
+            with_left: true,
+            with_right: true,
+            marker: marker::PhantomData,
+        };
+
+        unsafe {
+            match ((*node.as_ptr()).left, (*node.as_ptr()).right) {
+                (Some(left), _) if self.with_left => (
+                    subtree(Some(left)),
+                    Some(SubtreeProducer {
+                        with_left: false,
+                        ..self
+                    }),
+                ),
+
+                (_, Some(right)) if self.with_right => (
+                    SubtreeProducer {
+                        with_left: false,
+                        with_right: false,
+                        ..self
+                    },
+                    Some(subtree(Some(right))),
+                ),
+
+                _ => (self, None),
+            }
+        }
+    }
+
+    fn fold_with<F>(self, folder: F) -> F
+    where
+        F: rayon::iter::plumbing::Folder<Self::Item>,
+    {
+        let Some(node) = self.node else {
+            return folder;
+        };
+
+        unsafe {
+            let (left, right) = ((*node.as_ptr()).left, (*node.as_ptr()).right);
+            let (mut front, mut back, mut len) = (node, node, 1);
+
+            if let (Some(left), true) = (left, self.with_left) {
+                front = Node::leftmost(left);
+                len += (*left.as_ptr()).size;
+            }
+
+            if let (Some(right), true) = (right, self.with_right) {
+                back = Node::rightmost(right);
+                len += (*right.as_ptr()).size;
+            }
+
+            folder.consume_iter(Iter {
+                front: Some(front),
+                back: Some(back),
+                len,
+                marker: marker::PhantomData,
+            })
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, C, A: Allocator> serde::Serialize for BinaryTree<T, C, A> {
+    /// Serializes the tree as a sequence of values in ascending order.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T, C> serde::Deserialize<'de> for BinaryTree<T, C>
+where
+    T: serde::Deserialize<'de>,
+    C: Compare<T> + Default,
+{
+    /// Deserializes a sequence of values into a balanced tree. The sequence
+    /// doesn't need to be sorted, duplicated values are discarded.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut values = Vec::<T>::deserialize(deserializer)?;
+        let mut tree = Self::default();
+
+        values.sort_by(|a, b| tree.comparator.compare(a, b));
+        values.dedup_by(|a, b| tree.comparator.compare(a, b) == Ordering::Equal);
+
+        let len = values.len();
+        tree.extend_sorted(values.into_iter(), len);
+
+        Ok(tree)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: Ord + fmt::Debug> BinaryTree<T> {
+    /// Returns a [proptest](https://docs.rs/proptest) strategy that
+    /// generates balanced trees of distinct values from `element` with a
+    /// size in `size`. Failing cases shrink like `BTreeSet`s do, dropping
+    /// values first and then shrinking the ones left.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BinaryTree;
+    /// use proptest::{prop_assert, prop_assert_eq, test_runner::TestRunner};
+    ///
+    /// let mut runner = TestRunner::default();
+    ///
+    /// let result = runner.run(&BinaryTree::strategy(0..1000, 0..50), |tree| {
+    ///     prop_assert!(tree.is_balanced());
+    ///     prop_assert_eq!(tree.iter().count(), tree.size());
+    ///     Ok(())
+    /// });
+    ///
+    /// assert!(result.is_ok());
+    /// ```
+    pub fn strategy<S: proptest::strategy::Strategy<Value = T>>(
+        element: S,
+        size: impl Into<proptest::collection::SizeRange>,
+    ) -> impl proptest::strategy::Strategy<Value = Self> {
+        use proptest::strategy::Strategy;
+
+        proptest::collection::btree_set(element, size).prop_map(Self::from_set)
+    }
+
+    fn from_set(values: std::collections::BTreeSet<T>) -> Self {
+        let mut tree = Self::new();
+
+        let len = values.len();
+        tree.extend_sorted(values.into_iter(), len);
+
+        tree
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary + Ord> proptest::arbitrary::Arbitrary for BinaryTree<T> {
+    type Parameters = <std::collections::BTreeSet<T> as proptest::arbitrary::Arbitrary>::Parameters;
+    type Strategy = proptest::strategy::Map<
+        <std::collections::BTreeSet<T> as proptest::arbitrary::Arbitrary>::Strategy,
+        fn(std::collections::BTreeSet<T>) -> Self,
+    >;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+
+        proptest::arbitrary::any_with::<std::collections::BTreeSet<T>>(args)
+            .prop_map(Self::from_set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        alloc::Layout,
+        cell::Cell,
+        cmp::Ordering,
+        mem,
+        ops::{Bound, Range},
+        panic::{self, AssertUnwindSafe},
+        ptr::NonNull,
+    };
+
+    use super::{BinaryTree, DiffItem, InvariantError, Link, Node};
+    use crate::{Allocator, Global, HeapSize};
+
+    fn tree_values() -> Vec<i32> {
+        vec![20, 10, 30, 5, 15, 16, 14, 25, 21, 35, 40, 50]
+    }
+
+    fn check_links<T, C, A: Allocator>(tree: &BinaryTree<T, C, A>) {
+        unsafe fn check_node<T>(node: Link<T>, parent: Link<T>) -> usize {
+            match node {
+                None => 0,
+
+                Some(node) => {
+                    assert!((*node.as_ptr()).parent == parent);
+
+                    let left = check_node((*node.as_ptr()).left, Some(node));
+                    let right = check_node((*node.as_ptr()).right, Some(node));
+
+                    assert_eq!((*node.as_ptr()).size, 1 + left + right);
+
+                    1 + left + right
+                }
+            }
+        }
+
+        unsafe {
+            assert_eq!(check_node(tree.root, None), tree.size());
+        }
+    }
+
+    /// Builds the degenerate tree that inserting `values` in order builds.
+    /// Inserting would take quadratic time, so the nodes are linked by hand.
+    fn sorted_tree(values: Range<usize>) -> BinaryTree<usize> {
+        let mut tree = BinaryTree::new();
+
+        unsafe {
+            for value in values.rev() {
+                tree.root = Some(tree.new_node(value, tree.root, None));
+                tree.size += 1;
+            }
+        }
+
+        tree
+    }
+
+    /// Runs `f` in a thread whose stack is too small to recurse on the height
+    /// of the trees built by `sorted_tree`.
+    fn with_small_stack<F: FnOnce() + Send + 'static>(f: F) {
+        std::thread::Builder::new()
+            .stack_size(256 * 1024)
+            .spawn(f)
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_insert() {
+        let numbers = tree_values();
+
+        let mut tree = BinaryTree::new();
+
+        tree.insert(numbers[0]);
+        assert!(tree.contains(&numbers[0]));
+
+        tree.insert(numbers[1]);
+        assert!(tree.contains(&numbers[1]));
+
+        tree.insert(numbers[2]);
+        assert!(tree.contains(&numbers[2]));
+
+        assert_eq!(tree.size(), 3);
+
+        for n in &numbers[3..] {
+            tree.insert(*n);
+        }
+
+        for n in &numbers {
+            assert!(tree.contains(n));
+        }
+
+        assert_eq!(tree.size(), numbers.len());
+        check_links(&tree);
+    }
+
+    #[test]
+    fn test_remove() {
+        let numbers = tree_values();
+
+        let mut tree = BinaryTree::new();
+
+        for n in &numbers {
+            tree.insert(*n);
+        }
+
+        // Node with no children
+        tree.remove(&50);
+        assert!(!tree.contains(&50));
+
+        // Node with one child to the right
+        tree.remove(&35);
+        assert!(!tree.contains(&35));
+        assert!(tree.contains(&40));
+
+        // Node with one child to the left
+        tree.remove(&25);
+        assert!(!tree.contains(&25));
+        assert!(tree.contains(&21));
+
+        // Node with two children
+        tree.remove(&10);
+        assert!(!tree.contains(&10));
+        assert!(tree.contains(&5));
+        assert!(tree.contains(&15));
+
+        // Root
+        tree.remove(&20);
+        assert!(!tree.contains(&20));
+
+        // Check remaining values
+        assert!(tree.contains(&40));
+        assert!(tree.contains(&21));
+        assert!(tree.contains(&30));
+        assert!(tree.contains(&5));
+        assert!(tree.contains(&15));
+        assert!(tree.contains(&14));
+        assert!(tree.contains(&16));
+        check_links(&tree);
+    }
+
+    #[test]
+    fn test_remove_owned_values() {
+        let mut tree = BinaryTree::new();
+
+        for value in tree_values() {
+            tree.insert(value.to_string());
+        }
+
+        // Two children, successor is the right child
+        assert!(tree.remove(&String::from("15")));
+
+        // Two children, successor deeper in the right subtree
+        assert!(tree.remove(&String::from("20")));
+
+        // One child
+        assert!(tree.remove(&String::from("10")));
+
+        // Leaf and missing value
+        assert!(tree.remove(&String::from("40")));
+        assert!(!tree.remove(&String::from("40")));
+
+        let mut values: Vec<_> = tree_values()
+            .iter()
+            .map(|value| value.to_string())
+            .collect();
+        values.retain(|value| !["15", "20", "10", "40"].contains(&value.as_str()));
+        values.sort();
+
+        assert_eq!(tree.size(), values.len());
+        assert!(tree.iter().eq(values.iter()));
+        check_links(&tree);
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut values = tree_values();
+
+        let mut tree = BinaryTree::new();
+
+        for value in values.iter() {
+            tree.insert(*value);
+        }
+
+        let mut iter = tree.iter();
+
+        values.sort();
+
+        for value in values.iter() {
+            let tree_value = iter.next();
+            assert!(tree_value.is_some());
+            assert_eq!(value, tree_value.unwrap());
+        }
+
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_iter_double_ended() {
+        let mut values = tree_values();
+
+        let mut tree = BinaryTree::new();
+
+        for value in values.iter() {
+            tree.insert(*value);
+        }
+
+        values.sort();
+
+        assert!(tree.iter().rev().eq(values.iter().rev()));
+
+        let mut iter = tree.iter();
+        assert_eq!(iter.len(), values.len());
+        assert_eq!(iter.next(), Some(&5));
+        assert_eq!(iter.next_back(), Some(&50));
+        assert_eq!(iter.next_back(), Some(&40));
+        assert_eq!(iter.len(), values.len() - 3);
+
+        // Both ends meet in the middle
+        assert!(iter.by_ref().eq(values[1..values.len() - 2].iter()));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        let empty = BinaryTree::<i32>::new();
+        assert_eq!(empty.iter().next(), None);
+        assert_eq!(empty.iter().next_back(), None);
+    }
+
+    #[test]
+    fn test_cursor() {
+        let mut values = tree_values();
+
+        let mut tree = BinaryTree::new();
+
+        for value in values.iter() {
+            tree.insert(*value);
+        }
+
+        values.sort();
+
+        let mut cursor = tree.cursor();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&5));
+        assert_eq!(cursor.peek_prev(), Some(&50));
+
+        for value in &values {
+            cursor.move_next();
+            assert_eq!(cursor.current(), Some(value));
+        }
+
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+
+        for value in values.iter().rev() {
+            cursor.move_prev();
+            assert_eq!(cursor.current(), Some(value));
+        }
+
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+
+        assert!(cursor.seek(&16));
+        assert_eq!(cursor.current(), Some(&16));
+        assert_eq!(cursor.peek_next(), Some(&20));
+        assert_eq!(cursor.peek_prev(), Some(&15));
+
+        assert!(!cursor.seek(&17));
+        assert_eq!(cursor.current(), Some(&20));
+
+        assert!(!cursor.seek(&60));
+        assert_eq!(cursor.current(), None);
+
+        let empty = BinaryTree::<i32>::new();
+        let mut cursor = empty.cursor();
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+    }
+
+    #[test]
+    fn test_cursor_mut() {
+        let mut values = tree_values();
+
+        let mut tree = BinaryTree::new();
+
+        for value in values.iter() {
+            tree.insert(*value);
+        }
+
+        values.sort();
+
+        // Modify values in place without changing their order
+        let mut cursor = tree.cursor_mut();
+        cursor.move_next();
+
+        while let Some(value) = cursor.current() {
+            *value *= 10;
+            cursor.move_next();
+        }
+
+        assert!(tree.iter().eq(values
+            .iter()
+            .map(|value| value * 10)
+            .collect::<Vec<_>>()
+            .iter()));
+
+        // Remove nodes with zero, one and two children while scanning
+        let mut cursor = tree.cursor_mut();
+        assert_eq!(cursor.remove_current(), None);
+
+        assert!(cursor.seek(&200));
+        assert_eq!(cursor.remove_current(), Some(200));
+        assert_eq!(cursor.current(), Some(&mut 210));
+
+        assert!(cursor.seek(&100));
+        assert_eq!(cursor.remove_current(), Some(100));
+        assert_eq!(cursor.current(), Some(&mut 140));
+
+        assert!(cursor.seek(&500));
+        assert_eq!(cursor.remove_current(), Some(500));
+        assert_eq!(cursor.current(), None);
+
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 400));
+        assert_eq!(cursor.peek_prev(), Some(&350));
+
+        values.retain(|value| ![20, 10, 50].contains(value));
+        assert_eq!(tree.size(), values.len());
+        assert!(tree.iter().eq(values
+            .iter()
+            .map(|value| value * 10)
+            .collect::<Vec<_>>()
+            .iter()));
+        check_links(&tree);
+
+        // Remove everything
+        let mut cursor = tree.cursor_mut();
+        cursor.move_next();
+
+        while cursor.remove_current().is_some() {}
+
+        assert!(tree.is_empty());
+        check_links(&tree);
+    }
+
+    #[test]
+    fn test_next_after() {
+        let mut values = tree_values();
+
+        let mut tree = BinaryTree::new();
+
+        for value in values.iter() {
+            tree.insert(*value);
+        }
+
+        values.sort();
+
+        for window in values.windows(2) {
+            assert_eq!(tree.next_after(&window[0]), Some(&window[1]));
+            assert_eq!(tree.next_after(&(window[1] - 1)), Some(&window[1]));
+        }
+
+        assert_eq!(tree.next_after(&0), Some(&5));
+        assert_eq!(tree.next_after(&50), None);
+        assert_eq!(tree.next_after(&100), None);
+    }
+
+    #[test]
+    fn test_default() {
+        #[derive(Default)]
+        struct Wrapper<T> {
+            tree: BinaryTree<T>,
+        }
+
+        let wrapper: Wrapper<f64> = Wrapper::default();
+        assert!(wrapper.tree.is_empty());
+        assert_eq!(wrapper.tree.size(), 0);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut values = tree_values();
+
+        let mut tree = BinaryTree::new();
+
+        for value in &values {
+            tree.insert(*value);
+        }
+
+        let mut visited = Vec::new();
+        tree.retain(|value| {
+            visited.push(*value);
+            value % 5 != 0
+        });
+
+        values.sort();
+        assert_eq!(visited, values);
+
+        values.retain(|value| value % 5 != 0);
+        assert_eq!(tree.size(), values.len());
+        assert!(tree.iter().eq(values.iter()));
+
+        for value in &values {
+            assert!(tree.contains(value));
+        }
+
+        check_links(&tree);
+
+        tree.retain(|_| false);
+        assert!(tree.is_empty());
+        assert_eq!(tree.iter().next(), None);
+    }
+
+    #[test]
+    fn test_macro() {
+        let tree: BinaryTree<i32> = binary_tree! {};
+        assert!(tree.is_empty());
+
+        let tree = binary_tree! {5, 3, 8, 1, 4, 3};
+        check_links(&tree);
+        assert_eq!(tree.size(), 5);
+        assert_eq!(tree.height(), 3);
+        assert!(tree.iter().eq(&[1, 3, 4, 5, 8]));
+    }
+
+    #[test]
+    fn test_try_retain() {
+        let mut tree = BinaryTree::new();
+        assert_eq!(tree.try_extend((0..20).map(Ok::<_, ()>)), Ok(()));
+        assert_eq!(tree.try_extend([Ok(25), Err(30), Ok(35)]), Err(30));
+
+        let mut calls = 0;
+        let result = tree.try_retain(|&value| {
+            calls += 1;
+            match value {
+                10 => Err(value),
+                _ => Ok(value % 2 == 0),
+            }
+        });
+
+        // Odd values before 10 are removed, 10 and the ones after it kept
+        assert_eq!(result, Err(10));
+        assert_eq!(calls, 11);
+        assert!(tree
+            .iter()
+            .copied()
+            .eq([0, 2, 4, 6, 8].into_iter().chain(10..20).chain([25])));
+        check_links(&tree);
+    }
+
+    #[test]
+    fn test_extract_if() {
+        let mut values = tree_values();
+
+        let mut tree = BinaryTree::new();
+
+        for value in &values {
+            tree.insert(*value);
+        }
+
+        let extracted: Vec<_> = tree.extract_if(|value| *value > 15).collect();
+        assert_eq!(extracted, [16, 20, 21, 25, 30, 35, 40, 50]);
+
+        values.sort();
+        values.retain(|value| *value <= 15);
+        assert_eq!(tree.size(), values.len());
+        assert!(tree.iter().eq(values.iter()));
+        check_links(&tree);
+
+        // Not consuming the iterator still removes the values
+        drop(tree.extract_if(|value| *value == 10));
+        assert!(!tree.contains(&10));
+        assert_eq!(tree.size(), values.len() - 1);
+    }
+
+    #[test]
+    fn test_split_off() {
+        let mut values = tree_values();
+        values.sort();
+
+        let build = || {
+            let mut tree = BinaryTree::new();
+
+            for value in tree_values() {
+                tree.insert(value);
+            }
+
+            tree
+        };
+
+        // Present value, absent value, below the minimum and above the maximum
+        for key in [20, 15, 17, 0, 100] {
+            let mut tree = build();
+            let greater_or_equal = tree.split_off(&key);
+
+            let (less, rest): (Vec<i32>, Vec<i32>) = values.iter().partition(|value| **value < key);
+
+            assert_eq!(tree.size(), less.len());
+            assert_eq!(greater_or_equal.size(), rest.len());
+            assert!(tree.iter().eq(less.iter()));
+            assert!(greater_or_equal.iter().eq(rest.iter()));
+            check_links(&tree);
+            check_links(&greater_or_equal);
+
+            for value in &less {
+                assert!(tree.contains(value));
+                assert!(!greater_or_equal.contains(value));
+            }
+
+            for value in &rest {
+                assert!(greater_or_equal.contains(value));
+                assert!(!tree.contains(value));
+            }
+        }
+    }
+
+    #[test]
+    fn test_remove_range() {
+        let mut tree = BinaryTree::new();
+
+        for value in tree_values() {
+            tree.insert(value);
+        }
+
+        assert_eq!(tree.remove_range(14..=21), 5);
+        assert!(tree.iter().eq([5, 10, 25, 30, 35, 40, 50].iter()));
+        assert_eq!(tree.size(), 7);
+        check_links(&tree);
+
+        assert_eq!(tree.remove_range(11..12), 0);
+        assert_eq!(
+            tree.remove_range((Bound::Included(40), Bound::Excluded(10))),
+            0
+        );
+        assert_eq!(tree.size(), 7);
+        check_links(&tree);
+
+        assert_eq!(tree.remove_range(..=10), 2);
+        assert_eq!(
+            tree.remove_range((Bound::Excluded(30), Bound::Unbounded)),
+            3
+        );
+        assert!(tree.iter().eq([25, 30].iter()));
+        check_links(&tree);
+
+        assert_eq!(tree.remove_range(..), 2);
+        assert!(tree.is_empty());
+        assert_eq!(tree.iter().next(), None);
+
+        let mut tree = BinaryTree::new();
+
+        for value in 0..100 {
+            tree.insert(value.to_string());
+        }
+
+        assert_eq!(tree.remove_range(String::from("2")..String::from("5")), 33);
+        assert!(tree
+            .iter()
+            .all(|value| !("2".."5").contains(&value.as_str())));
+        check_links(&tree);
+
+        // Every pair of bounds, including inverted ones, on an unbalanced tree
+        let values: Vec<i32> = (0..16).map(|value| value * 7 % 16).collect();
+
+        for start in -1..=16 {
+            for end in -1..=16 {
+                let mut tree = BinaryTree::new();
+                values.iter().for_each(|&value| {
+                    tree.insert(value);
+                });
+
+                let mut expected: Vec<i32> = (values.iter().copied())
+                    .filter(|&value| value <= start || value > end)
+                    .collect();
+                expected.sort();
+
+                let removed = tree.remove_range((Bound::Excluded(start), Bound::Included(end)));
+
+                assert_eq!(removed, values.len() - expected.len());
+                assert!(tree.iter().eq(expected.iter()));
+                check_links(&tree);
+            }
+        }
+    }
+
+    #[test]
+    fn test_append() {
+        // Small tree, values are inserted one by one
+        let mut a = BinaryTree::new();
+        let mut b = BinaryTree::new();
+
+        for value in 0..100 {
+            a.insert(value * 2);
+        }
+
+        b.insert(1);
+        b.insert(50);
+
+        a.append(b);
+
+        assert_eq!(a.size(), 101);
+        assert!(a.contains(&1));
+        assert!(a.contains(&50));
+        assert!(a.iter().zip(a.iter().skip(1)).all(|(x, y)| x < y));
+        check_links(&a);
+
+        // Similar sizes, the trees are merged and rebuilt
+        let mut a = BinaryTree::new();
+        let mut b = BinaryTree::new();
+
+        for value in 0..100 {
+            a.insert(value);
+            b.insert(value + 50);
+        }
+
+        a.append(b);
+
+        assert_eq!(a.size(), 150);
+        assert!(a.iter().eq((0..150).collect::<Vec<_>>().iter()));
+        assert!(a.is_balanced());
+        check_links(&a);
+
+        for value in 0..150 {
+            assert!(a.contains(&value));
+        }
+
+        // Empty operands
+        let mut empty = BinaryTree::new();
+        empty.append(a);
+        assert_eq!(empty.size(), 150);
+
+        empty.append(BinaryTree::new());
+        assert_eq!(empty.size(), 150);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut tree = BinaryTree::new();
+
+        for value in tree_values() {
+            tree.insert(value);
+        }
+
+        tree.clear();
+
+        assert!(tree.is_empty());
+        assert_eq!(tree.height(), 0);
+        assert_eq!(tree.iter().next(), None);
+
+        for value in tree_values() {
+            assert!(!tree.contains(&value));
+        }
+
+        // Reuse
+        for value in tree_values() {
+            assert!(tree.insert(value));
+        }
+
+        assert_eq!(tree.size(), tree_values().len());
+    }
+
+    #[test]
+    fn test_drop_deep_tree() {
+        // Build degenerate trees by hand, inserting this many sorted values
+        // would take quadratic time.
+        let len = 1_000_000;
+
+        for right_spine in [true, false] {
+            let mut tree = BinaryTree::new();
+
+            unsafe {
+                for value in 0..len {
+                    tree.root = if right_spine {
+                        Some(tree.new_node(len - value, tree.root, None))
+                    } else {
+                        Some(tree.new_node(value, None, tree.root))
+                    };
+                }
+            }
+
+            tree.size = len;
+            drop(tree);
+        }
+    }
+
+    #[test]
+    fn test_retain_deep_tree() {
+        with_small_stack(|| {
+            let len = 100_000;
+            let mut tree = sorted_tree(0..len);
+
+            tree.retain(|value| value % 3 != 0);
+            assert_eq!(tree.check_invariants(), Ok(()));
+            assert!(tree
+                .iter()
+                .copied()
+                .eq((0..len).filter(|value| value % 3 != 0)));
+
+            let evens: Vec<_> = tree.extract_if(|value| value % 2 == 0).collect();
+            let expected = (0..len).filter(|value| value % 3 != 0 && value % 2 == 0);
+            assert!(evens.into_iter().eq(expected));
+            assert_eq!(tree.check_invariants(), Ok(()));
+            assert!(tree
+                .iter()
+                .copied()
+                .eq((0..len).filter(|value| value % 6 == 1 || value % 6 == 5)));
+        });
+    }
+
+    #[test]
+    fn test_append_deep_trees() {
+        with_small_stack(|| {
+            let mut tree = sorted_tree(0..60_000);
+            tree.append(sorted_tree(40_000..100_000));
+
+            assert_eq!(tree.check_invariants(), Ok(()));
+            assert!(tree.iter().copied().eq(0..100_000));
+        });
+    }
+
+    #[test]
+    fn test_map_deep_tree() {
+        with_small_stack(|| {
+            let doubled = sorted_tree(0..100_000).map(|value| value * 2);
+            assert_eq!(doubled.check_invariants(), Ok(()));
+            assert!(doubled
+                .iter()
+                .copied()
+                .eq((0..100_000).map(|value| value * 2)));
+
+            let halved = doubled.map(|value| value / 4);
+            assert_eq!(halved.check_invariants(), Ok(()));
+            assert!(halved.iter().copied().eq(0..50_000));
+        });
+    }
+
+    #[test]
+    fn test_split_deep_tree() {
+        with_small_stack(|| {
+            let mut tree = sorted_tree(0..100_000);
+
+            assert_eq!(tree.remove_range(20_000..30_000), 10_000);
+            assert_eq!(tree.check_invariants(), Ok(()));
+            assert!(tree.iter().copied().eq((0..20_000).chain(30_000..100_000)));
+
+            let greater = tree.split_off(&60_000);
+            assert_eq!(tree.check_invariants(), Ok(()));
+            assert_eq!(greater.check_invariants(), Ok(()));
+            assert!(tree.iter().copied().eq((0..20_000).chain(30_000..60_000)));
+            assert!(greater.iter().copied().eq(60_000..100_000));
+        });
+    }
+
+    #[test]
+    fn test_height_deep_tree() {
+        with_small_stack(|| {
+            let mut tree = sorted_tree(0..100_000);
+
+            assert_eq!(tree.height(), 100_000);
+            assert!(!tree.is_balanced());
+            assert_eq!(tree.depth_of(&99_999), Some(99_999));
+            assert_eq!(tree.depth_of(&100_000), None);
+
+            tree.rebalance();
+            assert_eq!(tree.height(), 17);
+            assert!(tree.is_balanced());
+        });
+    }
+
+    #[test]
+    fn test_draw_deep_tree() {
+        with_small_stack(|| {
+            let dot = sorted_tree(0..100_000).to_dot();
+            assert!(dot.contains("n0 [label=\"0\"];"));
+            assert!(dot.contains("n199998 [label=\"99999\"];"));
+            assert!(dot.contains("n199996 -> n199998;"));
+
+            // Every line is indented below the previous one, keep the
+            // drawing small
+            let ascii = sorted_tree(0..3_000).to_ascii();
+            assert_eq!(ascii.lines().count(), 5_999);
+            assert!(ascii.ends_with(&format!("{}`-- 2999\n", "    ".repeat(2_998))));
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "splay_tree")]
+    fn test_splay() {
+        let mut tree = BinaryTree::new();
+
+        for value in tree_values() {
+            assert!(tree.insert_and_splay(value));
+            assert_eq!(tree.root_value(), Some(&value));
+            check_links(&tree);
+        }
+
+        for value in [5, 50, 21, 99, 0, 16] {
+            assert_eq!(tree.splay(&value), tree.contains(&value));
+            check_links(&tree);
+        }
+
+        assert_eq!(tree.root_value(), Some(&16));
+        assert!(!tree.insert_and_splay(30));
+        assert_eq!(tree.depth_of(&30), Some(0));
+
+        let mut values = tree_values();
+        values.sort();
+        assert!(tree.iter().eq(values.iter()));
+    }
+
+    #[test]
+    fn test_arena() {
+        let mut tree = BinaryTree::new_with_arena(4);
+
+        for value in tree_values() {
+            assert!(tree.insert(value.to_string()));
+        }
+
+        check_links(&tree);
+        assert_eq!(tree.arena.as_ref().unwrap().chunk_count(), 3);
+
+        // Freed slots are reused
+        assert!(tree.remove(&String::from("20")));
+        assert!(tree.insert(String::from("60")));
+        assert_eq!(tree.arena.as_ref().unwrap().chunk_count(), 3);
+
+        tree.retain(|value| value != "15");
+        assert_eq!(tree.extract_if(|value| value == "5").count(), 1);
+        assert_eq!(tree.remove_range(String::from("40")..String::from("50")), 1);
+        check_links(&tree);
 
-        assert_eq!(tree.size(), 3);
+        let greater = tree.split_off(&String::from("3"));
+        check_links(&tree);
+        check_links(&greater);
+        assert!(greater.arena.is_some());
+        assert!(tree.iter().eq(["10", "14", "16", "21", "25"].iter()));
+        assert!(greater.iter().eq(["30", "35", "50", "60"].iter()));
 
-        for n in &numbers[3..] {
-            tree.insert(*n);
+        tree.append(greater);
+        assert_eq!(tree.size(), 9);
+        check_links(&tree);
+
+        let mut boxed = BinaryTree::new();
+        boxed.insert(String::from("0"));
+        tree.append(boxed);
+
+        let mut empty = BinaryTree::new_with_arena(2);
+        empty.append(tree);
+        assert_eq!(empty.size(), 10);
+        check_links(&empty);
+
+        empty.clear();
+        assert!(empty.is_empty());
+        assert_eq!(empty.arena.as_ref().unwrap().chunk_count(), 0);
+
+        empty.insert(String::from("again"));
+        assert!(empty.iter().eq(["again"].iter()));
+    }
+
+    #[test]
+    fn test_heap_size() {
+        let node = mem::size_of::<Node<String>>();
+        let strings = |tree: &BinaryTree<String>| tree.iter().map(String::capacity).sum::<usize>();
+
+        let mut boxed = BinaryTree::new();
+        let mut arena = BinaryTree::new_with_arena(4);
+
+        for value in 0..10 {
+            boxed.insert(value.to_string());
+            arena.insert(value.to_string());
         }
 
-        for n in &numbers {
-            assert!(tree.contains(n));
+        assert_eq!(boxed.heap_size_of_children(), 10 * node + strings(&boxed));
+
+        // Whole chunks count, whether their slots are used or not, plus the
+        // list of chunks
+        let chunks = arena.heap_size_of_children() - strings(&arena);
+        assert_eq!(arena.arena.as_ref().unwrap().chunk_count(), 3);
+        assert!(chunks > 12 * node && chunks < 14 * node);
+
+        boxed.remove(&String::from("5"));
+        assert_eq!(boxed.heap_size_of_children(), 9 * node + strings(&boxed));
+    }
+
+    #[test]
+    fn test_allocator() {
+        /// Allocator that hands out a limited number of blocks.
+        struct Limited(Cell<usize>);
+
+        unsafe impl Allocator for Limited {
+            fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+                self.0.set(self.0.get().checked_sub(1)?);
+                Global.allocate(layout)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                self.0.set(self.0.get() + 1);
+                Global.deallocate(ptr, layout);
+            }
         }
 
-        assert_eq!(tree.size(), numbers.len());
+        let limited = Limited(Cell::new(8));
+        let mut tree = BinaryTree::new_in(&limited);
+
+        for value in tree_values().into_iter().take(8) {
+            assert_eq!(tree.try_insert(value.to_string()), Ok(true));
+        }
+
+        assert!(tree.try_insert(String::from("0")).is_err());
+        assert_eq!(tree.size(), 8);
+        check_links(&tree);
+
+        tree.retain(|value| value.len() == 2);
+        assert_eq!(limited.0.get(), 1);
+        assert!(tree
+            .iter()
+            .eq(["10", "14", "15", "16", "20", "25", "30"].iter()));
+
+        // The split nodes stay in the same allocator
+        let greater = tree.split_off(&String::from("16"));
+        assert!(tree.iter().eq(["10", "14", "15"].iter()));
+        assert!(greater.iter().eq(["16", "20", "25", "30"].iter()));
+        assert_eq!(limited.0.get(), 1);
+        check_links(&tree);
+        check_links(&greater);
+
+        tree.append(greater);
+        assert_eq!(tree.size(), 7);
+        check_links(&tree);
+
+        drop(tree);
+        assert_eq!(limited.0.get(), 8);
     }
 
     #[test]
-    fn test_remove() {
-        let numbers = tree_values();
+    fn test_set_operations() {
+        let mut a = BinaryTree::new();
+        let mut b = BinaryTree::new();
+
+        for value in [5, 1, 9, 3, 7] {
+            a.insert(value);
+        }
+
+        for value in [4, 3, 8, 9, 10, 2] {
+            b.insert(value);
+        }
+
+        let union: Vec<_> = a.union(&b).copied().collect();
+        assert_eq!(union, [1, 2, 3, 4, 5, 7, 8, 9, 10]);
+
+        let intersection: Vec<_> = a.intersection(&b).copied().collect();
+        assert_eq!(intersection, [3, 9]);
+
+        let difference: Vec<_> = a.difference(&b).copied().collect();
+        assert_eq!(difference, [1, 5, 7]);
 
+        let difference: Vec<_> = b.difference(&a).copied().collect();
+        assert_eq!(difference, [2, 4, 8, 10]);
+
+        let symmetric_difference: Vec<_> = a.symmetric_difference(&b).copied().collect();
+        assert_eq!(symmetric_difference, [1, 2, 4, 5, 7, 8, 10]);
+
+        let diff: Vec<_> = a.diff(&b).collect();
+        assert_eq!(
+            diff,
+            [
+                DiffItem::OnlyInSelf(&1),
+                DiffItem::OnlyInOther(&2),
+                DiffItem::OnlyInOther(&4),
+                DiffItem::OnlyInSelf(&5),
+                DiffItem::OnlyInSelf(&7),
+                DiffItem::OnlyInOther(&8),
+                DiffItem::OnlyInOther(&10),
+            ]
+        );
+
+        // Empty operands
+        let empty = BinaryTree::new();
+        assert!(a.union(&empty).eq(a.iter()));
+        assert_eq!(a.intersection(&empty).next(), None);
+        assert!(a.difference(&empty).eq(a.iter()));
+        assert_eq!(empty.difference(&a).next(), None);
+        assert!(empty.symmetric_difference(&a).eq(a.iter()));
+        assert!(a.diff(&empty).eq(a.iter().map(DiffItem::OnlyInSelf)));
+        assert!(empty.diff(&a).eq(a.iter().map(DiffItem::OnlyInOther)));
+    }
+
+    #[test]
+    fn test_shape() {
         let mut tree = BinaryTree::new();
 
-        for n in &numbers {
-            tree.insert(*n);
+        assert_eq!(tree.height(), 0);
+        assert!(tree.is_balanced());
+
+        for value in tree_values() {
+            tree.insert(value);
         }
 
-        // Node with no children
-        tree.remove(&50);
-        assert!(!tree.contains(&50));
+        assert_eq!(tree.height(), 5);
+        assert!(!tree.is_balanced());
 
-        // Node with one child to the right
-        tree.remove(&35);
-        assert!(!tree.contains(&35));
-        assert!(tree.contains(&40));
+        assert_eq!(tree.depth_of(&20), Some(0));
+        assert_eq!(tree.depth_of(&10), Some(1));
+        assert_eq!(tree.depth_of(&16), Some(3));
+        assert_eq!(tree.depth_of(&50), Some(4));
+        assert_eq!(tree.depth_of(&0), None);
 
-        // Node with one child to the left
-        tree.remove(&25);
-        assert!(!tree.contains(&25));
-        assert!(tree.contains(&21));
+        // Degenerate tree
+        let mut tree = BinaryTree::new();
 
-        // Node with two children
-        tree.remove(&10);
-        assert!(!tree.contains(&10));
-        assert!(tree.contains(&5));
-        assert!(tree.contains(&15));
+        for value in 0..10 {
+            tree.insert(value);
+        }
 
-        // Root
-        tree.remove(&20);
-        assert!(!tree.contains(&20));
+        assert_eq!(tree.height(), 10);
+        assert_eq!(tree.depth_of(&9), Some(9));
+        assert!(!tree.is_balanced());
 
-        // Check remaining values
-        assert!(tree.contains(&40));
-        assert!(tree.contains(&21));
-        assert!(tree.contains(&30));
-        assert!(tree.contains(&5));
-        assert!(tree.contains(&15));
-        assert!(tree.contains(&14));
-        assert!(tree.contains(&16));
+        tree.rebalance();
+        check_links(&tree);
+        assert_eq!(tree.height(), 4);
+        assert!(tree.is_balanced());
+        assert!(tree.iter().copied().eq(0..10));
+
+        // Rebalancing keeps the nodes, so the arena is not touched
+        let mut tree = BinaryTree::new_with_arena(16);
+
+        for value in (0..100).rev() {
+            tree.insert(value.to_string());
+        }
+
+        tree.rebalance();
+        check_links(&tree);
+        assert_eq!(tree.height(), 7);
+        assert_eq!(tree.arena.as_ref().unwrap().chunk_count(), 7);
+
+        let mut empty = BinaryTree::<i32>::new();
+        empty.rebalance();
+        assert!(empty.is_empty());
     }
 
     #[test]
-    fn test_iter() {
-        let mut values = tree_values();
+    fn test_ancestors() {
+        let mut tree = BinaryTree::new();
+
+        for value in tree_values() {
+            tree.insert(value);
+        }
 
+        assert_eq!(tree.lowest_common_ancestor(&5, &16), Some(&10));
+        assert_eq!(tree.lowest_common_ancestor(&14, &50), Some(&20));
+        assert_eq!(tree.lowest_common_ancestor(&21, &40), Some(&30));
+        assert_eq!(tree.lowest_common_ancestor(&15, &14), Some(&15));
+        assert_eq!(tree.lowest_common_ancestor(&20, &20), Some(&20));
+        assert_eq!(tree.lowest_common_ancestor(&0, &20), None);
+
+        assert_eq!(
+            tree.path_between(&14, &50),
+            Some(vec![&14, &15, &10, &20, &30, &35, &40, &50])
+        );
+        assert_eq!(tree.path_between(&50, &30), Some(vec![&50, &40, &35, &30]));
+        assert_eq!(tree.path_between(&30, &21), Some(vec![&30, &25, &21]));
+        assert_eq!(tree.path_between(&5, &99), None);
+        assert_eq!(BinaryTree::<i32>::new().path_between(&1, &1), None);
+    }
+
+    #[test]
+    fn test_iter_with_depth() {
         let mut tree = BinaryTree::new();
 
-        for value in values.iter() {
-            tree.insert(*value);
+        for value in tree_values() {
+            tree.insert(value);
         }
 
-        let mut iter = tree.iter();
+        for (value, depth) in tree.iter_with_depth() {
+            assert_eq!(tree.depth_of(value), Some(depth));
+        }
+
+        assert!(tree
+            .iter_with_depth()
+            .map(|(value, _)| value)
+            .eq(tree.iter()));
+        assert_eq!(tree.iter_with_depth().len(), tree.size());
+        assert_eq!(
+            tree.iter_with_depth().map(|(_, depth)| depth).max(),
+            Some(tree.height() - 1)
+        );
+
+        assert_eq!(BinaryTree::<i32>::new().iter_with_depth().next(), None);
+    }
+
+    #[test]
+    fn test_map() {
+        let mut tree = BinaryTree::new_with_arena(4);
+
+        for value in tree_values() {
+            tree.insert(value);
+        }
+
+        let mut expected = tree_values();
+        expected.sort();
+
+        let strings = tree.map(|value| format!("{value:03}"));
+        check_links(&strings);
+        assert!(strings.is_balanced());
+        assert_eq!(strings.arena.as_ref().unwrap().chunk_capacity(), 4);
+        assert!(strings
+            .iter()
+            .cloned()
+            .eq(expected.iter().map(|value| format!("{value:03}"))));
+
+        let negated = strings.map(|value| -value.parse::<i32>().unwrap());
+        check_links(&negated);
+        assert!(negated
+            .iter()
+            .copied()
+            .eq(expected.iter().rev().map(|value| -value)));
+
+        let tens = negated.map(|value| value / 10);
+        check_links(&tens);
+        assert!(tens.iter().eq([-5, -4, -3, -2, -1, 0].iter()));
+        assert_eq!(tens.check_invariants(), Ok(()));
+
+        let empty = BinaryTree::<i32>::new().map(|value| value + 1);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        let mut tree = BinaryTree::new();
+
+        for value in tree_values() {
+            tree.insert(value);
+        }
+
+        assert_eq!(tree.check_invariants(), Ok(()));
+        assert_eq!(BinaryTree::<i32>::new().check_invariants(), Ok(()));
+
+        unsafe {
+            // 25 is at index 7, its left child is 21
+            let node = tree.find_node(&25).unwrap();
+            let left = (*node.as_ptr()).left.unwrap();
+
+            (*node.as_ptr()).value = 18;
+            assert_eq!(
+                tree.check_invariants(),
+                Err(InvariantError::Unordered { index: 7 })
+            );
+            (*node.as_ptr()).value = 25;
+
+            (*left.as_ptr()).parent = None;
+            assert_eq!(
+                tree.check_invariants(),
+                Err(InvariantError::ParentLink { index: 7 })
+            );
+            (*left.as_ptr()).parent = Some(node);
+
+            (*node.as_ptr()).size += 1;
+            assert_eq!(
+                tree.check_invariants(),
+                Err(InvariantError::SubtreeSize {
+                    index: 7,
+                    stored: 3,
+                    expected: 2,
+                })
+            );
+            (*node.as_ptr()).size -= 1;
+
+            // Cycle
+            (*left.as_ptr()).left = Some(node);
+            assert_eq!(
+                tree.check_invariants(),
+                Err(InvariantError::Size {
+                    stored: 12,
+                    actual: None,
+                })
+            );
+            (*left.as_ptr()).left = None;
+        }
+
+        tree.size += 1;
+        let error = tree.check_invariants().unwrap_err();
+        assert_eq!(
+            error,
+            InvariantError::Size {
+                stored: 13,
+                actual: Some(12),
+            }
+        );
+        assert_eq!(error.to_string(), "tree stores size 13 but has 12 nodes");
+        tree.size -= 1;
+
+        assert_eq!(tree.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_comparator() {
+        fn reverse(a: &i32, b: &i32) -> Ordering {
+            b.cmp(a)
+        }
+
+        let mut tree = BinaryTree::new_with_comparator(reverse);
+
+        for value in tree_values() {
+            tree.insert(value);
+        }
+
+        let mut expected = tree_values();
+        expected.sort_by(|a, b| b.cmp(a));
+
+        assert!(tree.iter().eq(expected.iter()));
+        assert_eq!(tree.next_after(&20), Some(&16));
+
+        let mut other = BinaryTree::new_with_comparator(reverse);
+        other.insert(100);
+        other.insert(20);
+
+        assert!(tree.union(&other).take(2).eq([100, 50].iter()));
+        assert!(tree.intersection(&other).eq([20].iter()));
+
+        tree.append(other);
+        assert_eq!(tree.size(), expected.len() + 1);
+        check_links(&tree);
+
+        // Values that are not Ord
+        let mut tree = BinaryTree::new_with_comparator(f64::total_cmp);
+
+        for value in [2.5, -1.0, 0.0, 10.25] {
+            tree.insert(value);
+        }
+
+        assert!(tree.contains(&0.0));
+        assert!(tree.remove(&-1.0));
+        assert!(tree.iter().eq([0.0, 2.5, 10.25].iter()));
+
+        let mut cursor = tree.cursor();
+        assert!(!cursor.seek(&1.0));
+        assert_eq!(cursor.current(), Some(&2.5));
+    }
+
+    #[test]
+    fn test_visualize() {
+        let mut tree = BinaryTree::new();
+
+        assert_eq!(tree.to_ascii(), "");
+        assert_eq!(tree.to_dot(), "digraph BinaryTree {\n}\n");
+
+        for value in tree_values() {
+            tree.insert(value);
+        }
+
+        let expected = "\
+20
+|-- 10
+|   |-- 5
+|   `-- 15
+|       |-- 14
+|       `-- 16
+`-- 30
+    |-- 25
+    |   |-- 21
+    |   `-- (none)
+    `-- 35
+        |-- (none)
+        `-- 40
+            |-- (none)
+            `-- 50
+";
+
+        assert_eq!(tree.to_ascii(), expected);
+
+        let dot = tree.to_dot();
+
+        assert_eq!(dot.matches("[label=").count(), tree.size());
+        assert_eq!(dot.matches("[shape=point]").count(), 3);
+        assert_eq!(dot.matches(" -> ").count(), tree.size() - 1 + 3);
+        assert!(dot.contains("n7 [label=\"25\"];\n    n8 [label=\"21\"];\n    n7 -> n8;"));
+
+        let mut tree = BinaryTree::new();
+        tree.insert(String::from("say \"hi\""));
+
+        assert!(tree.to_dot().contains(r#"n0 [label="say \"hi\""];"#));
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random() {
+        use rand::{rngs::SmallRng, SeedableRng};
+
+        let mut rng = SmallRng::seed_from_u64(407);
+        let mut tree = BinaryTree::new();
+
+        assert_eq!(tree.choose(&mut rng), None);
+        assert_eq!(tree.iter_random(&mut rng).next(), None);
+
+        // Degenerate shape on purpose, picks must not depend on depth.
+        for value in 0..10 {
+            tree.insert(value);
+        }
+
+        let mut counts = [0; 10];
+
+        for _ in 0..50_000 {
+            counts[*tree.choose(&mut rng).unwrap()] += 1;
+        }
+
+        for value in tree.iter_random(&mut rng).take(50_000) {
+            counts[*value] += 1;
+        }
+
+        for count in counts {
+            assert!((9_000..11_000).contains(&count), "{counts:?}");
+        }
+
+        // Subtree sizes stay consistent after structural changes.
+        tree.retain(|value| value % 3 != 0);
+        let greater = tree.split_off(&5);
+        tree.remove(&1);
+        check_links(&tree);
+        check_links(&greater);
+
+        for _ in 0..100 {
+            assert!([2, 4].contains(tree.choose(&mut rng).unwrap()));
+            assert!([5, 7, 8].contains(greater.choose(&mut rng).unwrap()));
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_iter() {
+        use rayon::prelude::*;
+
+        let mut tree = BinaryTree::new();
+
+        assert_eq!(tree.par_iter().count(), 0);
+
+        for value in (0..2_000).map(|value| (value * 7919) % 2_000) {
+            tree.insert(value);
+        }
+
+        let doubled: Vec<_> = tree.par_iter().map(|value| value * 2).collect();
+        assert!(doubled
+            .iter()
+            .copied()
+            .eq((0..2_000).map(|value| value * 2)));
+        assert_eq!(tree.par_iter().sum::<i32>(), (0..2_000).sum::<i32>());
+        assert_eq!(
+            tree.par_iter().filter(|value| *value % 10 == 0).count(),
+            200
+        );
+
+        // Degenerate shapes split too
+        let mut path = BinaryTree::new();
+
+        for value in 0..1_000 {
+            path.insert(value);
+        }
+
+        let values: Vec<_> = path.par_iter().copied().collect();
+        assert!(values.into_iter().eq(0..1_000));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut tree = BinaryTree::new();
+
+        for value in tree_values() {
+            tree.insert(value);
+        }
+
+        let json = serde_json::to_string(&tree).unwrap();
+        assert_eq!(json, "[5,10,14,15,16,20,21,25,30,35,40,50]");
 
+        let tree: BinaryTree<i32> = serde_json::from_str(&json).unwrap();
+        let mut values = tree_values();
         values.sort();
+        assert_eq!(tree.size(), values.len());
+        assert!(tree.iter().eq(values.iter()));
+        assert!(tree.is_balanced());
+        assert_eq!(tree.height(), 4);
+        check_links(&tree);
 
-        for value in values.iter() {
-            let tree_value = iter.next();
-            assert!(tree_value.is_some());
-            assert_eq!(value, tree_value.unwrap());
+        let tree: BinaryTree<i32> = serde_json::from_str("[3, 1, 2, 3, 1]").unwrap();
+        assert_eq!(tree.size(), 3);
+        assert!(tree.iter().eq([1, 2, 3].iter()));
+    }
+
+    #[cfg(feature = "proptest")]
+    #[test]
+    fn test_proptest() {
+        use proptest::{
+            arbitrary::any,
+            prop_assert,
+            test_runner::{TestError, TestRunner},
+        };
+
+        let mut runner = TestRunner::deterministic();
+        let result = runner.run(&any::<BinaryTree<i16>>(), |tree| {
+            prop_assert!(tree.is_balanced());
+            prop_assert!(tree.size() < 10);
+            Ok(())
+        });
+
+        let Err(TestError::Fail(_, tree)) = result else {
+            panic!("expected a failing tree, got {result:?}");
+        };
+        assert_eq!(tree.size(), 10);
+        assert!(tree.check_invariants().is_ok());
+        check_links(&tree);
+    }
+
+    #[test]
+    fn test_dump_layout() {
+        let mut tree = BinaryTree::new();
+
+        for value in tree_values() {
+            tree.insert(value);
         }
 
-        assert!(iter.next().is_none());
+        tree.remove(&20);
+
+        let mut layout = String::new();
+        tree.dump_layout(&mut layout).unwrap();
+
+        // Lines look like "0x1000: 5, parent 0x2000, left 0x0, right 0x0, size 1"
+        let nodes: Vec<Vec<&str>> = layout
+            .lines()
+            .skip(1)
+            .map(|line| line.split([':', ',']).map(str::trim).collect())
+            .collect();
+        let address = |value: &str| nodes.iter().find(|node| node[1] == value).unwrap()[0];
+
+        assert_eq!(nodes.len(), tree.size());
+        assert!(layout.starts_with(&format!("root {}, ", nodes[0][0])));
+
+        // Preorder, the successor of 20 took its place
+        let values: Vec<&str> = nodes.iter().map(|node| node[1]).collect();
+        assert_eq!(values[..3], ["21", "10", "5"]);
+
+        assert_eq!(nodes[0][2], "parent 0x0");
+        assert_eq!(nodes[0][3], format!("left {}", address("10")));
+        assert_eq!(nodes[0][4], format!("right {}", address("30")));
+        assert_eq!(nodes[0][5], format!("size {}", tree.size()));
+
+        let node_25 = nodes.iter().find(|node| node[1] == "25").unwrap();
+        assert_eq!(node_25[2], format!("parent {}", address("30")));
+        assert_eq!(node_25[3..], ["left 0x0", "right 0x0", "size 1"]);
+    }
+
+    /// Value that counts its drops and panics when dropped if it's 13.
+    struct Fragile<'a> {
+        value: i32,
+        drops: &'a Cell<usize>,
+    }
+
+    impl Drop for Fragile<'_> {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+            assert_ne!(self.value, 13, "dropping 13");
+        }
+    }
+
+    #[test]
+    fn test_panic_safety() {
+        let mut sorted = tree_values();
+        sorted.sort();
+
+        // A comparator that panics after `fuel` calls, at every point of the
+        // removal
+        for fuel in 0..12 {
+            let fuel_left = Cell::new(usize::MAX);
+            let mut tree = BinaryTree::new_with_comparator(|a: &i32, b: &i32| {
+                fuel_left.set(fuel_left.get().checked_sub(1).expect("out of fuel"));
+                a.cmp(b)
+            });
+
+            tree_values().into_iter().for_each(|value| {
+                tree.insert(value);
+            });
+
+            fuel_left.set(fuel);
+            let removed = panic::catch_unwind(AssertUnwindSafe(|| tree.remove_range(14..=21)));
+            fuel_left.set(usize::MAX);
+
+            match removed {
+                Ok(removed) => assert_eq!(removed, 5),
+                Err(_) => assert!(tree.iter().eq(sorted.iter())),
+            }
+
+            assert!(tree.check_invariants().is_ok());
+            check_links(&tree);
+        }
+
+        let mut tree = BinaryTree::new();
+        tree_values().into_iter().for_each(|value| {
+            tree.insert(value);
+        });
+
+        let mut calls = 0;
+        let retained = panic::catch_unwind(AssertUnwindSafe(|| {
+            tree.retain(|value| {
+                calls += 1;
+                assert!(calls < 6, "predicate panicked");
+                value % 2 == 0
+            })
+        }));
+
+        assert!(retained.is_err());
+        assert!(tree.iter().eq(sorted.iter()));
+        check_links(&tree);
+
+        // Dropping a value that panics doesn't stop the others from dropping
+        let (drops, bound_drops) = (Cell::new(0), Cell::new(0));
+        let mut tree =
+            BinaryTree::new_with_comparator(|a: &Fragile, b: &Fragile| a.value.cmp(&b.value));
+
+        for value in (0..20).chain(100..120) {
+            tree.insert(Fragile {
+                value,
+                drops: &drops,
+            });
+        }
+
+        let bound = |value| Fragile {
+            value,
+            drops: &bound_drops,
+        };
+
+        let removed =
+            panic::catch_unwind(AssertUnwindSafe(|| tree.remove_range(bound(10)..bound(20))));
+
+        assert!(removed.is_err());
+        assert_eq!(drops.get(), 10);
+        assert_eq!(tree.size(), 30);
+        assert!(tree.check_invariants().is_ok());
+        check_links(&tree);
+
+        tree.insert(Fragile {
+            value: 13,
+            drops: &drops,
+        });
+
+        let cleared = panic::catch_unwind(AssertUnwindSafe(|| tree.clear()));
+
+        assert!(cleared.is_err());
+        assert!(tree.is_empty());
+        assert_eq!(drops.get(), 41);
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut tree = BinaryTree::new();
+        for value in [3, 1, 5, 0, 2, 4, 6] {
+            tree.insert(value);
+        }
+        assert_eq!(tree.height(), 3);
+        assert_eq!(tree.stats().allocations, 7);
+
+        // Inserting the leaves walked one link each
+        assert_eq!(tree.stats().pointer_walks, 4);
+
+        // Misses stop at a leaf, 2 links below the root
+        assert!(!tree.contains(&10));
+        assert!(tree.contains(&3));
+        assert_eq!(tree.stats().pointer_walks, 6);
+
+        tree.remove(&0);
+        tree.insert(0);
+        tree.insert(7);
+
+        let stats = tree.stats();
+        assert_eq!((stats.allocations, stats.peak_len), (9, 8));
+        assert_eq!(stats.reallocations, 0);
     }
 }
@@ -0,0 +1,404 @@
+use std::ops::{BitAndAssign, BitOrAssign, BitXorAssign};
+
+use crate::{heap_size::HeapSize, vector::Vector};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Growable sequence of bits packed 64 per word, so it takes 8 times less
+/// memory than a [`Vector<bool>`](crate::Vector) and the set operations work
+/// on whole words at a time.
+///
+/// The bits of the last word past [`BitVec::len`] are always zero, which lets
+/// [`BitVec::count_ones`] and the comparisons look at whole words.
+pub struct BitVec {
+    words: Vector<u64>,
+    len: usize,
+}
+
+impl BitVec {
+    /// Creates an empty bit vector.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BitVec;
+    ///
+    /// let bits = BitVec::new();
+    ///
+    /// assert!(bits.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            words: Vector::new(),
+            len: 0,
+        }
+    }
+
+    /// Creates a bit vector of `len` bits all set to `bit`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BitVec;
+    ///
+    /// let bits = BitVec::from_elem(100, true);
+    ///
+    /// assert_eq!(bits.len(), 100);
+    /// assert_eq!(bits.count_ones(), 100);
+    /// ```
+    pub fn from_elem(len: usize, bit: bool) -> Self {
+        let mut bits = Self::new();
+        let fill = if bit { u64::MAX } else { 0 };
+
+        for _ in 0..len.div_ceil(WORD_BITS) {
+            bits.words.push(fill);
+        }
+
+        bits.len = len;
+        bits.clear_unused_bits();
+
+        bits
+    }
+
+    /// Returns the number of bits.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the bit vector contains no bits.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all the bits.
+    pub fn clear(&mut self) {
+        while self.words.pop().is_some() {}
+        self.len = 0;
+    }
+
+    /// Zeroes the bits of the last word that are past the length.
+    fn clear_unused_bits(&mut self) {
+        let used = self.len % WORD_BITS;
+
+        if used != 0 {
+            let last = self.words.len() - 1;
+            self.words[last] &= (1 << used) - 1;
+        }
+    }
+
+    /// Appends `bit` to the end.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BitVec;
+    ///
+    /// let mut bits = BitVec::new();
+    /// bits.push(true);
+    /// bits.push(false);
+    ///
+    /// assert_eq!(bits.get(0), Some(true));
+    /// assert_eq!(bits.get(1), Some(false));
+    /// assert_eq!(bits.get(2), None);
+    /// ```
+    pub fn push(&mut self, bit: bool) {
+        if self.len.is_multiple_of(WORD_BITS) {
+            self.words.push(0);
+        }
+
+        self.len += 1;
+        self.set(self.len - 1, bit);
+    }
+
+    /// Removes and returns the last bit.
+    pub fn pop(&mut self) -> Option<bool> {
+        let bit = self.get(self.len.checked_sub(1)?)?;
+        self.set(self.len - 1, false);
+        self.len -= 1;
+
+        if self.len.is_multiple_of(WORD_BITS) {
+            self.words.pop();
+        }
+
+        Some(bit)
+    }
+
+    /// Returns the bit at `index`, or `None` if it's out of bounds.
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+
+        Some(self.words[index / WORD_BITS] & (1 << (index % WORD_BITS)) != 0)
+    }
+
+    /// Sets the bit at `index` to `bit`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, bit: bool) {
+        assert!(index < self.len, "Index out of bounds");
+
+        let word = &mut self.words[index / WORD_BITS];
+        let mask = 1 << (index % WORD_BITS);
+
+        if bit {
+            *word |= mask;
+        } else {
+            *word &= !mask;
+        }
+    }
+
+    /// Returns the words holding the bits, least significant bit first.
+    #[cfg(feature = "fixed_bit_set")]
+    pub(crate) fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Returns the number of bits set to `true`.
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns the number of bits set to `false`.
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    /// Flips every bit in place.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BitVec;
+    ///
+    /// let mut bits: BitVec = [true, false, false].into_iter().collect();
+    /// bits.negate();
+    ///
+    /// assert!(bits.iter().eq([false, true, true]));
+    /// ```
+    pub fn negate(&mut self) {
+        for word in self.words.iter_mut() {
+            *word = !*word;
+        }
+
+        self.clear_unused_bits();
+    }
+
+    /// Combines every word with the same word of `other` using `op`.
+    fn combine(&mut self, other: &Self, op: impl Fn(&mut u64, u64)) {
+        assert_eq!(self.len, other.len, "Bit vectors must have the same length");
+
+        for (word, other) in self.words.iter_mut().zip(other.words.iter()) {
+            op(word, *other);
+        }
+    }
+
+    /// Returns an iterator over the bits from first to last.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            bits: self,
+            front: 0,
+            back: self.len,
+        }
+    }
+}
+
+impl Default for BitVec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq for BitVec {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && *self.words == *other.words
+    }
+}
+
+impl Eq for BitVec {}
+
+/// Intersection in place.
+///
+/// # Panics
+///
+/// Panics if both bit vectors don't have the same length.
+///
+/// # Examples
+///
+/// ```rust
+/// use collections_rust::BitVec;
+///
+/// let mut a: BitVec = [true, true, false].into_iter().collect();
+/// let b: BitVec = [true, false, false].into_iter().collect();
+///
+/// a &= &b;
+///
+/// assert!(a.iter().eq([true, false, false]));
+/// ```
+impl BitAndAssign<&BitVec> for BitVec {
+    fn bitand_assign(&mut self, other: &BitVec) {
+        self.combine(other, |word, other| *word &= other);
+    }
+}
+
+/// Union in place.
+///
+/// # Panics
+///
+/// Panics if both bit vectors don't have the same length.
+impl BitOrAssign<&BitVec> for BitVec {
+    fn bitor_assign(&mut self, other: &BitVec) {
+        self.combine(other, |word, other| *word |= other);
+    }
+}
+
+/// Symmetric difference in place.
+///
+/// # Panics
+///
+/// Panics if both bit vectors don't have the same length.
+impl BitXorAssign<&BitVec> for BitVec {
+    fn bitxor_assign(&mut self, other: &BitVec) {
+        self.combine(other, |word, other| *word ^= other);
+    }
+}
+
+impl Extend<bool> for BitVec {
+    fn extend<I: IntoIterator<Item = bool>>(&mut self, iter: I) {
+        for bit in iter {
+            self.push(bit);
+        }
+    }
+}
+
+impl FromIterator<bool> for BitVec {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut bits = Self::new();
+        bits.extend(iter);
+
+        bits
+    }
+}
+
+impl<'a> IntoIterator for &'a BitVec {
+    type Item = bool;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the bits of a [`BitVec`].
+pub struct Iter<'a> {
+    bits: &'a BitVec,
+    /// Range of bits that haven't been yielded yet.
+    front: usize,
+    back: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+
+        self.front += 1;
+        self.bits.get(self.front - 1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+
+        (len, Some(len))
+    }
+}
+
+impl<'a> DoubleEndedIterator for Iter<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+
+        self.back -= 1;
+        self.bits.get(self.back)
+    }
+}
+
+impl<'a> ExactSizeIterator for Iter<'a> {}
+
+impl HeapSize for BitVec {
+    fn heap_size_of_children(&self) -> usize {
+        self.words.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BitVec;
+
+    #[test]
+    fn test_push_pop() {
+        let expected: Vec<bool> = (0..200).map(|i| i % 3 == 0).collect();
+        let mut bits: BitVec = expected.iter().copied().collect();
+
+        assert_eq!(bits.len(), 200);
+        assert_eq!(bits.count_ones(), 67);
+        assert_eq!(bits.count_zeros(), 133);
+        assert!(bits.iter().eq(expected.iter().copied()));
+        assert!(bits.iter().rev().eq(expected.iter().rev().copied()));
+
+        bits.set(1, true);
+        assert_eq!(bits.get(1), Some(true));
+        bits.set(1, false);
+
+        for &bit in expected.iter().rev() {
+            assert_eq!(bits.pop(), Some(bit));
+        }
+
+        assert_eq!(bits.pop(), None);
+        assert!(bits.is_empty());
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let evens: BitVec = (0..130).map(|i| i % 2 == 0).collect();
+        let threes: BitVec = (0..130).map(|i| i % 3 == 0).collect();
+
+        let mut and = BitVec::from_elem(130, true);
+        and &= &evens;
+        and &= &threes;
+        assert!(and.iter().eq((0..130).map(|i| i % 6 == 0)));
+
+        let mut or = BitVec::from_elem(130, false);
+        or |= &evens;
+        or |= &threes;
+        assert!(or.iter().eq((0..130).map(|i| i % 2 == 0 || i % 3 == 0)));
+
+        let mut xor = or;
+        xor ^= &and;
+        assert!(xor
+            .iter()
+            .eq((0..130).map(|i| (i % 2 == 0) != (i % 3 == 0))));
+
+        // Negating must not set the unused bits of the last word
+        let mut not = BitVec::from_elem(130, false);
+        not.negate();
+        assert_eq!(not.count_ones(), 130);
+        assert!(not == BitVec::from_elem(130, true));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_length_mismatch() {
+        let mut bits = BitVec::from_elem(10, true);
+        bits |= &BitVec::from_elem(11, true);
+    }
+}
@@ -0,0 +1,351 @@
+use std::{
+    cell::UnsafeCell,
+    mem::{self, MaybeUninit},
+    ops::Deref,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::heap_size::HeapSize;
+
+/// Aligns its value to a cache line, so the head and the tail of the queue
+/// don't share one and producers don't slow down consumers.
+#[repr(align(64))]
+struct CachePadded<T>(T);
+
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+struct Slot<T> {
+    /// Twice the position of the next push that can use the slot when the
+    /// slot is empty, and that plus one once the value is written. Doubling
+    /// keeps the states of consecutive positions apart even when the
+    /// capacity is 1.
+    sequence: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// Lock-free bounded queue for many producer and many consumer threads,
+/// like the task queue of a thread pool. It's the ring buffer of Dmitry
+/// Vyukov: every slot has a sequence number that tells threads whether it's
+/// ready to be written or read at their position, so a push or pop only
+/// takes a compare-and-swap on the tail or head and never waits for other
+/// threads.
+///
+/// The capacity is fixed when the queue is created. [`BoundedQueue::push`]
+/// gives the value back when the queue is full and [`BoundedQueue::pop`]
+/// returns `None` when it's empty, so callers decide whether to retry, back
+/// off or drop work.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::thread;
+///
+/// use collections_rust::BoundedQueue;
+///
+/// let queue = BoundedQueue::new(16);
+///
+/// thread::scope(|scope| {
+///     for producer in 0..4 {
+///         let queue = &queue;
+///         scope.spawn(move || {
+///             for task in 0..100 {
+///                 let mut task = producer * 100 + task;
+///                 while let Err(rejected) = queue.push(task) {
+///                     task = rejected;
+///                     thread::yield_now();
+///                 }
+///             }
+///         });
+///     }
+///
+///     let mut done = 0;
+///     while done < 400 {
+///         if queue.pop().is_some() {
+///             done += 1;
+///         }
+///     }
+/// });
+///
+/// assert!(queue.is_empty());
+/// ```
+pub struct BoundedQueue<T> {
+    slots: Box<[Slot<T>]>,
+    /// Position of the next pop.
+    head: CachePadded<AtomicUsize>,
+    /// Position of the next push.
+    tail: CachePadded<AtomicUsize>,
+}
+
+// Values are moved between threads through the slots, and each slot is only
+// accessed by the thread that claimed its position.
+unsafe impl<T: Send> Send for BoundedQueue<T> {}
+unsafe impl<T: Send> Sync for BoundedQueue<T> {}
+
+impl<T> BoundedQueue<T> {
+    /// Creates an empty queue with room for `capacity` values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "Capacity must be positive");
+
+        let slots = (0..capacity)
+            .map(|position| Slot {
+                sequence: AtomicUsize::new(2 * position),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+            })
+            .collect();
+
+        Self {
+            slots,
+            head: CachePadded(AtomicUsize::new(0)),
+            tail: CachePadded(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Returns the maximum number of values.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns the number of values in the queue. Other threads may change
+    /// it right after it's read, so it's only a hint.
+    pub fn len(&self) -> usize {
+        loop {
+            let tail = self.tail.load(Ordering::SeqCst);
+            let head = self.head.load(Ordering::SeqCst);
+
+            // Make sure both positions were read at the same time
+            if self.tail.load(Ordering::SeqCst) == tail {
+                return tail.wrapping_sub(head).min(self.capacity());
+            }
+        }
+    }
+
+    /// Returns `true` if the queue contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the queue can't take more values.
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Appends `value` to the back of the queue.
+    ///
+    /// # Errors
+    ///
+    /// Returns the value back if the queue is full.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BoundedQueue;
+    ///
+    /// let queue = BoundedQueue::new(1);
+    ///
+    /// assert_eq!(queue.push(1), Ok(()));
+    /// assert_eq!(queue.push(2), Err(2));
+    /// ```
+    pub fn push(&self, value: T) -> Result<(), T> {
+        let mut position = self.tail.load(Ordering::Relaxed);
+
+        loop {
+            let slot = &self.slots[position % self.slots.len()];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let lag = sequence.wrapping_sub(position.wrapping_mul(2)) as isize;
+
+            if lag == 0 {
+                // The slot is empty, claim the position
+                match self.tail.compare_exchange_weak(
+                    position,
+                    position.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        unsafe { (*slot.value.get()).write(value) };
+                        slot.sequence
+                            .store(position.wrapping_mul(2) + 1, Ordering::Release);
+
+                        return Ok(());
+                    }
+                    Err(current) => position = current,
+                }
+            } else if lag < 0 {
+                // The slot still holds the value pushed one lap ago
+                return Err(value);
+            } else {
+                // Another producer claimed the position first
+                position = self.tail.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Removes and returns the value at the front of the queue, or `None` if
+    /// it's empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BoundedQueue;
+    ///
+    /// let queue = BoundedQueue::new(4);
+    /// queue.push(1).unwrap();
+    /// queue.push(2).unwrap();
+    ///
+    /// assert_eq!(queue.pop(), Some(1));
+    /// assert_eq!(queue.pop(), Some(2));
+    /// assert_eq!(queue.pop(), None);
+    /// ```
+    pub fn pop(&self) -> Option<T> {
+        let mut position = self.head.load(Ordering::Relaxed);
+
+        loop {
+            let slot = &self.slots[position % self.slots.len()];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let lag = sequence.wrapping_sub(position.wrapping_mul(2) + 1) as isize;
+
+            if lag == 0 {
+                // The slot holds a value, claim the position
+                match self.head.compare_exchange_weak(
+                    position,
+                    position.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        let value = unsafe { (*slot.value.get()).assume_init_read() };
+
+                        // Free the slot for the push one lap later
+                        let next = position.wrapping_add(self.slots.len());
+                        slot.sequence.store(next.wrapping_mul(2), Ordering::Release);
+
+                        return Some(value);
+                    }
+                    Err(current) => position = current,
+                }
+            } else if lag < 0 {
+                // The value for this position hasn't been pushed yet
+                return None;
+            } else {
+                // Another consumer claimed the position first
+                position = self.head.load(Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl<T> Drop for BoundedQueue<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+/// Values aren't visited, since other threads may be taking them out of the
+/// slots at the same time.
+impl<T> HeapSize for BoundedQueue<T> {
+    fn heap_size_of_children(&self) -> usize {
+        mem::size_of_val::<[Slot<T>]>(&self.slots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        rc::Rc,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex,
+        },
+        thread,
+    };
+
+    use super::BoundedQueue;
+
+    #[test]
+    fn test_threads() {
+        let queue = BoundedQueue::new(8);
+        let popped = Mutex::new(Vec::new());
+        let consumed = AtomicUsize::new(0);
+        let (producers, per_producer) = (4, 5_000);
+
+        thread::scope(|scope| {
+            for producer in 0..producers {
+                let queue = &queue;
+                scope.spawn(move || {
+                    for index in 0..per_producer {
+                        let mut value = producer * per_producer + index;
+
+                        while let Err(rejected) = queue.push(value) {
+                            value = rejected;
+                            thread::yield_now();
+                        }
+                    }
+                });
+            }
+
+            for _ in 0..4 {
+                let (queue, popped, consumed) = (&queue, &popped, &consumed);
+                scope.spawn(move || {
+                    let mut local = Vec::new();
+                    let mut last = vec![None; producers];
+
+                    while consumed.load(Ordering::Relaxed) < producers * per_producer {
+                        let Some(value) = queue.pop() else {
+                            thread::yield_now();
+                            continue;
+                        };
+
+                        // Values of the same producer come out in the order
+                        // they were pushed
+                        let producer = value / per_producer;
+                        assert!(last[producer] < Some(value));
+                        last[producer] = Some(value);
+
+                        local.push(value);
+                        consumed.fetch_add(1, Ordering::Relaxed);
+                    }
+
+                    popped.lock().unwrap().append(&mut local);
+                });
+            }
+        });
+
+        let mut popped = popped.into_inner().unwrap();
+        popped.sort();
+        assert!(popped.into_iter().eq(0..producers * per_producer));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_full_and_drop() {
+        let value = Rc::new(());
+        let queue = BoundedQueue::new(3);
+
+        // Go around the ring a few times before filling it
+        for _ in 0..10 {
+            assert!(queue.push(value.clone()).is_ok());
+            assert!(queue.pop().is_some());
+        }
+
+        for _ in 0..3 {
+            assert!(queue.push(value.clone()).is_ok());
+        }
+
+        assert!(queue.is_full());
+        assert!(queue.push(value.clone()).is_err());
+        assert_eq!(Rc::strong_count(&value), 4);
+
+        drop(queue);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+}
@@ -0,0 +1,788 @@
+use std::{
+    cmp::Ordering,
+    mem,
+    ops::{Bound, RangeBounds},
+    ptr,
+};
+
+use crate::heap_size::HeapSize;
+
+/// Minimum degree of the tree. Every node except the root stores between
+/// `B - 1` and `2 * B - 1` keys, and internal nodes have one more child than
+/// keys.
+const B: usize = 6;
+
+/// Maximum number of keys in a node.
+const CAPACITY: usize = 2 * B - 1;
+
+/// Keys and values are stored in separate arrays so searching a node only
+/// touches the keys. A node is a leaf if it has no children.
+struct Node<K, V> {
+    keys: Vec<K>,
+    values: Vec<V>,
+    children: Vec<Node<K, V>>,
+}
+
+impl<K, V> Node<K, V> {
+    fn new() -> Self {
+        Self {
+            keys: Vec::with_capacity(CAPACITY),
+            values: Vec::with_capacity(CAPACITY),
+            children: Vec::new(),
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    fn is_full(&self) -> bool {
+        self.keys.len() == CAPACITY
+    }
+
+    /// Splits the full child at index `i` in two halves and moves its median
+    /// entry into this node, between the halves.
+    fn split_child(&mut self, i: usize) {
+        let child = &mut self.children[i];
+        let mut right = Node::new();
+
+        right.keys.extend(child.keys.drain(B..));
+        right.values.extend(child.values.drain(B..));
+
+        if !child.is_leaf() {
+            right.children.reserve(CAPACITY + 1);
+            right.children.extend(child.children.drain(B..));
+        }
+
+        let (key, value) = (child.keys.pop().unwrap(), child.values.pop().unwrap());
+
+        self.keys.insert(i, key);
+        self.values.insert(i, value);
+        self.children.insert(i + 1, right);
+    }
+
+    /// Moves the entry at index `i` and all the entries of the child at
+    /// `i + 1` into the child at `i`. Both children must have `B - 1` keys.
+    fn merge_children(&mut self, i: usize) {
+        let right = self.children.remove(i + 1);
+        let (key, value) = (self.keys.remove(i), self.values.remove(i));
+        let left = &mut self.children[i];
+
+        left.keys.push(key);
+        left.values.push(value);
+        left.keys.extend(right.keys);
+        left.values.extend(right.values);
+        left.children.extend(right.children);
+    }
+
+    /// Makes sure the child at index `i` has at least `B` keys before
+    /// descending into it, so that removing a key from it never leaves it
+    /// underfull. A key is borrowed from a sibling through this node if
+    /// possible, otherwise the child is merged with a sibling. Returns the
+    /// index of the child that now covers the keys of the original child.
+    fn fill_child(&mut self, i: usize) -> usize {
+        if self.children[i].keys.len() >= B {
+            return i;
+        }
+
+        if i > 0 && self.children[i - 1].keys.len() >= B {
+            let (left, right) = self.children.split_at_mut(i);
+            let (left, child) = (&mut left[i - 1], &mut right[0]);
+
+            let key = mem::replace(&mut self.keys[i - 1], left.keys.pop().unwrap());
+            let value = mem::replace(&mut self.values[i - 1], left.values.pop().unwrap());
+
+            child.keys.insert(0, key);
+            child.values.insert(0, value);
+
+            if let Some(grandchild) = left.children.pop() {
+                child.children.insert(0, grandchild);
+            }
+
+            return i;
+        }
+
+        if i < self.keys.len() && self.children[i + 1].keys.len() >= B {
+            let (left, right) = self.children.split_at_mut(i + 1);
+            let (child, right) = (&mut left[i], &mut right[0]);
+
+            let key = mem::replace(&mut self.keys[i], right.keys.remove(0));
+            let value = mem::replace(&mut self.values[i], right.values.remove(0));
+
+            child.keys.push(key);
+            child.values.push(value);
+
+            if !right.is_leaf() {
+                child.children.push(right.children.remove(0));
+            }
+
+            return i;
+        }
+
+        if i < self.keys.len() {
+            self.merge_children(i);
+            i
+        } else {
+            self.merge_children(i - 1);
+            i - 1
+        }
+    }
+}
+
+impl<K: Ord, V> Node<K, V> {
+    /// Inserts the entry into the subtree rooted at this node, which must not
+    /// be full. Full children are split before descending into them, so
+    /// there's always room for the median of a split below.
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut i = match self.keys.binary_search(&key) {
+            Ok(i) => return Some(mem::replace(&mut self.values[i], value)),
+            Err(i) => i,
+        };
+
+        if self.is_leaf() {
+            self.keys.insert(i, key);
+            self.values.insert(i, value);
+            return None;
+        }
+
+        if self.children[i].is_full() {
+            self.split_child(i);
+
+            match key.cmp(&self.keys[i]) {
+                Ordering::Less => {}
+                Ordering::Equal => return Some(mem::replace(&mut self.values[i], value)),
+                Ordering::Greater => i += 1,
+            }
+        }
+
+        self.children[i].insert(key, value)
+    }
+
+    /// Removes `key` from the subtree rooted at this node, which must have at
+    /// least `B` keys unless it's the root. Every child is filled before
+    /// descending into it, so no node has to be fixed on the way back up.
+    fn remove(&mut self, key: &K) -> Option<(K, V)> {
+        let position = self.keys.binary_search(key);
+
+        if self.is_leaf() {
+            let i = position.ok()?;
+            return Some((self.keys.remove(i), self.values.remove(i)));
+        }
+
+        match position {
+            // Replace the entry with its predecessor or successor if one of
+            // the children around it can give a key away, otherwise merge
+            // both children and remove the key from the merged child.
+            Ok(i) => {
+                if self.children[i].keys.len() >= B {
+                    let (key, value) = self.children[i].remove_last();
+                    Some(self.replace_entry(i, key, value))
+                } else if self.children[i + 1].keys.len() >= B {
+                    let (key, value) = self.children[i + 1].remove_first();
+                    Some(self.replace_entry(i, key, value))
+                } else {
+                    self.merge_children(i);
+                    self.children[i].remove(key)
+                }
+            }
+
+            Err(i) => {
+                let i = self.fill_child(i);
+                self.children[i].remove(key)
+            }
+        }
+    }
+}
+
+impl<K, V> Node<K, V> {
+    fn replace_entry(&mut self, i: usize, key: K, value: V) -> (K, V) {
+        (
+            mem::replace(&mut self.keys[i], key),
+            mem::replace(&mut self.values[i], value),
+        )
+    }
+
+    /// Removes the smallest entry of the subtree, see `remove`.
+    fn remove_first(&mut self) -> (K, V) {
+        if self.is_leaf() {
+            return (self.keys.remove(0), self.values.remove(0));
+        }
+
+        let i = self.fill_child(0);
+        self.children[i].remove_first()
+    }
+
+    /// Removes the greatest entry of the subtree, see `remove`.
+    fn remove_last(&mut self) -> (K, V) {
+        if self.is_leaf() {
+            return (self.keys.pop().unwrap(), self.values.pop().unwrap());
+        }
+
+        let i = self.fill_child(self.children.len() - 1);
+        self.children[i].remove_last()
+    }
+}
+
+/// Sorted map implemented as a B-tree. Each node stores up to 11 entries in
+/// contiguous arrays, so lookups touch a few cache lines per level and memory
+/// is allocated per node instead of per entry like in [`crate::TreeMap`]. The
+/// tree is always balanced, all the leaves are at the same depth.
+pub struct BTreeMap<K, V> {
+    root: Option<Box<Node<K, V>>>,
+    len: usize,
+}
+
+impl<K, V> BTreeMap<K, V> {
+    /// Creates an empty map. Doesn't allocate memory until the first entry
+    /// is inserted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BTreeMap;
+    ///
+    /// let map: BTreeMap<i32, &str> = BTreeMap::new();
+    ///
+    /// assert!(map.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all the entries from the map.
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+
+    /// Returns an iterator over the entries of the map in ascending key
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BTreeMap;
+    ///
+    /// let map: BTreeMap<_, _> = [(2, "b"), (1, "a")].into_iter().collect();
+    ///
+    /// assert_eq!(map.iter().collect::<Vec<_>>(), vec![(&1, &"a"), (&2, &"b")]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut path = Path { stack: Vec::new() };
+        path.push_first_leaf(self.root.as_deref());
+
+        Iter {
+            path,
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over the keys of the map in ascending order.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys(self.iter())
+    }
+
+    /// Returns an iterator over the values of the map, ordered by their keys.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values(self.iter())
+    }
+}
+
+impl<K: Ord, V> BTreeMap<K, V> {
+    /// Returns the node and index where `key` is stored.
+    fn find(&self, key: &K) -> Option<(&Node<K, V>, usize)> {
+        let mut current = self.root.as_deref()?;
+
+        loop {
+            match current.keys.binary_search(key) {
+                Ok(i) => return Some((current, i)),
+                Err(i) => current = current.children.get(i)?,
+            }
+        }
+    }
+
+    /// Inserts `value` under `key`. If the key was already present its value
+    /// is replaced and the old one is returned, the key itself is not
+    /// updated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BTreeMap;
+    ///
+    /// let mut map = BTreeMap::new();
+    ///
+    /// assert_eq!(map.insert(1, "a"), None);
+    /// assert_eq!(map.insert(1, "b"), Some("a"));
+    /// assert_eq!(map.get(&1), Some(&"b"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let root = self.root.get_or_insert_with(|| Box::new(Node::new()));
+
+        // The tree only grows in height here, by splitting a full root.
+        if root.is_full() {
+            let old_root = mem::replace(root, Box::new(Node::new()));
+            root.children.reserve(CAPACITY + 1);
+            root.children.push(*old_root);
+            root.split_child(0);
+        }
+
+        let old = root.insert(key, value);
+
+        if old.is_none() {
+            self.len += 1;
+        }
+
+        old
+    }
+
+    /// Returns a reference to the value stored under `key`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BTreeMap;
+    ///
+    /// let map: BTreeMap<_, _> = [(1, "a")].into_iter().collect();
+    ///
+    /// assert_eq!(map.get(&1), Some(&"a"));
+    /// assert_eq!(map.get(&2), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.find(key).map(|(node, i)| &node.values[i])
+    }
+
+    /// Returns a mutable reference to the value stored under `key`.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut current = self.root.as_deref_mut()?;
+
+        loop {
+            match current.keys.binary_search(key) {
+                Ok(i) => return Some(&mut current.values[i]),
+                Err(i) => current = current.children.get_mut(i)?,
+            }
+        }
+    }
+
+    /// Returns `true` if the map contains an entry for `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Removes the entry stored under `key` and returns its value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BTreeMap;
+    ///
+    /// let mut map: BTreeMap<_, _> = [(1, "a")].into_iter().collect();
+    ///
+    /// assert_eq!(map.remove(&1), Some("a"));
+    /// assert_eq!(map.remove(&1), None);
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let root = self.root.as_mut()?;
+        let (_, value) = root.remove(key)?;
+
+        self.len -= 1;
+
+        // The tree only shrinks in height here, when the root runs out of
+        // keys after merging its last two children.
+        if root.keys.is_empty() {
+            self.root = root.children.pop().map(Box::new);
+        }
+
+        Some(value)
+    }
+
+    /// Returns an iterator over the entries whose keys fall within `range`,
+    /// in ascending key order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BTreeMap;
+    ///
+    /// let map: BTreeMap<_, _> = (0..100).map(|key| (key, key * 10)).collect();
+    ///
+    /// let middle: Vec<_> = map.range(40..=42).collect();
+    /// assert_eq!(middle, vec![(&40, &400), (&41, &410), (&42, &420)]);
+    /// ```
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V> {
+        let after_start = |key: &K| match range.start_bound() {
+            Bound::Included(start) => start <= key,
+            Bound::Excluded(start) => start < key,
+            Bound::Unbounded => true,
+        };
+
+        let before_end = |key: &K| match range.end_bound() {
+            Bound::Included(end) => key <= end,
+            Bound::Excluded(end) => key < end,
+            Bound::Unbounded => true,
+        };
+
+        // Path to the first entry not before the start bound, and the first
+        // entry after the end bound, where the iteration stops.
+        let mut stack = Vec::new();
+        let mut end = None;
+        let mut current = self.root.as_deref();
+
+        while let Some(node) = current {
+            let i = node.keys.partition_point(|key| !after_start(key));
+            stack.push((node, i));
+            current = node.children.get(i);
+        }
+
+        let mut current = self.root.as_deref();
+
+        while let Some(node) = current {
+            let i = node.keys.partition_point(before_end);
+
+            if i < node.keys.len() {
+                end = Some((ptr::from_ref(node), i));
+            }
+
+            current = node.children.get(i);
+        }
+
+        let mut path = Path { stack };
+
+        // If the start is after the end the iteration would begin past the
+        // end entry.
+        if path
+            .peek()
+            .is_some_and(|(node, i)| !before_end(&node.keys[i]))
+        {
+            path.stack.clear();
+        }
+
+        Range { path, end }
+    }
+}
+
+impl<K, V> Default for BTreeMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for BTreeMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for BTreeMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+
+        map
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a BTreeMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Path from the root to the next entry of an iteration, each node paired
+/// with the index of its next key.
+struct Path<'a, K, V> {
+    stack: Vec<(&'a Node<K, V>, usize)>,
+}
+
+impl<'a, K, V> Path<'a, K, V> {
+    /// Pushes the nodes from `current` down to its leftmost leaf.
+    fn push_first_leaf(&mut self, mut current: Option<&'a Node<K, V>>) {
+        while let Some(node) = current {
+            self.stack.push((node, 0));
+            current = node.children.first();
+        }
+    }
+
+    /// Drops the nodes whose keys have all been visited and returns the
+    /// position of the next entry.
+    fn peek(&mut self) -> Option<(&'a Node<K, V>, usize)> {
+        while let Some(&(node, i)) = self.stack.last() {
+            if i < node.keys.len() {
+                return Some((node, i));
+            }
+
+            self.stack.pop();
+        }
+
+        None
+    }
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let (node, i) = self.peek()?;
+
+        // The entries of the child after the key come next.
+        self.stack.last_mut().unwrap().1 += 1;
+        self.push_first_leaf(node.children.get(i + 1));
+
+        Some((&node.keys[i], &node.values[i]))
+    }
+}
+
+/// Iterator over the entries of a [`BTreeMap`] in ascending key order.
+pub struct Iter<'a, K, V> {
+    path: Path<'a, K, V>,
+    len: usize,
+}
+
+/// Iterator over the keys of a [`BTreeMap`] in ascending order.
+pub struct Keys<'a, K, V>(Iter<'a, K, V>);
+
+/// Iterator over the values of a [`BTreeMap`], ordered by their keys.
+pub struct Values<'a, K, V>(Iter<'a, K, V>);
+
+/// Iterator over the entries of a [`BTreeMap`] within a range of keys. See
+/// [`BTreeMap::range`].
+pub struct Range<'a, K, V> {
+    path: Path<'a, K, V>,
+    /// Node and index of the first entry after the range, if any.
+    end: Option<(*const Node<K, V>, usize)>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.path.next()?;
+        self.len -= 1;
+
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, i) = self.path.peek()?;
+
+        if self.end == Some((ptr::from_ref(node), i)) {
+            self.path.stack.clear();
+            return None;
+        }
+
+        self.path.next()
+    }
+}
+
+impl<K: HeapSize, V: HeapSize> HeapSize for Node<K, V> {
+    fn heap_size_of_children(&self) -> usize {
+        self.keys.heap_size_of_children()
+            + self.values.heap_size_of_children()
+            + self.children.heap_size_of_children()
+    }
+}
+
+impl<K: HeapSize, V: HeapSize> HeapSize for BTreeMap<K, V> {
+    fn heap_size_of_children(&self) -> usize {
+        self.root.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use super::{BTreeMap, Node, B, CAPACITY};
+
+    /// Checks the number of keys of every node, the order of the keys and
+    /// that all the leaves are at the same depth. Returns the height and the
+    /// number of entries of the subtree.
+    fn check_node<K: Ord, V>(
+        node: &Node<K, V>,
+        is_root: bool,
+        bounds: (Option<&K>, Option<&K>),
+    ) -> (usize, usize) {
+        assert!(node.keys.len() <= CAPACITY);
+        assert!(is_root || node.keys.len() >= B - 1);
+        assert_eq!(node.keys.len(), node.values.len());
+        assert!(node.keys.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(bounds.0.is_none_or(|low| low < &node.keys[0]));
+        assert!(bounds.1.is_none_or(|high| node.keys.last().unwrap() < high));
+
+        if node.is_leaf() {
+            return (1, node.keys.len());
+        }
+
+        assert_eq!(node.children.len(), node.keys.len() + 1);
+
+        let mut height = None;
+        let mut len = node.keys.len();
+
+        for (i, child) in node.children.iter().enumerate() {
+            let low = if i == 0 {
+                bounds.0
+            } else {
+                node.keys.get(i - 1)
+            };
+            let high = node.keys.get(i).or(bounds.1);
+            let (child_height, child_len) = check_node(child, false, (low, high));
+
+            assert!(height.is_none_or(|height| height == child_height));
+            height = Some(child_height);
+            len += child_len;
+        }
+
+        (height.unwrap() + 1, len)
+    }
+
+    fn check<K: Ord, V>(map: &BTreeMap<K, V>) -> usize {
+        match &map.root {
+            None => {
+                assert_eq!(map.len(), 0);
+                0
+            }
+
+            Some(root) => {
+                let (height, len) = check_node(root, true, (None, None));
+                assert_eq!(len, map.len());
+                height
+            }
+        }
+    }
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut map = BTreeMap::new();
+        let mut expected = std::collections::BTreeMap::new();
+
+        // Pseudorandom keys with repetitions
+        let keys: Vec<u32> = (0..5_000).map(|i| (i * 7_919 + 13) % 1_500).collect();
+
+        for (i, &key) in keys.iter().enumerate() {
+            assert_eq!(map.insert(key, i), expected.insert(key, i));
+        }
+
+        assert!(check(&map) > 1);
+        assert!(map.iter().eq(expected.iter()));
+        assert_eq!(map.iter().len(), 1_500);
+
+        for key in 0..1_600 {
+            assert_eq!(map.get(&key), expected.get(&key));
+        }
+
+        *map.get_mut(&7).unwrap() = 0;
+        assert_eq!(map.get(&7), Some(&0));
+        expected.insert(7, 0);
+
+        for &key in keys.iter().rev().step_by(2) {
+            assert_eq!(map.remove(&key), expected.remove(&key));
+            assert!(!map.contains_key(&key));
+        }
+
+        check(&map);
+        assert!(map.iter().eq(expected.iter()));
+
+        for key in 0..1_500 {
+            assert_eq!(map.remove(&key), expected.remove(&key));
+        }
+
+        assert_eq!(check(&map), 0);
+        assert!(map.is_empty());
+        assert_eq!(map.iter().next(), None);
+    }
+
+    #[test]
+    fn test_range() {
+        let map: BTreeMap<_, _> = (0..500).map(|key| (key * 2, key)).collect();
+        let keys =
+            |range: super::Range<'_, i32, i32>| range.map(|(key, _)| *key).collect::<Vec<_>>();
+
+        assert_eq!(keys(map.range(10..16)), [10, 12, 14]);
+        assert_eq!(keys(map.range(9..=16)), [10, 12, 14, 16]);
+        assert_eq!(keys(map.range(..5)), [0, 2, 4]);
+        assert_eq!(keys(map.range(995..)), [996, 998]);
+        assert_eq!(
+            keys(map.range(200..400)),
+            (200..400).step_by(2).collect::<Vec<_>>()
+        );
+        assert!(keys(map.range(11..12)).is_empty());
+        assert!(keys(map.range(2_000..)).is_empty());
+        assert!(keys(map.range((Bound::Included(30), Bound::Excluded(20)))).is_empty());
+        assert_eq!(keys(map.range(..)).len(), 500);
+
+        assert!(BTreeMap::<i32, i32>::new().range(..).next().is_none());
+    }
+
+    #[test]
+    fn test_owned_values() {
+        let mut map = BTreeMap::new();
+
+        for i in 0..200 {
+            map.insert(format!("{i:03}"), vec![i; 3]);
+        }
+
+        for i in (0..200).filter(|i| i % 3 != 0) {
+            assert_eq!(map.remove(&format!("{i:03}")), Some(vec![i; 3]));
+        }
+
+        check(&map);
+        assert!(map.keys().eq((0..200)
+            .step_by(3)
+            .map(|i| format!("{i:03}"))
+            .collect::<Vec<_>>()
+            .iter()));
+        assert_eq!(map.values().map(|value| value.len()).sum::<usize>(), 67 * 3);
+
+        map.clear();
+        assert!(map.is_empty());
+    }
+}
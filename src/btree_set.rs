@@ -0,0 +1,234 @@
+use std::ops::RangeBounds;
+
+use crate::{
+    btree_map::{self, BTreeMap},
+    heap_size::HeapSize,
+};
+
+/// Sorted set backed by a [`BTreeMap`] with `()` values. Prefer it over
+/// [`crate::BinaryTree`] for large sets, the B-tree is always balanced and
+/// stores many values per node.
+pub struct BTreeSet<T> {
+    map: BTreeMap<T, ()>,
+}
+
+impl<T> BTreeSet<T> {
+    /// Creates an empty set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BTreeSet;
+    ///
+    /// let set: BTreeSet<i32> = BTreeSet::new();
+    ///
+    /// assert!(set.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            map: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the number of values in the set.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the set contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Removes all the values from the set.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Returns an iterator over the values in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BTreeSet;
+    ///
+    /// let set: BTreeSet<i32> = [3, 1, 2].into_iter().collect();
+    ///
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(self.map.keys())
+    }
+}
+
+impl<T: Ord> BTreeSet<T> {
+    /// Inserts `value` and returns `true` unless an equal value is already
+    /// present, in which case the set is not modified.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BTreeSet;
+    ///
+    /// let mut set = BTreeSet::new();
+    ///
+    /// assert!(set.insert(1));
+    /// assert!(!set.insert(1));
+    /// ```
+    pub fn insert(&mut self, value: T) -> bool {
+        if self.map.contains_key(&value) {
+            return false;
+        }
+
+        self.map.insert(value, ());
+
+        true
+    }
+
+    /// Returns `true` if the set contains `value`.
+    pub fn contains(&self, value: &T) -> bool {
+        self.map.contains_key(value)
+    }
+
+    /// Removes `value` and returns `true` unless it's not present.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BTreeSet;
+    ///
+    /// let mut set: BTreeSet<i32> = [1, 2].into_iter().collect();
+    ///
+    /// assert!(set.remove(&1));
+    /// assert!(!set.remove(&1));
+    /// assert!(!set.contains(&1));
+    /// ```
+    pub fn remove(&mut self, value: &T) -> bool {
+        self.map.remove(value).is_some()
+    }
+
+    /// Returns an iterator over the values within `range` in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::BTreeSet;
+    ///
+    /// let set: BTreeSet<i32> = (0..100).collect();
+    ///
+    /// assert!(set.range(40..43).eq([40, 41, 42].iter()));
+    /// ```
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Range<'_, T> {
+        Range(self.map.range(range))
+    }
+}
+
+impl<T> Default for BTreeSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> Extend<T> for BTreeSet<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for BTreeSet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+
+        set
+    }
+}
+
+impl<'a, T> IntoIterator for &'a BTreeSet<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the values of a [`BTreeSet`] in ascending order.
+pub struct Iter<'a, T>(btree_map::Keys<'a, T, ()>);
+
+/// Iterator over the values of a [`BTreeSet`] within a range. See
+/// [`BTreeSet::range`].
+pub struct Range<'a, T>(btree_map::Range<'a, T, ()>);
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> Iterator for Range<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(value, _)| value)
+    }
+}
+
+impl<T: HeapSize> HeapSize for BTreeSet<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.map.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BTreeSet;
+
+    #[test]
+    fn test_insert_contains_remove() {
+        let mut set = BTreeSet::new();
+
+        for value in (0..1_000).map(|value| (value * 37) % 500) {
+            set.insert(value);
+        }
+
+        assert_eq!(set.len(), 500);
+        assert!(set.iter().copied().eq(0..500));
+        assert_eq!(set.iter().len(), 500);
+
+        for value in (0..500).step_by(2) {
+            assert!(set.remove(&value));
+        }
+
+        assert!(!set.remove(&0));
+        assert!(set.contains(&1));
+        assert!(!set.contains(&2));
+        assert!(set.iter().copied().eq((1..500).step_by(2)));
+
+        set.clear();
+        assert!(set.is_empty());
+        assert_eq!(set.iter().next(), None);
+    }
+
+    #[test]
+    fn test_range() {
+        let set: BTreeSet<_> = ["apple", "banana", "cherry", "date", "fig"]
+            .into_iter()
+            .collect();
+
+        assert!(set.range("b".."d").eq(["banana", "cherry"].iter()));
+        assert!(set.range("cherry"..).eq(["cherry", "date", "fig"].iter()));
+        assert_eq!(set.range(.."a").next(), None);
+        assert_eq!((&set).into_iter().count(), 5);
+    }
+}
@@ -0,0 +1,320 @@
+use std::{mem::MaybeUninit, slice};
+
+use crate::heap_size::{self, HeapSize};
+
+/// Ring buffer with room for `N` values stored inline, without any heap
+/// allocation. Pushing into a full buffer overwrites the oldest value, so it
+/// always holds the last `N` values pushed, which is what rolling windows
+/// like recent log lines or telemetry samples need.
+pub struct CircularBuffer<T, const N: usize> {
+    slots: [MaybeUninit<T>; N],
+    /// Index of the oldest value.
+    head: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> CircularBuffer<T, N> {
+    /// Creates an empty buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::CircularBuffer;
+    ///
+    /// let buffer: CircularBuffer<i32, 4> = CircularBuffer::new();
+    ///
+    /// assert!(buffer.is_empty());
+    /// assert_eq!(buffer.capacity(), 4);
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            slots: [const { MaybeUninit::uninit() }; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of values in the buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the buffer contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if the next push will overwrite the oldest value.
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Returns the maximum number of values, `N`.
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Removes all the values from the buffer.
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+        self.head = 0;
+    }
+
+    /// Returns the slot of the value at `index`, counting from the oldest.
+    fn slot(&self, index: usize) -> usize {
+        (self.head + index) % N
+    }
+
+    /// Pushes `value` as the newest value. If the buffer is full, the oldest
+    /// value is removed to make room and returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::CircularBuffer;
+    ///
+    /// let mut buffer: CircularBuffer<i32, 2> = CircularBuffer::new();
+    ///
+    /// assert_eq!(buffer.push(1), None);
+    /// assert_eq!(buffer.push(2), None);
+    /// assert_eq!(buffer.push(3), Some(1));
+    ///
+    /// assert!(buffer.iter().eq([2, 3].iter()));
+    /// ```
+    pub fn push(&mut self, value: T) -> Option<T> {
+        if N == 0 {
+            return Some(value);
+        }
+
+        if self.len == N {
+            let oldest = std::mem::replace(&mut self.slots[self.head], MaybeUninit::new(value));
+            self.head = self.slot(1);
+
+            return unsafe { Some(oldest.assume_init()) };
+        }
+
+        let slot = self.slot(self.len);
+        self.slots[slot].write(value);
+        self.len += 1;
+
+        None
+    }
+
+    /// Removes and returns the oldest value.
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let value = unsafe { self.slots[self.head].assume_init_read() };
+        self.head = self.slot(1);
+        self.len -= 1;
+
+        Some(value)
+    }
+
+    /// Removes and returns the newest value.
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.len -= 1;
+
+        unsafe { Some(self.slots[self.slot(self.len)].assume_init_read()) }
+    }
+
+    /// Returns a reference to the value at `index`, counting from the oldest.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+
+        unsafe { Some(self.slots[self.slot(index)].assume_init_ref()) }
+    }
+
+    /// Returns the oldest value.
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Returns the newest value.
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    /// Returns the values as two slices, from oldest to newest. The second
+    /// one is empty unless the values wrap around the end of the storage.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let until_end = N - self.head;
+        let (first, wrapped) = if self.len <= until_end {
+            (self.len, 0)
+        } else {
+            (until_end, self.len - until_end)
+        };
+
+        let base = self.slots.as_ptr() as *const T;
+
+        unsafe {
+            (
+                slice::from_raw_parts(base.add(self.head), first),
+                slice::from_raw_parts(base, wrapped),
+            )
+        }
+    }
+
+    /// Returns an iterator over the values from oldest to newest.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::CircularBuffer;
+    ///
+    /// let mut buffer: CircularBuffer<u32, 3> = CircularBuffer::new();
+    ///
+    /// for sample in 1..=5 {
+    ///     buffer.push(sample);
+    /// }
+    ///
+    /// // Average of the last 3 samples
+    /// assert_eq!(buffer.iter().sum::<u32>() / buffer.len() as u32, 4);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (front, back) = self.as_slices();
+
+        Iter {
+            front: front.iter(),
+            back: back.iter(),
+        }
+    }
+}
+
+impl<T, const N: usize> Drop for CircularBuffer<T, N> {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<T, const N: usize> Default for CircularBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Extend<T> for CircularBuffer<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for CircularBuffer<T, N> {
+    /// Collects the last `N` values of the iterator.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut buffer = Self::new();
+        buffer.extend(iter);
+
+        buffer
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a CircularBuffer<T, N> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the values of a [`CircularBuffer`] from oldest to newest.
+pub struct Iter<'a, T> {
+    front: slice::Iter<'a, T>,
+    back: slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<T: HeapSize, const N: usize> HeapSize for CircularBuffer<T, N> {
+    fn heap_size_of_children(&self) -> usize {
+        heap_size::children(self.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::CircularBuffer;
+
+    #[test]
+    fn test_overwrite() {
+        let mut buffer: CircularBuffer<i32, 4> = CircularBuffer::new();
+
+        for value in 0..10 {
+            let evicted = buffer.push(value);
+            assert_eq!(evicted, (value >= 4).then(|| value - 4));
+        }
+
+        assert!(buffer.is_full());
+        assert!(buffer.iter().copied().eq(6..10));
+        assert!(buffer.iter().rev().copied().eq((6..10).rev()));
+        assert_eq!(buffer.as_slices(), (&[6, 7][..], &[8, 9][..]));
+        assert_eq!(buffer.front(), Some(&6));
+        assert_eq!(buffer.back(), Some(&9));
+        assert_eq!(buffer.get(4), None);
+
+        assert_eq!(buffer.pop_front(), Some(6));
+        assert_eq!(buffer.pop_back(), Some(9));
+        assert!(buffer.iter().copied().eq(7..9));
+
+        buffer.clear();
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.pop_back(), None);
+
+        let buffer: CircularBuffer<_, 3> = (0..100).collect();
+        assert!((&buffer).into_iter().copied().eq(97..100));
+    }
+
+    #[test]
+    fn test_drops() {
+        let value = Rc::new(());
+
+        {
+            let mut buffer: CircularBuffer<_, 3> = CircularBuffer::new();
+
+            for _ in 0..5 {
+                buffer.push(Rc::clone(&value));
+            }
+
+            assert_eq!(Rc::strong_count(&value), 4);
+        }
+
+        assert_eq!(Rc::strong_count(&value), 1);
+
+        let mut empty: CircularBuffer<_, 0> = CircularBuffer::new();
+        assert_eq!(empty.push(1), Some(1));
+        assert_eq!(empty.iter().count(), 0);
+    }
+}
@@ -0,0 +1,30 @@
+use std::cmp::Ordering;
+
+/// Defines the order of the values stored in sorted collections like
+/// [`crate::BinaryTree`]. Implemented for [`Natural`], which uses the [`Ord`]
+/// implementation of the values, and for any closure or function with the
+/// signature `Fn(&T, &T) -> Ordering`.
+///
+/// Implementations must define a total order, otherwise the collections
+/// behave unpredictably (but memory safely).
+pub trait Compare<T: ?Sized> {
+    /// Compares `a` with `b`.
+    fn compare(&self, a: &T, b: &T) -> Ordering;
+}
+
+/// Comparator that orders values by their [`Ord`] implementation. It's the
+/// default comparator of every sorted collection in this crate.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Natural;
+
+impl<T: Ord + ?Sized> Compare<T> for Natural {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+impl<T: ?Sized, F: Fn(&T, &T) -> Ordering> Compare<T> for F {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        self(a, b)
+    }
+}
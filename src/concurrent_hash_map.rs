@@ -0,0 +1,363 @@
+use std::{
+    hash::{BuildHasher, Hash, RandomState},
+    mem,
+    sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use crate::{
+    heap_size::HeapSize,
+    index_map::IndexMap,
+    vector::{self, Vector},
+};
+
+/// Number of shards of [`ConcurrentHashMap::new`].
+const DEFAULT_SHARDS: usize = 16;
+
+/// Hash map that can be shared between threads. Entries are spread over a
+/// fixed number of shards, each an [`IndexMap`] behind its own [`RwLock`],
+/// so threads that access keys in different shards never wait for each
+/// other and readers of the same shard don't block one another.
+///
+/// References to values can't outlive the lock of their shard, so reads and
+/// updates take closures instead, like [`ConcurrentHashMap::get`]. Locks are
+/// recovered if a thread panics while holding one.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::thread;
+///
+/// use collections_rust::ConcurrentHashMap;
+///
+/// let hits = ConcurrentHashMap::new();
+///
+/// thread::scope(|scope| {
+///     for _ in 0..4 {
+///         scope.spawn(|| {
+///             for page in ["/", "/about", "/"] {
+///                 hits.insert_with(page, || 0, |count| *count += 1);
+///             }
+///         });
+///     }
+/// });
+///
+/// assert_eq!(hits.get(&"/", |count| *count), Some(8));
+/// assert_eq!(hits.get(&"/about", |count| *count), Some(4));
+/// ```
+pub struct ConcurrentHashMap<K, V, S = RandomState> {
+    shards: Box<[RwLock<IndexMap<K, V, S>>]>,
+    hasher: S,
+}
+
+impl<K, V> ConcurrentHashMap<K, V> {
+    /// Creates an empty map with 16 shards.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::ConcurrentHashMap;
+    ///
+    /// let map: ConcurrentHashMap<String, u32> = ConcurrentHashMap::new();
+    ///
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::with_shards_and_hasher(DEFAULT_SHARDS, RandomState::new())
+    }
+
+    /// Creates an empty map with `shards` shards. More shards mean less
+    /// contention between threads and slower [`ConcurrentHashMap::len`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shards` is zero.
+    pub fn with_shards(shards: usize) -> Self {
+        Self::with_shards_and_hasher(shards, RandomState::new())
+    }
+}
+
+impl<K, V, S: Clone> ConcurrentHashMap<K, V, S> {
+    /// Creates an empty map with `shards` shards that uses `hasher` to hash
+    /// the keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shards` is zero.
+    pub fn with_shards_and_hasher(shards: usize, hasher: S) -> Self {
+        assert!(shards > 0, "A map needs at least one shard");
+
+        let shards = (0..shards)
+            .map(|_| RwLock::new(IndexMap::with_hasher(hasher.clone())))
+            .collect();
+
+        Self { shards, hasher }
+    }
+}
+
+impl<K, V, S> ConcurrentHashMap<K, V, S> {
+    /// Locks shard `index` for reading.
+    fn read(&self, index: usize) -> RwLockReadGuard<'_, IndexMap<K, V, S>> {
+        self.shards[index]
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Locks shard `index` for writing.
+    fn write(&self, index: usize) -> RwLockWriteGuard<'_, IndexMap<K, V, S>> {
+        self.shards[index]
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the number of shards.
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Returns the number of entries. Shards are counted one after the
+    /// other, so the result may be stale if other threads are writing.
+    pub fn len(&self) -> usize {
+        (0..self.shards.len())
+            .map(|index| self.read(index).len())
+            .sum()
+    }
+
+    /// Returns `true` if the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        (0..self.shards.len()).all(|index| self.read(index).is_empty())
+    }
+
+    /// Removes all the entries, one shard at a time.
+    pub fn clear(&self) {
+        for index in 0..self.shards.len() {
+            self.write(index).clear();
+        }
+    }
+
+    /// Returns an iterator over clones of the entries. Every shard is
+    /// copied when the iterator reaches it, so each shard is seen at a
+    /// single point in time, but changes to later shards made meanwhile are
+    /// visible.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::ConcurrentHashMap;
+    ///
+    /// let map: ConcurrentHashMap<u32, char> = [(1, 'a'), (2, 'b')].into_iter().collect();
+    ///
+    /// let mut entries: Vec<(u32, char)> = map.iter().collect();
+    /// entries.sort();
+    ///
+    /// assert_eq!(entries, [(1, 'a'), (2, 'b')]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V, S>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        Iter {
+            map: self,
+            shard: 0,
+            snapshot: Vector::new().into_iter(),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> ConcurrentHashMap<K, V, S> {
+    /// Returns the index of the shard of `key`. Shards are chosen with the
+    /// high bits of the hash because the low ones pick the slot inside the
+    /// shard.
+    fn shard_of(&self, key: &K) -> usize {
+        (self.hasher.hash_one(key) >> 32) as usize % self.shards.len()
+    }
+
+    /// Calls `f` with the value of `key` while its shard is locked for
+    /// reading and returns the result, or `None` if the key is absent.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::ConcurrentHashMap;
+    ///
+    /// let map = ConcurrentHashMap::new();
+    /// map.insert("names", vec!["ada", "alan"]);
+    ///
+    /// assert_eq!(map.get(&"names", |names| names.len()), Some(2));
+    /// assert_eq!(map.get(&"ages", |ages| ages.len()), None);
+    /// ```
+    pub fn get<R, F: FnOnce(&V) -> R>(&self, key: &K, f: F) -> Option<R> {
+        self.read(self.shard_of(key)).get(key).map(f)
+    }
+
+    /// Returns `true` if the map contains `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.read(self.shard_of(key)).contains_key(key)
+    }
+
+    /// Calls `f` with a mutable reference to the value of `key` while its
+    /// shard is locked for writing and returns the result, or `None` if the
+    /// key is absent.
+    pub fn update<R, F: FnOnce(&mut V) -> R>(&self, key: &K, f: F) -> Option<R> {
+        self.write(self.shard_of(key)).get_mut(key).map(f)
+    }
+
+    /// Inserts `key` with the value returned by `default` unless it's
+    /// already present, then calls `f` with the value, all while the shard
+    /// is locked, so no other thread can insert the key in between.
+    pub fn insert_with<R, D, F>(&self, key: K, default: D, f: F) -> R
+    where
+        D: FnOnce() -> V,
+        F: FnOnce(&mut V) -> R,
+    {
+        let mut shard = self.write(self.shard_of(&key));
+
+        if !shard.contains_key(&key) {
+            shard.insert(key, default());
+
+            // New keys are appended, so it's the last entry of the shard
+            let last = shard.len() - 1;
+            return f(shard.get_index_mut(last).unwrap().1);
+        }
+
+        f(shard.get_mut(&key).unwrap())
+    }
+
+    /// Inserts `value` for `key` and returns the previous value, if any.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        self.write(self.shard_of(&key)).insert(key, value)
+    }
+
+    /// Removes `key` and returns its value, if present.
+    pub fn remove(&self, key: &K) -> Option<V> {
+        self.write(self.shard_of(key)).swap_remove(key)
+    }
+}
+
+impl<K, V> Default for ConcurrentHashMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Clone + Default> FromIterator<(K, V)>
+    for ConcurrentHashMap<K, V, S>
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let map = Self::with_shards_and_hasher(DEFAULT_SHARDS, S::default());
+
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+
+        map
+    }
+}
+
+/// Iterator over clones of the entries of a [`ConcurrentHashMap`], taken
+/// one shard at a time.
+pub struct Iter<'a, K, V, S> {
+    map: &'a ConcurrentHashMap<K, V, S>,
+    /// Index of the next shard to copy.
+    shard: usize,
+    snapshot: vector::IntoIter<(K, V)>,
+}
+
+impl<'a, K: Clone, V: Clone, S> Iterator for Iter<'a, K, V, S> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.snapshot.next() {
+                return Some(entry);
+            }
+
+            if self.shard == self.map.shards.len() {
+                return None;
+            }
+
+            let mut snapshot = Vector::new();
+            for (key, value) in self.map.read(self.shard).iter() {
+                snapshot.push((key.clone(), value.clone()));
+            }
+
+            self.snapshot = snapshot.into_iter();
+            self.shard += 1;
+        }
+    }
+}
+
+impl<K: HeapSize, V: HeapSize, S> HeapSize for ConcurrentHashMap<K, V, S> {
+    /// Locks one shard at a time, so writers on other shards aren't blocked
+    /// and the result is only a snapshot.
+    fn heap_size_of_children(&self) -> usize {
+        let shards = mem::size_of_val::<[RwLock<IndexMap<K, V, S>>]>(&self.shards);
+
+        shards
+            + (0..self.shards.len())
+                .map(|index| self.read(index).heap_size_of_children())
+                .sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, thread};
+
+    use super::ConcurrentHashMap;
+
+    #[test]
+    fn test_threads() {
+        let map = ConcurrentHashMap::with_shards(8);
+        let (threads, per_thread) = (4, 2_000);
+
+        thread::scope(|scope| {
+            for thread in 0..threads {
+                let map = &map;
+
+                scope.spawn(move || {
+                    for key in 0..per_thread {
+                        // Every thread counts the shared keys and owns others
+                        map.insert_with((threads, key), || 0, |count| *count += 1);
+                        map.insert((thread, key), key);
+
+                        if key % 2 == 0 {
+                            assert_eq!(map.remove(&(thread, key)), Some(key));
+                        }
+                    }
+                });
+            }
+        });
+
+        assert_eq!(map.len(), per_thread + threads * per_thread / 2);
+        assert!(
+            (0..per_thread).all(|key| map.get(&(threads, key), |count| *count) == Some(threads))
+        );
+        assert!((0..threads).all(|thread| !map.contains_key(&(thread, 0))));
+        assert_eq!(map.get(&(0, 1), |value| *value), Some(1));
+
+        let entries: HashMap<(usize, usize), usize> = map.iter().collect();
+        assert_eq!(entries.len(), map.len());
+    }
+
+    #[test]
+    fn test_update() {
+        let map: ConcurrentHashMap<&str, Vec<u32>> = ConcurrentHashMap::new();
+
+        assert_eq!(map.update(&"a", |values| values.push(1)), None);
+        assert_eq!(map.insert("a", vec![1]), None);
+        assert_eq!(
+            map.update(&"a", |values| {
+                values.push(2);
+                values.len()
+            }),
+            Some(2)
+        );
+        assert_eq!(map.insert("a", vec![]), Some(vec![1, 2]));
+
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.iter().count(), 0);
+        assert_eq!(map.shards(), 16);
+    }
+}
@@ -0,0 +1,660 @@
+use std::{
+    mem, ptr,
+    sync::atomic::{AtomicBool, AtomicPtr, AtomicU64, AtomicUsize, Ordering},
+    thread,
+};
+
+use crate::heap_size::HeapSize;
+
+/// Maximum number of levels of the list, enough for 2^32 entries.
+const MAX_HEIGHT: usize = 32;
+
+/// Pointers to the last node before a key at every level, and to the node
+/// after it.
+type Window<K, V> = [*mut Node<K, V>; MAX_HEIGHT];
+
+struct Node<K, V> {
+    /// `None` only for the head of the list.
+    entry: Option<(K, V)>,
+    /// Next node at every level this node takes part in, level 0 first.
+    next: Box<[AtomicPtr<Node<K, V>>]>,
+    /// Set when the node is logically removed, before it's unlinked.
+    marked: AtomicBool,
+    /// Set once the node is linked at every level, which is when it starts
+    /// to count as part of the map.
+    fully_linked: AtomicBool,
+    /// Spin lock taken to change the links of the node or to remove it.
+    locked: AtomicBool,
+    /// Next node of the retired list once the node is unlinked.
+    retired_next: AtomicPtr<Node<K, V>>,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(entry: Option<(K, V)>, height: usize) -> *mut Self {
+        Box::into_raw(Box::new(Self {
+            entry,
+            next: (0..height)
+                .map(|_| AtomicPtr::new(ptr::null_mut()))
+                .collect(),
+            marked: AtomicBool::new(false),
+            fully_linked: AtomicBool::new(false),
+            locked: AtomicBool::new(false),
+            retired_next: AtomicPtr::new(ptr::null_mut()),
+        }))
+    }
+
+    fn key(&self) -> &K {
+        &self.entry.as_ref().unwrap().0
+    }
+
+    fn height(&self) -> usize {
+        self.next.len()
+    }
+
+    fn lock(&self) {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            thread::yield_now();
+        }
+    }
+
+    fn unlock(&self) {
+        self.locked.store(false, Ordering::Release);
+    }
+
+    /// Returns `true` if the node is part of the map.
+    fn is_live(&self) -> bool {
+        self.fully_linked.load(Ordering::Acquire) && !self.marked.load(Ordering::Acquire)
+    }
+}
+
+/// Sorted map that can be shared between threads, built as the lazy skip
+/// list of Herlihy, Lev, Luchangco and Shavit. Lookups and iteration never
+/// take locks, while insertions and removals lock only the few nodes whose
+/// links they change, so threads working on different parts of the map
+/// don't wait for each other.
+///
+/// Removed nodes may still be in use by threads that were traversing them,
+/// so they're retired to a list that is freed whenever a thread finishes an
+/// operation while no other is running. Values can't be moved out or
+/// changed for the same reason, which is why [`ConcurrentSkipMap::insert`]
+/// doesn't replace existing values and reads take closures.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::thread;
+///
+/// use collections_rust::ConcurrentSkipMap;
+///
+/// let index = ConcurrentSkipMap::new();
+///
+/// thread::scope(|scope| {
+///     for thread in 0..4 {
+///         let index = &index;
+///         scope.spawn(move || {
+///             for key in (thread..100).step_by(4) {
+///                 index.insert(key, key * key);
+///             }
+///         });
+///     }
+/// });
+///
+/// assert_eq!(index.len(), 100);
+/// assert_eq!(index.get(&7, |value| *value), Some(49));
+/// assert!(index.iter().map(|(key, _)| key).eq(0..100));
+/// ```
+pub struct ConcurrentSkipMap<K, V> {
+    head: *mut Node<K, V>,
+    len: AtomicUsize,
+    seed: AtomicU64,
+    /// Number of operations in progress.
+    active: AtomicUsize,
+    /// Unlinked nodes waiting until no operation can be reading them.
+    retired: AtomicPtr<Node<K, V>>,
+}
+
+// Nodes are shared between threads and only freed once no other thread can
+// access them. Keys and values are read from many threads at once.
+unsafe impl<K: Send + Sync, V: Send + Sync> Send for ConcurrentSkipMap<K, V> {}
+unsafe impl<K: Send + Sync, V: Send + Sync> Sync for ConcurrentSkipMap<K, V> {}
+
+/// Marks an operation in progress while alive. Dropping the last one frees
+/// the retired nodes.
+struct Guard<'a, K, V> {
+    map: &'a ConcurrentSkipMap<K, V>,
+}
+
+impl<'a, K, V> Drop for Guard<'a, K, V> {
+    fn drop(&mut self) {
+        let map = self.map;
+
+        if map.active.load(Ordering::SeqCst) > 1 {
+            map.active.fetch_sub(1, Ordering::SeqCst);
+            return;
+        }
+
+        // Operations that start from now on can't reach the retired nodes,
+        // but the ones that started before taking the list may
+        let retired = map.retired.swap(ptr::null_mut(), Ordering::SeqCst);
+
+        if map.active.fetch_sub(1, Ordering::SeqCst) == 1 {
+            unsafe { ConcurrentSkipMap::free_retired(retired) };
+        } else if !retired.is_null() {
+            let mut last = retired;
+
+            loop {
+                let next = unsafe { (*last).retired_next.load(Ordering::Relaxed) };
+
+                if next.is_null() {
+                    break;
+                }
+
+                last = next;
+            }
+
+            map.chain_retired(retired, last);
+        }
+    }
+}
+
+impl<K, V> ConcurrentSkipMap<K, V> {
+    /// Creates an empty map.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::ConcurrentSkipMap;
+    ///
+    /// let map: ConcurrentSkipMap<u32, String> = ConcurrentSkipMap::new();
+    ///
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        let head = Node::new(None, MAX_HEIGHT);
+        unsafe { (*head).fully_linked.store(true, Ordering::Relaxed) };
+
+        Self {
+            head,
+            len: AtomicUsize::new(0),
+            seed: AtomicU64::new(0x9E37_79B9_7F4A_7C15),
+            active: AtomicUsize::new(0),
+            retired: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    /// Returns the number of entries. Other threads may change it right
+    /// after it's read, so it's only a hint.
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Registers an operation in progress until the guard is dropped.
+    fn pin(&self) -> Guard<'_, K, V> {
+        self.active.fetch_add(1, Ordering::SeqCst);
+
+        Guard { map: self }
+    }
+
+    /// Returns a random height between 1 and [`MAX_HEIGHT`], where every
+    /// level is half as likely as the one below.
+    fn random_height(&self) -> usize {
+        // Mix consecutive states of a counter with the SplitMix64 finalizer
+        let mut bits = self
+            .seed
+            .fetch_add(0x9E37_79B9_7F4A_7C15, Ordering::Relaxed);
+        bits = (bits ^ (bits >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        bits = (bits ^ (bits >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        bits ^= bits >> 31;
+
+        (bits.trailing_ones() as usize + 1).min(MAX_HEIGHT)
+    }
+
+    /// Adds the list of nodes from `first` to `last` to the retired list.
+    fn chain_retired(&self, first: *mut Node<K, V>, last: *mut Node<K, V>) {
+        let mut retired = self.retired.load(Ordering::SeqCst);
+
+        loop {
+            unsafe { (*last).retired_next.store(retired, Ordering::Relaxed) };
+
+            match self.retired.compare_exchange_weak(
+                retired,
+                first,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return,
+                Err(current) => retired = current,
+            }
+        }
+    }
+
+    /// Frees a list of retired nodes.
+    ///
+    /// # Safety
+    ///
+    /// No other thread may access any node of the list.
+    unsafe fn free_retired(mut node: *mut Node<K, V>) {
+        while !node.is_null() {
+            let next = (*node).retired_next.load(Ordering::Relaxed);
+            drop(Box::from_raw(node));
+            node = next;
+        }
+    }
+
+    /// Returns an iterator over clones of the entries in ascending order of
+    /// key. Entries inserted or removed by other threads while iterating
+    /// may or may not be seen, but keys always come out sorted and at most
+    /// once. Removed nodes aren't freed until the iterator is dropped.
+    pub fn iter(&self) -> Iter<'_, K, V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        Iter {
+            current: self.head,
+            _guard: self.pin(),
+        }
+    }
+}
+
+impl<K: Ord, V> ConcurrentSkipMap<K, V> {
+    /// Finds the last node before `key` and the node after it at every
+    /// level, and returns the highest level where the node after it has
+    /// `key`, if any.
+    ///
+    /// # Safety
+    ///
+    /// The caller must hold a guard.
+    unsafe fn find(
+        &self,
+        key: &K,
+        preds: &mut Window<K, V>,
+        succs: &mut Window<K, V>,
+    ) -> Option<usize> {
+        let mut found = None;
+        let mut pred = self.head;
+
+        for level in (0..MAX_HEIGHT).rev() {
+            let mut current = (*pred).next[level].load(Ordering::Acquire);
+
+            while !current.is_null() && (*current).key() < key {
+                pred = current;
+                current = (*pred).next[level].load(Ordering::Acquire);
+            }
+
+            if found.is_none() && !current.is_null() && (*current).key() == key {
+                found = Some(level);
+            }
+
+            preds[level] = pred;
+            succs[level] = current;
+        }
+
+        found
+    }
+
+    /// Locks the distinct predecessors of the lowest `height` levels and
+    /// checks that `valid` holds for every level. Returns the locked nodes,
+    /// which stay locked even if the check fails.
+    ///
+    /// # Safety
+    ///
+    /// The caller must hold a guard.
+    unsafe fn lock_preds<F: Fn(usize) -> bool>(
+        preds: &Window<K, V>,
+        height: usize,
+        valid: F,
+    ) -> (Vec<*mut Node<K, V>>, bool) {
+        let mut locked: Vec<*mut Node<K, V>> = Vec::with_capacity(height);
+
+        for (level, &pred) in preds.iter().enumerate().take(height) {
+            // Predecessors only move left going up, so repeated ones are
+            // consecutive
+            if locked.last() != Some(&pred) {
+                (*pred).lock();
+                locked.push(pred);
+            }
+
+            if !(*pred).marked.load(Ordering::Acquire) && valid(level) {
+                continue;
+            }
+
+            return (locked, false);
+        }
+
+        (locked, true)
+    }
+
+    /// Calls `f` with the value of `key` and returns the result, or `None`
+    /// if the key is absent. Never takes a lock.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::ConcurrentSkipMap;
+    ///
+    /// let map = ConcurrentSkipMap::new();
+    /// map.insert("a", vec![1, 2, 3]);
+    ///
+    /// assert_eq!(map.get(&"a", |values| values.len()), Some(3));
+    /// assert_eq!(map.get(&"b", |values| values.len()), None);
+    /// ```
+    pub fn get<R, F: FnOnce(&V) -> R>(&self, key: &K, f: F) -> Option<R> {
+        let _guard = self.pin();
+        let (mut preds, mut succs) = ([ptr::null_mut(); MAX_HEIGHT], [ptr::null_mut(); MAX_HEIGHT]);
+
+        unsafe {
+            let level = self.find(key, &mut preds, &mut succs)?;
+            let node = &*succs[level];
+
+            node.is_live().then(|| f(&node.entry.as_ref().unwrap().1))
+        }
+    }
+
+    /// Returns `true` if the map contains `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key, |_| ()).is_some()
+    }
+
+    /// Inserts `key` with `value` and returns `true`, or returns `false`
+    /// without changing the map if `key` is already present.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::ConcurrentSkipMap;
+    ///
+    /// let map = ConcurrentSkipMap::new();
+    ///
+    /// assert!(map.insert(1, 'a'));
+    /// assert!(!map.insert(1, 'b'));
+    /// assert_eq!(map.get(&1, |value| *value), Some('a'));
+    /// ```
+    pub fn insert(&self, key: K, value: V) -> bool {
+        let _guard = self.pin();
+        let height = self.random_height();
+        let (mut preds, mut succs) = ([ptr::null_mut(); MAX_HEIGHT], [ptr::null_mut(); MAX_HEIGHT]);
+
+        unsafe {
+            loop {
+                if let Some(level) = self.find(&key, &mut preds, &mut succs) {
+                    let node = &*succs[level];
+
+                    if node.marked.load(Ordering::Acquire) {
+                        // Wait until the removal unlinks it
+                        thread::yield_now();
+                        continue;
+                    }
+
+                    while !node.fully_linked.load(Ordering::Acquire) {
+                        thread::yield_now();
+                    }
+
+                    return false;
+                }
+
+                let (locked, valid) = Self::lock_preds(&preds, height, |level| {
+                    let succ = succs[level];
+
+                    (succ.is_null() || !(*succ).marked.load(Ordering::Acquire))
+                        && (*preds[level]).next[level].load(Ordering::Acquire) == succ
+                });
+
+                if valid {
+                    let node = Node::new(Some((key, value)), height);
+
+                    for (level, &succ) in succs.iter().enumerate().take(height) {
+                        (*node).next[level].store(succ, Ordering::Relaxed);
+                    }
+
+                    for (level, &pred) in preds.iter().enumerate().take(height) {
+                        (*pred).next[level].store(node, Ordering::Release);
+                    }
+
+                    (*node).fully_linked.store(true, Ordering::Release);
+                    locked.iter().for_each(|&pred| (*pred).unlock());
+                    self.len.fetch_add(1, Ordering::Relaxed);
+
+                    return true;
+                }
+
+                locked.iter().for_each(|&pred| (*pred).unlock());
+            }
+        }
+    }
+
+    /// Removes `key` and returns `true` if it was present.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::ConcurrentSkipMap;
+    ///
+    /// let map: ConcurrentSkipMap<i32, i32> = (0..10).map(|key| (key, key)).collect();
+    ///
+    /// assert!(map.remove(&3));
+    /// assert!(!map.remove(&3));
+    /// assert_eq!(map.len(), 9);
+    /// ```
+    pub fn remove(&self, key: &K) -> bool {
+        let _guard = self.pin();
+        let (mut preds, mut succs) = ([ptr::null_mut(); MAX_HEIGHT], [ptr::null_mut(); MAX_HEIGHT]);
+        let mut victim: *mut Node<K, V> = ptr::null_mut();
+
+        unsafe {
+            loop {
+                let found = self.find(key, &mut preds, &mut succs);
+
+                if victim.is_null() {
+                    let Some(level) = found else {
+                        return false;
+                    };
+
+                    // Only remove nodes found at their top level, since ones
+                    // found lower are still being linked
+                    let node = succs[level];
+                    if !(*node).is_live() || (*node).height() != level + 1 {
+                        return false;
+                    }
+
+                    (*node).lock();
+
+                    if (*node).marked.load(Ordering::Acquire) {
+                        (*node).unlock();
+                        return false;
+                    }
+
+                    (*node).marked.store(true, Ordering::Release);
+                    victim = node;
+                }
+
+                let height = (*victim).height();
+                let (locked, valid) = Self::lock_preds(&preds, height, |level| {
+                    (*preds[level]).next[level].load(Ordering::Acquire) == victim
+                });
+
+                if valid {
+                    for level in (0..height).rev() {
+                        let next = (*victim).next[level].load(Ordering::Acquire);
+                        (*preds[level]).next[level].store(next, Ordering::Release);
+                    }
+
+                    (*victim).unlock();
+                    locked.iter().for_each(|&pred| (*pred).unlock());
+                    self.len.fetch_sub(1, Ordering::Relaxed);
+                    self.chain_retired(victim, victim);
+
+                    return true;
+                }
+
+                locked.iter().for_each(|&pred| (*pred).unlock());
+            }
+        }
+    }
+}
+
+impl<K, V> Default for ConcurrentSkipMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> Drop for ConcurrentSkipMap<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            let mut node = self.head;
+
+            while !node.is_null() {
+                let next = (*node).next[0].load(Ordering::Relaxed);
+                drop(Box::from_raw(node));
+                node = next;
+            }
+
+            Self::free_retired(*self.retired.get_mut());
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for ConcurrentSkipMap<K, V> {
+    /// Builds a map with the entries of the iterator, keeping the first
+    /// value of repeated keys.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let map = Self::new();
+
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+
+        map
+    }
+}
+
+/// Iterator over clones of the entries of a [`ConcurrentSkipMap`] in
+/// ascending order of key.
+pub struct Iter<'a, K, V> {
+    current: *mut Node<K, V>,
+    _guard: Guard<'a, K, V>,
+}
+
+impl<'a, K: Clone, V: Clone> Iterator for Iter<'a, K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // The guard keeps every node reachable from `current` alive
+            let next = unsafe { (*self.current).next[0].load(Ordering::Acquire) };
+            let node = unsafe { next.as_ref()? };
+            self.current = next;
+
+            if node.is_live() {
+                return node.entry.clone();
+            }
+        }
+    }
+}
+
+impl<K: HeapSize, V: HeapSize> HeapSize for ConcurrentSkipMap<K, V> {
+    /// Counts the nodes reachable when each one is visited, including removed
+    /// ones that are still linked, like [`ConcurrentSkipMap::iter`] does.
+    fn heap_size_of_children(&self) -> usize {
+        let _guard = self.pin();
+        let mut size = 0;
+        let mut current = self.head;
+
+        // The guard keeps every node reachable from `current` alive
+        while let Some(node) = unsafe { current.as_ref() } {
+            size += mem::size_of::<Node<K, V>>()
+                + mem::size_of_val::<[AtomicPtr<Node<K, V>>]>(&node.next)
+                + node.entry.heap_size_of_children();
+
+            current = node.next[0].load(Ordering::Acquire);
+        }
+
+        size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, thread};
+
+    use super::ConcurrentSkipMap;
+
+    #[test]
+    fn test_threads() {
+        let map = ConcurrentSkipMap::new();
+        let (threads, keys) = (4, 4_000);
+
+        thread::scope(|scope| {
+            for thread in 0..threads {
+                let map = &map;
+
+                scope.spawn(move || {
+                    // Keys of different threads are interleaved, so they
+                    // keep changing the links of the same nodes
+                    for key in (thread..keys).step_by(threads) {
+                        assert!(map.insert(key, key * 10));
+                        assert!(!map.insert(key, 0));
+                    }
+
+                    for key in (thread..keys).step_by(threads).filter(|key| key % 2 == 0) {
+                        assert!(map.remove(&key));
+                        assert!(!map.remove(&key));
+                    }
+                });
+            }
+
+            // Readers see sorted keys while the writers run
+            let map = &map;
+            scope.spawn(move || {
+                for _ in 0..20 {
+                    let keys: Vec<usize> = map.iter().map(|(key, _)| key).collect();
+                    assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+                }
+            });
+        });
+
+        let expected: BTreeMap<usize, usize> =
+            (1..keys).step_by(2).map(|key| (key, key * 10)).collect();
+
+        assert_eq!(map.len(), expected.len());
+        assert!(map.iter().eq(expected.into_iter()));
+        assert!(map.contains_key(&1));
+        assert!(!map.contains_key(&0));
+    }
+
+    #[test]
+    fn test_reinsert() {
+        let map = ConcurrentSkipMap::new();
+
+        for round in 0..10 {
+            for key in 0..100 {
+                assert!(map.insert(key, round));
+            }
+
+            assert_eq!(map.get(&50, |value| *value), Some(round));
+
+            for key in 0..100 {
+                assert!(map.remove(&key));
+            }
+
+            assert!(map.is_empty());
+        }
+
+        // Every removed node was freed when its operation ended
+        assert!(map
+            .retired
+            .load(std::sync::atomic::Ordering::Relaxed)
+            .is_null());
+        assert_eq!(map.iter().count(), 0);
+    }
+}
@@ -0,0 +1,319 @@
+use std::{
+    cmp::Reverse,
+    hash::Hash,
+    ops::{Add, Sub},
+};
+
+use crate::{
+    heap_size::HeapSize,
+    index_map::{self, IndexMap},
+};
+
+/// Multiset that counts how many times each item has been added, for
+/// frequency analysis like counting words or events. Counts are stored in an
+/// [`IndexMap`], so items are iterated in the order they were first added and
+/// ties in [`Counter::most_common`] are broken the same way.
+///
+/// Items whose count drops to zero are removed, so [`Counter::len`] is always
+/// the number of distinct items with a positive count. Removing an item moves
+/// the last one to its position, like [`IndexMap::swap_remove`].
+pub struct Counter<T> {
+    counts: IndexMap<T, usize>,
+    total: usize,
+}
+
+impl<T> Counter<T> {
+    /// Creates an empty counter.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Counter;
+    ///
+    /// let counter: Counter<&str> = Counter::new();
+    ///
+    /// assert!(counter.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            counts: IndexMap::new(),
+            total: 0,
+        }
+    }
+
+    /// Returns the number of distinct items.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Returns `true` if no item has been counted.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Returns the sum of the counts of every item.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Removes all the items.
+    pub fn clear(&mut self) {
+        self.counts.clear();
+        self.total = 0;
+    }
+
+    /// Returns an iterator over the items and their counts in the order the
+    /// items were first added.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(self.counts.iter())
+    }
+}
+
+impl<T: Hash + Eq> Counter<T> {
+    /// Counts one more occurrence of `item` and returns its new count.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Counter;
+    ///
+    /// let mut counter = Counter::new();
+    ///
+    /// assert_eq!(counter.add("a"), 1);
+    /// assert_eq!(counter.add("a"), 2);
+    /// assert_eq!(counter.count(&"a"), 2);
+    /// ```
+    pub fn add(&mut self, item: T) -> usize {
+        self.add_n(item, 1)
+    }
+
+    /// Counts `n` more occurrences of `item` and returns its new count.
+    /// Adding zero occurrences of a new item doesn't insert it.
+    pub fn add_n(&mut self, item: T, n: usize) -> usize {
+        self.total += n;
+
+        if let Some(count) = self.counts.get_mut(&item) {
+            *count += n;
+            return *count;
+        }
+
+        if n > 0 {
+            self.counts.insert(item, n);
+        }
+
+        n
+    }
+
+    /// Counts `n` fewer occurrences of `item`, down to zero, and returns its
+    /// new count. The item is removed when it reaches zero.
+    pub fn remove_n(&mut self, item: &T, n: usize) -> usize {
+        let Some(count) = self.counts.get_mut(item) else {
+            return 0;
+        };
+
+        let removed = n.min(*count);
+        *count -= removed;
+        self.total -= removed;
+
+        let count = *count;
+
+        if count == 0 {
+            self.counts.swap_remove(item);
+        }
+
+        count
+    }
+
+    /// Returns the number of occurrences of `item`, zero if it was never
+    /// added.
+    pub fn count(&self, item: &T) -> usize {
+        self.counts.get(item).copied().unwrap_or(0)
+    }
+
+    /// Returns the `n` items with the greatest counts in descending order of
+    /// count, or all of them if there are fewer than `n`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Counter;
+    ///
+    /// let counter: Counter<char> = "mississippi".chars().collect();
+    ///
+    /// assert_eq!(counter.most_common(2), vec![(&'i', 4), (&'s', 4)]);
+    /// assert_eq!(counter.most_common(10).len(), 4);
+    /// ```
+    pub fn most_common(&self, n: usize) -> Vec<(&T, usize)> {
+        let mut items: Vec<(&T, usize)> = self.iter().collect();
+
+        // Stable, so ties keep insertion order
+        items.sort_by_key(|&(_, count)| Reverse(count));
+        items.truncate(n);
+
+        items
+    }
+}
+
+impl<T> Default for Counter<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Hash + Eq> Extend<T> for Counter<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.add(item);
+        }
+    }
+}
+
+impl<T: Hash + Eq> FromIterator<T> for Counter<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut counter = Self::new();
+        counter.extend(iter);
+
+        counter
+    }
+}
+
+/// Adds the counts of both counters.
+///
+/// # Examples
+///
+/// ```rust
+/// use collections_rust::Counter;
+///
+/// let monday: Counter<&str> = ["login", "login", "error"].into_iter().collect();
+/// let tuesday: Counter<&str> = ["login", "logout"].into_iter().collect();
+///
+/// let week = monday + tuesday;
+///
+/// assert_eq!(week.count(&"login"), 3);
+/// assert_eq!(week.count(&"logout"), 1);
+/// assert_eq!(week.total(), 5);
+/// ```
+impl<T: Hash + Eq> Add for Counter<T> {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        for (item, count) in other.counts {
+            self.add_n(item, count);
+        }
+
+        self
+    }
+}
+
+/// Subtracts the counts of `other`, keeping only the items whose count is
+/// still positive.
+///
+/// # Examples
+///
+/// ```rust
+/// use collections_rust::Counter;
+///
+/// let stock: Counter<&str> = ["apple", "apple", "pear"].into_iter().collect();
+/// let sold: Counter<&str> = ["apple", "pear", "pear"].into_iter().collect();
+///
+/// let left = stock - sold;
+///
+/// assert_eq!(left.count(&"apple"), 1);
+/// assert_eq!(left.count(&"pear"), 0);
+/// assert_eq!(left.len(), 1);
+/// ```
+impl<T: Hash + Eq> Sub for Counter<T> {
+    type Output = Self;
+
+    fn sub(mut self, other: Self) -> Self {
+        for (item, count) in other.iter() {
+            self.remove_n(item, count);
+        }
+
+        self
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Counter<T> {
+    type Item = (&'a T, usize);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the items of a [`Counter`] and their counts.
+pub struct Iter<'a, T>(index_map::Iter<'a, T, usize>);
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (&'a T, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(item, count)| (item, *count))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Counter<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.counts.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Counter;
+
+    #[test]
+    fn test_counts() {
+        let text = "the quick brown fox jumps over the lazy dog the end";
+        let mut counter: Counter<&str> = text.split(' ').collect();
+
+        assert_eq!(counter.total(), 11);
+        assert_eq!(counter.len(), 9);
+        assert_eq!(counter.count(&"the"), 3);
+        assert_eq!(counter.count(&"cat"), 0);
+        assert_eq!(counter.most_common(1), vec![(&"the", 3)]);
+        assert_eq!(
+            counter.most_common(3),
+            vec![(&"the", 3), (&"quick", 1), (&"brown", 1)]
+        );
+
+        assert_eq!(counter.remove_n(&"the", 2), 1);
+        assert_eq!(counter.remove_n(&"fox", 5), 0);
+        assert_eq!(counter.remove_n(&"cat", 1), 0);
+        assert_eq!(counter.total(), 8);
+        assert_eq!(counter.len(), 8);
+
+        assert_eq!(counter.add_n("cat", 0), 0);
+        assert_eq!(counter.len(), 8);
+
+        counter.clear();
+        assert!(counter.is_empty());
+        assert_eq!(counter.total(), 0);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a: Counter<u32> = [1, 1, 2, 3, 3, 3].into_iter().collect();
+        let b: Counter<u32> = [1, 3, 4, 4].into_iter().collect();
+
+        let sum = a + b;
+        assert_eq!(sum.total(), 10);
+        assert_eq!(
+            sum.iter().collect::<Vec<_>>(),
+            vec![(&1, 3), (&2, 1), (&3, 4), (&4, 2)]
+        );
+
+        let difference = sum - [3, 3, 3, 3, 3, 4, 4].into_iter().collect();
+        assert_eq!(difference.total(), 4);
+        assert_eq!(difference.count(&3), 0);
+        assert_eq!(difference.count(&4), 0);
+        assert_eq!(difference.count(&1), 3);
+        assert_eq!(difference.len(), 2);
+    }
+}
@@ -0,0 +1,220 @@
+use std::{fmt, ops::Deref, rc::Rc};
+
+use crate::{
+    heap_size::{self, HeapSize},
+    vector::Vector,
+};
+
+/// [`Vector`] whose clones share the same buffer until one of them is
+/// modified. Cloning only bumps a reference count, and the first mutation
+/// through [`CowVector::make_mut`] copies the values if the buffer is still
+/// shared, so readers that never modify their clone never pay for a copy.
+///
+/// # Examples
+///
+/// ```rust
+/// use collections_rust::CowVector;
+///
+/// let original: CowVector<_> = (1..=3).collect();
+/// let mut copy = original.clone();
+///
+/// assert!(copy.ptr_eq(&original));
+///
+/// copy.push(4);
+///
+/// assert!(!copy.ptr_eq(&original));
+/// assert_eq!(*original, [1, 2, 3]);
+/// assert_eq!(*copy, [1, 2, 3, 4]);
+/// ```
+pub struct CowVector<T> {
+    inner: Rc<Vector<T>>,
+}
+
+impl<T> CowVector<T> {
+    /// Creates an empty vector.
+    pub fn new() -> Self {
+        Self::from(Vector::new())
+    }
+
+    /// Returns `true` if other clones are sharing the buffer, which means the
+    /// next call to [`CowVector::make_mut`] will copy it.
+    pub fn is_shared(&self) -> bool {
+        Rc::strong_count(&self.inner) > 1
+    }
+
+    /// Returns `true` if both vectors share the same buffer, which means
+    /// they contain the same values and neither has been modified since one
+    /// was cloned from the other.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl<T: Clone> CowVector<T> {
+    /// Returns the underlying vector to modify it, copying its values first
+    /// if the buffer is shared with other clones. Clones that keep sharing
+    /// the old buffer are not affected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::CowVector;
+    ///
+    /// let mut prices: CowVector<u32> = [100, 250].into_iter().collect();
+    /// let snapshot = prices.clone();
+    ///
+    /// prices.make_mut().iter_mut().for_each(|price| *price *= 2);
+    ///
+    /// assert_eq!(*prices, [200, 500]);
+    /// assert_eq!(*snapshot, [100, 250]);
+    /// assert!(!snapshot.is_shared());
+    /// ```
+    pub fn make_mut(&mut self) -> &mut Vector<T> {
+        Rc::make_mut(&mut self.inner)
+    }
+
+    /// Adds `value` at the end, see [`CowVector::make_mut`].
+    pub fn push(&mut self, value: T) {
+        self.make_mut().push(value);
+    }
+
+    /// Removes the last value and returns it, see [`CowVector::make_mut`].
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        self.make_mut().pop()
+    }
+
+    /// Returns the underlying vector, copying its values if the buffer is
+    /// shared.
+    pub fn into_vector(self) -> Vector<T> {
+        Rc::try_unwrap(self.inner).unwrap_or_else(|inner| (*inner).clone())
+    }
+}
+
+impl<T> Clone for CowVector<T> {
+    /// Shares the buffer with the new vector, without copying any value.
+    fn clone(&self) -> Self {
+        Self {
+            inner: Rc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T> Default for CowVector<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Deref for CowVector<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.inner
+    }
+}
+
+impl<T> From<Vector<T>> for CowVector<T> {
+    fn from(vector: Vector<T>) -> Self {
+        Self {
+            inner: Rc::new(vector),
+        }
+    }
+}
+
+impl<T> FromIterator<T> for CowVector<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vector = Vector::new();
+        vector.extend(iter);
+
+        Self::from(vector)
+    }
+}
+
+impl<T: PartialEq> PartialEq for CowVector<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || **self == **other
+    }
+}
+
+impl<T: Eq> Eq for CowVector<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for CowVector<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// A buffer shared with other clones is counted by every one of them.
+impl<T: HeapSize> HeapSize for CowVector<T> {
+    fn heap_size_of_children(&self) -> usize {
+        heap_size::rc_allocation::<Vector<T>>() + self.inner.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::CowVector;
+
+    /// Counts how many times it's cloned.
+    struct Counted<'a>(u32, &'a Cell<usize>);
+
+    impl Clone for Counted<'_> {
+        fn clone(&self) -> Self {
+            self.1.set(self.1.get() + 1);
+            Counted(self.0, self.1)
+        }
+    }
+
+    #[test]
+    fn test_copy_on_write() {
+        let clones = Cell::new(0);
+        let mut original: CowVector<_> = (0..10).map(|value| Counted(value, &clones)).collect();
+        let readers: Vec<_> = (0..5).map(|_| original.clone()).collect();
+
+        assert_eq!(clones.get(), 0);
+        assert!(readers.iter().all(|reader| reader.ptr_eq(&original)));
+
+        // Only the first mutation of a shared buffer copies it
+        original.push(Counted(10, &clones));
+        original.make_mut()[0].0 = 100;
+        assert_eq!(original.pop().map(|value| value.0), Some(10));
+        assert_eq!(clones.get(), 10);
+
+        assert!(!original.is_shared());
+        assert!(readers.iter().all(|reader| reader[0].0 == 0));
+        assert_eq!(original[0].0, 100);
+
+        // The last reader owns the buffer, so it doesn't copy it either
+        let mut readers = readers.into_iter();
+        let last = readers.next_back().unwrap();
+        drop(readers);
+
+        assert!(!last.is_shared());
+        assert_eq!(last.into_vector().len(), 10);
+        assert_eq!(clones.get(), 10);
+    }
+
+    #[test]
+    fn test_equality() {
+        let mut a: CowVector<_> = (0..3).collect();
+        let b = a.clone();
+
+        assert_eq!(a, b);
+        assert_eq!(format!("{b:?}"), "[0, 1, 2]");
+
+        a.push(3);
+        assert_ne!(a, b);
+
+        assert_eq!(a.pop(), Some(3));
+        assert_eq!(a, b);
+        assert!(!a.ptr_eq(&b));
+
+        assert_eq!(CowVector::<i32>::new().pop(), None);
+    }
+}
@@ -0,0 +1,375 @@
+use std::{
+    hash::{BuildHasher, Hash, RandomState},
+    marker::PhantomData,
+};
+
+use crate::{heap_size::HeapSize, vector::Vector};
+
+/// Number of fingerprints that fit in a bucket.
+const BUCKET_SIZE: usize = 4;
+
+/// Maximum number of fingerprints relocated by a single insertion before the
+/// filter is considered full.
+const MAX_KICKS: usize = 500;
+
+/// Fingerprint of a value. Zero marks an empty slot, so it's never used.
+type Fingerprint = u16;
+
+/// Probabilistic set that answers whether a value might have been inserted,
+/// with no false negatives and a small rate of false positives (about 0.01%
+/// with the 16-bit fingerprints used here). Unlike a Bloom filter, values can
+/// also be removed.
+///
+/// Only a fingerprint of every value is stored, in one of two candidate
+/// buckets. The second bucket can be computed from the first one and the
+/// fingerprint alone, which lets insertions make room by moving fingerprints
+/// to their alternate bucket, the same way cuckoos push eggs out of nests.
+pub struct CuckooFilter<T: ?Sized, S = RandomState> {
+    buckets: Vector<[Fingerprint; BUCKET_SIZE]>,
+    /// Fingerprint that couldn't be placed after [`MAX_KICKS`] relocations,
+    /// along with one of its buckets. While it's occupied the filter is full.
+    victim: Option<(usize, Fingerprint)>,
+    len: usize,
+    hasher: S,
+    /// State of the xorshift generator used to pick fingerprints to relocate.
+    seed: u64,
+    marker: PhantomData<fn(&T)>,
+}
+
+impl<T: ?Sized> CuckooFilter<T> {
+    /// Creates an empty filter with room for at least `capacity` values.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::CuckooFilter;
+    ///
+    /// let filter: CuckooFilter<str> = CuckooFilter::with_capacity(1000);
+    ///
+    /// assert!(filter.is_empty());
+    /// assert!(filter.capacity() >= 1000);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<T: ?Sized, S> CuckooFilter<T, S> {
+    /// Creates an empty filter with room for at least `capacity` values that
+    /// uses `hasher` to hash them.
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        // Insertions start failing at around 95% load, leave some slack
+        let buckets = (capacity + capacity / 8)
+            .div_ceil(BUCKET_SIZE)
+            .next_power_of_two();
+
+        let mut filter = Self {
+            buckets: Vector::new(),
+            victim: None,
+            len: 0,
+            hasher,
+            seed: 0x2545_F491_4F6C_DD1D,
+            marker: PhantomData,
+        };
+
+        for _ in 0..buckets {
+            filter.buckets.push([0; BUCKET_SIZE]);
+        }
+
+        filter
+    }
+
+    /// Returns the number of values in the filter.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the filter contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of fingerprint slots.
+    pub fn capacity(&self) -> usize {
+        self.buckets.len() * BUCKET_SIZE
+    }
+
+    /// Returns the fraction of slots in use, between 0 and 1.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::CuckooFilter;
+    ///
+    /// let mut filter = CuckooFilter::with_capacity(100);
+    ///
+    /// for value in 0..50 {
+    ///     filter.insert(&value);
+    /// }
+    ///
+    /// assert_eq!(filter.load_factor(), 50.0 / filter.capacity() as f64);
+    /// ```
+    pub fn load_factor(&self) -> f64 {
+        self.len as f64 / self.capacity() as f64
+    }
+
+    /// Removes all the values from the filter.
+    pub fn clear(&mut self) {
+        for bucket in self.buckets.iter_mut() {
+            *bucket = [0; BUCKET_SIZE];
+        }
+
+        self.victim = None;
+        self.len = 0;
+    }
+
+    /// Returns the bit mask that turns hashes into bucket indices.
+    fn mask(&self) -> usize {
+        self.buckets.len() - 1
+    }
+
+    /// Returns the other bucket where `fingerprint` can be stored. Applying
+    /// it twice gives back `bucket`.
+    fn alternate(&self, bucket: usize, fingerprint: Fingerprint) -> usize {
+        let hash = (fingerprint as u64).wrapping_mul(0x5BD1_E995);
+
+        (bucket ^ hash as usize) & self.mask()
+    }
+
+    /// Returns `true` if `bucket` holds `fingerprint`.
+    fn bucket_contains(&self, bucket: usize, fingerprint: Fingerprint) -> bool {
+        self.buckets[bucket].contains(&fingerprint)
+    }
+
+    /// Stores `fingerprint` in an empty slot of `bucket` if there's one.
+    fn try_place(&mut self, bucket: usize, fingerprint: Fingerprint) -> bool {
+        match self.buckets[bucket].iter_mut().find(|slot| **slot == 0) {
+            Some(slot) => {
+                *slot = fingerprint;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the next random number.
+    fn next_random(&mut self) -> u64 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+
+        self.seed
+    }
+}
+
+impl<T: Hash + ?Sized, S: BuildHasher> CuckooFilter<T, S> {
+    /// Returns the fingerprint of `value` and its first bucket.
+    fn locate(&self, value: &T) -> (usize, Fingerprint) {
+        let hash = self.hasher.hash_one(value);
+        let fingerprint = ((hash >> 48) as Fingerprint).max(1);
+
+        (hash as usize & self.mask(), fingerprint)
+    }
+
+    /// Returns `true` if `value` might be in the filter, or `false` if it's
+    /// definitely not.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::CuckooFilter;
+    ///
+    /// let mut filter = CuckooFilter::with_capacity(100);
+    /// filter.insert("cached");
+    ///
+    /// assert!(filter.contains("cached"));
+    /// ```
+    pub fn contains(&self, value: &T) -> bool {
+        let (bucket, fingerprint) = self.locate(value);
+        let alternate = self.alternate(bucket, fingerprint);
+
+        self.bucket_contains(bucket, fingerprint)
+            || self.bucket_contains(alternate, fingerprint)
+            || self.victim.is_some_and(|(victim, victim_fingerprint)| {
+                victim_fingerprint == fingerprint && (victim == bucket || victim == alternate)
+            })
+    }
+
+    /// Inserts `value` and returns `true`, or returns `false` without
+    /// modifying the filter if it's full. Inserting the same value twice
+    /// stores it twice, so it must be removed twice as well.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::CuckooFilter;
+    ///
+    /// let mut filter = CuckooFilter::with_capacity(10);
+    ///
+    /// while filter.insert(&filter.len()) {}
+    ///
+    /// assert!(filter.load_factor() > 0.5);
+    /// ```
+    pub fn insert(&mut self, value: &T) -> bool {
+        if self.victim.is_some() {
+            return false;
+        }
+
+        let (bucket, mut fingerprint) = self.locate(value);
+        let alternate = self.alternate(bucket, fingerprint);
+
+        self.len += 1;
+
+        if self.try_place(bucket, fingerprint) || self.try_place(alternate, fingerprint) {
+            return true;
+        }
+
+        let mut bucket = if self.next_random() & 1 == 0 {
+            bucket
+        } else {
+            alternate
+        };
+
+        for _ in 0..MAX_KICKS {
+            let slot = self.next_random() as usize % BUCKET_SIZE;
+            std::mem::swap(&mut fingerprint, &mut self.buckets[bucket][slot]);
+
+            bucket = self.alternate(bucket, fingerprint);
+
+            if self.try_place(bucket, fingerprint) {
+                return true;
+            }
+        }
+
+        // The value itself is stored by now, only some other fingerprint is
+        // left without a slot.
+        self.victim = Some((bucket, fingerprint));
+
+        true
+    }
+
+    /// Removes one copy of `value` and returns `true` if it might have been
+    /// in the filter. Removing a value that was never inserted can remove
+    /// another value with the same fingerprint, so only remove values that
+    /// are known to be present.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::CuckooFilter;
+    ///
+    /// let mut filter = CuckooFilter::with_capacity(100);
+    /// filter.insert("evicted");
+    ///
+    /// assert!(filter.remove("evicted"));
+    /// assert!(!filter.contains("evicted"));
+    /// ```
+    pub fn remove(&mut self, value: &T) -> bool {
+        let (bucket, fingerprint) = self.locate(value);
+        let alternate = self.alternate(bucket, fingerprint);
+
+        if let Some((victim, victim_fingerprint)) = self.victim {
+            if victim_fingerprint == fingerprint && (victim == bucket || victim == alternate) {
+                self.victim = None;
+                self.len -= 1;
+
+                return true;
+            }
+        }
+
+        for index in [bucket, alternate] {
+            if let Some(slot) = self.buckets[index]
+                .iter_mut()
+                .find(|slot| **slot == fingerprint)
+            {
+                *slot = 0;
+                self.len -= 1;
+
+                // There might be room now for the victim
+                if let Some((victim, victim_fingerprint)) = self.victim.take() {
+                    self.reinsert(victim, victim_fingerprint);
+                }
+
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Places the fingerprint of the victim, which belongs in `bucket` or its
+    /// alternate, back in a bucket, or keeps it as the victim if both are
+    /// still full.
+    fn reinsert(&mut self, bucket: usize, fingerprint: Fingerprint) {
+        let alternate = self.alternate(bucket, fingerprint);
+
+        if !self.try_place(bucket, fingerprint) && !self.try_place(alternate, fingerprint) {
+            self.victim = Some((bucket, fingerprint));
+        }
+    }
+}
+
+impl<T: ?Sized, S> HeapSize for CuckooFilter<T, S> {
+    fn heap_size_of_children(&self) -> usize {
+        self.buckets.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CuckooFilter;
+
+    #[test]
+    fn test_insert_contains_remove() {
+        let mut filter = CuckooFilter::with_capacity(10_000);
+
+        for value in 0..10_000 {
+            assert!(filter.insert(&value));
+        }
+
+        assert_eq!(filter.len(), 10_000);
+        assert!((0..10_000).all(|value| filter.contains(&value)));
+
+        let false_positives = (10_000..20_000)
+            .filter(|value| filter.contains(value))
+            .count();
+        assert!(false_positives < 20);
+
+        for value in (0..10_000).step_by(2) {
+            assert!(filter.remove(&value));
+        }
+
+        assert_eq!(filter.len(), 5_000);
+        assert!((1..10_000).step_by(2).all(|value| filter.contains(&value)));
+
+        let still_present = (0..10_000)
+            .step_by(2)
+            .filter(|value| filter.contains(value))
+            .count();
+        assert!(still_present < 20);
+
+        filter.clear();
+        assert!(filter.is_empty());
+        assert!(!filter.contains(&1));
+    }
+
+    #[test]
+    fn test_full() {
+        let mut filter = CuckooFilter::with_capacity(100);
+        let mut inserted = 0;
+
+        while filter.insert(&inserted) {
+            inserted += 1;
+        }
+
+        // No false negatives even with the victim slot in use
+        assert_eq!(filter.len(), inserted);
+        assert!(filter.load_factor() > 0.9);
+        assert!((0..inserted).all(|value| filter.contains(&value)));
+
+        // Removing any value makes room for the victim
+        assert!(filter.remove(&0));
+        assert_eq!(filter.len(), inserted - 1);
+        assert!((1..inserted).all(|value| filter.contains(&value)));
+    }
+}
@@ -1,4 +1,24 @@
-use std::{fmt::Debug, hash::Hash, marker::PhantomData, ptr::NonNull};
+use std::{
+    convert::Infallible,
+    fmt::{self, Debug},
+    hash::Hash,
+    io,
+    marker::PhantomData,
+    mem,
+    ptr::{self, NonNull},
+    sync::Arc,
+};
+
+#[cfg(feature = "stats")]
+use crate::stats::Stats;
+use crate::{
+    allocator::{self, Allocator, Global},
+    drop_guard,
+    heap_size::{self, HeapSize},
+    stats::Counters,
+    traits,
+    try_reserve_error::TryReserveError,
+};
 
 struct Node<T> {
     next: Link<T>,
@@ -8,13 +28,42 @@ struct Node<T> {
 
 type Link<T> = Option<NonNull<Node<T>>>;
 
-pub struct Dequeue<T> {
+/// Creates a [`Dequeue`] containing the arguments, from front to back.
+///
+/// # Examples
+///
+/// ```rust
+/// use collections_rust::dequeue;
+///
+/// let mut dequeue = dequeue![1, 2, 3];
+///
+/// assert_eq!(dequeue.pop_front(), Some(1));
+/// assert_eq!(dequeue.pop_back(), Some(3));
+/// ```
+#[macro_export]
+macro_rules! dequeue {
+    ($($value:expr),* $(,)?) => {{
+        // Not mutated when the macro is called without values
+        #[allow(unused_mut)]
+        let mut dequeue = $crate::Dequeue::new();
+        $(dequeue.push_back($value);)*
+        dequeue
+    }};
+}
+
+pub struct Dequeue<T, A: Allocator = Global> {
     head: Link<T>,
     tail: Link<T>,
     len: usize,
+    alloc: A,
+    stats: Counters,
     marker: PhantomData<T>,
 }
 
+// The dequeue owns its nodes, so it can move to another thread with its
+// values, for example out of a `static Mutex<Dequeue<T>>`.
+unsafe impl<T: Send, A: Allocator + Send> Send for Dequeue<T, A> {}
+
 pub struct Iter<'a, T> {
     head: Link<T>,
     tail: Link<T>,
@@ -29,30 +78,82 @@ pub struct IterMut<'a, T> {
     marker: PhantomData<&'a mut T>,
 }
 
-pub struct IntoIter<T>(Dequeue<T>);
+pub struct IntoIter<T, A: Allocator = Global>(Dequeue<T, A>);
 
-pub struct CursorMut<'a, T> {
+pub struct CursorMut<'a, T, A: Allocator = Global> {
     current: Link<T>,
-    dequeue: &'a mut Dequeue<T>,
+    dequeue: &'a mut Dequeue<T, A>,
     index: Option<usize>,
 }
 
 impl<T> Node<T> {
-    unsafe fn new_non_null(value: T, next: Link<T>, prev: Link<T>) -> NonNull<Node<T>> {
-        NonNull::new_unchecked(Box::into_raw(Box::new(Node { next, prev, value })))
+    fn detached(value: T) -> Self {
+        Node {
+            next: None,
+            prev: None,
+            value,
+        }
     }
 }
 
 impl<T> Dequeue<T> {
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    /// Swaps the nodes of two dequeues, leaving their counters alone.
+    fn swap_nodes(&mut self, other: &mut Self) {
+        mem::swap(&mut self.head, &mut other.head);
+        mem::swap(&mut self.tail, &mut other.tail);
+        mem::swap(&mut self.len, &mut other.len);
+
+        self.stats.record_len(self.len);
+        other.stats.record_len(other.len);
+    }
+}
+
+impl<T, A: Allocator> Dequeue<T, A> {
+    /// Creates an empty dequeue whose nodes are allocated by `alloc`. See
+    /// [`Allocator`] for an example. Cursors of these dequeues can't split
+    /// or splice, since nodes can't move between allocators.
+    pub const fn new_in(alloc: A) -> Self {
         Self {
             head: None,
             tail: None,
             len: 0,
+            alloc,
+            stats: Counters::new(),
             marker: PhantomData,
         }
     }
 
+    /// Returns the allocator of the dequeue.
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    /// Returns the counters of the dequeue, see [`Stats`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::{traits::List, Dequeue};
+    ///
+    /// let mut dequeue: Dequeue<_> = (0..10).collect();
+    /// dequeue.pop_front();
+    ///
+    /// // Reaching the value at index 5 follows 5 links from the front
+    /// assert_eq!(List::get(&dequeue, 5), Some(&6));
+    ///
+    /// let stats = dequeue.stats();
+    /// assert_eq!((stats.allocations, stats.peak_len), (10, 10));
+    /// assert_eq!(stats.pointer_walks, 5);
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+
     pub fn len(&self) -> usize {
         self.len
     }
@@ -65,41 +166,104 @@ impl<T> Dequeue<T> {
         while self.pop_front().is_some() {}
     }
 
-    pub fn push_front(&mut self, value: T) {
-        unsafe {
-            let node = Node::new_non_null(value, None, None);
+    /// Allocates a detached node for `value`.
+    fn new_node(&self, value: T) -> NonNull<Node<T>> {
+        self.stats.record_allocation();
 
-            if let Some(old_head) = self.head {
-                (*old_head.as_ptr()).prev = Some(node);
-                (*node.as_ptr()).next = Some(old_head);
-            } else {
-                self.tail = Some(node);
-            }
+        allocator::new_in(Node::detached(value), &self.alloc)
+    }
 
-            self.head = Some(node);
-            self.len += 1;
-        }
+    /// Like `new_node`, but returns an error if there's no memory.
+    fn try_new_node(&self, value: T) -> Result<NonNull<Node<T>>, TryReserveError> {
+        let node = allocator::try_new_in(Node::detached(value), &self.alloc)?;
+        self.stats.record_allocation();
+
+        Ok(node)
+    }
+
+    pub fn push_front(&mut self, value: T) {
+        let node = self.new_node(value);
+
+        unsafe { self.link_front(node) }
     }
 
     pub fn push_back(&mut self, value: T) {
-        unsafe {
-            let node = Node::new_non_null(value, None, None);
+        let node = self.new_node(value);
 
-            if let Some(old_tail) = self.tail {
-                (*old_tail.as_ptr()).next = Some(node);
-                (*node.as_ptr()).prev = Some(old_tail);
-            } else {
-                self.head = Some(node);
-            }
+        unsafe { self.link_back(node) }
+    }
+
+    /// Like [`Dequeue::push_front`], but returns an error instead of aborting
+    /// if the node can't be allocated. `value` is dropped in that case.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryReserveError`] if the allocator fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Dequeue;
+    ///
+    /// let mut dequeue = Dequeue::new();
+    ///
+    /// assert_eq!(dequeue.try_push_front(1), Ok(()));
+    /// assert_eq!(dequeue.try_push_back(2), Ok(()));
+    /// assert_eq!(dequeue.front(), Some(&1));
+    /// ```
+    pub fn try_push_front(&mut self, value: T) -> Result<(), TryReserveError> {
+        let node = self.try_new_node(value)?;
 
+        unsafe { self.link_front(node) };
+
+        Ok(())
+    }
+
+    /// Like [`Dequeue::push_back`], but returns an error instead of aborting
+    /// if the node can't be allocated. `value` is dropped in that case.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryReserveError`] if the allocator fails.
+    pub fn try_push_back(&mut self, value: T) -> Result<(), TryReserveError> {
+        let node = self.try_new_node(value)?;
+
+        unsafe { self.link_back(node) };
+
+        Ok(())
+    }
+
+    /// Makes the detached `node` the new head.
+    unsafe fn link_front(&mut self, node: NonNull<Node<T>>) {
+        if let Some(old_head) = self.head {
+            (*old_head.as_ptr()).prev = Some(node);
+            (*node.as_ptr()).next = Some(old_head);
+        } else {
             self.tail = Some(node);
-            self.len += 1;
         }
+
+        self.head = Some(node);
+        self.len += 1;
+        self.stats.record_len(self.len);
+    }
+
+    /// Makes the detached `node` the new tail.
+    unsafe fn link_back(&mut self, node: NonNull<Node<T>>) {
+        if let Some(old_tail) = self.tail {
+            (*old_tail.as_ptr()).next = Some(node);
+            (*node.as_ptr()).prev = Some(old_tail);
+        } else {
+            self.head = Some(node);
+        }
+
+        self.tail = Some(node);
+        self.len += 1;
+        self.stats.record_len(self.len);
     }
 
     pub fn pop_front(&mut self) -> Option<T> {
         self.head.map(|node| unsafe {
-            let current_head = Box::from_raw(node.as_ptr());
+            let current_head = allocator::take_in(node, &self.alloc);
             let value = current_head.value;
 
             self.head = current_head.next;
@@ -118,7 +282,7 @@ impl<T> Dequeue<T> {
 
     pub fn pop_back(&mut self) -> Option<T> {
         self.tail.map(|node| unsafe {
-            let current_tail = Box::from_raw(node.as_ptr());
+            let current_tail = allocator::take_in(node, &self.alloc);
             let value = current_tail.value;
 
             self.tail = current_tail.prev;
@@ -151,7 +315,7 @@ impl<T> Dequeue<T> {
         unsafe { self.tail.map(|node| &mut (*node.as_ptr()).value) }
     }
 
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter {
             head: self.head,
             tail: self.tail,
@@ -160,7 +324,7 @@ impl<T> Dequeue<T> {
         }
     }
 
-    pub fn iter_mut(&self) -> IterMut<T> {
+    pub fn iter_mut(&self) -> IterMut<'_, T> {
         IterMut {
             head: self.head,
             tail: self.tail,
@@ -169,22 +333,241 @@ impl<T> Dequeue<T> {
         }
     }
 
-    pub fn cursor_mut(&mut self) -> CursorMut<T> {
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T, A> {
         CursorMut {
             dequeue: self,
             current: None,
             index: None,
         }
     }
+
+    /// Retains only the values for which `keep` returns `true`, in their
+    /// original order.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut keep: F) {
+        let Ok(()) = self.try_retain(|value| Ok::<_, Infallible>(keep(value)));
+    }
+
+    /// Like [`Dequeue::retain`], but stops at the first error returned by
+    /// `keep`. The values checked before it are removed or kept as `keep`
+    /// said, the value that failed and the ones after it are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by `keep`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Dequeue;
+    ///
+    /// let mut dequeue: Dequeue<_> = (1..=5).collect();
+    ///
+    /// let result = dequeue.try_retain(|&value| match value {
+    ///     4 => Err("too big"),
+    ///     _ => Ok(value % 2 == 1),
+    /// });
+    ///
+    /// assert_eq!(result, Err("too big"));
+    /// assert!(dequeue.iter().eq(&[1, 3, 4, 5]));
+    /// ```
+    pub fn try_retain<E, F>(&mut self, mut keep: F) -> Result<(), E>
+    where
+        F: FnMut(&T) -> Result<bool, E>,
+    {
+        let mut cursor = self.cursor_mut();
+        cursor.move_next();
+
+        while let Some(value) = cursor.current() {
+            if keep(value)? {
+                cursor.move_next();
+            } else {
+                cursor.remove_current();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Pushes the values of `iter` to the back until it yields an error. The
+    /// values before the error stay in the dequeue.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error yielded by `iter`.
+    pub fn try_extend<E, I>(&mut self, iter: I) -> Result<(), E>
+    where
+        I: IntoIterator<Item = Result<T, E>>,
+    {
+        for value in iter {
+            self.push_back(value?);
+        }
+
+        Ok(())
+    }
+
+    /// Calls `f` on every value from front to back until it returns an
+    /// error. The values before the error keep the changes made by `f`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by `f`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Dequeue;
+    ///
+    /// let mut dequeue: Dequeue<u8> = [100, 200, 50].into_iter().collect();
+    ///
+    /// let result = dequeue.try_for_each_mut(|value| {
+    ///     *value = value.checked_add(100).ok_or("overflow")?;
+    ///     Ok(())
+    /// });
+    ///
+    /// assert_eq!(result, Err("overflow"));
+    /// assert!(dequeue.iter().eq(&[200, 200, 50]));
+    /// ```
+    pub fn try_for_each_mut<E, F>(&mut self, f: F) -> Result<(), E>
+    where
+        F: FnMut(&mut T) -> Result<(), E>,
+    {
+        self.iter_mut().try_for_each(f)
+    }
+
+    /// Turns the dequeue into an immutable snapshot that any number of
+    /// threads can read at once. Cloning the snapshot shares the nodes
+    /// instead of copying them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::dequeue;
+    /// use std::thread;
+    ///
+    /// let frozen = dequeue!["a", "b", "c"].freeze();
+    ///
+    /// thread::scope(|s| {
+    ///     for _ in 0..4 {
+    ///         let frozen = frozen.clone();
+    ///         s.spawn(move || assert_eq!(frozen.back(), Some(&"c")));
+    ///     }
+    /// });
+    ///
+    /// let mut dequeue = frozen.thaw().unwrap();
+    /// dequeue.push_front("z");
+    /// ```
+    pub fn freeze(self) -> FrozenDeque<T, A> {
+        FrozenDeque {
+            inner: Arc::new(self),
+        }
+    }
+
+    /// Pushes `value` to the front and returns a handle to its node.
+    #[cfg(feature = "lru_cache")]
+    pub(crate) fn push_front_handle(&mut self, value: T) -> Handle<T> {
+        self.push_front(value);
+
+        Handle(self.head.unwrap())
+    }
+
+    /// Returns the value of the node pointed by `handle`.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must point to a node of this dequeue.
+    #[cfg(feature = "lru_cache")]
+    pub(crate) unsafe fn get_handle(&self, handle: Handle<T>) -> &T {
+        &(*handle.0.as_ptr()).value
+    }
+
+    /// Returns a mutable reference to the value of the node pointed by
+    /// `handle`.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must point to a node of this dequeue.
+    #[cfg(feature = "lru_cache")]
+    pub(crate) unsafe fn get_handle_mut(&mut self, handle: Handle<T>) -> &mut T {
+        &mut (*handle.0.as_ptr()).value
+    }
+
+    /// Detaches `node` from its neighbours without freeing it.
+    #[cfg(feature = "lru_cache")]
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) {
+        let prev = (*node.as_ptr()).prev.take();
+        let next = (*node.as_ptr()).next.take();
+
+        match prev {
+            Some(prev) => (*prev.as_ptr()).next = next,
+            None => self.head = next,
+        }
+
+        match next {
+            Some(next) => (*next.as_ptr()).prev = prev,
+            None => self.tail = prev,
+        }
+
+        self.len -= 1;
+    }
+
+    /// Moves the node pointed by `handle` to the front in O(1).
+    ///
+    /// # Safety
+    ///
+    /// `handle` must point to a node of this dequeue.
+    #[cfg(feature = "lru_cache")]
+    pub(crate) unsafe fn move_to_front(&mut self, handle: Handle<T>) {
+        let node = handle.0;
+
+        if self.head == Some(node) {
+            return;
+        }
+
+        self.unlink(node);
+
+        (*node.as_ptr()).next = self.head;
+        (*self.head.unwrap().as_ptr()).prev = Some(node);
+        self.head = Some(node);
+        self.len += 1;
+    }
+
+    /// Removes the node pointed by `handle` in O(1) and returns its value.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must point to a node of this dequeue, and it's no longer
+    /// valid afterwards.
+    #[cfg(feature = "lru_cache")]
+    pub(crate) unsafe fn remove_handle(&mut self, handle: Handle<T>) -> T {
+        self.unlink(handle.0);
+
+        allocator::take_in(handle.0, &self.alloc).value
+    }
 }
 
-impl<T> Drop for Dequeue<T> {
+/// Pointer to a node of a [`Dequeue`] that other collections of the crate
+/// can keep to access, move or remove that node in O(1). It's only valid
+/// while the node is in the dequeue that returned it.
+#[cfg(feature = "lru_cache")]
+pub(crate) struct Handle<T>(NonNull<Node<T>>);
+
+#[cfg(feature = "lru_cache")]
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+#[cfg(feature = "lru_cache")]
+impl<T> Copy for Handle<T> {}
+
+impl<T, A: Allocator> Drop for Dequeue<T, A> {
     fn drop(&mut self) {
-        while self.pop_front().is_some() {}
+        drop_guard::drop_each(&mut || self.pop_front());
     }
 }
 
-impl<'a, T> IntoIterator for &'a Dequeue<T> {
+impl<'a, T, A: Allocator> IntoIterator for &'a Dequeue<T, A> {
     type IntoIter = Iter<'a, T>;
     type Item = &'a T;
 
@@ -233,7 +616,7 @@ impl<'a, T> ExactSizeIterator for Iter<'a, T> {
     }
 }
 
-impl<'a, T> IntoIterator for &'a mut Dequeue<T> {
+impl<'a, T, A: Allocator> IntoIterator for &'a mut Dequeue<T, A> {
     type IntoIter = IterMut<'a, T>;
     type Item = &'a mut T;
 
@@ -282,8 +665,8 @@ impl<'a, T> ExactSizeIterator for IterMut<'a, T> {
     }
 }
 
-impl<T> IntoIterator for Dequeue<T> {
-    type IntoIter = IntoIter<T>;
+impl<T, A: Allocator> IntoIterator for Dequeue<T, A> {
+    type IntoIter = IntoIter<T, A>;
     type Item = T;
 
     fn into_iter(self) -> Self::IntoIter {
@@ -291,7 +674,7 @@ impl<T> IntoIterator for Dequeue<T> {
     }
 }
 
-impl<T> Iterator for IntoIter<T> {
+impl<T, A: Allocator> Iterator for IntoIter<T, A> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -303,13 +686,13 @@ impl<T> Iterator for IntoIter<T> {
     }
 }
 
-impl<T> DoubleEndedIterator for IntoIter<T> {
+impl<T, A: Allocator> DoubleEndedIterator for IntoIter<T, A> {
     fn next_back(&mut self) -> Option<Self::Item> {
         self.0.pop_back()
     }
 }
 
-impl<T> ExactSizeIterator for IntoIter<T> {
+impl<T, A: Allocator> ExactSizeIterator for IntoIter<T, A> {
     fn len(&self) -> usize {
         self.0.len
     }
@@ -321,6 +704,87 @@ impl<T> Default for Dequeue<T> {
     }
 }
 
+impl<T, A: Allocator> traits::Collection for Dequeue<T, A> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// Positional access walks the nodes from the front, so it's O(n).
+impl<T> traits::List<T> for Dequeue<T> {
+    fn get(&self, index: usize) -> Option<&T> {
+        self.stats.record_walk(index.min(self.len));
+        self.iter().nth(index)
+    }
+
+    fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len, "index out of bounds");
+        self.stats.record_walk(index);
+
+        if index == self.len {
+            self.push_back(value);
+        } else {
+            // The node is allocated by the spliced dequeue
+            self.stats.record_allocation();
+
+            let mut cursor = self.cursor_mut();
+            (0..=index).for_each(|_| cursor.move_next());
+            cursor.splice_before(Dequeue::from_iter([value]));
+        }
+    }
+
+    fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "index out of bounds");
+        self.stats.record_walk(index);
+
+        let mut cursor = self.cursor_mut();
+        (0..=index).for_each(|_| cursor.move_next());
+        cursor.remove_current().unwrap()
+    }
+}
+
+impl<T, A: Allocator> traits::Stack<T> for Dequeue<T, A> {
+    fn push(&mut self, value: T) {
+        self.push_back(value);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop_back()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.back()
+    }
+}
+
+impl<T, A: Allocator> traits::FifoQueue<T> for Dequeue<T, A> {
+    fn enqueue(&mut self, value: T) {
+        self.push_back(value);
+    }
+
+    fn dequeue(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+
+    fn front(&self) -> Option<&T> {
+        Dequeue::front(self)
+    }
+}
+
+impl<T: HeapSize, A: Allocator> HeapSize for Dequeue<T, A> {
+    fn heap_size_of_children(&self) -> usize {
+        self.len * mem::size_of::<Node<T>>() + heap_size::children(self.iter())
+    }
+}
+
+/// The node belongs to the dequeue, a handle doesn't count it.
+#[cfg(feature = "lru_cache")]
+impl<T> HeapSize for Handle<T> {
+    fn heap_size_of_children(&self) -> usize {
+        0
+    }
+}
+
 impl<T: Clone> Clone for Dequeue<T> {
     fn clone(&self) -> Self {
         let mut dequeue = Self::new();
@@ -333,7 +797,7 @@ impl<T: Clone> Clone for Dequeue<T> {
     }
 }
 
-impl<T> Extend<T> for Dequeue<T> {
+impl<T, A: Allocator> Extend<T> for Dequeue<T, A> {
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
         for item in iter {
             self.push_back(item);
@@ -341,6 +805,71 @@ impl<T> Extend<T> for Dequeue<T> {
     }
 }
 
+/// Copies the values, so slices can be appended without `.copied()`.
+impl<'a, T: Copy + 'a, A: Allocator> Extend<&'a T> for Dequeue<T, A> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+/// Appends the bytes at the back, so the dequeue can buffer a stream
+/// until it's read back with [`io::Read`].
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::{Read, Write};
+///
+/// use collections_rust::Dequeue;
+///
+/// let mut buffer = Dequeue::new();
+/// write!(buffer, "{} {}", 1, 2).unwrap();
+///
+/// let mut text = String::new();
+/// buffer.read_to_string(&mut text).unwrap();
+///
+/// assert_eq!(text, "1 2");
+/// ```
+impl<A: Allocator> io::Write for Dequeue<u8, A> {
+    /// Stops at the first byte that can't be allocated. That error is only
+    /// returned if no bytes were written.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for (written, &byte) in buf.iter().enumerate() {
+            if let Err(err) = self.try_push_back(byte) {
+                if written == 0 {
+                    return Err(io::Error::new(io::ErrorKind::OutOfMemory, err));
+                }
+
+                return Ok(written);
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Consumes bytes from the front. Reading an empty dequeue returns `Ok(0)`,
+/// which readers take as the end of the stream.
+impl<A: Allocator> io::Read for Dequeue<u8, A> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut read = 0;
+
+        while read < buf.len() {
+            match self.pop_front() {
+                Some(byte) => buf[read] = byte,
+                None => break,
+            }
+            read += 1;
+        }
+
+        Ok(read)
+    }
+}
+
 impl<T> FromIterator<T> for Dequeue<T> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         let mut dequeue = Self::new();
@@ -350,13 +879,73 @@ impl<T> FromIterator<T> for Dequeue<T> {
     }
 }
 
-impl<T: Debug> Debug for Dequeue<T> {
+impl<T: Debug, A: Allocator> Debug for Dequeue<T, A> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_list().entries(self).finish()
     }
 }
 
-impl<T: PartialEq> PartialEq for Dequeue<T> {
+impl<T: Debug, A: Allocator> Dequeue<T, A> {
+    /// Writes the address of the head and tail nodes, then one line per node
+    /// from front to back with its address, value and links. Missing links
+    /// are written as null pointers, `0x0`. Meant for seeing how operations
+    /// rewire the nodes, the format may change at any time.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `w` if writing fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Dequeue;
+    ///
+    /// let dequeue: Dequeue<_> = [1, 2].into_iter().collect();
+    ///
+    /// let mut layout = String::new();
+    /// dequeue.dump_layout(&mut layout).unwrap();
+    ///
+    /// // Something like:
+    /// // head 0x5581e0a31b10, tail 0x5581e0a31b30, len 2
+    /// // 0x5581e0a31b10: 1, prev 0x0, next 0x5581e0a31b30
+    /// // 0x5581e0a31b30: 2, prev 0x5581e0a31b10, next 0x0
+    /// assert_eq!(layout.lines().count(), 3);
+    /// assert!(layout.lines().nth(1).unwrap().contains(": 1, prev 0x0, next 0x"));
+    /// ```
+    pub fn dump_layout(&self, mut w: impl fmt::Write) -> fmt::Result {
+        writeln!(
+            w,
+            "head {:p}, tail {:p}, len {}",
+            as_ptr(self.head),
+            as_ptr(self.tail),
+            self.len
+        )?;
+
+        let mut current = self.head;
+
+        while let Some(node) = current {
+            let node = unsafe { node.as_ref() };
+            writeln!(
+                w,
+                "{:p}: {:?}, prev {:p}, next {:p}",
+                node,
+                node.value,
+                as_ptr(node.prev),
+                as_ptr(node.next)
+            )?;
+            current = node.next;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the address `link` points to, null if there's no node.
+fn as_ptr<T>(link: Link<T>) -> *const Node<T> {
+    link.map_or(ptr::null(), |node| node.as_ptr())
+}
+
+impl<T: PartialEq, A: Allocator> PartialEq for Dequeue<T, A> {
     fn eq(&self, other: &Self) -> bool {
         self.len() == other.len() && self.iter().eq(other)
     }
@@ -366,21 +955,21 @@ impl<T: PartialEq> PartialEq for Dequeue<T> {
     // }
 }
 
-impl<T: Eq> Eq for Dequeue<T> {}
+impl<T: Eq, A: Allocator> Eq for Dequeue<T, A> {}
 
-impl<T: PartialOrd> PartialOrd for Dequeue<T> {
+impl<T: PartialOrd, A: Allocator> PartialOrd for Dequeue<T, A> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         self.iter().partial_cmp(other)
     }
 }
 
-impl<T: Ord> Ord for Dequeue<T> {
+impl<T: Ord, A: Allocator> Ord for Dequeue<T, A> {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         self.iter().cmp(other)
     }
 }
 
-impl<T: Hash> Hash for Dequeue<T> {
+impl<T: Hash, A: Allocator> Hash for Dequeue<T, A> {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.len().hash(state);
         for item in self {
@@ -389,7 +978,88 @@ impl<T: Hash> Hash for Dequeue<T> {
     }
 }
 
-impl<'a, T> CursorMut<'a, T> {
+/// Immutable [`Dequeue`] shared between its clones, returned by
+/// [`Dequeue::freeze`]. It can be sent to and read from other threads
+/// whenever the values can.
+pub struct FrozenDeque<T, A: Allocator = Global> {
+    inner: Arc<Dequeue<T, A>>,
+}
+
+// Only shared access to the dequeue is handed out, and the last clone to go
+// drops it, so the snapshot crosses threads like an `Arc<[T]>`.
+unsafe impl<T: Send + Sync, A: Allocator + Send + Sync> Send for FrozenDeque<T, A> {}
+unsafe impl<T: Send + Sync, A: Allocator + Send + Sync> Sync for FrozenDeque<T, A> {}
+
+impl<T, A: Allocator> FrozenDeque<T, A> {
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.inner.front()
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.inner.back()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.inner.iter()
+    }
+
+    /// Returns the dequeue back without copying it if no other clone is
+    /// alive, otherwise returns the snapshot unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns `self` if other clones share the nodes.
+    pub fn thaw(self) -> Result<Dequeue<T, A>, Self> {
+        Arc::try_unwrap(self.inner).map_err(|inner| Self { inner })
+    }
+
+    /// Returns `true` if both snapshots share the same nodes.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl<T, A: Allocator> Clone for FrozenDeque<T, A> {
+    /// Shares the nodes with the new snapshot, without copying any of them.
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<'a, T, A: Allocator> IntoIterator for &'a FrozenDeque<T, A> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: PartialEq, A: Allocator> PartialEq for FrozenDeque<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || self.inner == other.inner
+    }
+}
+
+impl<T: Eq, A: Allocator> Eq for FrozenDeque<T, A> {}
+
+impl<T: Debug, A: Allocator> Debug for FrozenDeque<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&*self.inner, f)
+    }
+}
+
+impl<'a, T, A: Allocator> CursorMut<'a, T, A> {
     pub fn index(&self) -> Option<usize> {
         self.index
     }
@@ -457,10 +1127,17 @@ impl<'a, T> CursorMut<'a, T> {
             prev.map(|node| &mut (*node.as_ptr()).value)
         }
     }
+}
 
+/// Splitting and splicing move nodes from one dequeue to another, which is
+/// only possible when both allocate them with the global allocator.
+impl<'a, T> CursorMut<'a, T> {
     pub fn split_before(&mut self) -> Dequeue<T> {
         if self.current.is_none() {
-            return std::mem::replace(self.dequeue, Dequeue::new());
+            let mut output = Dequeue::new();
+            output.swap_nodes(self.dequeue);
+
+            return output;
         }
 
         unsafe {
@@ -475,8 +1152,9 @@ impl<'a, T> CursorMut<'a, T> {
             let new_tail = self.dequeue.tail;
             let new_idx = Some(0);
 
+            // Splitting before the head leaves nothing for the output
             let output_len = old_len - new_len;
-            let output_head = self.dequeue.head;
+            let output_head = prev.and(self.dequeue.head);
             let output_tail = prev;
 
             if let Some(prev) = prev {
@@ -493,6 +1171,8 @@ impl<'a, T> CursorMut<'a, T> {
                 head: output_head,
                 tail: output_tail,
                 len: output_len,
+                alloc: Global,
+                stats: Counters::new(),
                 marker: PhantomData,
             }
         }
@@ -500,7 +1180,10 @@ impl<'a, T> CursorMut<'a, T> {
 
     pub fn split_after(&mut self) -> Dequeue<T> {
         if self.current.is_none() {
-            return std::mem::replace(self.dequeue, Dequeue::new());
+            let mut output = Dequeue::new();
+            output.swap_nodes(self.dequeue);
+
+            return output;
         }
 
         unsafe {
@@ -517,7 +1200,7 @@ impl<'a, T> CursorMut<'a, T> {
 
             let output_len = old_len - new_len;
             let output_head = next;
-            let output_tail = self.dequeue.tail;
+            let output_tail = next.and(self.dequeue.tail);
 
             if let Some(next) = next {
                 (*current.as_ptr()).next = None;
@@ -533,6 +1216,8 @@ impl<'a, T> CursorMut<'a, T> {
                 tail: output_tail,
                 head: output_head,
                 len: output_len,
+                alloc: Global,
+                stats: Counters::new(),
                 marker: PhantomData,
             }
         }
@@ -544,7 +1229,7 @@ impl<'a, T> CursorMut<'a, T> {
         }
 
         if self.dequeue.is_empty() {
-            *self.dequeue = input;
+            self.dequeue.swap_nodes(&mut input);
             return;
         }
 
@@ -570,6 +1255,7 @@ impl<'a, T> CursorMut<'a, T> {
             }
 
             self.dequeue.len += input.len;
+            self.dequeue.stats.record_len(self.dequeue.len);
             input.len = 0;
         }
     }
@@ -580,7 +1266,7 @@ impl<'a, T> CursorMut<'a, T> {
         }
 
         if self.dequeue.is_empty() {
-            *self.dequeue = input;
+            self.dequeue.swap_nodes(&mut input);
             return;
         }
 
@@ -606,14 +1292,17 @@ impl<'a, T> CursorMut<'a, T> {
             }
 
             self.dequeue.len += input.len;
+            self.dequeue.stats.record_len(self.dequeue.len);
             input.len = 0;
         }
     }
+}
 
+impl<'a, T, A: Allocator> CursorMut<'a, T, A> {
     /// Removes the node pointed by the cursor and returns its value. If the list
     /// is empty or the cursor doesn't point anywhere, it returns None and does
     /// nothing.
-    /// 
+    ///
     /// # Examples
     ///
     /// ```
@@ -637,12 +1326,8 @@ impl<'a, T> CursorMut<'a, T> {
             return None;
         }
 
-        if self.current.is_none() {
-            return None;
-        }
-
         unsafe {
-            let mut current = Box::from_raw(self.current.unwrap().as_ptr());
+            let current = allocator::take_in(self.current?, &self.dequeue.alloc);
 
             let value = current.value;
 
@@ -666,9 +1351,6 @@ impl<'a, T> CursorMut<'a, T> {
                 self.current = None;
             }
 
-            current.next = None;
-            current.prev = None;
-
             self.dequeue.len -= 1;
 
             Some(value)
@@ -676,9 +1358,41 @@ impl<'a, T> CursorMut<'a, T> {
     }
 }
 
+#[cfg(feature = "proptest")]
+impl<T: Debug> Dequeue<T> {
+    /// Returns a [proptest](https://docs.rs/proptest) strategy that
+    /// generates dequeues of values from `element` with a length in `size`.
+    /// Failing cases shrink like `Vec`s do, dropping values first and then
+    /// shrinking the ones left.
+    pub fn strategy<S: proptest::strategy::Strategy<Value = T>>(
+        element: S,
+        size: impl Into<proptest::collection::SizeRange>,
+    ) -> impl proptest::strategy::Strategy<Value = Self> {
+        use proptest::strategy::Strategy;
+
+        proptest::collection::vec(element, size).prop_map(Self::from_iter)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary> proptest::arbitrary::Arbitrary for Dequeue<T> {
+    type Parameters = <Vec<T> as proptest::arbitrary::Arbitrary>::Parameters;
+    type Strategy = proptest::strategy::Map<
+        <Vec<T> as proptest::arbitrary::Arbitrary>::Strategy,
+        fn(Vec<T>) -> Self,
+    >;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+
+        proptest::arbitrary::any_with::<Vec<T>>(args).prop_map(Self::from_iter)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::Dequeue;
+    use std::{sync::Mutex, thread};
 
     fn generate_test() -> Dequeue<i32> {
         list_from(&[0, 1, 2, 3, 4, 5, 6])
@@ -803,6 +1517,8 @@ mod test {
     }
 
     #[test]
+    // Kept like the std test it comes from, `rev` must yield the back first
+    #[allow(clippy::manual_next_back)]
     fn test_rev_iter() {
         let m = generate_test();
         for (i, elt) in m.iter().rev().enumerate() {
@@ -883,6 +1599,9 @@ mod test {
     }
 
     #[test]
+    // Kept like the std test it comes from, the negated comparisons check
+    // that none of them holds with NaN
+    #[allow(clippy::neg_cmp_op_on_partial_ord, clippy::zero_divided_by_zero)]
     fn test_ord_nan() {
         let nan = 0.0f64 / 0.0;
         let n = list_from(&[nan]);
@@ -915,6 +1634,8 @@ mod test {
     }
 
     #[test]
+    // Collects from a `Vec` on purpose, like the std test it comes from
+    #[allow(clippy::useless_vec)]
     fn test_debug() {
         let list: Dequeue<i32> = (0..10).collect();
         assert_eq!(format!("{:?}", list), "[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]");
@@ -926,6 +1647,129 @@ mod test {
         assert_eq!(format!("{:?}", list), r#"["just", "one", "test", "more"]"#);
     }
 
+    #[test]
+    fn test_extend_copied() {
+        let mut m = list_from(&[0, 1]);
+        let tail = [2, 3, 4];
+
+        m.extend(&tail);
+        m.extend(tail[1..].iter().rev());
+
+        check_links(&m);
+        assert_eq!(m, list_from(&[0, 1, 2, 3, 4, 4, 3]));
+    }
+
+    #[test]
+    fn test_io() {
+        use std::io::{BufRead, BufReader, Read, Write};
+
+        let mut m: Dequeue<u8> = Dequeue::new();
+        let mut buf = [0; 4];
+        assert_eq!(m.read(&mut buf).unwrap(), 0);
+
+        m.write_all(b"first\nsecond\n").unwrap();
+        assert_eq!(m.read(&mut buf).unwrap(), 4);
+        assert_eq!(&buf, b"firs");
+        assert_eq!(m.len(), 9);
+
+        // Leftover bytes are read back in order, line by line
+        let lines: Vec<_> = BufReader::new(&mut m).lines().map(Result::unwrap).collect();
+        assert_eq!(lines, ["t", "second"]);
+        assert!(m.is_empty());
+        check_links(&m);
+    }
+
+    #[test]
+    fn test_macro() {
+        let m: Dequeue<i32> = dequeue![];
+        assert!(m.is_empty());
+
+        let m = dequeue![1, 2, 3,];
+        check_links(&m);
+        assert_eq!(m, list_from(&[1, 2, 3]));
+    }
+
+    #[test]
+    fn test_try_retain() {
+        let mut m = list_from(&[0, 1, 2, 3, 4, 5, 6]);
+
+        m.retain(|value| value % 3 != 0);
+        check_links(&m);
+        assert_eq!(m, list_from(&[1, 2, 4, 5]));
+
+        // Removing the last value checked leaves the failing one in place
+        assert_eq!(
+            m.try_retain(|&value| if value == 4 {
+                Err(value)
+            } else {
+                Ok(value != 2)
+            }),
+            Err(4)
+        );
+        check_links(&m);
+        assert_eq!(m, list_from(&[1, 4, 5]));
+
+        assert_eq!(m.try_extend([Ok(6), Err(7), Ok(8)]), Err(7));
+        assert_eq!(
+            m.try_for_each_mut(|value| {
+                *value *= 10;
+                if *value > 40 {
+                    return Err(*value);
+                }
+                Ok(())
+            }),
+            Err(50)
+        );
+        check_links(&m);
+        assert_eq!(m, list_from(&[10, 40, 50, 6]));
+
+        m.retain(|_| false);
+        check_links(&m);
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn test_static() {
+        static LOG: Mutex<Dequeue<String>> = Mutex::new(Dequeue::new());
+
+        thread::scope(|s| {
+            for i in 0..4 {
+                s.spawn(move || LOG.lock().unwrap().push_back(i.to_string()));
+            }
+        });
+
+        let mut log = LOG.lock().unwrap();
+        check_links(&log);
+        assert_eq!(log.len(), 4);
+        log.clear();
+    }
+
+    #[test]
+    fn test_freeze() {
+        let frozen = generate_test().freeze();
+        let copy = frozen.clone();
+
+        let sum = thread::scope(|s| {
+            let readers: Vec<_> = (0..3)
+                .map(|_| s.spawn(|| copy.iter().sum::<i32>()))
+                .collect();
+            readers.into_iter().map(|r| r.join().unwrap()).sum::<i32>()
+        });
+
+        assert_eq!(sum, 63);
+        assert_eq!(frozen, copy);
+        assert_eq!(frozen.front(), Some(&0));
+        assert_eq!(frozen.back(), Some(&6));
+
+        let frozen = frozen.thaw().unwrap_err();
+        drop(copy);
+
+        let mut m = frozen.thaw().unwrap();
+        check_links(&m);
+        m.push_back(7);
+        assert_eq!(m.len(), 8);
+    }
+
     #[test]
     fn test_hashmap() {
         // Check that HashMap works with this as a key
@@ -1053,7 +1897,7 @@ mod test {
         cursor.move_next();
         cursor.move_prev();
         let tmp = cursor.split_before();
-        assert_eq!(m.into_iter().collect::<Vec<_>>(), &[]);
+        assert_eq!(m.into_iter().collect::<Vec<_>>(), &[] as &[u32]);
         m = tmp;
         let mut cursor = m.cursor_mut();
         cursor.move_next();
@@ -1075,6 +1919,135 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_cursor_mut_split_at_ends() {
+        let mut m: Dequeue<u32> = (0..3).collect();
+
+        // Nothing before the head or after the tail, and the nodes stay
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        let front = cursor.split_before();
+        assert!(front.is_empty() && front.iter().next().is_none());
+        drop(front);
+
+        cursor.move_prev();
+        cursor.move_prev();
+        let back = cursor.split_after();
+        assert!(back.is_empty() && back.iter().next_back().is_none());
+        drop(back);
+
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 1, 2]);
+    }
+
+    #[test]
+    fn test_dump_layout() {
+        let mut m: Dequeue<u32> = (0..3).collect();
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.splice_after((10..12).collect());
+
+        let mut layout = String::new();
+        m.dump_layout(&mut layout).unwrap();
+
+        // Lines look like "0x1000: 10, prev 0x2000, next 0x3000"
+        let nodes: Vec<Vec<&str>> = layout
+            .lines()
+            .skip(1)
+            .map(|line| line.split([':', ',']).map(str::trim).collect())
+            .collect();
+        let addresses: Vec<&str> = nodes.iter().map(|node| node[0]).collect();
+
+        assert_eq!(nodes.len(), 5);
+        assert!(layout.starts_with(&format!("head {}, ", addresses[0])));
+
+        for (i, node) in nodes.iter().enumerate() {
+            let prev = i.checked_sub(1).map_or("0x0", |i| addresses[i]);
+            let next = addresses.get(i + 1).copied().unwrap_or("0x0");
+
+            assert_eq!(node[2], format!("prev {prev}"));
+            assert_eq!(node[3], format!("next {next}"));
+        }
+
+        let values: Vec<&str> = nodes.iter().map(|node| node[1]).collect();
+        assert_eq!(values, ["0", "10", "11", "1", "2"]);
+    }
+
+    /// Counts its drops. Panics when cloned if it's 3, and when dropped if
+    /// it's 13.
+    struct Fragile<'a> {
+        value: i32,
+        drops: &'a std::cell::Cell<usize>,
+    }
+
+    impl Clone for Fragile<'_> {
+        fn clone(&self) -> Self {
+            assert_ne!(self.value, 3, "cloning 3");
+
+            Fragile {
+                value: self.value,
+                drops: self.drops,
+            }
+        }
+    }
+
+    impl Drop for Fragile<'_> {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+            assert_ne!(self.value, 13, "dropping 13");
+        }
+    }
+
+    #[test]
+    fn test_panic_safety() {
+        use std::panic::{self, AssertUnwindSafe};
+
+        let drops = std::cell::Cell::new(0);
+        let m: Dequeue<_> = (0..20)
+            .map(|value| Fragile {
+                value,
+                drops: &drops,
+            })
+            .collect();
+
+        // The values cloned before the panic are dropped with the clone
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| m.clone())).is_err());
+        assert_eq!(drops.get(), 3);
+        assert!(m.iter().map(|fragile| fragile.value).eq(0..20));
+        assert!(m
+            .iter()
+            .rev()
+            .map(|fragile| fragile.value)
+            .eq((0..20).rev()));
+
+        // All the values are dropped even if one of them panics
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| drop(m))).is_err());
+        assert_eq!(drops.get(), 23);
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        use crate::traits::List;
+
+        let mut m: Dequeue<u32> = (0..6).collect();
+        List::insert(&mut m, 3, 10);
+        assert_eq!(List::remove(&mut m, 1), 1);
+
+        let stats = m.stats();
+        assert_eq!((stats.allocations, stats.peak_len), (7, 7));
+        assert_eq!(stats.pointer_walks, 4);
+
+        // Splitting everything off hands the nodes over, not the counters
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.move_prev();
+        let all = cursor.split_after();
+        assert_eq!(all.stats().peak_len, 6);
+        assert_eq!(all.stats().allocations, 0);
+        assert_eq!(m.stats(), stats);
+    }
+
     fn check_links<T: Eq + std::fmt::Debug>(list: &Dequeue<T>) {
         let from_front: Vec<_> = list.iter().collect();
         let from_back: Vec<_> = list.iter().rev().collect();
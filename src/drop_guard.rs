@@ -0,0 +1,64 @@
+use std::mem;
+
+/// Drops every value returned by `next` until it returns `None`. If
+/// dropping a value panics the rest are still dropped while unwinding, like
+/// std collections do, so only the value that panicked is lost. A second
+/// panic aborts the process.
+pub(crate) fn drop_each<T>(next: &mut dyn FnMut() -> Option<T>) {
+    /// Drops the values left if dropping the current one panics.
+    struct Guard<'a, T>(&'a mut dyn FnMut() -> Option<T>);
+
+    impl<T> Drop for Guard<'_, T> {
+        fn drop(&mut self) {
+            drop_each(self.0);
+        }
+    }
+
+    while let Some(value) = next() {
+        let guard = Guard(&mut *next);
+        drop(value);
+        mem::forget(guard);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::Cell,
+        panic::{self, AssertUnwindSafe},
+    };
+
+    use super::drop_each;
+
+    /// Counts its drops and panics when dropped if `panics` is set.
+    struct Noisy<'a> {
+        drops: &'a Cell<usize>,
+        panics: bool,
+    }
+
+    impl Drop for Noisy<'_> {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+            assert!(!self.panics, "dropping a noisy value");
+        }
+    }
+
+    #[test]
+    fn test_drop_each() {
+        let drops = Cell::new(0);
+        let mut values: Vec<_> = (0..5)
+            .map(|index| Noisy {
+                drops: &drops,
+                panics: index == 1,
+            })
+            .collect();
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            drop_each(&mut || values.pop());
+        }));
+
+        assert!(result.is_err());
+        assert!(values.is_empty());
+        assert_eq!(drops.get(), 5);
+    }
+}
@@ -0,0 +1,177 @@
+use crate::traits::Collection;
+use crate::Allocator;
+#[cfg(feature = "deque")]
+use crate::Dequeue;
+#[cfg(feature = "queue")]
+use crate::Queue;
+#[cfg(feature = "vector")]
+use crate::Vector;
+
+/// Implements `PartialEq` between two sequences, comparing their lengths
+/// first and then their values in order, like `Vec<T> == VecDeque<U>` does
+/// in std. The sequences may use different allocators.
+macro_rules! impl_sequence_eq {
+    ($(#[$cfg:meta] $lhs:ident == $rhs:ident),* $(,)?) => {$(
+        #[$cfg]
+        impl<T: PartialEq<U>, U, A: Allocator, B: Allocator> PartialEq<$rhs<U, B>> for $lhs<T, A> {
+            fn eq(&self, other: &$rhs<U, B>) -> bool {
+                Collection::len(self) == Collection::len(other) && self.iter().eq(other.iter())
+            }
+        }
+    )*};
+}
+
+/// Implements `PartialEq` between a sequence and slices, arrays and `Vec`,
+/// in both directions.
+macro_rules! impl_slice_eq {
+    ($(#[$cfg:meta] $lhs:ident)*) => {$(
+        #[$cfg]
+        impl<T: PartialEq<U>, U, A: Allocator> PartialEq<[U]> for $lhs<T, A> {
+            fn eq(&self, other: &[U]) -> bool {
+                Collection::len(self) == other.len() && self.iter().eq(other)
+            }
+        }
+
+        #[$cfg]
+        impl<T: PartialEq<U>, U, A: Allocator> PartialEq<&[U]> for $lhs<T, A> {
+            fn eq(&self, other: &&[U]) -> bool {
+                *self == **other
+            }
+        }
+
+        #[$cfg]
+        impl<T: PartialEq<U>, U, A: Allocator, const N: usize> PartialEq<[U; N]> for $lhs<T, A> {
+            fn eq(&self, other: &[U; N]) -> bool {
+                *self == other[..]
+            }
+        }
+
+        #[$cfg]
+        impl<T: PartialEq<U>, U, A: Allocator> PartialEq<Vec<U>> for $lhs<T, A> {
+            fn eq(&self, other: &Vec<U>) -> bool {
+                *self == other[..]
+            }
+        }
+
+        #[$cfg]
+        impl<T: PartialEq<U>, U, A: Allocator> PartialEq<$lhs<U, A>> for [T] {
+            fn eq(&self, other: &$lhs<U, A>) -> bool {
+                self.len() == Collection::len(other) && self.iter().eq(other.iter())
+            }
+        }
+
+        #[$cfg]
+        impl<T: PartialEq<U>, U, A: Allocator> PartialEq<$lhs<U, A>> for &[T] {
+            fn eq(&self, other: &$lhs<U, A>) -> bool {
+                **self == *other
+            }
+        }
+
+        #[$cfg]
+        impl<T: PartialEq<U>, U, A: Allocator, const N: usize> PartialEq<$lhs<U, A>> for [T; N] {
+            fn eq(&self, other: &$lhs<U, A>) -> bool {
+                self[..] == *other
+            }
+        }
+
+        #[$cfg]
+        impl<T: PartialEq<U>, U, A: Allocator> PartialEq<$lhs<U, A>> for Vec<T> {
+            fn eq(&self, other: &$lhs<U, A>) -> bool {
+                self[..] == *other
+            }
+        }
+    )*};
+}
+
+impl_sequence_eq!(
+    #[cfg(feature = "vector")]
+    Vector
+        == Vector,
+    #[cfg(all(feature = "vector", feature = "deque"))]
+    Vector
+        == Dequeue,
+    #[cfg(all(feature = "vector", feature = "queue"))]
+    Vector
+        == Queue,
+    #[cfg(all(feature = "deque", feature = "vector"))]
+    Dequeue
+        == Vector,
+    #[cfg(all(feature = "deque", feature = "queue"))]
+    Dequeue
+        == Queue,
+    #[cfg(feature = "queue")]
+    Queue
+        == Queue,
+    #[cfg(all(feature = "queue", feature = "vector"))]
+    Queue
+        == Vector,
+    #[cfg(all(feature = "queue", feature = "deque"))]
+    Queue
+        == Dequeue,
+);
+
+impl_slice_eq!(
+    #[cfg(feature = "vector")] Vector
+    #[cfg(feature = "deque")] Dequeue
+    #[cfg(feature = "queue")] Queue
+);
+
+#[cfg(all(test, feature = "deque", feature = "queue", feature = "vector"))]
+mod tests {
+    use crate::{Dequeue, Queue, Vector};
+
+    #[test]
+    fn test_sequences() {
+        let mut vector = Vector::new();
+        let mut queue = Queue::new();
+
+        for value in 1..=3 {
+            vector.push(value);
+            queue.append(value);
+        }
+
+        let dequeue: Dequeue<_> = (1..=3).collect();
+
+        // Both directions of every pair
+        assert!(vector == dequeue);
+        assert!(dequeue == vector);
+        assert!(vector == queue);
+        assert!(queue == vector);
+        assert!(queue == dequeue);
+        assert!(dequeue == queue);
+
+        queue.pop();
+        assert!(queue != vector);
+        assert!(dequeue != queue);
+
+        vector.pop();
+        assert!(vector != dequeue);
+        assert!(vector != queue);
+    }
+
+    #[test]
+    fn test_slices() {
+        let dequeue: Dequeue<_> = ["a", "b"].into_iter().map(String::from).collect();
+
+        // Values of different types compare if they implement `PartialEq`
+        assert!(dequeue == ["a", "b"]);
+
+        let slice: &[&str] = &["a", "b"];
+        assert!(dequeue == slice);
+        assert!(dequeue != ["a"]);
+
+        let mut vector = Vector::new();
+        vector.push(1);
+        assert!(vector == [1]);
+        assert!(vector != [1, 2]);
+
+        // Slices, arrays and `Vec` compare from either side
+        assert!(vec![1] == vector);
+        assert!(vector == vec![1]);
+        assert!([1] == vector);
+        assert!([1, 2][..] != vector);
+        assert!(slice == dequeue);
+        assert!(vec!["a", "b"] == dequeue);
+        assert!(Queue::<i32>::new() == Vec::<i32>::new());
+    }
+}
@@ -0,0 +1,312 @@
+use crate::{bit_vec::BitVec, heap_size::HeapSize, vector::Vector};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Set of integers in `0..len` for a `len` fixed at construction, stored as
+/// one bit per integer. Alongside the bits it keeps the number of members
+/// before every 64-bit block, which answers [`FixedBitSet::rank`] in O(1) and
+/// [`FixedBitSet::select`] in O(log n), the two primitives succinct data
+/// structures are built on.
+///
+/// Keeping the block summaries up to date makes [`FixedBitSet::insert`] and
+/// [`FixedBitSet::remove`] O(n / 64), so this set is meant to be built once
+/// and queried many times.
+pub struct FixedBitSet {
+    bits: BitVec,
+    /// `ranks[b]` is the number of members in the blocks before `b`, with one
+    /// extra entry at the end holding the total.
+    ranks: Vector<usize>,
+}
+
+impl FixedBitSet {
+    /// Creates an empty set that can hold the integers in `0..len`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::FixedBitSet;
+    ///
+    /// let set = FixedBitSet::new(100);
+    ///
+    /// assert_eq!(set.len(), 100);
+    /// assert_eq!(set.count_ones(), 0);
+    /// ```
+    pub fn new(len: usize) -> Self {
+        let bits = BitVec::from_elem(len, false);
+        let mut ranks = Vector::new();
+
+        for _ in 0..=bits.words().len() {
+            ranks.push(0);
+        }
+
+        Self { bits, ranks }
+    }
+
+    /// Returns the size of the universe, not the number of members.
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// Returns `true` if the universe is empty.
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    /// Returns the number of members.
+    pub fn count_ones(&self) -> usize {
+        self.ranks[self.ranks.len() - 1]
+    }
+
+    /// Returns `true` if `value` is a member.
+    pub fn contains(&self, value: usize) -> bool {
+        self.bits.get(value).unwrap_or(false)
+    }
+
+    /// Adds `value` to the set and returns `true` unless it was already a
+    /// member.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is not smaller than [`FixedBitSet::len`].
+    pub fn insert(&mut self, value: usize) -> bool {
+        self.update(value, true)
+    }
+
+    /// Removes `value` from the set and returns `true` unless it wasn't a
+    /// member.
+    pub fn remove(&mut self, value: usize) -> bool {
+        value < self.len() && self.update(value, false)
+    }
+
+    /// Sets the bit of `value` and adjusts the summaries of the following
+    /// blocks if it changed.
+    fn update(&mut self, value: usize, bit: bool) -> bool {
+        if self.contains(value) == bit {
+            return false;
+        }
+
+        self.bits.set(value, bit);
+
+        for rank in self.ranks[value / WORD_BITS + 1..].iter_mut() {
+            if bit {
+                *rank += 1;
+            } else {
+                *rank -= 1;
+            }
+        }
+
+        true
+    }
+
+    /// Returns the number of members smaller than `index`, in O(1).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than [`FixedBitSet::len`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::FixedBitSet;
+    ///
+    /// let set: FixedBitSet = [1, 4, 70].into_iter().collect();
+    ///
+    /// assert_eq!(set.rank(0), 0);
+    /// assert_eq!(set.rank(2), 1);
+    /// assert_eq!(set.rank(70), 2);
+    /// assert_eq!(set.rank(71), 3);
+    /// ```
+    pub fn rank(&self, index: usize) -> usize {
+        assert!(index <= self.len(), "Index out of bounds");
+
+        let block = index / WORD_BITS;
+        let offset = index % WORD_BITS;
+
+        if offset == 0 {
+            return self.ranks[block];
+        }
+
+        let below = self.bits.words()[block] & ((1 << offset) - 1);
+
+        self.ranks[block] + below.count_ones() as usize
+    }
+
+    /// Returns the `k`-th smallest member counting from zero, or `None` if
+    /// there are not that many members. Takes O(log n): a binary search over
+    /// the block summaries followed by a search inside one block.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::FixedBitSet;
+    ///
+    /// let set: FixedBitSet = [1, 4, 70].into_iter().collect();
+    ///
+    /// assert_eq!(set.select(0), Some(1));
+    /// assert_eq!(set.select(2), Some(70));
+    /// assert_eq!(set.select(3), None);
+    ///
+    /// // Select is the inverse of rank for members
+    /// assert_eq!(set.rank(set.select(1).unwrap()), 1);
+    /// ```
+    pub fn select(&self, k: usize) -> Option<usize> {
+        if k >= self.count_ones() {
+            return None;
+        }
+
+        // Last block with fewer than k + 1 members before it
+        let block = self.ranks.partition_point(|&rank| rank <= k) - 1;
+        let mut word = self.bits.words()[block];
+
+        for _ in 0..k - self.ranks[block] {
+            word &= word - 1;
+        }
+
+        Some(block * WORD_BITS + word.trailing_zeros() as usize)
+    }
+
+    /// Returns an iterator over the members in ascending order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            words: self.bits.words(),
+            block: 0,
+            current: self.bits.words().first().copied().unwrap_or(0),
+            remaining: self.count_ones(),
+        }
+    }
+}
+
+impl FromIterator<usize> for FixedBitSet {
+    /// Creates a set whose universe is just large enough for the greatest
+    /// value.
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let values: Vec<usize> = iter.into_iter().collect();
+        let len = values.iter().max().map_or(0, |max| max + 1);
+
+        let mut bits = BitVec::from_elem(len, false);
+
+        for &value in values.iter() {
+            bits.set(value, true);
+        }
+
+        let mut ranks = Vector::new();
+        let mut total = 0;
+        ranks.push(total);
+
+        for word in bits.words() {
+            total += word.count_ones() as usize;
+            ranks.push(total);
+        }
+
+        Self { bits, ranks }
+    }
+}
+
+impl<'a> IntoIterator for &'a FixedBitSet {
+    type Item = usize;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the members of a [`FixedBitSet`] in ascending order.
+pub struct Iter<'a> {
+    words: &'a [u64],
+    block: usize,
+    /// Bits of the current block that haven't been yielded yet.
+    current: u64,
+    remaining: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        while self.current == 0 {
+            self.block += 1;
+            self.current = self.words[self.block];
+        }
+
+        let value = self.block * WORD_BITS + self.current.trailing_zeros() as usize;
+        self.current &= self.current - 1;
+        self.remaining -= 1;
+
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for Iter<'a> {}
+
+impl HeapSize for FixedBitSet {
+    fn heap_size_of_children(&self) -> usize {
+        self.bits.heap_size_of_children() + self.ranks.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FixedBitSet;
+
+    #[test]
+    fn test_rank_select() {
+        let members: Vec<usize> = (0..1_000).filter(|i| i % 7 == 0 || i % 13 == 0).collect();
+        let set: FixedBitSet = members.iter().copied().collect();
+
+        assert_eq!(set.count_ones(), members.len());
+        assert!(set.iter().eq(members.iter().copied()));
+
+        for index in 0..=set.len() {
+            let expected = members.iter().filter(|&&member| member < index).count();
+            assert_eq!(set.rank(index), expected);
+        }
+
+        for (k, &member) in members.iter().enumerate() {
+            assert_eq!(set.select(k), Some(member));
+        }
+
+        assert_eq!(set.select(members.len()), None);
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut set = FixedBitSet::new(300);
+
+        assert!(set.insert(0));
+        assert!(set.insert(299));
+        assert!(set.insert(128));
+        assert!(!set.insert(128));
+
+        assert_eq!(set.rank(200), 2);
+        assert_eq!(set.select(1), Some(128));
+        assert_eq!(set.select(2), Some(299));
+
+        assert!(set.remove(128));
+        assert!(!set.remove(128));
+        assert!(!set.remove(1_000));
+
+        assert_eq!(set.rank(300), 2);
+        assert_eq!(set.select(1), Some(299));
+        assert!(set.iter().eq([0, 299]));
+
+        let empty = FixedBitSet::new(0);
+        assert_eq!(empty.rank(0), 0);
+        assert_eq!(empty.select(0), None);
+        assert_eq!(empty.iter().next(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_insert_out_of_bounds() {
+        FixedBitSet::new(10).insert(10);
+    }
+}
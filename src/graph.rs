@@ -0,0 +1,362 @@
+use std::slice;
+
+use crate::{heap_size::HeapSize, queue::Queue, vector::Vector};
+
+/// Directed graph stored as adjacency lists. Nodes carry a value of type `N`
+/// and are identified by the index returned from [`Graph::add_node`], edges
+/// carry a value of type `E`. For an undirected graph add every edge in both
+/// directions.
+pub struct Graph<N, E> {
+    nodes: Vector<N>,
+    /// Outgoing edges of every node as `(target, value)` pairs, in the order
+    /// they were added.
+    edges: Vector<Vector<(usize, E)>>,
+    edge_count: usize,
+}
+
+impl<N, E> Graph<N, E> {
+    /// Creates a graph without nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Graph;
+    ///
+    /// let graph: Graph<&str, u32> = Graph::new();
+    ///
+    /// assert_eq!(graph.node_count(), 0);
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            nodes: Vector::new(),
+            edges: Vector::new(),
+            edge_count: 0,
+        }
+    }
+
+    /// Returns the number of nodes.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the number of edges.
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    /// Adds a node with the given value and returns its index.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Graph;
+    ///
+    /// let mut graph: Graph<&str, ()> = Graph::new();
+    ///
+    /// let a = graph.add_node("a");
+    /// let b = graph.add_node("b");
+    ///
+    /// assert_eq!((a, b), (0, 1));
+    /// assert_eq!(graph.node(b), Some(&"b"));
+    /// ```
+    pub fn add_node(&mut self, value: N) -> usize {
+        self.nodes.push(value);
+        self.edges.push(Vector::new());
+
+        self.nodes.len() - 1
+    }
+
+    /// Adds an edge from `from` to `to` with the given value. Parallel edges
+    /// and self loops are allowed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` or `to` is not a node of the graph.
+    pub fn add_edge(&mut self, from: usize, to: usize, value: E) {
+        assert!(
+            from < self.nodes.len() && to < self.nodes.len(),
+            "edge ({from}, {to}) out of bounds for {} nodes",
+            self.nodes.len()
+        );
+
+        self.edges[from].push((to, value));
+        self.edge_count += 1;
+    }
+
+    /// Returns the value of node `index`.
+    pub fn node(&self, index: usize) -> Option<&N> {
+        self.nodes.get(index)
+    }
+
+    /// Returns a mutable reference to the value of node `index`.
+    pub fn node_mut(&mut self, index: usize) -> Option<&mut N> {
+        self.nodes.get_mut(index)
+    }
+
+    /// Returns an iterator over the targets and values of the edges that
+    /// leave node `index`, in the order they were added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not a node of the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Graph;
+    ///
+    /// let mut graph = Graph::new();
+    /// let a = graph.add_node('a');
+    /// let b = graph.add_node('b');
+    ///
+    /// graph.add_edge(a, b, 5);
+    /// graph.add_edge(a, a, 1);
+    ///
+    /// assert!(graph.neighbors(a).eq([(b, &5), (a, &1)]));
+    /// assert_eq!(graph.neighbors(b).count(), 0);
+    /// ```
+    pub fn neighbors(&self, index: usize) -> Neighbors<'_, E> {
+        Neighbors(self.edges[index].iter())
+    }
+
+    /// Returns an iterator over the nodes reachable from `start` in breadth
+    /// first order, nearest nodes first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` is not a node of the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Graph;
+    ///
+    /// let mut graph: Graph<(), ()> = Graph::new();
+    ///
+    /// for _ in 0..5 {
+    ///     graph.add_node(());
+    /// }
+    ///
+    /// for (from, to) in [(0, 1), (0, 2), (1, 3), (2, 3), (3, 0)] {
+    ///     graph.add_edge(from, to, ());
+    /// }
+    ///
+    /// assert!(graph.bfs(0).eq([0, 1, 2, 3]));
+    /// assert!(graph.bfs(4).eq([4]));
+    /// ```
+    pub fn bfs(&self, start: usize) -> Bfs<'_, N, E> {
+        let mut visited = self.visited();
+        let mut queue = Queue::new();
+
+        visited[start] = true;
+        queue.append(start);
+
+        Bfs {
+            graph: self,
+            visited,
+            queue,
+        }
+    }
+
+    /// Returns an iterator over the nodes reachable from `start` in depth
+    /// first preorder. Neighbors are explored in the order their edges were
+    /// added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` is not a node of the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Graph;
+    ///
+    /// let mut graph: Graph<(), ()> = Graph::new();
+    ///
+    /// for _ in 0..5 {
+    ///     graph.add_node(());
+    /// }
+    ///
+    /// for (from, to) in [(0, 1), (0, 2), (1, 3), (2, 3), (3, 0)] {
+    ///     graph.add_edge(from, to, ());
+    /// }
+    ///
+    /// assert!(graph.dfs(0).eq([0, 1, 3, 2]));
+    /// ```
+    pub fn dfs(&self, start: usize) -> Dfs<'_, N, E> {
+        assert!(start < self.nodes.len(), "node {start} out of bounds");
+
+        let mut stack = Vector::new();
+        stack.push(start);
+
+        Dfs {
+            graph: self,
+            visited: self.visited(),
+            stack,
+        }
+    }
+
+    /// Returns a vector with one `false` flag per node.
+    fn visited(&self) -> Vector<bool> {
+        let mut visited = Vector::new();
+
+        for _ in 0..self.nodes.len() {
+            visited.push(false);
+        }
+
+        visited
+    }
+}
+
+impl<N, E> Default for Graph<N, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Iterator over the outgoing edges of a node. See [`Graph::neighbors`].
+pub struct Neighbors<'a, E>(slice::Iter<'a, (usize, E)>);
+
+impl<'a, E> Iterator for Neighbors<'a, E> {
+    type Item = (usize, &'a E);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(target, value)| (*target, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, E> ExactSizeIterator for Neighbors<'a, E> {}
+
+/// Breadth first traversal of a [`Graph`]. See [`Graph::bfs`].
+pub struct Bfs<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    visited: Vector<bool>,
+    /// Nodes already marked as visited but not returned yet.
+    queue: Queue<usize>,
+}
+
+impl<'a, N, E> Iterator for Bfs<'a, N, E> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop()?;
+
+        for (target, _) in self.graph.neighbors(node) {
+            if !self.visited[target] {
+                self.visited[target] = true;
+                self.queue.append(target);
+            }
+        }
+
+        Some(node)
+    }
+}
+
+/// Depth first traversal of a [`Graph`]. See [`Graph::dfs`].
+pub struct Dfs<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    visited: Vector<bool>,
+    /// Nodes to visit, the last one first. A node can be in the stack more
+    /// than once, it's skipped once it's been visited.
+    stack: Vector<usize>,
+}
+
+impl<'a, N, E> Iterator for Dfs<'a, N, E> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.stack.pop() {
+            if self.visited[node] {
+                continue;
+            }
+
+            self.visited[node] = true;
+
+            // Reversed so the first neighbor is on top of the stack
+            for &(target, _) in self.graph.edges[node].iter().rev() {
+                if !self.visited[target] {
+                    self.stack.push(target);
+                }
+            }
+
+            return Some(node);
+        }
+
+        None
+    }
+}
+
+impl<N: HeapSize, E: HeapSize> HeapSize for Graph<N, E> {
+    fn heap_size_of_children(&self) -> usize {
+        self.nodes.heap_size_of_children() + self.edges.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Graph;
+
+    /// Builds a graph with a node per letter and the given edges.
+    fn graph(edges: &[(usize, usize)]) -> Graph<char, usize> {
+        let mut graph = Graph::new();
+
+        for letter in 'a'..='h' {
+            graph.add_node(letter);
+        }
+
+        for (weight, &(from, to)) in edges.iter().enumerate() {
+            graph.add_edge(from, to, weight);
+        }
+
+        graph
+    }
+
+    #[test]
+    fn test_nodes_and_edges() {
+        let mut graph = graph(&[(0, 1), (0, 2), (2, 0), (7, 7)]);
+
+        assert_eq!(graph.node_count(), 8);
+        assert_eq!(graph.edge_count(), 4);
+        assert_eq!(graph.node(7), Some(&'h'));
+        assert_eq!(graph.node(8), None);
+        assert!(graph.neighbors(0).eq([(1, &0), (2, &1)]));
+        assert_eq!(graph.neighbors(7).len(), 1);
+
+        *graph.node_mut(0).unwrap() = 'z';
+        assert_eq!(graph.node(0), Some(&'z'));
+    }
+
+    #[test]
+    fn test_traversals() {
+        // Binary tree a -> (b, c), b -> (d, e), c -> (f, g), plus back edges
+        // and a cycle g -> c
+        let graph = graph(&[
+            (0, 1),
+            (0, 2),
+            (1, 3),
+            (1, 4),
+            (2, 5),
+            (2, 6),
+            (4, 0),
+            (6, 2),
+        ]);
+
+        assert!(graph.bfs(0).eq([0, 1, 2, 3, 4, 5, 6]));
+        assert!(graph.dfs(0).eq([0, 1, 3, 4, 2, 5, 6]));
+        assert!(graph.bfs(2).eq([2, 5, 6]));
+        assert!(graph.dfs(4).eq([4, 0, 1, 3, 2, 5, 6]));
+        assert!(graph.bfs(7).eq([7]));
+        assert!(graph.dfs(7).eq([7]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_edge_out_of_bounds() {
+        let mut graph = graph(&[]);
+        graph.add_edge(0, 8, 0);
+    }
+}
@@ -0,0 +1,397 @@
+use std::{
+    iter::StepBy,
+    ops::{Index, IndexMut},
+    slice,
+};
+
+use crate::{heap_size::HeapSize, vector::Vector};
+
+/// Offsets of the 8 cells around a cell, in row-major order.
+const ALL_OFFSETS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// Offsets of the 4 cells that share a side with a cell, in row-major order.
+const ORTHOGONAL_OFFSETS: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+/// Two dimensional grid of `width * height` cells stored row by row in a
+/// single [`Vector`]. Cells are addressed by `(x, y)` coordinates, where `x`
+/// is the column and `y` the row, both counting from the top left corner, so
+/// code for boards, images or cellular automata never computes flat indices
+/// by hand.
+pub struct Grid<T> {
+    cells: Vector<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid where every cell is `f(x, y)`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Grid;
+    ///
+    /// let grid = Grid::from_fn(3, 2, |x, y| 10 * y + x);
+    ///
+    /// assert_eq!(grid[(2, 1)], 12);
+    /// assert_eq!(grid.row(1), [10, 11, 12]);
+    /// ```
+    pub fn from_fn<F: FnMut(usize, usize) -> T>(width: usize, height: usize, mut f: F) -> Self {
+        let mut cells = Vector::new();
+
+        for y in 0..height {
+            for x in 0..width {
+                cells.push(f(x, y));
+            }
+        }
+
+        Self {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    /// Creates a grid where every cell is a clone of `value`.
+    pub fn from_elem(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_fn(width, height, |_, _| value.clone())
+    }
+
+    /// Returns the number of columns.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the number of rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the index of `(x, y)` in `cells`, if it's inside the grid.
+    fn index_of(&self, x: usize, y: usize) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| y * self.width + x)
+    }
+
+    /// Returns the cell at `(x, y)`, or `None` if it's outside the grid.
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        self.index_of(x, y).map(|index| &self.cells[index])
+    }
+
+    /// Returns a mutable reference to the cell at `(x, y)`, or `None` if it's
+    /// outside the grid.
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        self.index_of(x, y).map(|index| &mut self.cells[index])
+    }
+
+    /// Returns the cells of row `y` from left to right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `y` is not smaller than the height.
+    pub fn row(&self, y: usize) -> &[T] {
+        assert!(y < self.height, "Row out of bounds");
+
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    /// Returns the cells of row `y` from left to right as a mutable slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `y` is not smaller than the height.
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        assert!(y < self.height, "Row out of bounds");
+
+        &mut self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    /// Returns an iterator over the rows from top to bottom.
+    pub fn rows(&self) -> Rows<'_, T> {
+        Rows {
+            grid: self,
+            front: 0,
+            back: self.height,
+        }
+    }
+
+    /// Returns an iterator over the cells of column `x` from top to bottom.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is not smaller than the width.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Grid;
+    ///
+    /// let grid = Grid::from_fn(3, 3, |x, y| x * y);
+    ///
+    /// assert!(grid.column(2).eq(&[0, 2, 4]));
+    /// ```
+    pub fn column(&self, x: usize) -> Column<'_, T> {
+        assert!(x < self.width, "Column out of bounds");
+
+        Column {
+            cells: self.cells[x..].iter().step_by(self.width),
+        }
+    }
+
+    /// Returns an iterator over the cells around `(x, y)` along with their
+    /// coordinates, including the diagonal ones. Cells outside the grid are
+    /// skipped, so corners have 3 neighbors.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Grid;
+    ///
+    /// // Conway's game of life
+    /// let alive = Grid::from_fn(3, 3, |x, _| x == 1);
+    /// let next = Grid::from_fn(3, 3, |x, y| {
+    ///     let count = alive.neighbors(x, y).filter(|(_, alive)| **alive).count();
+    ///     count == 3 || (count == 2 && alive[(x, y)])
+    /// });
+    ///
+    /// assert!(next.row(1).iter().all(|alive| *alive));
+    /// assert_eq!(next.iter().filter(|alive| **alive).count(), 3);
+    /// ```
+    pub fn neighbors(&self, x: usize, y: usize) -> Neighbors<'_, T> {
+        Neighbors {
+            grid: self,
+            x,
+            y,
+            offsets: ALL_OFFSETS.iter(),
+        }
+    }
+
+    /// Returns an iterator over the cells that share a side with `(x, y)`
+    /// along with their coordinates.
+    pub fn orthogonal_neighbors(&self, x: usize, y: usize) -> Neighbors<'_, T> {
+        Neighbors {
+            grid: self,
+            x,
+            y,
+            offsets: ORTHOGONAL_OFFSETS.iter(),
+        }
+    }
+
+    /// Returns an iterator over all the cells in row-major order.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    /// Returns an iterator over mutable references to all the cells in
+    /// row-major order.
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
+        self.cells.iter_mut()
+    }
+
+    /// Returns the grid flipped over its diagonal, so the cell at `(x, y)`
+    /// moves to `(y, x)` and rows become columns.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Grid;
+    ///
+    /// let grid = Grid::from_fn(3, 2, |x, y| (x, y));
+    /// let transposed = grid.transpose();
+    ///
+    /// assert_eq!((transposed.width(), transposed.height()), (2, 3));
+    /// assert_eq!(transposed[(1, 2)], (2, 1));
+    /// ```
+    pub fn transpose(self) -> Self {
+        let (width, height) = (self.width, self.height);
+        let mut cells: Vec<Option<T>> = self.cells.into_iter().map(Some).collect();
+
+        // Cell (x, y) of the result is cell (y, x) of the original
+        Self::from_fn(height, width, |x, y| cells[x * width + y].take().unwrap())
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    /// Returns the cell at `(x, y)`, panicking if it's outside the grid.
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y).expect("Position out of bounds")
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        self.get_mut(x, y).expect("Position out of bounds")
+    }
+}
+
+/// Iterator over the rows of a [`Grid`].
+pub struct Rows<'a, T> {
+    grid: &'a Grid<T>,
+    /// Range of rows that haven't been yielded yet.
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T> Iterator for Rows<'a, T> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+
+        self.front += 1;
+        Some(self.grid.row(self.front - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Rows<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+
+        self.back -= 1;
+        Some(self.grid.row(self.back))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Rows<'a, T> {}
+
+/// Iterator over the cells of a column of a [`Grid`].
+pub struct Column<'a, T> {
+    cells: StepBy<slice::Iter<'a, T>>,
+}
+
+impl<'a, T> Iterator for Column<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cells.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.cells.size_hint()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Column<'a, T> {}
+
+/// Iterator over the neighbors of a cell of a [`Grid`].
+pub struct Neighbors<'a, T> {
+    grid: &'a Grid<T>,
+    x: usize,
+    y: usize,
+    offsets: slice::Iter<'static, (isize, isize)>,
+}
+
+impl<'a, T> Iterator for Neighbors<'a, T> {
+    type Item = ((usize, usize), &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for &(dx, dy) in self.offsets.by_ref() {
+            let (Some(x), Some(y)) = (self.x.checked_add_signed(dx), self.y.checked_add_signed(dy))
+            else {
+                continue;
+            };
+
+            if let Some(cell) = self.grid.get(x, y) {
+                return Some(((x, y), cell));
+            }
+        }
+
+        None
+    }
+}
+
+impl<T: HeapSize> HeapSize for Grid<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.cells.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Grid;
+
+    #[test]
+    fn test_access() {
+        let mut grid = Grid::from_elem(4, 3, 0);
+
+        for (index, cell) in grid.iter_mut().enumerate() {
+            *cell = index;
+        }
+
+        assert_eq!(grid.get(3, 2), Some(&11));
+        assert_eq!(grid.get(4, 0), None);
+        assert_eq!(grid.get(0, 3), None);
+
+        grid[(1, 1)] = 100;
+        *grid.get_mut(0, 0).unwrap() = 50;
+        grid.row_mut(2)[3] = 200;
+
+        let rows: Vec<&[usize]> = grid.rows().collect();
+        assert_eq!(
+            rows,
+            [&[50, 1, 2, 3][..], &[4, 100, 6, 7], &[8, 9, 10, 200]]
+        );
+        assert!(grid.column(1).eq(&[1, 100, 9]));
+        assert_eq!(grid.column(3).len(), 3);
+
+        let transposed = grid.transpose();
+        assert_eq!(transposed.width(), 3);
+        assert_eq!(transposed.height(), 4);
+        assert_eq!(transposed.row(1), [1, 100, 9]);
+        assert!(transposed.column(2).eq(&[8, 9, 10, 200]));
+
+        let empty: Grid<u8> = Grid::from_elem(0, 5, 0);
+        assert_eq!(empty.rows().count(), 5);
+        assert!(empty.rows().all(|row| row.is_empty()));
+        assert_eq!(empty.transpose().height(), 0);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::from_fn(3, 3, |x, y| (x, y));
+
+        let positions = |neighbors: super::Neighbors<'_, _>| -> Vec<(usize, usize)> {
+            neighbors
+                .map(|(position, cell)| {
+                    assert_eq!(position, *cell);
+                    position
+                })
+                .collect()
+        };
+
+        assert_eq!(positions(grid.neighbors(1, 1)).len(), 8);
+        assert_eq!(positions(grid.neighbors(0, 0)), [(1, 0), (0, 1), (1, 1)]);
+        assert_eq!(
+            positions(grid.neighbors(2, 1)),
+            [(1, 0), (2, 0), (1, 1), (1, 2), (2, 2)]
+        );
+        assert_eq!(positions(grid.orthogonal_neighbors(0, 2)), [(0, 1), (1, 2)]);
+        assert_eq!(
+            positions(grid.orthogonal_neighbors(1, 1)),
+            [(1, 0), (0, 1), (2, 1), (1, 2)]
+        );
+    }
+}
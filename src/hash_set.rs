@@ -0,0 +1,334 @@
+use std::{
+    hash::{BuildHasher, Hash, RandomState},
+    iter::Chain,
+};
+
+use crate::{
+    heap_size::HeapSize,
+    index_map::{self, IndexMap},
+};
+
+/// Hash set backed by an [`IndexMap`] with `()` values. Lookups take O(1) on
+/// average. Values are iterated in insertion order until one is removed, the
+/// last value then takes the place of the removed one.
+pub struct HashSet<T, S = RandomState> {
+    map: IndexMap<T, (), S>,
+}
+
+impl<T> HashSet<T> {
+    /// Creates an empty set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::HashSet;
+    ///
+    /// let set: HashSet<i32> = HashSet::new();
+    ///
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            map: IndexMap::new(),
+        }
+    }
+}
+
+impl<T, S> HashSet<T, S> {
+    /// Creates an empty set that uses `hasher` to hash the values.
+    pub fn with_hasher(hasher: S) -> Self {
+        Self {
+            map: IndexMap::with_hasher(hasher),
+        }
+    }
+
+    /// Returns the number of values in the set.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the set contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Removes all the values from the set.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Returns an iterator over the values.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::HashSet;
+    ///
+    /// let set: HashSet<i32> = [3, 1, 2].into_iter().collect();
+    ///
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), vec![&3, &1, &2]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(self.map.keys())
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher> HashSet<T, S> {
+    /// Inserts `value` and returns `true` unless an equal value is already
+    /// present, in which case the set is not modified.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::HashSet;
+    ///
+    /// let mut set = HashSet::new();
+    ///
+    /// assert!(set.insert(1));
+    /// assert!(!set.insert(1));
+    /// ```
+    pub fn insert(&mut self, value: T) -> bool {
+        if self.map.contains_key(&value) {
+            return false;
+        }
+
+        self.map.insert(value, ());
+
+        true
+    }
+
+    /// Returns `true` if the set contains `value`.
+    pub fn contains(&self, value: &T) -> bool {
+        self.map.contains_key(value)
+    }
+
+    /// Removes `value` and returns `true` unless it's not present.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::HashSet;
+    ///
+    /// let mut set: HashSet<i32> = [1, 2].into_iter().collect();
+    ///
+    /// assert!(set.remove(&1));
+    /// assert!(!set.remove(&1));
+    /// assert!(!set.contains(&1));
+    /// ```
+    pub fn remove(&mut self, value: &T) -> bool {
+        self.map.swap_remove(value).is_some()
+    }
+
+    /// Returns an iterator over the values in `self` or `other`, without
+    /// duplicates. The values of `self` come first.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::HashSet;
+    ///
+    /// let a: HashSet<i32> = [1, 2, 3].into_iter().collect();
+    /// let b: HashSet<i32> = [4, 2, 3, 4].into_iter().collect();
+    ///
+    /// assert!(a.union(&b).eq([1, 2, 3, 4].iter()));
+    /// ```
+    pub fn union<'a>(&'a self, other: &'a HashSet<T, S>) -> Union<'a, T, S> {
+        Union(self.iter().chain(other.difference(self)))
+    }
+
+    /// Returns an iterator over the values in both `self` and `other`, in the
+    /// order of `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::HashSet;
+    ///
+    /// let a: HashSet<i32> = [1, 2, 3].into_iter().collect();
+    /// let b: HashSet<i32> = [4, 2, 3, 4].into_iter().collect();
+    ///
+    /// assert!(a.intersection(&b).eq([2, 3].iter()));
+    /// ```
+    pub fn intersection<'a>(&'a self, other: &'a HashSet<T, S>) -> Intersection<'a, T, S> {
+        Intersection {
+            iter: self.iter(),
+            other,
+        }
+    }
+
+    /// Returns an iterator over the values in `self` but not in `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::HashSet;
+    ///
+    /// let a: HashSet<i32> = [1, 2, 3].into_iter().collect();
+    /// let b: HashSet<i32> = [4, 2, 3, 4].into_iter().collect();
+    ///
+    /// assert!(a.difference(&b).eq([1].iter()));
+    /// assert!(b.difference(&a).eq([4].iter()));
+    /// ```
+    pub fn difference<'a>(&'a self, other: &'a HashSet<T, S>) -> Difference<'a, T, S> {
+        Difference {
+            iter: self.iter(),
+            other,
+        }
+    }
+}
+
+impl<T, S: Default> Default for HashSet<T, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher> Extend<T> for HashSet<T, S> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher + Default> FromIterator<T> for HashSet<T, S> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::default();
+        set.extend(iter);
+
+        set
+    }
+}
+
+impl<'a, T, S> IntoIterator for &'a HashSet<T, S> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the values of a [`HashSet`].
+pub struct Iter<'a, T>(index_map::Keys<'a, T, ()>);
+
+/// Iterator over the values of two [`HashSet`]s without duplicates. See
+/// [`HashSet::union`].
+pub struct Union<'a, T, S>(Chain<Iter<'a, T>, Difference<'a, T, S>>);
+
+/// Iterator over the values in both of two [`HashSet`]s. See
+/// [`HashSet::intersection`].
+pub struct Intersection<'a, T, S> {
+    iter: Iter<'a, T>,
+    other: &'a HashSet<T, S>,
+}
+
+/// Iterator over the values of a [`HashSet`] that are not in another one. See
+/// [`HashSet::difference`].
+pub struct Difference<'a, T, S> {
+    iter: Iter<'a, T>,
+    other: &'a HashSet<T, S>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T: Hash + Eq, S: BuildHasher> Iterator for Union<'a, T, S> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+impl<'a, T: Hash + Eq, S: BuildHasher> Iterator for Intersection<'a, T, S> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.by_ref().find(|value| self.other.contains(value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+impl<'a, T: Hash + Eq, S: BuildHasher> Iterator for Difference<'a, T, S> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.by_ref().find(|value| !self.other.contains(value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+impl<T: HeapSize, S> HeapSize for HashSet<T, S> {
+    fn heap_size_of_children(&self) -> usize {
+        self.map.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HashSet;
+
+    #[test]
+    fn test_insert_contains_remove() {
+        let mut set = HashSet::new();
+
+        for value in (0..1_000).map(|value| (value * 37) % 500) {
+            set.insert(value);
+        }
+
+        assert_eq!(set.len(), 500);
+        assert_eq!(set.iter().len(), 500);
+
+        let mut values: Vec<_> = set.iter().copied().collect();
+        values.sort();
+        assert!(values.into_iter().eq(0..500));
+
+        for value in (0..500).step_by(2) {
+            assert!(set.remove(&value));
+        }
+
+        assert!(!set.remove(&0));
+        assert!(set.contains(&1));
+        assert!(!set.contains(&2));
+        assert_eq!(set.len(), 250);
+        assert!(set.iter().all(|value| value % 2 == 1));
+
+        set.clear();
+        assert!(set.is_empty());
+        assert_eq!(set.iter().next(), None);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a: HashSet<_> = (0..10).collect();
+        let b: HashSet<_> = (5..15).collect();
+
+        assert!(a.union(&b).copied().eq(0..15));
+        assert!(a.intersection(&b).copied().eq(5..10));
+        assert!(a.difference(&b).copied().eq(0..5));
+        assert!(b.difference(&a).copied().eq(10..15));
+
+        let empty = HashSet::default();
+        assert!(a.union(&empty).copied().eq(0..10));
+        assert_eq!(a.intersection(&empty).next(), None);
+        assert_eq!((&a).into_iter().count(), 10);
+    }
+}
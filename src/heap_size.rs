@@ -0,0 +1,235 @@
+//! Deep memory usage reporting, so long running programs can tell how much
+//! memory each of their data structures holds.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use std::mem;
+//!
+//! use collections_rust::{Dequeue, HeapSize, Vector};
+//!
+//! let mut names = Vector::new();
+//! names.push(String::from("Alice"));
+//! names.push(String::from("Bob"));
+//!
+//! // The buffer of the vector, with room for 2 strings, plus the buffers of
+//! // the strings
+//! assert_eq!(
+//!     names.heap_size_of_children(),
+//!     2 * mem::size_of::<String>() + 5 + 3,
+//! );
+//!
+//! // Linked collections also count one allocation per node
+//! let numbers: Dequeue<u64> = (0..10).collect();
+//! assert!(numbers.heap_size_of_children() > 10 * mem::size_of::<u64>());
+//! ```
+
+#[cfg(any(
+    feature = "cow_vector",
+    feature = "list",
+    feature = "persistent_tree",
+    feature = "persistent_vec"
+))]
+use std::alloc::Layout;
+use std::{mem, rc::Weak};
+
+/// Values that can report the heap memory they own. Implemented by all the
+/// collections of the crate whenever their values implement it too, and by
+/// the common types of std they're usually filled with.
+pub trait HeapSize {
+    /// Returns the number of bytes owned by `self` on the heap, not counting
+    /// `size_of::<Self>()`. Allocations are counted by the size they were
+    /// requested with, so spare capacity counts but allocator overhead
+    /// doesn't.
+    fn heap_size_of_children(&self) -> usize;
+}
+
+/// Implements `HeapSize` for types that never own heap memory.
+macro_rules! impl_heap_size_zero {
+    ($($ty:ty)*) => {$(
+        impl HeapSize for $ty {
+            fn heap_size_of_children(&self) -> usize {
+                0
+            }
+        }
+    )*};
+}
+
+impl_heap_size_zero!(
+    () bool char f32 f64
+    u8 u16 u32 u64 u128 usize
+    i8 i16 i32 i64 i128 isize
+);
+
+/// Implements `HeapSize` for tuples whose members implement it.
+macro_rules! impl_heap_size_tuple {
+    ($(($($name:ident)+))*) => {$(
+        impl<$($name: HeapSize),+> HeapSize for ($($name,)+) {
+            // The members are bound to the type parameter names, like `A`
+            #[allow(non_snake_case)]
+            fn heap_size_of_children(&self) -> usize {
+                let ($($name,)+) = self;
+
+                0 $(+ $name.heap_size_of_children())+
+            }
+        }
+    )*};
+}
+
+impl_heap_size_tuple!((A) (A B) (A B C) (A B C D));
+
+impl<T: HeapSize, const N: usize> HeapSize for [T; N] {
+    fn heap_size_of_children(&self) -> usize {
+        children(self)
+    }
+}
+
+impl<T: HeapSize> HeapSize for Option<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.as_ref().map_or(0, T::heap_size_of_children)
+    }
+}
+
+impl<T: HeapSize> HeapSize for Box<T> {
+    fn heap_size_of_children(&self) -> usize {
+        mem::size_of::<T>() + (**self).heap_size_of_children()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Box<[T]> {
+    fn heap_size_of_children(&self) -> usize {
+        mem::size_of_val::<[T]>(self) + children(self.iter())
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.capacity() * mem::size_of::<T>() + children(self)
+    }
+}
+
+impl HeapSize for String {
+    fn heap_size_of_children(&self) -> usize {
+        self.capacity()
+    }
+}
+
+/// The value belongs to the strong references, a weak one doesn't count it.
+impl<T> HeapSize for Weak<T> {
+    fn heap_size_of_children(&self) -> usize {
+        0
+    }
+}
+
+/// Adds up the heap memory owned by every one of `values`.
+pub(crate) fn children<'a, T: HeapSize + 'a>(values: impl IntoIterator<Item = &'a T>) -> usize {
+    values.into_iter().map(T::heap_size_of_children).sum()
+}
+
+/// Returns the size of the allocation behind an `Rc<T>`, which stores the
+/// strong and weak counts before the value.
+#[cfg(any(
+    feature = "cow_vector",
+    feature = "list",
+    feature = "persistent_tree",
+    feature = "persistent_vec"
+))]
+pub(crate) fn rc_allocation<T>() -> usize {
+    let counts = Layout::new::<[usize; 2]>();
+    let (layout, _) = counts.extend(Layout::new::<T>()).unwrap();
+
+    layout.pad_to_align().size()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem;
+    #[cfg(feature = "full")]
+    use std::{collections::hash_map::DefaultHasher, hash::BuildHasherDefault};
+
+    #[cfg(any(
+        feature = "cow_vector",
+        feature = "list",
+        feature = "persistent_tree",
+        feature = "persistent_vec"
+    ))]
+    use super::rc_allocation;
+    use super::HeapSize;
+    #[cfg(feature = "full")]
+    use crate::{
+        ArrayDeque, BTreeMap, ConcurrentHashMap, ConcurrentSkipMap, CowVector, Dequeue, IndexMap,
+        LinkedList, List, PersistentVec, ScapegoatTree, SkipMap, SortedVec, Treap, TreeMap,
+    };
+
+    /// Checks that the strings of the entries are counted once each, on top
+    /// of the memory of the collection.
+    #[cfg(feature = "full")]
+    fn check_values<C: FromIterator<(u32, String)> + HeapSize>() {
+        let empty: C = (0..100).map(|key| (key, String::new())).collect();
+        let full: C = (0..100).map(|key| (key, String::from("abc"))).collect();
+
+        assert!(empty.heap_size_of_children() > 0);
+        assert_eq!(
+            full.heap_size_of_children() - empty.heap_size_of_children(),
+            300
+        );
+    }
+
+    #[test]
+    fn test_std_types() {
+        let mut strings = Vec::with_capacity(4);
+        strings.push(String::from("abc"));
+        strings.push(String::with_capacity(10));
+
+        assert_eq!(
+            strings.heap_size_of_children(),
+            4 * mem::size_of::<String>() + 3 + 10
+        );
+
+        let boxed = Box::new((7u8, Some(String::from("hi"))));
+        assert_eq!(
+            boxed.heap_size_of_children(),
+            mem::size_of::<(u8, Option<String>)>() + 2
+        );
+
+        let slice: Box<[u32]> = vec![1, 2, 3].into_boxed_slice();
+        assert_eq!(slice.heap_size_of_children(), 12);
+        assert_eq!([1u64; 8].heap_size_of_children(), 0);
+    }
+
+    #[cfg(feature = "full")]
+    #[test]
+    fn test_collections() {
+        check_values::<Dequeue<_>>();
+        check_values::<ArrayDeque<_>>();
+        check_values::<List<_>>();
+        check_values::<LinkedList<_>>();
+        check_values::<PersistentVec<_>>();
+        check_values::<CowVector<_>>();
+        check_values::<SortedVec<_>>();
+        check_values::<ScapegoatTree<_>>();
+        check_values::<Treap<_>>();
+        check_values::<BTreeMap<_, _>>();
+        check_values::<TreeMap<_, _>>();
+        check_values::<SkipMap<_, _>>();
+        check_values::<ConcurrentSkipMap<_, _>>();
+        check_values::<IndexMap<_, _>>();
+        // Same hashes on both maps, so their shards grow the same
+        check_values::<ConcurrentHashMap<_, _, BuildHasherDefault<DefaultHasher>>>();
+    }
+
+    #[test]
+    #[cfg(any(
+        feature = "cow_vector",
+        feature = "list",
+        feature = "persistent_tree",
+        feature = "persistent_vec"
+    ))]
+    fn test_rc_allocation() {
+        assert_eq!(rc_allocation::<u8>(), 3 * mem::size_of::<usize>());
+        assert_eq!(
+            rc_allocation::<[u64; 3]>(),
+            2 * mem::size_of::<usize>() + 24
+        );
+    }
+}
@@ -0,0 +1,628 @@
+use std::{
+    hash::{BuildHasher, Hash, RandomState},
+    slice,
+};
+
+use crate::{
+    heap_size::HeapSize,
+    vector::{self, Vector},
+};
+
+/// Entry of an [`IndexMap`] along with the hash of its key, so the table can
+/// be rebuilt without hashing the keys again.
+struct Bucket<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+/// Hash map that remembers the order in which the keys were inserted. Entries
+/// are stored contiguously in insertion order in a [`Vector`], and a separate
+/// open addressing table with linear probing maps hashes to entry indices.
+/// Lookups take O(1) on average and entries can also be accessed by index.
+pub struct IndexMap<K, V, S = RandomState> {
+    entries: Vector<Bucket<K, V>>,
+    /// Indices into `entries`. Its length is zero or a power of two at least
+    /// twice the number of entries, so probing always finds an empty slot.
+    table: Vector<Option<usize>>,
+    hasher: S,
+}
+
+impl<K, V> IndexMap<K, V> {
+    /// Creates an empty map.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::IndexMap;
+    ///
+    /// let map: IndexMap<&str, i32> = IndexMap::new();
+    ///
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+}
+
+impl<K, V, S> IndexMap<K, V, S> {
+    /// Creates an empty map that uses `hasher` to hash the keys.
+    pub fn with_hasher(hasher: S) -> Self {
+        Self {
+            entries: Vector::new(),
+            table: Vector::new(),
+            hasher,
+        }
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all the entries from the map.
+    pub fn clear(&mut self) {
+        while self.entries.pop().is_some() {}
+        while self.table.pop().is_some() {}
+    }
+
+    /// Returns the entry at `index` in insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::IndexMap;
+    ///
+    /// let map: IndexMap<_, _> = [("b", 2), ("a", 1)].into_iter().collect();
+    ///
+    /// assert_eq!(map.get_index(0), Some((&"b", &2)));
+    /// assert_eq!(map.get_index(2), None);
+    /// ```
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.entries
+            .get(index)
+            .map(|bucket| (&bucket.key, &bucket.value))
+    }
+
+    /// Returns the entry at `index` in insertion order with a mutable
+    /// reference to the value.
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        self.entries
+            .get_mut(index)
+            .map(|bucket| (&bucket.key, &mut bucket.value))
+    }
+
+    /// Returns the first inserted entry.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.get_index(0)
+    }
+
+    /// Returns the last inserted entry.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.get_index(self.len().checked_sub(1)?)
+    }
+
+    /// Returns an iterator over the entries in insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::IndexMap;
+    ///
+    /// let map: IndexMap<_, _> = [("c", 3), ("a", 1), ("b", 2)].into_iter().collect();
+    ///
+    /// assert_eq!(map.iter().collect::<Vec<_>>(), vec![(&"c", &3), (&"a", &1), (&"b", &2)]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter(self.entries.iter())
+    }
+
+    /// Returns an iterator over the keys in insertion order.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys(self.iter())
+    }
+
+    /// Returns an iterator over the values in insertion order.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values(self.iter())
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> IndexMap<K, V, S> {
+    /// Returns the bit mask that turns hashes into slots of the table.
+    fn mask(&self) -> usize {
+        self.table.len() - 1
+    }
+
+    /// Probes the table for `key`. Returns `Ok` with the slot that points to
+    /// its entry if present, or `Err` with the empty slot where it should go.
+    /// The table must not be empty.
+    fn probe(&self, hash: u64, key: &K) -> Result<usize, usize> {
+        let mask = self.mask();
+        let mut slot = hash as usize & mask;
+
+        while let Some(index) = self.table[slot] {
+            let bucket = &self.entries[index];
+
+            if bucket.hash == hash && bucket.key == *key {
+                return Ok(slot);
+            }
+
+            slot = (slot + 1) & mask;
+        }
+
+        Err(slot)
+    }
+
+    /// Returns the slot that points to the entry at `index`.
+    fn slot_of(&self, index: usize) -> usize {
+        let mask = self.mask();
+        let mut slot = self.entries[index].hash as usize & mask;
+
+        while self.table[slot] != Some(index) {
+            slot = (slot + 1) & mask;
+        }
+
+        slot
+    }
+
+    /// Returns the slot that points to the entry with `key`, if present.
+    fn find(&self, key: &K) -> Option<usize> {
+        if self.entries.is_empty() {
+            return None;
+        }
+
+        self.probe(self.hasher.hash_one(key), key).ok()
+    }
+
+    /// Doubles the size of the table (or allocates the first one) and
+    /// reinserts every entry using its stored hash.
+    fn grow(&mut self) {
+        let capacity = (self.table.len() * 2).max(8);
+
+        while self.table.pop().is_some() {}
+        for _ in 0..capacity {
+            self.table.push(None);
+        }
+
+        let mask = self.mask();
+
+        for (index, bucket) in self.entries.iter().enumerate() {
+            let mut slot = bucket.hash as usize & mask;
+
+            while self.table[slot].is_some() {
+                slot = (slot + 1) & mask;
+            }
+
+            self.table[slot] = Some(index);
+        }
+    }
+
+    /// Empties `slot` and shifts back the entries after it that would no
+    /// longer be reachable from their ideal slot (backward shift deletion),
+    /// so no tombstones are needed.
+    fn erase_slot(&mut self, mut hole: usize) {
+        let mask = self.mask();
+        let mut slot = (hole + 1) & mask;
+
+        while let Some(index) = self.table[slot] {
+            let ideal = self.entries[index].hash as usize & mask;
+
+            // The entry can fill the hole if the hole is between its ideal
+            // slot and its current slot, taking wrap around into account.
+            if slot.wrapping_sub(ideal) & mask >= slot.wrapping_sub(hole) & mask {
+                self.table[hole] = Some(index);
+                hole = slot;
+            }
+
+            slot = (slot + 1) & mask;
+        }
+
+        self.table[hole] = None;
+    }
+
+    /// Returns a reference to the value of `key`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::IndexMap;
+    ///
+    /// let mut map = IndexMap::new();
+    /// map.insert("port", 8080);
+    ///
+    /// assert_eq!(map.get(&"port"), Some(&8080));
+    /// assert_eq!(map.get(&"host"), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.get_index_of(key)
+            .map(|index| &self.entries[index].value)
+    }
+
+    /// Returns a mutable reference to the value of `key`.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let index = self.get_index_of(key)?;
+
+        Some(&mut self.entries[index].value)
+    }
+
+    /// Returns the position of `key` in insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::IndexMap;
+    ///
+    /// let map: IndexMap<_, _> = [("a", 1), ("b", 2)].into_iter().collect();
+    ///
+    /// assert_eq!(map.get_index_of(&"b"), Some(1));
+    /// ```
+    pub fn get_index_of(&self, key: &K) -> Option<usize> {
+        self.find(key).map(|slot| self.table[slot].unwrap())
+    }
+
+    /// Returns `true` if the map contains `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Inserts `value` with `key` and returns the previous value of the key,
+    /// if any. A new key is appended at the end of the order, an existing key
+    /// keeps its position.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::IndexMap;
+    ///
+    /// let mut map = IndexMap::new();
+    ///
+    /// assert_eq!(map.insert("a", 1), None);
+    /// assert_eq!(map.insert("b", 2), None);
+    /// assert_eq!(map.insert("a", 3), Some(1));
+    ///
+    /// assert!(map.keys().eq(["a", "b"].iter()));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if (self.entries.len() + 1) * 2 > self.table.len() {
+            self.grow();
+        }
+
+        let hash = self.hasher.hash_one(&key);
+
+        match self.probe(hash, &key) {
+            Ok(slot) => {
+                let index = self.table[slot].unwrap();
+
+                Some(std::mem::replace(&mut self.entries[index].value, value))
+            }
+
+            Err(slot) => {
+                self.table[slot] = Some(self.entries.len());
+                self.entries.push(Bucket { hash, key, value });
+
+                None
+            }
+        }
+    }
+
+    /// Removes `key` and returns its value in O(1) by moving the last entry
+    /// into its position, which changes the order of the remaining entries.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::IndexMap;
+    ///
+    /// let mut map: IndexMap<_, _> = [("a", 1), ("b", 2), ("c", 3)].into_iter().collect();
+    ///
+    /// assert_eq!(map.swap_remove(&"a"), Some(1));
+    /// assert_eq!(map.swap_remove(&"a"), None);
+    ///
+    /// assert!(map.keys().eq(["c", "b"].iter()));
+    /// ```
+    pub fn swap_remove(&mut self, key: &K) -> Option<V> {
+        let slot = self.find(key)?;
+        let index = self.table[slot].unwrap();
+
+        self.erase_slot(slot);
+
+        let last = self.entries.len() - 1;
+
+        if index != last {
+            let last_slot = self.slot_of(last);
+            self.table[last_slot] = Some(index);
+            self.entries.swap(index, last);
+        }
+
+        self.entries.pop().map(|bucket| bucket.value)
+    }
+
+    /// Removes and returns the last inserted entry.
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        let last = self.entries.len().checked_sub(1)?;
+        let slot = self.slot_of(last);
+
+        self.erase_slot(slot);
+
+        self.entries.pop().map(|bucket| (bucket.key, bucket.value))
+    }
+}
+
+impl<K, V, S: Default> Default for IndexMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Extend<(K, V)> for IndexMap<K, V, S> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> FromIterator<(K, V)> for IndexMap<K, V, S> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+
+        map
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a IndexMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V, S> IntoIterator for IndexMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.entries.into_iter())
+    }
+}
+
+/// Iterator over the entries of an [`IndexMap`] in insertion order.
+pub struct Iter<'a, K, V>(slice::Iter<'a, Bucket<K, V>>);
+
+/// Iterator over the keys of an [`IndexMap`] in insertion order.
+pub struct Keys<'a, K, V>(Iter<'a, K, V>);
+
+/// Iterator over the values of an [`IndexMap`] in insertion order.
+pub struct Values<'a, K, V>(Iter<'a, K, V>);
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|bucket| (&bucket.key, &bucket.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0
+            .next_back()
+            .map(|bucket| (&bucket.key, &bucket.value))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+/// Owning iterator over the entries of an [`IndexMap`] in insertion order.
+pub struct IntoIter<K, V>(vector::IntoIter<Bucket<K, V>>);
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|bucket| (bucket.key, bucket.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|bucket| (bucket.key, bucket.value))
+    }
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Keys<'a, K, V> {}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Values<'a, K, V> {}
+
+#[cfg(feature = "serde")]
+impl<K: serde::Serialize, V: serde::Serialize, S> serde::Serialize for IndexMap<K, V, S> {
+    /// Serializes the map with its entries in insertion order.
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V, S> serde::Deserialize<'de> for IndexMap<K, V, S>
+where
+    K: serde::Deserialize<'de> + Hash + Eq,
+    V: serde::Deserialize<'de>,
+    S: BuildHasher + Default,
+{
+    /// Deserializes a map keeping the order of its entries. Later values of
+    /// repeated keys replace earlier ones.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct MapVisitor<K, V, S>(std::marker::PhantomData<IndexMap<K, V, S>>);
+
+        impl<'de, K, V, S> serde::de::Visitor<'de> for MapVisitor<K, V, S>
+        where
+            K: serde::Deserialize<'de> + Hash + Eq,
+            V: serde::Deserialize<'de>,
+            S: BuildHasher + Default,
+        {
+            type Value = IndexMap<K, V, S>;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a map")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut access: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut map = IndexMap::default();
+
+                while let Some((key, value)) = access.next_entry()? {
+                    map.insert(key, value);
+                }
+
+                Ok(map)
+            }
+        }
+
+        deserializer.deserialize_map(MapVisitor(std::marker::PhantomData))
+    }
+}
+
+impl<K: HeapSize, V: HeapSize> HeapSize for Bucket<K, V> {
+    fn heap_size_of_children(&self) -> usize {
+        self.key.heap_size_of_children() + self.value.heap_size_of_children()
+    }
+}
+
+impl<K: HeapSize, V: HeapSize, S> HeapSize for IndexMap<K, V, S> {
+    fn heap_size_of_children(&self) -> usize {
+        self.entries.heap_size_of_children() + self.table.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IndexMap;
+
+    /// Checks that every entry is reachable from the table and that the
+    /// table only points to existing entries.
+    fn check_table<V>(map: &IndexMap<i32, V>) {
+        for (index, (key, _)) in map.iter().enumerate() {
+            assert_eq!(map.get_index_of(key), Some(index));
+        }
+
+        let used = map.table.iter().filter(|slot| slot.is_some()).count();
+        assert_eq!(used, map.len());
+    }
+
+    #[test]
+    fn test_insert_get() {
+        let mut map = IndexMap::new();
+
+        for key in (0..1_000).map(|key| (key * 7_919) % 1_000) {
+            assert_eq!(map.insert(key, key * 2), None);
+        }
+
+        check_table(&map);
+        assert_eq!(map.len(), 1_000);
+        assert!(map
+            .keys()
+            .copied()
+            .eq((0..1_000).map(|key| (key * 7_919) % 1_000)));
+
+        assert_eq!(map.insert(5, 0), Some(10));
+        assert_eq!(map.get(&5), Some(&0));
+        assert_eq!(map.get(&1_000), None);
+        assert!(map.contains_key(&999));
+
+        *map.get_mut(&5).unwrap() = 1;
+        *map.get_index_mut(0).unwrap().1 = 2;
+        assert_eq!(map.get(&5), Some(&1));
+        assert_eq!(map.first(), Some((&0, &2)));
+        assert_eq!(
+            map.last(),
+            Some((&((999 * 7_919) % 1_000), &((999 * 7_919) % 1_000 * 2)))
+        );
+        assert_eq!(map.values().len(), 1_000);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut map: IndexMap<i32, i32> = (0..500).map(|key| (key, -key)).collect();
+
+        for key in (0..500).step_by(2) {
+            assert_eq!(map.swap_remove(&key), Some(-key));
+            check_table(&map);
+        }
+
+        assert_eq!(map.swap_remove(&0), None);
+        assert_eq!(map.len(), 250);
+        assert!((1..500).step_by(2).all(|key| map.get(&key) == Some(&-key)));
+
+        let (&key, &value) = map.last().unwrap();
+        assert_eq!(map.pop(), Some((key, value)));
+        assert!(!map.contains_key(&key));
+        check_table(&map);
+
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.get(&1), None);
+        assert_eq!(map.pop(), None);
+
+        map.insert(1, 1);
+        assert_eq!(map.iter().next_back(), Some((&1, &1)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let map: IndexMap<String, i32> = [("b", 1), ("a", 2), ("c", 3)]
+            .into_iter()
+            .map(|(key, value)| (String::from(key), value))
+            .collect();
+
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(json, r#"{"b":1,"a":2,"c":3}"#);
+
+        let map: IndexMap<String, i32> = serde_json::from_str(r#"{"z":1,"y":2,"z":3}"#).unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get_index(0), Some((&String::from("z"), &3)));
+    }
+}
@@ -0,0 +1,367 @@
+use crate::{heap_size::HeapSize, vector::Vector};
+
+/// Min-priority queue of keys that keeps track of where each key is stored,
+/// so the priority of any key can be changed or the key removed in O(log n).
+/// Keys are `usize` indices, which is what graph algorithms like Dijkstra or
+/// A* use to identify nodes, and the memory used for positions grows with the
+/// greatest key pushed.
+pub struct IndexedHeap<P> {
+    /// Min-heap of `(key, priority)` pairs ordered by priority.
+    heap: Vector<(usize, P)>,
+    /// Index in `heap` of every key, or `None` if the key is not present.
+    positions: Vector<Option<usize>>,
+}
+
+impl<P> IndexedHeap<P> {
+    /// Creates an empty heap.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::IndexedHeap;
+    ///
+    /// let heap: IndexedHeap<u32> = IndexedHeap::new();
+    ///
+    /// assert!(heap.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            heap: Vector::new(),
+            positions: Vector::new(),
+        }
+    }
+
+    /// Returns the number of keys in the heap.
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Returns `true` if the heap contains no keys.
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Removes all the keys from the heap.
+    pub fn clear(&mut self) {
+        while let Some((key, _)) = self.heap.pop() {
+            self.positions[key] = None;
+        }
+    }
+
+    /// Returns the index in the heap of `key`, if present.
+    fn position(&self, key: usize) -> Option<usize> {
+        self.positions.get(key).copied().flatten()
+    }
+
+    /// Returns `true` if `key` is in the heap.
+    pub fn contains(&self, key: usize) -> bool {
+        self.position(key).is_some()
+    }
+
+    /// Returns the priority of `key`, if present.
+    pub fn priority(&self, key: usize) -> Option<&P> {
+        self.position(key).map(|index| &self.heap[index].1)
+    }
+
+    /// Returns the key with the smallest priority and its priority without
+    /// removing it.
+    pub fn peek_min(&self) -> Option<(usize, &P)> {
+        self.heap.first().map(|(key, priority)| (*key, priority))
+    }
+
+    /// Returns an iterator over the keys and their priorities in arbitrary
+    /// order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &P)> {
+        self.heap.iter().map(|(key, priority)| (*key, priority))
+    }
+
+    /// Swaps the entries at indices `a` and `b` keeping positions in sync.
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.positions[self.heap[a].0] = Some(a);
+        self.positions[self.heap[b].0] = Some(b);
+    }
+}
+
+impl<P: Ord> IndexedHeap<P> {
+    /// Moves the entry at `index` up while its priority is smaller than its
+    /// parent's.
+    fn sift_up(&mut self, mut index: usize) {
+        while index > 0 {
+            let parent = (index - 1) / 2;
+
+            if self.heap[index].1 >= self.heap[parent].1 {
+                break;
+            }
+
+            self.swap(index, parent);
+            index = parent;
+        }
+    }
+
+    /// Moves the entry at `index` down while its priority is greater than one
+    /// of its children's.
+    fn sift_down(&mut self, mut index: usize) {
+        let len = self.heap.len();
+
+        loop {
+            let left = 2 * index + 1;
+            let right = left + 1;
+            let mut smallest = index;
+
+            if left < len && self.heap[left].1 < self.heap[smallest].1 {
+                smallest = left;
+            }
+
+            if right < len && self.heap[right].1 < self.heap[smallest].1 {
+                smallest = right;
+            }
+
+            if smallest == index {
+                break;
+            }
+
+            self.swap(index, smallest);
+            index = smallest;
+        }
+    }
+
+    /// Moves the entry at `index` to its place after its priority changed.
+    fn restore(&mut self, index: usize) {
+        self.sift_up(index);
+        self.sift_down(index);
+    }
+
+    /// Pushes `key` with the given `priority` in O(log n). If the key is
+    /// already present its priority is replaced and the old one is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::IndexedHeap;
+    ///
+    /// let mut heap = IndexedHeap::new();
+    ///
+    /// assert_eq!(heap.push(4, 10), None);
+    /// assert_eq!(heap.push(7, 5), None);
+    /// assert_eq!(heap.push(4, 1), Some(10));
+    ///
+    /// assert_eq!(heap.peek_min(), Some((4, &1)));
+    /// ```
+    pub fn push(&mut self, key: usize, priority: P) -> Option<P> {
+        if self.contains(key) {
+            return self.change_priority(key, priority);
+        }
+
+        while self.positions.len() <= key {
+            self.positions.push(None);
+        }
+
+        let index = self.heap.len();
+        self.heap.push((key, priority));
+        self.positions[key] = Some(index);
+        self.sift_up(index);
+
+        None
+    }
+
+    /// Changes the priority of `key` in O(log n) and returns the old one, or
+    /// returns `None` without modifying the heap if the key is not present.
+    /// The new priority can be either smaller (decrease-key) or greater.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::IndexedHeap;
+    ///
+    /// let mut heap: IndexedHeap<u32> = [(0, 5), (1, 3)].into_iter().collect();
+    ///
+    /// assert_eq!(heap.change_priority(0, 1), Some(5));
+    /// assert_eq!(heap.change_priority(2, 1), None);
+    ///
+    /// assert_eq!(heap.pop_min(), Some((0, 1)));
+    /// ```
+    pub fn change_priority(&mut self, key: usize, priority: P) -> Option<P> {
+        let index = self.position(key)?;
+        let old = std::mem::replace(&mut self.heap[index].1, priority);
+        self.restore(index);
+
+        Some(old)
+    }
+
+    /// Removes and returns the key with the smallest priority and its
+    /// priority in O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::IndexedHeap;
+    ///
+    /// // Dijkstra on a small graph given as adjacency lists
+    /// let graph: [&[(usize, u32)]; 4] = [&[(1, 4), (2, 1)], &[(3, 1)], &[(1, 2), (3, 5)], &[]];
+    /// let mut distances = [u32::MAX; 4];
+    /// let mut heap = IndexedHeap::new();
+    ///
+    /// distances[0] = 0;
+    /// heap.push(0, 0);
+    ///
+    /// while let Some((node, distance)) = heap.pop_min() {
+    ///     for &(next, weight) in graph[node] {
+    ///         if distance + weight < distances[next] {
+    ///             distances[next] = distance + weight;
+    ///             heap.push(next, distance + weight);
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(distances, [0, 3, 1, 4]);
+    /// ```
+    pub fn pop_min(&mut self) -> Option<(usize, P)> {
+        if self.heap.is_empty() {
+            return None;
+        }
+
+        self.remove_at(0)
+    }
+
+    /// Removes `key` and returns its priority in O(log n), or `None` if the
+    /// key is not present.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::IndexedHeap;
+    ///
+    /// let mut heap: IndexedHeap<u32> = [(0, 5), (1, 3), (2, 4)].into_iter().collect();
+    ///
+    /// assert_eq!(heap.remove(1), Some(3));
+    /// assert_eq!(heap.remove(1), None);
+    ///
+    /// assert_eq!(heap.peek_min(), Some((2, &4)));
+    /// ```
+    pub fn remove(&mut self, key: usize) -> Option<P> {
+        let index = self.position(key)?;
+
+        self.remove_at(index).map(|(_, priority)| priority)
+    }
+
+    /// Replaces the entry at `index` with the last one and restores the heap.
+    fn remove_at(&mut self, index: usize) -> Option<(usize, P)> {
+        let last = self.heap.len() - 1;
+
+        self.swap(index, last);
+        let (key, priority) = self.heap.pop()?;
+        self.positions[key] = None;
+
+        if index < last {
+            self.restore(index);
+        }
+
+        Some((key, priority))
+    }
+}
+
+impl<P> Default for IndexedHeap<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: Ord> Extend<(usize, P)> for IndexedHeap<P> {
+    fn extend<I: IntoIterator<Item = (usize, P)>>(&mut self, iter: I) {
+        for (key, priority) in iter {
+            self.push(key, priority);
+        }
+    }
+}
+
+impl<P: Ord> FromIterator<(usize, P)> for IndexedHeap<P> {
+    fn from_iter<I: IntoIterator<Item = (usize, P)>>(iter: I) -> Self {
+        let mut heap = Self::new();
+        heap.extend(iter);
+
+        heap
+    }
+}
+
+impl<P: HeapSize> HeapSize for IndexedHeap<P> {
+    fn heap_size_of_children(&self) -> usize {
+        self.heap.heap_size_of_children() + self.positions.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IndexedHeap;
+
+    /// Checks the heap property and that every position points back to its
+    /// key.
+    fn assert_heap<P: Ord>(heap: &IndexedHeap<P>) {
+        for index in 1..heap.len() {
+            assert!(heap.heap[index].1 >= heap.heap[(index - 1) / 2].1);
+        }
+
+        for (index, (key, _)) in heap.heap.iter().enumerate() {
+            assert_eq!(heap.positions[*key], Some(index));
+        }
+
+        let present = heap.positions.iter().filter(|position| position.is_some());
+        assert_eq!(present.count(), heap.len());
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let mut heap = IndexedHeap::new();
+
+        for key in 0..200 {
+            assert_eq!(heap.push(key, (key * 7_919) % 200), None);
+            assert_heap(&heap);
+        }
+
+        assert_eq!(heap.len(), 200);
+        assert!(heap.contains(199));
+        assert!(!heap.contains(200));
+        assert_eq!(heap.priority(3), Some(&((3 * 7_919) % 200)));
+
+        let mut previous = 0;
+
+        while let Some((key, priority)) = heap.pop_min() {
+            assert!(priority >= previous);
+            assert!(!heap.contains(key));
+            assert_heap(&heap);
+            previous = priority;
+        }
+
+        assert_eq!(heap.peek_min(), None);
+    }
+
+    #[test]
+    fn test_change_priority_and_remove() {
+        let mut heap: IndexedHeap<i32> = (0..100).map(|key| (key, key as i32)).collect();
+
+        // Reverse the order by changing every priority
+        for key in 0..100 {
+            assert_eq!(heap.change_priority(key, -(key as i32)), Some(key as i32));
+            assert_heap(&heap);
+        }
+
+        assert_eq!(heap.peek_min(), Some((99, &-99)));
+
+        for key in (0..100).step_by(3) {
+            assert_eq!(heap.remove(key), Some(-(key as i32)));
+            assert_heap(&heap);
+        }
+
+        assert_eq!(heap.remove(0), None);
+        assert_eq!(heap.change_priority(0, 0), None);
+        assert_eq!(heap.pop_min(), Some((98, -98)));
+        assert_eq!(heap.iter().count(), 65);
+
+        heap.clear();
+        assert!(heap.is_empty());
+        assert!(!heap.contains(1));
+
+        heap.extend([(1, 1)]);
+        assert_eq!(heap.pop_min(), Some((1, 1)));
+    }
+}
@@ -0,0 +1,350 @@
+use std::ops::Range;
+
+use crate::{heap_size::HeapSize, tree_map::TreeMap};
+
+/// Map from half-open ranges of keys to values, like the mappings of an
+/// address space or the allocated blocks of a pool of IP addresses. Ranges
+/// never overlap: inserting a range overwrites whatever the map had under
+/// it, splitting the ranges that stick out, and removing a range cuts a
+/// hole in them.
+///
+/// Adjacent ranges with equal values are merged on insertion, so the map
+/// always holds the fewest ranges that describe it. Ranges are stored in a
+/// [`TreeMap`] by start, and every operation takes O(log n) time plus the
+/// number of ranges it overwrites.
+///
+/// # Examples
+///
+/// ```rust
+/// use collections_rust::IntervalMap;
+///
+/// let mut memory = IntervalMap::new();
+///
+/// memory.insert(0x1000..0x3000, "code");
+/// memory.insert(0x3000..0x5000, "code");
+/// memory.insert(0x2000..0x2800, "data");
+///
+/// assert_eq!(memory.get(&0x4000), Some(&"code"));
+/// assert_eq!(memory.get(&0x2400), Some(&"data"));
+/// assert_eq!(memory.get(&0x5000), None);
+///
+/// let ranges: Vec<_> = memory.iter().map(|(range, value)| (*range.start, *range.end, *value)).collect();
+/// assert_eq!(
+///     ranges,
+///     [(0x1000, 0x2000, "code"), (0x2000, 0x2800, "data"), (0x2800, 0x5000, "code")]
+/// );
+/// ```
+pub struct IntervalMap<K, V> {
+    /// End and value of every range, by start.
+    ranges: TreeMap<K, (K, V)>,
+}
+
+impl<K, V> IntervalMap<K, V> {
+    /// Creates an empty map.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::IntervalMap;
+    ///
+    /// let map: IntervalMap<u32, &str> = IntervalMap::new();
+    ///
+    /// assert!(map.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            ranges: TreeMap::new(),
+        }
+    }
+
+    /// Returns the number of disjoint ranges in the map.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Returns `true` if the map contains no ranges.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Removes all the ranges from the map.
+    pub fn clear(&mut self) {
+        self.ranges.clear();
+    }
+
+    /// Returns an iterator over the ranges of the map and their values in
+    /// ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = (Range<&K>, &V)> {
+        self.ranges
+            .iter()
+            .map(|(start, (end, value))| (start..end, value))
+    }
+}
+
+impl<K: Ord, V> IntervalMap<K, V> {
+    /// Returns the range that contains `key` and its value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert(10..20, 'a');
+    ///
+    /// assert_eq!(map.get_key_value(&15), Some((&10..&20, &'a')));
+    /// assert_eq!(map.get_key_value(&20), None);
+    /// ```
+    pub fn get_key_value(&self, key: &K) -> Option<(Range<&K>, &V)> {
+        let (start, (end, value)) = self.ranges.range(..=key).next_back()?;
+
+        (key < end).then_some((start..end, value))
+    }
+
+    /// Returns the value of the range that contains `key`.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.get_key_value(key).map(|(_, value)| value)
+    }
+
+    /// Returns `true` if some range contains `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get_key_value(key).is_some()
+    }
+
+    /// Returns an iterator over the ranges that overlap `range` in
+    /// ascending order. The first and last ones may stick out of it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::IntervalMap;
+    ///
+    /// let map: IntervalMap<_, _> = [(0..10, 'a'), (10..20, 'b'), (30..40, 'c')].into_iter().collect();
+    ///
+    /// let values: Vec<_> = map.overlapping(&(5..30)).map(|(_, value)| *value).collect();
+    /// assert_eq!(values, ['a', 'b']);
+    /// ```
+    pub fn overlapping<'a>(
+        &'a self,
+        range: &'a Range<K>,
+    ) -> impl Iterator<Item = (Range<&'a K>, &'a V)> {
+        // Only the last range starting before `range` can reach into it
+        let before = self
+            .ranges
+            .range(..&range.start)
+            .next_back()
+            .filter(|(_, (end, _))| *end > range.start);
+
+        before
+            .into_iter()
+            .chain(self.ranges.range(&range.start..&range.end))
+            .map(|(start, (end, value))| (start..end, value))
+    }
+}
+
+impl<K: Ord + Clone, V: Clone + PartialEq> IntervalMap<K, V> {
+    /// Removes everything the map has between `start` and `end`, splitting
+    /// the ranges that stick out of them.
+    fn cut(&mut self, start: &K, end: &K) {
+        let before = self
+            .ranges
+            .range(..start)
+            .next_back()
+            .filter(|(_, (before_end, _))| before_end > start)
+            .map(|(before_start, _)| before_start.clone());
+
+        if let Some(before_start) = before {
+            let (before_end, value) = self.ranges.remove(&before_start).unwrap();
+
+            if before_end > *end {
+                self.ranges.insert(end.clone(), (before_end, value.clone()));
+            }
+
+            self.ranges.insert(before_start, (start.clone(), value));
+        }
+
+        let inside: Vec<K> = self
+            .ranges
+            .range(start..end)
+            .map(|(inside_start, _)| inside_start.clone())
+            .collect();
+
+        for inside_start in inside {
+            let (inside_end, value) = self.ranges.remove(&inside_start).unwrap();
+
+            // Only the last range inside can stick out
+            if inside_end > *end {
+                self.ranges.insert(end.clone(), (inside_end, value));
+            }
+        }
+    }
+
+    /// Maps every key in `range` to `value`, overwriting the parts of other
+    /// ranges it covers and merging it with adjacent ranges of equal value.
+    /// Empty ranges are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert(0..10, 'a');
+    /// map.insert(20..30, 'a');
+    /// map.insert(10..20, 'a');
+    ///
+    /// assert_eq!(map.len(), 1);
+    /// assert_eq!(map.get_key_value(&25), Some((&0..&30, &'a')));
+    /// ```
+    pub fn insert(&mut self, range: Range<K>, value: V) {
+        if range.is_empty() {
+            return;
+        }
+
+        self.cut(&range.start, &range.end);
+
+        let Range { mut start, mut end } = range;
+
+        let before = self
+            .ranges
+            .range(..&start)
+            .next_back()
+            .filter(|(_, (before_end, before_value))| {
+                *before_end == start && *before_value == value
+            })
+            .map(|(before_start, _)| before_start.clone());
+
+        if let Some(before_start) = before {
+            self.ranges.remove(&before_start);
+            start = before_start;
+        }
+
+        if self
+            .ranges
+            .get(&end)
+            .is_some_and(|(_, after_value)| *after_value == value)
+        {
+            end = self.ranges.remove(&end).unwrap().0;
+        }
+
+        self.ranges.insert(start, (end, value));
+    }
+
+    /// Removes every key in `range` from the map, splitting the ranges that
+    /// stick out of it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert(0..100, 'a');
+    /// map.remove(40..60);
+    ///
+    /// assert_eq!(map.len(), 2);
+    /// assert_eq!(map.get(&39), Some(&'a'));
+    /// assert_eq!(map.get(&50), None);
+    /// assert_eq!(map.get(&60), Some(&'a'));
+    /// ```
+    pub fn remove(&mut self, range: Range<K>) {
+        if !range.is_empty() {
+            self.cut(&range.start, &range.end);
+        }
+    }
+}
+
+impl<K, V> Default for IntervalMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone, V: Clone + PartialEq> Extend<(Range<K>, V)> for IntervalMap<K, V> {
+    fn extend<I: IntoIterator<Item = (Range<K>, V)>>(&mut self, iter: I) {
+        for (range, value) in iter {
+            self.insert(range, value);
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone + PartialEq> FromIterator<(Range<K>, V)> for IntervalMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (Range<K>, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+
+        map
+    }
+}
+
+impl<K: HeapSize, V: HeapSize> HeapSize for IntervalMap<K, V> {
+    fn heap_size_of_children(&self) -> usize {
+        self.ranges.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IntervalMap;
+
+    #[test]
+    fn test_random_operations() {
+        const SIZE: usize = 64;
+
+        let mut map = IntervalMap::new();
+        let mut expected = [None; SIZE];
+        let mut seed: u64 = 0x2545_F491_4F6C_DD1D;
+
+        for _ in 0..2_000 {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+
+            let (a, b) = ((seed % SIZE as u64) as usize, (seed >> 8) as usize % SIZE);
+            let (start, end) = (a.min(b), a.max(b) + 1);
+            let value = (seed >> 16) % 3;
+
+            if (seed >> 24).is_multiple_of(3) {
+                map.remove(start..end);
+                expected[start..end].fill(None);
+            } else {
+                map.insert(start..end, value);
+                expected[start..end].fill(Some(value));
+            }
+
+            assert!((0..SIZE).all(|key| map.get(&key) == expected[key].as_ref()));
+
+            // Ranges are sorted, disjoint and never adjacent with equal values
+            let ranges: Vec<_> = map.iter().collect();
+            assert!(ranges.iter().all(|(range, _)| range.start < range.end));
+            assert!(ranges.windows(2).all(|pair| {
+                pair[0].0.end < pair[1].0.start
+                    || (pair[0].0.end == pair[1].0.start && pair[0].1 != pair[1].1)
+            }));
+        }
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let mut map = IntervalMap::new();
+        map.insert(0..10, 'a');
+        map.insert(10..20, 'b');
+        map.insert(25..30, 'c');
+
+        let overlapping = |start, end| -> Vec<char> {
+            map.overlapping(&(start..end))
+                .map(|(_, value)| *value)
+                .collect()
+        };
+
+        assert_eq!(overlapping(5, 26), ['a', 'b', 'c']);
+        assert_eq!(overlapping(10, 25), ['b']);
+        assert_eq!(overlapping(20, 25), []);
+        assert_eq!(overlapping(29, 100), ['c']);
+
+        map.remove(5..27);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get_key_value(&27), Some((&27..&30, &'c')));
+        assert!(!map.contains_key(&5));
+    }
+}
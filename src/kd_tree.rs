@@ -0,0 +1,309 @@
+use crate::{heap_size::HeapSize, vector::Vector};
+
+/// Point with `K` coordinates.
+type Point<const K: usize> = [f64; K];
+
+/// Squared euclidean distance, enough to compare distances without taking
+/// square roots.
+fn distance_squared<const K: usize>(a: &Point<K>, b: &Point<K>) -> f64 {
+    a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum()
+}
+
+/// K-dimensional tree that maps points with `K` coordinates to values, for
+/// spatial lookups like finding the closest point to a position or every
+/// point within some distance of it.
+///
+/// The tree is built once from all the points and stored implicitly in a
+/// single [`Vector`]: the median of every subslice along the axis of its
+/// depth is placed in the middle, with the points below it on the left and
+/// the rest on the right. Building takes O(n log n), and queries skip every
+/// subtree that can't contain a match, which takes O(log n) on average for
+/// [`KdTree::nearest`].
+///
+/// # Examples
+///
+/// ```rust
+/// use collections_rust::KdTree;
+///
+/// let cities: KdTree<2, &str> = [
+///     ([40.4, -3.7], "Madrid"),
+///     ([48.9, 2.4], "Paris"),
+///     ([52.5, 13.4], "Berlin"),
+///     ([41.9, 12.5], "Rome"),
+/// ]
+/// .into_iter()
+/// .collect();
+///
+/// assert_eq!(cities.nearest(&[45.8, 4.8]), Some((&[48.9, 2.4], &"Paris")));
+/// ```
+pub struct KdTree<const K: usize, V> {
+    entries: Vector<(Point<K>, V)>,
+}
+
+impl<const K: usize, V> KdTree<K, V> {
+    /// Builds a tree with the given points and values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `K` is zero or any coordinate is NaN.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::KdTree;
+    ///
+    /// let tree = KdTree::new(vec![([0.0, 0.0, 0.0], 'a'), ([1.0, 1.0, 1.0], 'b')]);
+    ///
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn new<I: IntoIterator<Item = (Point<K>, V)>>(points: I) -> Self {
+        assert!(K > 0, "Points need at least one dimension");
+
+        let mut entries = Vector::new();
+
+        for (point, value) in points {
+            assert!(
+                point.iter().all(|coordinate| !coordinate.is_nan()),
+                "NaN coordinate"
+            );
+            entries.push((point, value));
+        }
+
+        Self::build(&mut entries, 0);
+
+        Self { entries }
+    }
+
+    /// Places the median of `entries` along the axis of `depth` in the
+    /// middle and builds both halves recursively.
+    fn build(entries: &mut [(Point<K>, V)], depth: usize) {
+        if entries.len() <= 1 {
+            return;
+        }
+
+        let axis = depth % K;
+        let middle = entries.len() / 2;
+
+        entries.select_nth_unstable_by(middle, |a, b| a.0[axis].total_cmp(&b.0[axis]));
+
+        let (left, right) = entries.split_at_mut(middle);
+        Self::build(left, depth + 1);
+        Self::build(&mut right[1..], depth + 1);
+    }
+
+    /// Returns the number of points in the tree.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the tree contains no points.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the point closest to `target` and its value, or `None` if
+    /// the tree is empty. Ties are broken arbitrarily.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::KdTree;
+    ///
+    /// let tree: KdTree<1, usize> = (0..10).map(|i| ([i as f64 * 10.0], i)).collect();
+    ///
+    /// assert_eq!(tree.nearest(&[44.0]), Some((&[40.0], &4)));
+    /// assert_eq!(tree.nearest(&[1000.0]), Some((&[90.0], &9)));
+    /// ```
+    pub fn nearest(&self, target: &Point<K>) -> Option<(&Point<K>, &V)> {
+        let mut best = None;
+        Self::nearest_in(&self.entries, 0, target, &mut best);
+
+        best.map(|(_, (point, value))| (point, value))
+    }
+
+    fn nearest_in<'a>(
+        entries: &'a [(Point<K>, V)],
+        depth: usize,
+        target: &Point<K>,
+        best: &mut Option<(f64, &'a (Point<K>, V))>,
+    ) {
+        if entries.is_empty() {
+            return;
+        }
+
+        let middle = entries.len() / 2;
+        let entry = &entries[middle];
+        let distance = distance_squared(&entry.0, target);
+
+        if best.is_none_or(|(best, _)| distance < best) {
+            *best = Some((distance, entry));
+        }
+
+        let axis = depth % K;
+        let offset = target[axis] - entry.0[axis];
+
+        let (near, far) = if offset < 0.0 {
+            (&entries[..middle], &entries[middle + 1..])
+        } else {
+            (&entries[middle + 1..], &entries[..middle])
+        };
+
+        Self::nearest_in(near, depth + 1, target, best);
+
+        // The far side can only have a closer point if the splitting plane
+        // is closer than the best point found so far
+        if best.is_some_and(|(best, _)| offset * offset < best) {
+            Self::nearest_in(far, depth + 1, target, best);
+        }
+    }
+
+    /// Returns every point whose distance to `target` is at most `radius`
+    /// along with its value, in no particular order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::KdTree;
+    ///
+    /// let tree: KdTree<2, char> = [([0.0, 0.0], 'a'), ([3.0, 4.0], 'b'), ([6.0, 8.0], 'c')]
+    ///     .into_iter()
+    ///     .collect();
+    ///
+    /// let mut found: Vec<char> = tree
+    ///     .within_radius(&[0.0, 0.0], 5.0)
+    ///     .into_iter()
+    ///     .map(|(_, value)| *value)
+    ///     .collect();
+    /// found.sort();
+    ///
+    /// assert_eq!(found, ['a', 'b']);
+    /// ```
+    pub fn within_radius(&self, target: &Point<K>, radius: f64) -> Vec<(&Point<K>, &V)> {
+        let mut found = Vec::new();
+        Self::within_radius_in(&self.entries, 0, target, radius, &mut found);
+
+        found
+    }
+
+    fn within_radius_in<'a>(
+        entries: &'a [(Point<K>, V)],
+        depth: usize,
+        target: &Point<K>,
+        radius: f64,
+        found: &mut Vec<(&'a Point<K>, &'a V)>,
+    ) {
+        if entries.is_empty() {
+            return;
+        }
+
+        let middle = entries.len() / 2;
+        let (point, value) = &entries[middle];
+
+        if distance_squared(point, target) <= radius * radius {
+            found.push((point, value));
+        }
+
+        let axis = depth % K;
+        let offset = target[axis] - point[axis];
+
+        // Points on the left are never after the median along the axis and
+        // points on the right never before it
+        if offset <= radius {
+            Self::within_radius_in(&entries[..middle], depth + 1, target, radius, found);
+        }
+
+        if offset >= -radius {
+            Self::within_radius_in(&entries[middle + 1..], depth + 1, target, radius, found);
+        }
+    }
+
+    /// Returns an iterator over the points and values in no particular
+    /// order.
+    pub fn iter(&self) -> impl Iterator<Item = (&Point<K>, &V)> {
+        self.entries.iter().map(|(point, value)| (point, value))
+    }
+}
+
+impl<const K: usize, V> FromIterator<(Point<K>, V)> for KdTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (Point<K>, V)>>(iter: I) -> Self {
+        Self::new(iter)
+    }
+}
+
+impl<const K: usize, V: HeapSize> HeapSize for KdTree<K, V> {
+    fn heap_size_of_children(&self) -> usize {
+        self.entries.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{distance_squared, KdTree};
+
+    /// Deterministic points spread over `[0, 100)` in every axis.
+    fn points<const K: usize>(count: usize, mut seed: u64) -> Vec<[f64; K]> {
+        (0..count)
+            .map(|_| {
+                std::array::from_fn(|_| {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 7;
+                    seed ^= seed << 17;
+                    (seed % 10_000) as f64 / 100.0
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_nearest() {
+        let data = points::<3>(2_000, 0x2545_F491_4F6C_DD1D);
+        let tree: KdTree<3, usize> = data.iter().copied().zip(0..).collect();
+
+        assert_eq!(tree.len(), data.len());
+
+        for target in points::<3>(200, 42) {
+            let (point, index) = tree.nearest(&target).unwrap();
+            let closest = data
+                .iter()
+                .map(|point| distance_squared(point, &target))
+                .fold(f64::INFINITY, f64::min);
+
+            assert_eq!(data[*index], *point);
+            assert_eq!(distance_squared(point, &target), closest);
+        }
+
+        let empty: KdTree<2, ()> = KdTree::new([]);
+        assert!(empty.nearest(&[0.0, 0.0]).is_none());
+    }
+
+    #[test]
+    fn test_within_radius() {
+        let data = points::<2>(1_000, 7);
+        let tree: KdTree<2, usize> = data.iter().copied().zip(0..).collect();
+
+        for (target, radius) in points::<2>(50, 99)
+            .into_iter()
+            .zip([0.0, 1.0, 5.0, 20.0].iter().cycle())
+        {
+            let mut found: Vec<usize> = tree
+                .within_radius(&target, *radius)
+                .into_iter()
+                .map(|(_, index)| *index)
+                .collect();
+            found.sort();
+
+            let expected: Vec<usize> = (0..data.len())
+                .filter(|&index| distance_squared(&data[index], &target) <= radius * radius)
+                .collect();
+
+            assert_eq!(found, expected);
+        }
+
+        // Points exactly on the boundary and on the splitting planes count
+        let grid: KdTree<2, ()> = (0..5)
+            .flat_map(|x| (0..5).map(move |y| ([x as f64, y as f64], ())))
+            .collect();
+        assert_eq!(grid.within_radius(&[2.0, 2.0], 1.0).len(), 5);
+        assert_eq!(grid.iter().count(), 25);
+    }
+}
@@ -1,9 +1,225 @@
+mod allocator;
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls;
+#[cfg(feature = "binary_tree")]
+mod arena;
+#[cfg(feature = "array_deque")]
+mod array_deque;
+#[cfg(feature = "binary_heap")]
+mod binary_heap;
+#[cfg(feature = "binary_multiset")]
+mod binary_multiset;
+#[cfg(feature = "binary_tree")]
+mod binary_tree;
+#[cfg(feature = "bit_vec")]
+mod bit_vec;
+#[cfg(feature = "bounded_queue")]
+mod bounded_queue;
+#[cfg(feature = "btree_map")]
+mod btree_map;
+#[cfg(feature = "btree_set")]
+mod btree_set;
+#[cfg(feature = "circular_buffer")]
+mod circular_buffer;
+mod compare;
+#[cfg(feature = "concurrent_hash_map")]
+mod concurrent_hash_map;
+#[cfg(feature = "concurrent_skip_map")]
+mod concurrent_skip_map;
+#[cfg(feature = "counter")]
+mod counter;
+#[cfg(feature = "cow_vector")]
+mod cow_vector;
+#[cfg(feature = "cuckoo_filter")]
+mod cuckoo_filter;
+#[cfg(feature = "deque")]
 mod dequeue;
+#[cfg(any(
+    feature = "binary_tree",
+    feature = "deque",
+    feature = "linked_list",
+    feature = "queue",
+    feature = "vector"
+))]
+mod drop_guard;
+#[cfg(any(feature = "deque", feature = "queue", feature = "vector"))]
+mod equality;
+#[cfg(feature = "fixed_bit_set")]
+mod fixed_bit_set;
+#[cfg(feature = "graph")]
+mod graph;
+#[cfg(feature = "grid")]
+mod grid;
+#[cfg(feature = "hash_set")]
+mod hash_set;
+mod heap_size;
+#[cfg(feature = "index_map")]
+mod index_map;
+#[cfg(feature = "indexed_heap")]
+mod indexed_heap;
+#[cfg(feature = "interval_map")]
+mod interval_map;
+#[cfg(feature = "kd_tree")]
+mod kd_tree;
+#[cfg(feature = "linked_list")]
+mod linked_list;
+#[cfg(feature = "list")]
+mod list;
+#[cfg(feature = "lru_cache")]
+mod lru_cache;
+#[cfg(feature = "min_max_heap")]
+mod min_max_heap;
+#[cfg(feature = "persistent_tree")]
+mod persistent_tree;
+#[cfg(feature = "persistent_vec")]
+mod persistent_vec;
+#[cfg(feature = "quad_tree")]
+mod quad_tree;
+#[cfg(feature = "queue")]
 mod queue;
+#[cfg(feature = "r_tree")]
+mod r_tree;
+#[cfg(feature = "rope")]
+mod rope;
+#[cfg(feature = "scapegoat_tree")]
+mod scapegoat_tree;
+#[cfg(feature = "segment_tree")]
+mod segment_tree;
+#[cfg(feature = "skip_map")]
+mod skip_map;
+#[cfg(feature = "skip_set")]
+mod skip_set;
+#[cfg(feature = "slot_map")]
+mod slot_map;
+#[cfg(feature = "sorted_vec")]
+mod sorted_vec;
+#[cfg(feature = "sparse_set")]
+mod sparse_set;
+#[cfg(feature = "sparse_table")]
+mod sparse_table;
+#[cfg(feature = "splay_tree")]
+mod splay_tree;
+mod stats;
+#[cfg(feature = "ternary_search_tree")]
+mod ternary_search_tree;
+#[cfg(feature = "timer_wheel")]
+mod timer_wheel;
+pub mod traits;
+#[cfg(feature = "treap")]
+mod treap;
+#[cfg(feature = "tree_map")]
+mod tree_map;
+#[cfg(feature = "treiber_stack")]
+mod treiber_stack;
+mod try_reserve_error;
+#[cfg(feature = "vector")]
 mod vector;
-mod binary_tree;
+#[cfg(feature = "weak_value_map")]
+mod weak_value_map;
 
-pub use dequeue::Dequeue;
+pub use allocator::{Allocator, Global};
+#[cfg(feature = "array_deque")]
+pub use array_deque::ArrayDeque;
+#[cfg(feature = "binary_heap")]
+pub use binary_heap::BinaryHeap;
+#[cfg(feature = "binary_multiset")]
+pub use binary_multiset::BinaryMultiSet;
+#[cfg(feature = "binary_tree")]
+pub use binary_tree::{BinaryTree, DiffItem, InvariantError};
+#[cfg(feature = "bit_vec")]
+pub use bit_vec::BitVec;
+#[cfg(feature = "bounded_queue")]
+pub use bounded_queue::BoundedQueue;
+#[cfg(feature = "btree_map")]
+pub use btree_map::BTreeMap;
+#[cfg(feature = "btree_set")]
+pub use btree_set::BTreeSet;
+#[cfg(feature = "circular_buffer")]
+pub use circular_buffer::CircularBuffer;
+pub use compare::{Compare, Natural};
+#[cfg(feature = "concurrent_hash_map")]
+pub use concurrent_hash_map::ConcurrentHashMap;
+#[cfg(feature = "concurrent_skip_map")]
+pub use concurrent_skip_map::ConcurrentSkipMap;
+#[cfg(feature = "counter")]
+pub use counter::Counter;
+#[cfg(feature = "cow_vector")]
+pub use cow_vector::CowVector;
+#[cfg(feature = "cuckoo_filter")]
+pub use cuckoo_filter::CuckooFilter;
+#[cfg(feature = "deque")]
+pub use dequeue::{Dequeue, FrozenDeque};
+#[cfg(feature = "fixed_bit_set")]
+pub use fixed_bit_set::FixedBitSet;
+#[cfg(feature = "graph")]
+pub use graph::Graph;
+#[cfg(feature = "grid")]
+pub use grid::Grid;
+#[cfg(feature = "hash_set")]
+pub use hash_set::HashSet;
+pub use heap_size::HeapSize;
+#[cfg(feature = "index_map")]
+pub use index_map::IndexMap;
+#[cfg(feature = "indexed_heap")]
+pub use indexed_heap::IndexedHeap;
+#[cfg(feature = "interval_map")]
+pub use interval_map::IntervalMap;
+#[cfg(feature = "kd_tree")]
+pub use kd_tree::KdTree;
+#[cfg(feature = "linked_list")]
+pub use linked_list::LinkedList;
+#[cfg(feature = "list")]
+pub use list::List;
+#[cfg(feature = "lru_cache")]
+pub use lru_cache::LruCache;
+#[cfg(feature = "min_max_heap")]
+pub use min_max_heap::MinMaxHeap;
+#[cfg(feature = "persistent_tree")]
+pub use persistent_tree::PersistentTree;
+#[cfg(feature = "persistent_vec")]
+pub use persistent_vec::PersistentVec;
+#[cfg(feature = "quad_tree")]
+pub use quad_tree::{QuadTree, Rect};
+#[cfg(feature = "queue")]
 pub use queue::Queue;
-pub use vector::Vector;
-pub use binary_tree::BinaryTree;
+#[cfg(feature = "r_tree")]
+pub use r_tree::RTree;
+#[cfg(feature = "rope")]
+pub use rope::Rope;
+#[cfg(feature = "scapegoat_tree")]
+pub use scapegoat_tree::ScapegoatTree;
+#[cfg(feature = "segment_tree")]
+pub use segment_tree::{
+    Action, Gcd, Idempotent, Max, Min, Monoid, NoUpdate, RangeAdd, SegmentTree, Sum,
+};
+#[cfg(feature = "skip_map")]
+pub use skip_map::SkipMap;
+#[cfg(feature = "skip_set")]
+pub use skip_set::SkipSet;
+#[cfg(feature = "slot_map")]
+pub use slot_map::{SlotKey, SlotMap};
+#[cfg(feature = "sorted_vec")]
+pub use sorted_vec::SortedVec;
+#[cfg(feature = "sparse_set")]
+pub use sparse_set::SparseSet;
+#[cfg(feature = "sparse_table")]
+pub use sparse_table::SparseTable;
+#[cfg(feature = "splay_tree")]
+pub use splay_tree::SplayTree;
+#[cfg(feature = "stats")]
+pub use stats::Stats;
+#[cfg(feature = "ternary_search_tree")]
+pub use ternary_search_tree::TernarySearchTree;
+#[cfg(feature = "timer_wheel")]
+pub use timer_wheel::TimerWheel;
+#[cfg(feature = "treap")]
+pub use treap::Treap;
+#[cfg(feature = "tree_map")]
+pub use tree_map::{Entry, TreeMap};
+#[cfg(feature = "treiber_stack")]
+pub use treiber_stack::TreiberStack;
+pub use try_reserve_error::TryReserveError;
+#[cfg(feature = "vector")]
+pub use vector::{FrozenVector, Vector};
+#[cfg(feature = "weak_value_map")]
+pub use weak_value_map::WeakValueMap;
@@ -0,0 +1,381 @@
+use std::{
+    fmt::{self, Debug, Display},
+    marker::PhantomData,
+    mem,
+    ptr::NonNull,
+};
+
+use crate::{
+    drop_guard,
+    heap_size::{self, HeapSize},
+};
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    value: T,
+    next: Link<T>,
+}
+
+/// Singly linked list that owns its nodes. Values can be added at both
+/// ends in O(1), since the list keeps a pointer to its last node, but only
+/// removed from the front. See [`crate::Dequeue`] for a list that can be
+/// walked and modified from both ends, or [`crate::List`] for an immutable
+/// one that shares its nodes.
+///
+/// # Examples
+///
+/// ```rust
+/// use collections_rust::LinkedList;
+///
+/// let mut list = LinkedList::new();
+///
+/// list.append(2);
+/// list.append(3);
+/// list.prepend(1);
+///
+/// assert_eq!(list.to_string(), "[1, 2, 3]");
+/// assert_eq!(list.pop(), Some(1));
+/// ```
+pub struct LinkedList<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<Box<Node<T>>>,
+}
+
+// The list owns its nodes like a chain of `Box`es, so it can move to another
+// thread with its values.
+unsafe impl<T: Send> Send for LinkedList<T> {}
+
+pub struct IntoIter<T>(LinkedList<T>);
+
+pub struct Iter<'a, T> {
+    next: Link<T>,
+    marker: PhantomData<&'a T>,
+}
+
+pub struct IterMut<'a, T> {
+    next: Link<T>,
+    marker: PhantomData<&'a mut T>,
+}
+
+impl<T> LinkedList<T> {
+    /// Creates an empty list.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::LinkedList;
+    ///
+    /// let list: LinkedList<i32> = LinkedList::new();
+    ///
+    /// assert!(list.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of values in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the list contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Adds `value` at the end of the list.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::LinkedList;
+    ///
+    /// let mut list = LinkedList::new();
+    ///
+    /// list.append(1);
+    /// list.append(2);
+    ///
+    /// assert!(list.iter().eq(&[1, 2]));
+    /// ```
+    pub fn append(&mut self, value: T) {
+        let node = Self::new_node(value, None);
+
+        match self.tail {
+            Some(tail) => unsafe { (*tail.as_ptr()).next = Some(node) },
+            None => self.head = Some(node),
+        }
+
+        self.tail = Some(node);
+        self.len += 1;
+    }
+
+    /// Adds `value` at the start of the list.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::LinkedList;
+    ///
+    /// let mut list = LinkedList::new();
+    ///
+    /// list.prepend(1);
+    /// list.prepend(2);
+    ///
+    /// assert!(list.iter().eq(&[2, 1]));
+    /// ```
+    pub fn prepend(&mut self, value: T) {
+        let node = Self::new_node(value, self.head);
+
+        if self.tail.is_none() {
+            self.tail = Some(node);
+        }
+
+        self.head = Some(node);
+        self.len += 1;
+    }
+
+    /// Removes the first value of the list and returns it, or `None` if the
+    /// list is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::LinkedList;
+    ///
+    /// let mut list: LinkedList<_> = [1, 2].into_iter().collect();
+    ///
+    /// assert_eq!(list.pop(), Some(1));
+    /// assert_eq!(list.pop(), Some(2));
+    /// assert_eq!(list.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        self.head.map(|head| {
+            let head = unsafe { Box::from_raw(head.as_ptr()) };
+
+            self.head = head.next;
+
+            if self.head.is_none() {
+                self.tail = None;
+            }
+
+            self.len -= 1;
+
+            head.value
+        })
+    }
+
+    /// Returns a reference to the first value of the list.
+    pub fn peek(&self) -> Option<&T> {
+        self.head.map(|head| unsafe { &(*head.as_ptr()).value })
+    }
+
+    /// Returns a mutable reference to the first value of the list.
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.head.map(|head| unsafe { &mut (*head.as_ptr()).value })
+    }
+
+    /// Returns an iterator over the values of the list, from first to last.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over mutable references to the values of the
+    /// list, from first to last.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: self.head,
+            marker: PhantomData,
+        }
+    }
+
+    fn new_node(value: T, next: Link<T>) -> NonNull<Node<T>> {
+        NonNull::from(Box::leak(Box::new(Node { value, next })))
+    }
+}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        drop_guard::drop_each(&mut || self.pop());
+    }
+}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+
+        list
+    }
+}
+
+impl<T> Extend<T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|value| self.append(value));
+    }
+}
+
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| unsafe {
+            let node = &*node.as_ptr();
+            self.next = node.next;
+
+            &node.value
+        })
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| unsafe {
+            let node = &mut *node.as_ptr();
+            self.next = node.next;
+
+            &mut node.value
+        })
+    }
+}
+
+impl<T: PartialEq> PartialEq for LinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for LinkedList<T> {}
+
+impl<T: Clone> Clone for LinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: Debug> Debug for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+/// Formats the values like a slice, `[1, 2, 3]`.
+impl<T: Display> Display for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
+
+        for (index, value) in self.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+
+            write!(f, "{value}")?;
+        }
+
+        write!(f, "]")
+    }
+}
+
+impl<T: HeapSize> HeapSize for LinkedList<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.len * mem::size_of::<Node<T>>() + heap_size::children(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LinkedList;
+
+    #[test]
+    fn test_both_ends() {
+        let mut list = LinkedList::new();
+        assert_eq!(list.pop(), None);
+
+        list.prepend(2);
+        list.append(3);
+        list.prepend(1);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.to_string(), "[1, 2, 3]");
+
+        // Emptying the list resets the tail, so appending works again
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.pop(), Some(2));
+        assert_eq!(list.pop(), Some(3));
+        assert_eq!(list.pop(), None);
+        assert_eq!(list.to_string(), "[]");
+
+        list.append(4);
+        list.prepend(5);
+        *list.peek_mut().unwrap() *= 10;
+        list.iter_mut().for_each(|value| *value += 1);
+
+        assert_eq!(list.peek(), Some(&51));
+        assert_eq!(list.clone(), [51, 5].into_iter().collect());
+        assert!(list.into_iter().eq([51, 5]));
+    }
+
+    #[test]
+    fn test_drop_long_list() {
+        let list: LinkedList<u32> = (0..1_000_000).collect();
+
+        assert_eq!(list.len(), 1_000_000);
+        assert_eq!(list.iter().last(), Some(&999_999));
+    }
+}
@@ -0,0 +1,281 @@
+use std::{fmt, rc::Rc};
+
+use crate::heap_size::{self, HeapSize};
+
+type Link<T> = Option<Rc<Node<T>>>;
+
+struct Node<T> {
+    value: T,
+    next: Link<T>,
+}
+
+/// Persistent singly linked list. Lists are never modified in place:
+/// [`List::cons`] returns a new list whose tail is the original one, and
+/// [`List::tail`] returns the list after the head, both in O(1). Nodes are
+/// reference counted and shared by all the lists that contain them, so many
+/// versions of a list can be kept alive for the cost of the nodes they don't
+/// have in common.
+///
+/// Cloning a list is O(1) as well, since it only increments the reference
+/// count of the first node.
+pub struct List<T> {
+    head: Link<T>,
+    len: usize,
+}
+
+impl<T> List<T> {
+    /// Creates an empty list.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::List;
+    ///
+    /// let list: List<i32> = List::new();
+    ///
+    /// assert!(list.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self { head: None, len: 0 }
+    }
+
+    /// Returns the number of values in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the list contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a new list with `value` in front of the values of this one,
+    /// which is left untouched and shares all its nodes with the new list.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::List;
+    ///
+    /// let tail = List::new().cons(3).cons(2);
+    /// let a = tail.cons(1);
+    /// let b = tail.cons(0);
+    ///
+    /// assert!(a.iter().eq(&[1, 2, 3]));
+    /// assert!(b.iter().eq(&[0, 2, 3]));
+    /// assert!(tail.iter().eq(&[2, 3]));
+    /// ```
+    pub fn cons(&self, value: T) -> Self {
+        Self {
+            head: Some(Rc::new(Node {
+                value,
+                next: self.head.clone(),
+            })),
+            len: self.len + 1,
+        }
+    }
+
+    /// Returns the first value, or `None` if the list is empty.
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.value)
+    }
+
+    /// Returns the list of every value after the first one, or `None` if the
+    /// list is empty. The tail shares its nodes with this list.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::List;
+    ///
+    /// let list: List<i32> = [1, 2, 3].into_iter().collect();
+    /// let tail = list.tail().unwrap();
+    ///
+    /// assert_eq!(tail.head(), Some(&2));
+    /// assert_eq!(tail.len(), 2);
+    /// assert!(List::<i32>::new().tail().is_none());
+    /// ```
+    pub fn tail(&self) -> Option<Self> {
+        self.head.as_ref().map(|node| Self {
+            head: node.next.clone(),
+            len: self.len - 1,
+        })
+    }
+
+    /// Returns `true` if both lists start at the same node, which means they
+    /// contain the same values without comparing them.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns a new list with the values of this one in reverse order. The
+    /// values are cloned since no node can be shared, which takes O(n).
+    pub fn reverse(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.iter()
+            .fold(Self::new(), |list, value| list.cons(value.clone()))
+    }
+
+    /// Returns an iterator over the values from head to end.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Clone for List<T> {
+    /// Returns a list that shares all its nodes with this one. O(1).
+    fn clone(&self) -> Self {
+        Self {
+            head: self.head.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for List<T> {
+    /// Drops the nodes that no other list shares in a loop, since letting
+    /// each node drop the next one would overflow the stack on long lists.
+    fn drop(&mut self) {
+        let mut link = self.head.take();
+
+        while let Some(node) = link {
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => link = node.next.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && (self.ptr_eq(other) || self.iter().eq(other.iter()))
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T> FromIterator<T> for List<T> {
+    /// Builds a list with the values in iteration order, so the first value
+    /// yielded becomes the head.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let values: Vec<T> = iter.into_iter().collect();
+
+        values
+            .into_iter()
+            .rev()
+            .fold(Self::new(), |list, value| list.cons(value))
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the values of a [`List`].
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next?;
+        self.next = node.next.as_deref();
+        self.len -= 1;
+
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+/// Nodes shared with other lists are counted by every one of them.
+impl<T: HeapSize> HeapSize for List<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.len * heap_size::rc_allocation::<Node<T>>() + heap_size::children(self.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::List;
+
+    #[test]
+    fn test_sharing() {
+        let base: List<i32> = (0..10).collect();
+        let mut versions = Vec::new();
+        let mut current = base.clone();
+
+        for value in 10..20 {
+            current = current.cons(value);
+            versions.push(current.clone());
+        }
+
+        assert_eq!(base.len(), 10);
+        assert!(base.iter().copied().eq(0..10));
+
+        for (index, version) in versions.iter().enumerate() {
+            assert_eq!(version.len(), 11 + index);
+            assert_eq!(version.head(), Some(&(10 + index as i32)));
+
+            // Following the tails of every version leads to the same nodes
+            let mut tail = version.clone();
+            for _ in 0..=index {
+                tail = tail.tail().unwrap();
+            }
+            assert!(tail.ptr_eq(&base));
+        }
+
+        assert_eq!(versions[3].tail().unwrap(), versions[2]);
+        assert_ne!(versions[3], versions[2]);
+        assert!(current.iter().copied().eq((10..20).rev().chain(0..10)));
+        assert!(current
+            .reverse()
+            .iter()
+            .copied()
+            .eq((0..10).rev().chain(10..20)));
+    }
+
+    #[test]
+    fn test_drop_long_list() {
+        let list: List<u32> = (0..1_000_000).collect();
+        let shared = list.tail().unwrap();
+
+        drop(list);
+        assert_eq!(shared.len(), 999_999);
+        assert_eq!(shared.head(), Some(&1));
+    }
+}
@@ -0,0 +1,290 @@
+use std::hash::Hash;
+
+use crate::{
+    dequeue::{self, Dequeue, Handle},
+    heap_size::HeapSize,
+    index_map::IndexMap,
+};
+
+/// Map with a fixed capacity that evicts the least recently used entry when
+/// a new one doesn't fit. Entries are kept in a [`Dequeue`] ordered from most
+/// to least recently used, and an [`IndexMap`] stores the handle of the node
+/// of each key, so lookups, promotions and evictions all take O(1).
+pub struct LruCache<K, V> {
+    map: IndexMap<K, Handle<(K, V)>>,
+    list: Dequeue<(K, V)>,
+    capacity: usize,
+}
+
+impl<K, V> LruCache<K, V> {
+    /// Creates an empty cache that holds up to `capacity` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::LruCache;
+    ///
+    /// let cache: LruCache<&str, i32> = LruCache::new(2);
+    ///
+    /// assert!(cache.is_empty());
+    /// assert_eq!(cache.capacity(), 2);
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "LruCache capacity must be greater than zero");
+
+        Self {
+            map: IndexMap::new(),
+            list: Dequeue::new(),
+            capacity,
+        }
+    }
+
+    /// Returns the number of entries in the cache.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns `true` if the cache contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Returns the maximum number of entries the cache can hold.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Removes all the entries from the cache.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.list.clear();
+    }
+
+    /// Returns an iterator over the entries from most to least recently
+    /// used. Doesn't change the order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter(self.list.iter())
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// Returns a reference to the value of `key` and marks it as the most
+    /// recently used entry.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    ///
+    /// cache.insert("a", 1);
+    /// cache.insert("b", 2);
+    ///
+    /// // "a" becomes the most recently used, so "b" is evicted
+    /// assert_eq!(cache.get(&"a"), Some(&1));
+    /// cache.insert("c", 3);
+    ///
+    /// assert_eq!(cache.get(&"b"), None);
+    /// ```
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let handle = *self.map.get(key)?;
+
+        unsafe {
+            self.list.move_to_front(handle);
+
+            Some(&self.list.get_handle(handle).1)
+        }
+    }
+
+    /// Returns a mutable reference to the value of `key` and marks it as the
+    /// most recently used entry.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let handle = *self.map.get(key)?;
+
+        unsafe {
+            self.list.move_to_front(handle);
+
+            Some(&mut self.list.get_handle_mut(handle).1)
+        }
+    }
+
+    /// Returns a reference to the value of `key` without changing the order.
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        let handle = *self.map.get(key)?;
+
+        unsafe { Some(&self.list.get_handle(handle).1) }
+    }
+
+    /// Returns `true` if the cache contains `key`. Doesn't change the order.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Inserts `value` with `key` as the most recently used entry. If the key
+    /// was already present its value is replaced and the old one is
+    /// returned, otherwise if the cache was full the least recently used
+    /// entry is evicted and returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    ///
+    /// assert_eq!(cache.insert(1, "a"), None);
+    /// assert_eq!(cache.insert(2, "b"), None);
+    /// assert_eq!(cache.insert(1, "c"), Some((1, "a")));
+    /// assert_eq!(cache.insert(3, "d"), Some((2, "b")));
+    ///
+    /// assert!(cache.iter().eq([(&3, &"d"), (&1, &"c")]));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<(K, V)> {
+        if let Some(&handle) = self.map.get(&key) {
+            unsafe {
+                self.list.move_to_front(handle);
+                let old = std::mem::replace(&mut self.list.get_handle_mut(handle).1, value);
+
+                return Some((key, old));
+            }
+        }
+
+        let evicted = if self.list.len() == self.capacity {
+            self.pop_lru()
+        } else {
+            None
+        };
+
+        let handle = self.list.push_front_handle((key.clone(), value));
+        self.map.insert(key, handle);
+
+        evicted
+    }
+
+    /// Removes `key` and returns its value.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let handle = self.map.swap_remove(key)?;
+
+        unsafe { Some(self.list.remove_handle(handle).1) }
+    }
+
+    /// Removes and returns the least recently used entry.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let (key, value) = self.list.pop_back()?;
+        self.map.swap_remove(&key);
+
+        Some((key, value))
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a LruCache<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the entries of an [`LruCache`] from most to least recently
+/// used.
+pub struct Iter<'a, K, V>(dequeue::Iter<'a, (K, V)>);
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, value)| (key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(key, value)| (key, value))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<K: HeapSize, V: HeapSize> HeapSize for LruCache<K, V> {
+    fn heap_size_of_children(&self) -> usize {
+        self.map.heap_size_of_children() + self.list.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LruCache;
+
+    #[test]
+    fn test_eviction_order() {
+        let mut cache = LruCache::new(3);
+
+        for key in 0..3 {
+            cache.insert(key, key * 10);
+        }
+
+        // Order is now 2, 1, 0, touching 0 and 1 leaves 2 as the oldest
+        assert_eq!(cache.get(&0), Some(&0));
+        assert_eq!(cache.get(&1), Some(&10));
+        assert_eq!(cache.peek(&2), Some(&20));
+
+        assert_eq!(cache.insert(3, 30), Some((2, 20)));
+        assert!(cache.iter().map(|(key, _)| *key).eq([3, 1, 0]));
+        assert!(cache.iter().rev().map(|(key, _)| *key).eq([0, 1, 3]));
+
+        *cache.get_mut(&0).unwrap() += 1;
+        assert_eq!(cache.insert(4, 40), Some((1, 10)));
+        assert_eq!(cache.pop_lru(), Some((3, 30)));
+        assert_eq!(cache.len(), 2);
+        assert!(!cache.contains_key(&3));
+        assert_eq!(cache.peek(&0), Some(&1));
+    }
+
+    #[test]
+    fn test_remove_and_reuse() {
+        let mut cache = LruCache::new(100);
+
+        for key in 0..1_000 {
+            cache.insert(key, key.to_string());
+
+            if key % 3 == 0 {
+                assert_eq!(cache.remove(&key), Some(key.to_string()));
+            }
+        }
+
+        // Inserting 999 evicted an entry before removing 999 itself
+        assert_eq!(cache.len(), 99);
+        assert_eq!(cache.remove(&0), None);
+
+        // Only the most recent keys not divisible by 3 remain
+        let mut keys: Vec<i32> = cache.iter().map(|(key, _)| *key).collect();
+        keys.reverse();
+        let expected: Vec<i32> = (0..1_000).filter(|key| key % 3 != 0).collect();
+        assert_eq!(keys, expected[expected.len() - 99..]);
+
+        for (key, value) in &cache {
+            assert_eq!(*value, key.to_string());
+        }
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.get(&999), None);
+        assert_eq!(cache.pop_lru(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_capacity() {
+        LruCache::<i32, i32>::new(0);
+    }
+}
@@ -0,0 +1,402 @@
+use std::slice;
+
+use crate::{heap_size::HeapSize, vector::Vector};
+
+/// Double-ended priority queue implemented as a min-max heap stored in a
+/// [`Vector`]. Levels alternate between min levels (the root's level is one)
+/// and max levels: a value on a min level is smaller than or equal to all its
+/// descendants, and a value on a max level is greater than or equal to all
+/// its descendants. The minimum is the root and the maximum is one of its
+/// children, so both ends can be read in O(1) and removed in O(log n).
+pub struct MinMaxHeap<T> {
+    data: Vector<T>,
+}
+
+/// Returns `true` if the value at `index` is on a min level.
+fn is_min_level(index: usize) -> bool {
+    (index + 1).ilog2().is_multiple_of(2)
+}
+
+impl<T> MinMaxHeap<T> {
+    /// Creates an empty heap.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::MinMaxHeap;
+    ///
+    /// let heap: MinMaxHeap<i32> = MinMaxHeap::new();
+    ///
+    /// assert!(heap.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            data: Vector::new(),
+        }
+    }
+
+    /// Returns the number of values in the heap.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns `true` if the heap contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Removes all the values from the heap.
+    pub fn clear(&mut self) {
+        while self.data.pop().is_some() {}
+    }
+
+    /// Returns an iterator over the values in the heap in arbitrary order.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    /// Returns the smallest value without removing it.
+    pub fn peek_min(&self) -> Option<&T> {
+        self.data.first()
+    }
+}
+
+impl<T: Ord> MinMaxHeap<T> {
+    /// Returns the index of the greatest value, which is the root if it has
+    /// no children or the greatest of its children otherwise.
+    fn max_index(&self) -> Option<usize> {
+        match self.data.len() {
+            0 => None,
+            1 => Some(0),
+            2 => Some(1),
+            _ if self.data[1] >= self.data[2] => Some(1),
+            _ => Some(2),
+        }
+    }
+
+    /// Returns the greatest value without removing it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::MinMaxHeap;
+    ///
+    /// let heap: MinMaxHeap<i32> = [3, 1, 4, 1, 5].into_iter().collect();
+    ///
+    /// assert_eq!(heap.peek_min(), Some(&1));
+    /// assert_eq!(heap.peek_max(), Some(&5));
+    /// ```
+    pub fn peek_max(&self) -> Option<&T> {
+        self.max_index().map(|index| &self.data[index])
+    }
+
+    /// Pushes `value` into the heap in O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::MinMaxHeap;
+    ///
+    /// let mut heap = MinMaxHeap::new();
+    ///
+    /// heap.push(2);
+    /// heap.push(1);
+    /// heap.push(3);
+    ///
+    /// assert_eq!(heap.peek_min(), Some(&1));
+    /// assert_eq!(heap.peek_max(), Some(&3));
+    /// ```
+    pub fn push(&mut self, value: T) {
+        self.data.push(value);
+        self.bubble_up(self.data.len() - 1);
+    }
+
+    /// Removes and returns the smallest value in O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::MinMaxHeap;
+    ///
+    /// let mut heap: MinMaxHeap<i32> = [2, 1, 3].into_iter().collect();
+    ///
+    /// assert_eq!(heap.pop_min(), Some(1));
+    /// assert_eq!(heap.pop_min(), Some(2));
+    /// ```
+    pub fn pop_min(&mut self) -> Option<T> {
+        self.remove_at(0)
+    }
+
+    /// Removes and returns the greatest value in O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::MinMaxHeap;
+    ///
+    /// let mut heap = MinMaxHeap::new();
+    ///
+    /// // Keep the 3 greatest values seen
+    /// for value in [5, 1, 9, 3, 7, 2] {
+    ///     heap.push(value);
+    ///
+    ///     if heap.len() > 3 {
+    ///         heap.pop_min();
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(heap.pop_max(), Some(9));
+    /// assert_eq!(heap.pop_max(), Some(7));
+    /// assert_eq!(heap.pop_max(), Some(5));
+    /// assert_eq!(heap.pop_max(), None);
+    /// ```
+    pub fn pop_max(&mut self) -> Option<T> {
+        self.remove_at(self.max_index()?)
+    }
+
+    /// Replaces the value at `index` with the last one and restores the heap
+    /// from there.
+    fn remove_at(&mut self, index: usize) -> Option<T> {
+        let last = self.data.len().checked_sub(1)?;
+
+        self.data.swap(index, last);
+        let value = self.data.pop();
+
+        if index < last {
+            self.trickle_down(index);
+        }
+
+        value
+    }
+
+    /// Moves a new value at `index` up to its place. It first goes to the
+    /// right kind of level by comparing it with its parent, then only moves
+    /// through grandparents on levels of that kind.
+    fn bubble_up(&mut self, index: usize) {
+        if index == 0 {
+            return;
+        }
+
+        let parent = (index - 1) / 2;
+
+        if is_min_level(index) {
+            if self.data[index] > self.data[parent] {
+                self.data.swap(index, parent);
+                self.bubble_up_through_grandparents(parent, |a, b| a > b);
+            } else {
+                self.bubble_up_through_grandparents(index, |a, b| a < b);
+            }
+        } else if self.data[index] < self.data[parent] {
+            self.data.swap(index, parent);
+            self.bubble_up_through_grandparents(parent, |a, b| a < b);
+        } else {
+            self.bubble_up_through_grandparents(index, |a, b| a > b);
+        }
+    }
+
+    /// Swaps the value at `index` with its grandparent while `goes_before`
+    /// says it should be above it.
+    fn bubble_up_through_grandparents<F>(&mut self, mut index: usize, goes_before: F)
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        while index >= 3 {
+            let grandparent = ((index - 1) / 2 - 1) / 2;
+
+            if !goes_before(&self.data[index], &self.data[grandparent]) {
+                break;
+            }
+
+            self.data.swap(index, grandparent);
+            index = grandparent;
+        }
+    }
+
+    /// Moves the value at `index` down to its place. On a min level it's
+    /// swapped with the smallest of its children and grandchildren while
+    /// that one is smaller, on a max level with the greatest while that one
+    /// is greater. After swapping with a grandchild, the value might have to
+    /// be swapped with the grandchild's parent, which is on the other kind of
+    /// level.
+    fn trickle_down(&mut self, mut index: usize) {
+        let goes_before = if is_min_level(index) {
+            |a: &T, b: &T| a < b
+        } else {
+            |a: &T, b: &T| a > b
+        };
+
+        loop {
+            let first_child = 2 * index + 1;
+            let first_grandchild = 2 * first_child + 1;
+
+            let descendants = (first_child..first_child + 2)
+                .chain(first_grandchild..first_grandchild + 4)
+                .filter(|&descendant| descendant < self.data.len());
+
+            let Some(best) = descendants.reduce(|best, descendant| {
+                if goes_before(&self.data[descendant], &self.data[best]) {
+                    descendant
+                } else {
+                    best
+                }
+            }) else {
+                return;
+            };
+
+            if !goes_before(&self.data[best], &self.data[index]) {
+                return;
+            }
+
+            self.data.swap(best, index);
+
+            if best < first_grandchild {
+                return;
+            }
+
+            let parent = (best - 1) / 2;
+
+            if goes_before(&self.data[parent], &self.data[best]) {
+                self.data.swap(parent, best);
+            }
+
+            index = best;
+        }
+    }
+
+    /// Builds a heap out of the values of `vector` in O(n).
+    pub fn from_vector(data: Vector<T>) -> Self {
+        let mut heap = Self { data };
+
+        for index in (0..heap.data.len() / 2).rev() {
+            heap.trickle_down(index);
+        }
+
+        heap
+    }
+}
+
+impl<T> Default for MinMaxHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> Extend<T> for MinMaxHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for MinMaxHeap<T> {
+    /// Collects the values first and then builds the heap in O(n).
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut data = Vector::new();
+
+        for value in iter {
+            data.push(value);
+        }
+
+        Self::from_vector(data)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a MinMaxHeap<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: HeapSize> HeapSize for MinMaxHeap<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.data.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_min_level, MinMaxHeap};
+
+    /// Checks every value against all its descendants.
+    fn assert_heap<T: Ord>(heap: &MinMaxHeap<T>) {
+        for index in 1..heap.len() {
+            let mut ancestor = index;
+
+            while ancestor > 0 {
+                ancestor = (ancestor - 1) / 2;
+
+                if is_min_level(ancestor) {
+                    assert!(heap.data[ancestor] <= heap.data[index]);
+                } else {
+                    assert!(heap.data[ancestor] >= heap.data[index]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_levels() {
+        let levels: Vec<_> = (0..15).map(is_min_level).collect();
+
+        assert_eq!(levels[0..1], [true]);
+        assert_eq!(levels[1..3], [false; 2]);
+        assert_eq!(levels[3..7], [true; 4]);
+        assert_eq!(levels[7..15], [false; 8]);
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let values: Vec<i32> = (0..500).map(|value| (value * 7_919) % 311).collect();
+        let mut heap = MinMaxHeap::new();
+
+        for &value in &values {
+            heap.push(value);
+            assert_heap(&heap);
+        }
+
+        let mut sorted = values.clone();
+        sorted.sort();
+
+        // Pop from both ends alternately
+        let (mut low, mut high) = (0, sorted.len());
+
+        while !heap.is_empty() {
+            if (low + high) % 2 == 0 {
+                assert_eq!(heap.pop_min(), Some(sorted[low]));
+                low += 1;
+            } else {
+                high -= 1;
+                assert_eq!(heap.pop_max(), Some(sorted[high]));
+            }
+
+            assert_heap(&heap);
+        }
+
+        assert_eq!(heap.pop_min(), None);
+        assert_eq!(heap.pop_max(), None);
+        assert_eq!(heap.peek_max(), None);
+    }
+
+    #[test]
+    fn test_from_iter() {
+        let heap: MinMaxHeap<_> = (0..1_000).map(|value| (value * 7_919) % 1_000).collect();
+
+        assert_heap(&heap);
+        assert_eq!(heap.peek_min(), Some(&0));
+        assert_eq!(heap.peek_max(), Some(&999));
+        assert_eq!(heap.iter().count(), 1_000);
+
+        let mut heap: MinMaxHeap<String> = ["b", "a"].into_iter().map(String::from).collect();
+        heap.extend([String::from("c")]);
+
+        assert_eq!(heap.pop_max().unwrap(), "c");
+        assert_eq!(heap.pop_min().unwrap(), "a");
+
+        heap.clear();
+        assert!(heap.is_empty());
+    }
+}
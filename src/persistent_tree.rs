@@ -0,0 +1,507 @@
+use std::{cmp::Ordering, marker, mem, rc::Rc};
+
+use crate::{
+    compare::{Compare, Natural},
+    heap_size::{self, HeapSize},
+};
+
+type Link<T> = Option<Rc<Node<T>>>;
+
+/// Nodes are shared between all the trees cloned from the same tree, so
+/// unlike [`crate::BinaryTree`] nodes they don't know their parent.
+#[derive(Clone)]
+struct Node<T> {
+    left: Link<T>,
+    right: Link<T>,
+    value: T,
+}
+
+impl<T> Node<T> {
+    fn leaf(value: T) -> Self {
+        Self {
+            left: None,
+            right: None,
+            value,
+        }
+    }
+}
+
+/// Persistent binary search tree. Nodes are reference counted and shared
+/// between clones, so [`Clone`] is O(1) and keeping a snapshot of the tree
+/// before every change is cheap. Mutations copy the nodes on the path from
+/// the root to the modified node (when they're shared) and leave the rest
+/// of the tree shared, which is why they need `T: Clone`.
+///
+/// Like [`crate::BinaryTree`] the tree is not self-balancing.
+///
+/// # Examples
+///
+/// ```rust
+/// use collections_rust::PersistentTree;
+///
+/// let mut tree = PersistentTree::new();
+/// let mut snapshots = Vec::new();
+///
+/// for value in [2, 1, 3] {
+///     tree.insert(value);
+///     snapshots.push(tree.clone());
+/// }
+///
+/// tree.remove(&2);
+///
+/// assert_eq!(snapshots[0].iter().collect::<Vec<_>>(), vec![&2]);
+/// assert_eq!(snapshots[2].iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+/// assert_eq!(tree.iter().collect::<Vec<_>>(), vec![&1, &3]);
+/// ```
+pub struct PersistentTree<T, C = Natural> {
+    root: Link<T>,
+    size: usize,
+    comparator: C,
+}
+
+impl<T> PersistentTree<T> {
+    /// Creates an empty tree ordered by [`Ord`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::PersistentTree;
+    ///
+    /// let tree: PersistentTree<i32> = PersistentTree::new();
+    ///
+    /// assert!(tree.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self::new_with_comparator(Natural)
+    }
+}
+
+impl<T, C> PersistentTree<T, C> {
+    /// Creates an empty tree that orders its values with `comparator`.
+    pub const fn new_with_comparator(comparator: C) -> Self {
+        Self {
+            root: None,
+            size: 0,
+            comparator,
+        }
+    }
+
+    /// Returns the number of values in the tree.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns `true` if the tree contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Removes all the values from the tree. Snapshots keep their values.
+    pub fn clear(&mut self) {
+        Self::drop_iteratively(self.root.take());
+        self.size = 0;
+    }
+
+    /// Returns `true` if both trees share the same root node, which means
+    /// they contain the same values and neither has been modified since one
+    /// was cloned from the other.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::PersistentTree;
+    ///
+    /// let mut tree: PersistentTree<i32> = [2, 1, 3].into_iter().collect();
+    /// let snapshot = tree.clone();
+    ///
+    /// assert!(tree.ptr_eq(&snapshot));
+    /// tree.insert(4);
+    /// assert!(!tree.ptr_eq(&snapshot));
+    /// ```
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.root, &other.root) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns an iterator over the values in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::PersistentTree;
+    ///
+    /// let tree: PersistentTree<i32> = [2, 1, 3].into_iter().collect();
+    ///
+    /// assert_eq!(tree.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            len: self.size,
+            marker: marker::PhantomData,
+        };
+        iter.push_left_spine(self.root.as_deref());
+
+        iter
+    }
+
+    /// Drops the nodes that are not shared with other trees. Nodes owned
+    /// only by this tree are unwrapped and their children visited with an
+    /// explicit stack, so deep trees don't overflow the call stack. Shared
+    /// nodes only lose one reference.
+    fn drop_iteratively(root: Link<T>) {
+        let mut stack: Vec<Rc<Node<T>>> = root.into_iter().collect();
+
+        while let Some(node) = stack.pop() {
+            if let Ok(node) = Rc::try_unwrap(node) {
+                stack.extend(node.left);
+                stack.extend(node.right);
+            }
+        }
+    }
+}
+
+impl<T, C: Compare<T>> PersistentTree<T, C> {
+    /// Returns `true` if the tree contains `value`.
+    pub fn contains(&self, value: &T) -> bool {
+        let mut current = self.root.as_deref();
+
+        while let Some(node) = current {
+            current = match self.comparator.compare(value, &node.value) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Greater => node.right.as_deref(),
+                Ordering::Equal => return true,
+            };
+        }
+
+        false
+    }
+}
+
+impl<T: Clone, C: Compare<T>> PersistentTree<T, C> {
+    /// Inserts `value` and returns `true` unless an equal value is already
+    /// present. Only the nodes on the path to the new node that are shared
+    /// with other trees are copied.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::PersistentTree;
+    ///
+    /// let mut tree = PersistentTree::new();
+    ///
+    /// assert!(tree.insert(1));
+    /// assert!(!tree.insert(1));
+    /// ```
+    pub fn insert(&mut self, value: T) -> bool {
+        // Don't copy the path if nothing is going to change
+        if self.contains(&value) {
+            return false;
+        }
+
+        let mut link = &mut self.root;
+
+        while let Some(node) = link {
+            let node = Rc::make_mut(node);
+
+            link = match self.comparator.compare(&value, &node.value) {
+                Ordering::Less => &mut node.left,
+                _ => &mut node.right,
+            };
+        }
+
+        *link = Some(Rc::new(Node::leaf(value)));
+        self.size += 1;
+
+        true
+    }
+
+    /// Removes `value` and returns `true` unless it's not present. Only the
+    /// nodes on the path to the removed node (or to its successor when the
+    /// node has two children) that are shared with other trees are copied.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::PersistentTree;
+    ///
+    /// let mut tree: PersistentTree<i32> = [2, 1, 3].into_iter().collect();
+    /// let snapshot = tree.clone();
+    ///
+    /// assert!(tree.remove(&2));
+    /// assert!(!tree.contains(&2));
+    /// assert!(snapshot.contains(&2));
+    /// ```
+    pub fn remove(&mut self, value: &T) -> bool {
+        if !self.contains(value) {
+            return false;
+        }
+
+        let mut link = &mut self.root;
+
+        loop {
+            let ordering = self
+                .comparator
+                .compare(value, &link.as_ref().unwrap().value);
+
+            if ordering == Ordering::Equal {
+                break;
+            }
+
+            let node = Rc::make_mut(link.as_mut().unwrap());
+
+            link = match ordering {
+                Ordering::Less => &mut node.left,
+                _ => &mut node.right,
+            };
+        }
+
+        let node = link.as_ref().unwrap();
+
+        if node.left.is_some() && node.right.is_some() {
+            let node = Rc::make_mut(link.as_mut().unwrap());
+            node.value = Self::take_min(&mut node.right);
+        } else {
+            let child = node.left.clone().or_else(|| node.right.clone());
+            Self::drop_iteratively(mem::replace(link, child));
+        }
+
+        self.size -= 1;
+
+        true
+    }
+
+    /// Removes the minimum value of the non-empty subtree behind `link` and
+    /// returns it. The value is moved out of its node if the node isn't
+    /// shared, otherwise it's cloned.
+    fn take_min(mut link: &mut Link<T>) -> T {
+        while link.as_ref().unwrap().left.is_some() {
+            link = &mut Rc::make_mut(link.as_mut().unwrap()).left;
+        }
+
+        let node = link.take().unwrap();
+        *link = node.right.clone();
+
+        match Rc::try_unwrap(node) {
+            Ok(node) => node.value,
+            Err(node) => node.value.clone(),
+        }
+    }
+}
+
+impl<T, C: Clone> Clone for PersistentTree<T, C> {
+    /// Returns a tree that shares all its nodes with this one. O(1).
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            size: self.size,
+            comparator: self.comparator.clone(),
+        }
+    }
+}
+
+impl<T, C: Default> Default for PersistentTree<T, C> {
+    fn default() -> Self {
+        Self::new_with_comparator(C::default())
+    }
+}
+
+impl<T, C> Drop for PersistentTree<T, C> {
+    fn drop(&mut self) {
+        Self::drop_iteratively(self.root.take());
+    }
+}
+
+impl<T: Clone, C: Compare<T>> Extend<T> for PersistentTree<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T: Clone, C: Compare<T> + Default> FromIterator<T> for PersistentTree<T, C> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::default();
+        tree.extend(iter);
+
+        tree
+    }
+}
+
+impl<'a, T, C> IntoIterator for &'a PersistentTree<T, C> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Inorder iterator over the values of a [`PersistentTree`]. Nodes don't
+/// have parent links, so the path to the next value is kept in a stack.
+pub struct Iter<'a, T> {
+    stack: Vec<&'a Node<T>>,
+    len: usize,
+    marker: marker::PhantomData<&'a T>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn push_left_spine(&mut self, mut current: Option<&'a Node<T>>) {
+        while let Some(node) = current {
+            self.stack.push(node);
+            current = node.left.as_deref();
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(node.right.as_deref());
+        self.len -= 1;
+
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// Nodes shared with other versions are counted by every one of them.
+impl<T: HeapSize, C> HeapSize for PersistentTree<T, C> {
+    fn heap_size_of_children(&self) -> usize {
+        self.size * heap_size::rc_allocation::<Node<T>>() + heap_size::children(self.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::{Node, PersistentTree};
+
+    #[test]
+    fn test_snapshots() {
+        let mut tree = PersistentTree::new();
+        let mut snapshots = vec![tree.clone()];
+
+        for value in [20, 10, 30, 5, 15, 25, 35] {
+            tree.insert(value);
+            snapshots.push(tree.clone());
+        }
+
+        for value in [20, 5, 30, 99] {
+            tree.remove(&value);
+            snapshots.push(tree.clone());
+        }
+
+        let expected: [&[i32]; 12] = [
+            &[],
+            &[20],
+            &[10, 20],
+            &[10, 20, 30],
+            &[5, 10, 20, 30],
+            &[5, 10, 15, 20, 30],
+            &[5, 10, 15, 20, 25, 30],
+            &[5, 10, 15, 20, 25, 30, 35],
+            &[5, 10, 15, 25, 30, 35],
+            &[10, 15, 25, 30, 35],
+            &[10, 15, 25, 35],
+            &[10, 15, 25, 35],
+        ];
+
+        for (snapshot, expected) in snapshots.iter().zip(expected) {
+            assert!(snapshot.iter().eq(expected.iter()));
+            assert_eq!(snapshot.size(), expected.len());
+        }
+
+        assert!(snapshots[10].ptr_eq(&snapshots[11]));
+
+        tree.clear();
+        assert!(tree.is_empty());
+        assert_eq!(snapshots[7].size(), 7);
+    }
+
+    #[test]
+    fn test_path_copying() {
+        let mut tree: PersistentTree<i32> = [20, 10, 30, 5, 15, 25, 35].into_iter().collect();
+        let snapshot = tree.clone();
+
+        tree.insert(27);
+
+        let (root, old_root) = (tree.root.as_ref().unwrap(), snapshot.root.as_ref().unwrap());
+
+        // The left subtree is untouched, the path 20 -> 30 -> 25 is copied
+        assert!(Rc::ptr_eq(
+            root.left.as_ref().unwrap(),
+            old_root.left.as_ref().unwrap()
+        ));
+        assert!(!Rc::ptr_eq(
+            root.right.as_ref().unwrap(),
+            old_root.right.as_ref().unwrap()
+        ));
+        assert!(Rc::ptr_eq(
+            root.right.as_ref().unwrap().right.as_ref().unwrap(),
+            old_root.right.as_ref().unwrap().right.as_ref().unwrap()
+        ));
+
+        // Nodes that aren't shared anymore are modified in place
+        let right = Rc::as_ptr(root.right.as_ref().unwrap());
+        drop(snapshot);
+        tree.insert(26);
+        assert_eq!(
+            Rc::as_ptr(tree.root.as_ref().unwrap().right.as_ref().unwrap()),
+            right
+        );
+    }
+
+    #[test]
+    fn test_owned_values() {
+        let mut tree = PersistentTree::new();
+
+        for word in ["b", "a", "d", "c", "e"] {
+            tree.insert(String::from(word));
+        }
+
+        let snapshot = tree.clone();
+        assert!(tree.remove(&String::from("d")));
+        assert!(tree.remove(&String::from("b")));
+
+        assert!(tree.iter().eq(["a", "c", "e"].iter()));
+        assert!(snapshot.iter().eq(["a", "b", "c", "d", "e"].iter()));
+    }
+
+    #[test]
+    fn test_drop_deep_tree() {
+        let mut tree = PersistentTree::new();
+
+        // Every value is the left child of the next one
+        for value in 0..1_000_000 {
+            tree.root = Some(Rc::new(Node {
+                left: tree.root.take(),
+                right: None,
+                value,
+            }));
+        }
+        tree.size = 1_000_000;
+
+        let snapshot = tree.clone();
+        tree.clear();
+
+        assert_eq!(snapshot.iter().len(), 1_000_000);
+        assert!(snapshot.iter().copied().eq(0..1_000_000));
+    }
+}
@@ -0,0 +1,511 @@
+use std::{mem, ops::Index, rc::Rc};
+
+use crate::heap_size::{self, HeapSize};
+
+/// Every node has up to `1 << BITS` children, so each level of the tree
+/// consumes `BITS` bits of an index.
+const BITS: usize = 5;
+const WIDTH: usize = 1 << BITS;
+const MASK: usize = WIDTH - 1;
+
+/// Branches are always at levels greater than zero and leaves at level
+/// zero, so every leaf is at the same depth.
+#[derive(Clone)]
+enum Node<T> {
+    Branch(Vec<Rc<Node<T>>>),
+    Leaf(Vec<T>),
+}
+
+impl<T> Node<T> {
+    /// Returns an empty node for `level`.
+    fn empty(level: usize) -> Self {
+        if level == 0 {
+            Self::Leaf(Vec::with_capacity(WIDTH))
+        } else {
+            Self::Branch(Vec::with_capacity(WIDTH))
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Self::Branch(children) => children.is_empty(),
+            Self::Leaf(values) => values.is_empty(),
+        }
+    }
+}
+
+/// Persistent vector stored as a radix balanced tree with 32 children per
+/// node. The bits of an index select the path from the root to the leaf
+/// that holds the value, so [`PersistentVec::get`], [`PersistentVec::push`]
+/// and [`PersistentVec::set`] take O(log n) with a base of 32, which is at
+/// most 7 levels for any vector that fits in memory.
+///
+/// Nodes are reference counted and shared between clones, so [`Clone`] is
+/// O(1) and old versions remain valid after the vector is modified.
+/// Mutations copy the nodes on the path to the modified leaf (when they're
+/// shared) and leave the rest of the tree shared, which is why they need
+/// `T: Clone`.
+///
+/// # Examples
+///
+/// ```rust
+/// use collections_rust::PersistentVec;
+///
+/// let mut document = PersistentVec::new();
+/// let mut undo = Vec::new();
+///
+/// for line in ["first", "second", "third"] {
+///     undo.push(document.clone());
+///     document.push(line);
+/// }
+///
+/// undo.push(document.clone());
+/// document.set(1, "edited");
+///
+/// assert_eq!(document[1], "edited");
+///
+/// document = undo.pop().unwrap();
+/// assert_eq!(document[1], "second");
+///
+/// document = undo.pop().unwrap();
+/// assert_eq!(document.len(), 2);
+/// ```
+pub struct PersistentVec<T> {
+    root: Option<Rc<Node<T>>>,
+    /// Number of index bits consumed above the leaves, `BITS` times the
+    /// number of branch levels.
+    shift: usize,
+    len: usize,
+}
+
+impl<T> PersistentVec<T> {
+    /// Creates an empty vector.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::PersistentVec;
+    ///
+    /// let vec: PersistentVec<i32> = PersistentVec::new();
+    ///
+    /// assert!(vec.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            root: None,
+            shift: 0,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of values in the vector.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the vector contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all the values from the vector. Snapshots keep their values.
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.shift = 0;
+        self.len = 0;
+    }
+
+    /// Returns `true` if both vectors share the same root node, which means
+    /// they contain the same values and neither has been modified since one
+    /// was cloned from the other.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.root, &other.root) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the values of the leaf that contains `index`.
+    fn leaf(&self, index: usize) -> &[T] {
+        let mut node = self.root.as_deref().unwrap();
+        let mut level = self.shift;
+
+        loop {
+            match node {
+                Node::Branch(children) => {
+                    node = &children[(index >> level) & MASK];
+                    level -= BITS;
+                }
+                Node::Leaf(values) => return values,
+            }
+        }
+    }
+
+    /// Returns the value at `index`, or `None` if it's out of bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+
+        self.leaf(index).get(index & MASK)
+    }
+
+    /// Returns the first value, or `None` if the vector is empty.
+    pub fn first(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Returns the last value, or `None` if the vector is empty.
+    pub fn last(&self) -> Option<&T> {
+        self.get(self.len.wrapping_sub(1))
+    }
+
+    /// Returns an iterator over the values in index order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            vec: self,
+            leaf: &[],
+            index: 0,
+        }
+    }
+}
+
+impl<T: Clone> PersistentVec<T> {
+    /// Returns a mutable reference to the value at `index`, or `None` if
+    /// it's out of bounds. The nodes on the path to the value are copied if
+    /// other vectors share them.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+
+        let mut node = Rc::make_mut(self.root.as_mut().unwrap());
+        let mut level = self.shift;
+
+        loop {
+            match node {
+                Node::Branch(children) => {
+                    node = Rc::make_mut(&mut children[(index >> level) & MASK]);
+                    level -= BITS;
+                }
+                Node::Leaf(values) => return values.get_mut(index & MASK),
+            }
+        }
+    }
+
+    /// Replaces the value at `index` with `value` and returns the old one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::PersistentVec;
+    ///
+    /// let mut vec: PersistentVec<i32> = (0..100).collect();
+    /// let snapshot = vec.clone();
+    ///
+    /// assert_eq!(vec.set(50, -1), 50);
+    /// assert_eq!(vec[50], -1);
+    /// assert_eq!(snapshot[50], 50);
+    /// ```
+    pub fn set(&mut self, index: usize, value: T) -> T {
+        let slot = self.get_mut(index).expect("Index out of bounds");
+
+        mem::replace(slot, value)
+    }
+
+    /// Appends `value` to the end of the vector. When the tree is full a new
+    /// root is added on top of it, so all leaves stay at the same depth.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::PersistentVec;
+    ///
+    /// let mut vec = PersistentVec::new();
+    /// vec.push(1);
+    ///
+    /// let snapshot = vec.clone();
+    /// vec.push(2);
+    ///
+    /// assert_eq!(vec.len(), 2);
+    /// assert_eq!(snapshot.len(), 1);
+    /// ```
+    pub fn push(&mut self, value: T) {
+        let index = self.len;
+
+        let root = match &mut self.root {
+            Some(root) if index == WIDTH << self.shift => {
+                let mut children = Vec::with_capacity(WIDTH);
+                children.push(root.clone());
+                *root = Rc::new(Node::Branch(children));
+                self.shift += BITS;
+                root
+            }
+            Some(root) => root,
+            None => self.root.insert(Rc::new(Node::empty(0))),
+        };
+
+        let mut node = Rc::make_mut(root);
+        let mut level = self.shift;
+
+        loop {
+            match node {
+                Node::Branch(children) => {
+                    let slot = (index >> level) & MASK;
+
+                    if slot == children.len() {
+                        children.push(Rc::new(Node::empty(level - BITS)));
+                    }
+
+                    node = Rc::make_mut(&mut children[slot]);
+                    level -= BITS;
+                }
+                Node::Leaf(values) => {
+                    values.push(value);
+                    break;
+                }
+            }
+        }
+
+        self.len += 1;
+    }
+
+    /// Removes the last value and returns it, or `None` if the vector is
+    /// empty.
+    pub fn pop(&mut self) -> Option<T> {
+        let index = self.len.checked_sub(1)?;
+        let value = Self::pop_from(self.root.as_mut().unwrap(), index, self.shift);
+
+        self.len -= 1;
+
+        if self.len == 0 {
+            self.clear();
+        }
+
+        // Drop the roots left with a single child so get doesn't walk levels
+        // it doesn't need
+        while let Some(Node::Branch(children)) = self.root.as_deref() {
+            if children.len() > 1 {
+                break;
+            }
+
+            self.root = Some(children[0].clone());
+            self.shift -= BITS;
+        }
+
+        Some(value)
+    }
+
+    /// Removes the value at `index`, which must be the last one under
+    /// `node`, and drops the nodes left empty.
+    fn pop_from(node: &mut Rc<Node<T>>, index: usize, level: usize) -> T {
+        match Rc::make_mut(node) {
+            Node::Branch(children) => {
+                let slot = (index >> level) & MASK;
+                let value = Self::pop_from(&mut children[slot], index, level - BITS);
+
+                if children[slot].is_empty() {
+                    children.pop();
+                }
+
+                value
+            }
+            Node::Leaf(values) => values.pop().unwrap(),
+        }
+    }
+}
+
+impl<T> Clone for PersistentVec<T> {
+    /// Returns a vector that shares all its nodes with this one. O(1).
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            shift: self.shift,
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for PersistentVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Index<usize> for PersistentVec<T> {
+    type Output = T;
+
+    /// Returns the value at `index`, panicking if it's out of bounds.
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("Index out of bounds")
+    }
+}
+
+impl<T: Clone> Extend<T> for PersistentVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T: Clone> FromIterator<T> for PersistentVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+
+        vec
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PersistentVec<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the values of a [`PersistentVec`]. It walks down the tree
+/// once per leaf instead of once per value.
+pub struct Iter<'a, T> {
+    vec: &'a PersistentVec<T>,
+    /// Values of the current leaf that haven't been yielded yet.
+    leaf: &'a [T],
+    index: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.vec.len {
+            return None;
+        }
+
+        if self.leaf.is_empty() {
+            self.leaf = &self.vec.leaf(self.index)[self.index & MASK..];
+        }
+
+        let (value, rest) = self.leaf.split_first()?;
+        self.leaf = rest;
+        self.index += 1;
+
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.vec.len - self.index;
+
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<T: HeapSize> HeapSize for Node<T> {
+    fn heap_size_of_children(&self) -> usize {
+        match self {
+            Node::Branch(children) => {
+                let child = heap_size::rc_allocation::<Node<T>>();
+
+                children.capacity() * mem::size_of::<Rc<Node<T>>>()
+                    + children
+                        .iter()
+                        .map(|node| child + node.heap_size_of_children())
+                        .sum::<usize>()
+            }
+
+            Node::Leaf(values) => values.heap_size_of_children(),
+        }
+    }
+}
+
+/// Nodes shared with other versions are counted by every one of them.
+impl<T: HeapSize> HeapSize for PersistentVec<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.root.as_ref().map_or(0, |root| {
+            heap_size::rc_allocation::<Node<T>>() + root.heap_size_of_children()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PersistentVec, BITS};
+
+    #[test]
+    fn test_push_pop() {
+        let mut vec = PersistentVec::new();
+        let count = 40_000;
+
+        for value in 0..count {
+            vec.push(value);
+        }
+
+        assert_eq!(vec.len(), count);
+        assert_eq!(vec.shift, 3 * BITS);
+        assert!(vec.iter().copied().eq(0..count));
+        assert!((0..count).all(|index| vec[index] == index));
+        assert_eq!(vec.get(count), None);
+
+        for value in (0..count).rev() {
+            assert_eq!(vec.pop(), Some(value));
+            assert_eq!(vec.len(), value);
+            assert_eq!(vec.last(), value.checked_sub(1).as_ref());
+        }
+
+        assert_eq!(vec.pop(), None);
+        assert_eq!(vec.shift, 0);
+        assert!(vec.root.is_none());
+    }
+
+    #[test]
+    fn test_snapshots() {
+        let mut vec: PersistentVec<usize> = (0..2_000).collect();
+        let mut snapshots = Vec::new();
+
+        for step in 0..200 {
+            snapshots.push(vec.clone());
+
+            match step % 3 {
+                0 => vec.push(step),
+                1 => {
+                    vec.pop();
+                }
+                _ => {
+                    vec.set((step * 7919) % vec.len(), step);
+                }
+            }
+        }
+
+        // Replay the same operations on a plain vector to check every
+        // snapshot kept the values it had when it was taken
+        let mut expected: Vec<usize> = (0..2_000).collect();
+
+        for (step, snapshot) in snapshots.iter().enumerate() {
+            assert!(snapshot.iter().eq(expected.iter()));
+
+            match step % 3 {
+                0 => expected.push(step),
+                1 => {
+                    expected.pop();
+                }
+                _ => {
+                    let index = (step * 7919) % expected.len();
+                    expected[index] = step;
+                }
+            }
+        }
+
+        assert!(vec.iter().eq(expected.iter()));
+        assert!(!vec.ptr_eq(&snapshots[0]));
+        assert!(snapshots[0].clone().ptr_eq(&snapshots[0]));
+    }
+}
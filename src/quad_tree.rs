@@ -0,0 +1,392 @@
+use std::mem;
+
+use crate::heap_size::HeapSize;
+
+/// Maximum number of points a leaf holds before it's split in quadrants.
+const LEAF_CAPACITY: usize = 8;
+
+/// Leaves this deep are never split, so many points at the same position
+/// can't make the tree grow forever.
+const MAX_DEPTH: usize = 20;
+
+/// Axis-aligned rectangle given by its minimum and maximum corners. Both
+/// edges are inclusive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub min: [f64; 2],
+    pub max: [f64; 2],
+}
+
+impl Rect {
+    /// Creates a rectangle from its minimum and maximum corners.
+    pub fn new(min: [f64; 2], max: [f64; 2]) -> Self {
+        Self { min, max }
+    }
+
+    /// Returns `true` if `point` is inside the rectangle or on its edges.
+    pub fn contains_point(&self, point: &[f64; 2]) -> bool {
+        (0..2).all(|axis| self.min[axis] <= point[axis] && point[axis] <= self.max[axis])
+    }
+
+    /// Returns `true` if `other` is completely inside this rectangle.
+    pub fn contains(&self, other: &Rect) -> bool {
+        (0..2).all(|axis| self.min[axis] <= other.min[axis] && other.max[axis] <= self.max[axis])
+    }
+
+    /// Returns `true` if both rectangles share at least one point.
+    pub fn intersects(&self, other: &Rect) -> bool {
+        (0..2).all(|axis| self.min[axis] <= other.max[axis] && other.min[axis] <= self.max[axis])
+    }
+
+    /// Returns the area of the rectangle.
+    pub fn area(&self) -> f64 {
+        (self.max[0] - self.min[0]) * (self.max[1] - self.min[1])
+    }
+
+    /// Returns the smallest rectangle that contains both rectangles.
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect {
+            min: [self.min[0].min(other.min[0]), self.min[1].min(other.min[1])],
+            max: [self.max[0].max(other.max[0]), self.max[1].max(other.max[1])],
+        }
+    }
+
+    /// Returns the quadrant of this rectangle at `index`, where bit 0 of
+    /// `index` selects the upper half along x and bit 1 along y.
+    fn quadrant(&self, index: usize) -> Rect {
+        let mut quadrant = *self;
+
+        for axis in 0..2 {
+            let middle = (self.min[axis] + self.max[axis]) / 2.0;
+
+            if index >> axis & 1 == 0 {
+                quadrant.max[axis] = middle;
+            } else {
+                quadrant.min[axis] = middle;
+            }
+        }
+
+        quadrant
+    }
+
+    /// Returns the index of the quadrant where `point` belongs. Points on
+    /// the middle lines go to the upper quadrants.
+    fn quadrant_of(&self, point: &[f64; 2]) -> usize {
+        (0..2)
+            .filter(|&axis| point[axis] >= (self.min[axis] + self.max[axis]) / 2.0)
+            .map(|axis| 1 << axis)
+            .sum()
+    }
+}
+
+/// Points are only stored in leaves. Branches have 4 children, one for each
+/// quadrant of their region.
+struct Node<V> {
+    points: Vec<([f64; 2], V)>,
+    children: Option<Box<[Node<V>; 4]>>,
+}
+
+impl<V> Node<V> {
+    fn leaf() -> Self {
+        Self {
+            points: Vec::new(),
+            children: None,
+        }
+    }
+
+    fn insert(&mut self, region: Rect, depth: usize, point: [f64; 2], value: V) {
+        if let Some(children) = &mut self.children {
+            let index = region.quadrant_of(&point);
+            return children[index].insert(region.quadrant(index), depth + 1, point, value);
+        }
+
+        self.points.push((point, value));
+
+        if self.points.len() > LEAF_CAPACITY && depth < MAX_DEPTH {
+            let mut children = Box::new([Self::leaf(), Self::leaf(), Self::leaf(), Self::leaf()]);
+
+            for (point, value) in mem::take(&mut self.points) {
+                let index = region.quadrant_of(&point);
+                children[index].insert(region.quadrant(index), depth + 1, point, value);
+            }
+
+            self.children = Some(children);
+        }
+    }
+
+    /// Removes a value at `point` and merges the quadrants back into a leaf
+    /// once they hold few enough points.
+    fn remove(&mut self, region: Rect, point: &[f64; 2]) -> Option<V> {
+        let Some(children) = &mut self.children else {
+            let index = self.points.iter().position(|(other, _)| other == point)?;
+            return Some(self.points.swap_remove(index).1);
+        };
+
+        let index = region.quadrant_of(point);
+        let value = children[index].remove(region.quadrant(index), point)?;
+
+        let all_leaves = children.iter().all(|child| child.children.is_none());
+        let count: usize = children.iter().map(|child| child.points.len()).sum();
+
+        if all_leaves && count <= LEAF_CAPACITY {
+            let children = *self.children.take().unwrap();
+
+            for child in children {
+                self.points.extend(child.points);
+            }
+        }
+
+        Some(value)
+    }
+
+    fn query<'a>(&'a self, region: Rect, range: &Rect, found: &mut Vec<(&'a [f64; 2], &'a V)>) {
+        match &self.children {
+            Some(children) => {
+                for (index, child) in children.iter().enumerate() {
+                    let quadrant = region.quadrant(index);
+
+                    if quadrant.intersects(range) {
+                        child.query(quadrant, range, found);
+                    }
+                }
+            }
+            None => found.extend(
+                self.points
+                    .iter()
+                    .filter(|(point, _)| range.contains_point(point))
+                    .map(|(point, value)| (point, value)),
+            ),
+        }
+    }
+}
+
+/// Region quadtree that maps points inside a fixed rectangle to values.
+/// Every node covers a region of the plane, and leaves holding more than a
+/// few points are split in 4 equal quadrants, so crowded areas get deeper
+/// subdivisions while empty ones stay cheap.
+///
+/// Unlike [`crate::KdTree`], which is built once from all its points, points
+/// can be inserted and removed at any time, which suits game worlds where
+/// objects keep moving. Range queries only visit the quadrants that
+/// intersect the queried rectangle.
+///
+/// # Examples
+///
+/// ```rust
+/// use collections_rust::{QuadTree, Rect};
+///
+/// let mut world = QuadTree::new(Rect::new([0.0, 0.0], [100.0, 100.0]));
+///
+/// world.insert([10.0, 10.0], "player").unwrap();
+/// world.insert([12.0, 15.0], "enemy").unwrap();
+/// world.insert([90.0, 90.0], "chest").unwrap();
+///
+/// let mut nearby: Vec<&str> = world
+///     .query(&Rect::new([0.0, 0.0], [20.0, 20.0]))
+///     .into_iter()
+///     .map(|(_, value)| *value)
+///     .collect();
+/// nearby.sort();
+///
+/// assert_eq!(nearby, ["enemy", "player"]);
+/// ```
+pub struct QuadTree<V> {
+    root: Node<V>,
+    bounds: Rect,
+    len: usize,
+}
+
+impl<V> QuadTree<V> {
+    /// Creates an empty quadtree that accepts points inside `bounds`.
+    pub fn new(bounds: Rect) -> Self {
+        Self {
+            root: Node::leaf(),
+            bounds,
+            len: 0,
+        }
+    }
+
+    /// Returns the rectangle that contains every point of the tree.
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    /// Returns the number of points in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree contains no points.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all the points.
+    pub fn clear(&mut self) {
+        self.root = Node::leaf();
+        self.len = 0;
+    }
+
+    /// Inserts `value` at `point`. Many values can share the same point.
+    ///
+    /// # Errors
+    ///
+    /// Returns the value back if `point` is outside the bounds of the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::{QuadTree, Rect};
+    ///
+    /// let mut tree = QuadTree::new(Rect::new([0.0, 0.0], [1.0, 1.0]));
+    ///
+    /// assert_eq!(tree.insert([0.5, 0.5], 'a'), Ok(()));
+    /// assert_eq!(tree.insert([2.0, 0.5], 'b'), Err('b'));
+    /// assert_eq!(tree.len(), 1);
+    /// ```
+    pub fn insert(&mut self, point: [f64; 2], value: V) -> Result<(), V> {
+        if !self.bounds.contains_point(&point) {
+            return Err(value);
+        }
+
+        self.root.insert(self.bounds, 0, point, value);
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Removes and returns a value at exactly `point`, or `None` if there's
+    /// no value there. When many values share the point any of them may be
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::{QuadTree, Rect};
+    ///
+    /// let mut tree = QuadTree::new(Rect::new([0.0, 0.0], [1.0, 1.0]));
+    /// tree.insert([0.5, 0.5], 'a').unwrap();
+    ///
+    /// assert_eq!(tree.remove(&[0.5, 0.5]), Some('a'));
+    /// assert_eq!(tree.remove(&[0.5, 0.5]), None);
+    /// ```
+    pub fn remove(&mut self, point: &[f64; 2]) -> Option<V> {
+        if !self.bounds.contains_point(point) {
+            return None;
+        }
+
+        let value = self.root.remove(self.bounds, point)?;
+        self.len -= 1;
+
+        Some(value)
+    }
+
+    /// Returns every point inside `range`, edges included, along with its
+    /// value, in no particular order.
+    pub fn query(&self, range: &Rect) -> Vec<(&[f64; 2], &V)> {
+        let mut found = Vec::new();
+
+        if self.bounds.intersects(range) {
+            self.root.query(self.bounds, range, &mut found);
+        }
+
+        found
+    }
+}
+
+impl HeapSize for Rect {
+    fn heap_size_of_children(&self) -> usize {
+        0
+    }
+}
+
+impl<V: HeapSize> HeapSize for Node<V> {
+    fn heap_size_of_children(&self) -> usize {
+        self.points.heap_size_of_children() + self.children.heap_size_of_children()
+    }
+}
+
+impl<V: HeapSize> HeapSize for QuadTree<V> {
+    fn heap_size_of_children(&self) -> usize {
+        self.root.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QuadTree, Rect};
+
+    #[test]
+    fn test_insert_query_remove() {
+        let mut tree = QuadTree::new(Rect::new([0.0, 0.0], [64.0, 64.0]));
+        let mut points = Vec::new();
+        let mut seed: u64 = 0x2545_F491_4F6C_DD1D;
+
+        for index in 0..2_000 {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+
+            let point = [
+                (seed % 6_400) as f64 / 100.0,
+                (seed / 6_400 % 6_400) as f64 / 100.0,
+            ];
+            tree.insert(point, index).unwrap();
+            points.push(point);
+        }
+
+        assert_eq!(tree.len(), 2_000);
+        assert_eq!(tree.insert([-1.0, 0.0], 0), Err(0));
+
+        let check = |tree: &QuadTree<usize>, points: &[[f64; 2]], range: Rect| {
+            let mut found: Vec<usize> = tree
+                .query(&range)
+                .into_iter()
+                .map(|(_, index)| *index)
+                .collect();
+            found.sort();
+
+            let expected: Vec<usize> = (0..points.len())
+                .filter(|&index| !points[index][0].is_nan() && range.contains_point(&points[index]))
+                .collect();
+
+            assert_eq!(found, expected);
+        };
+
+        check(&tree, &points, Rect::new([10.0, 10.0], [20.0, 40.0]));
+        check(&tree, &points, Rect::new([32.0, 0.0], [32.0, 64.0]));
+        check(&tree, &points, tree.bounds());
+
+        // Removing every other point merges quadrants back
+        for index in (0..2_000).step_by(2) {
+            assert!(tree.remove(&points[index]).is_some());
+            points[index] = [f64::NAN; 2];
+        }
+
+        assert_eq!(tree.len(), 1_000);
+        check(&tree, &points, Rect::new([0.0, 0.0], [64.0, 32.0]));
+
+        for index in (1..2_000).step_by(2) {
+            assert!(tree.remove(&points[index]).is_some());
+        }
+
+        assert!(tree.is_empty());
+        assert!(tree.root.children.is_none());
+    }
+
+    #[test]
+    fn test_same_point() {
+        let mut tree = QuadTree::new(Rect::new([0.0, 0.0], [1.0, 1.0]));
+
+        // Splitting can't separate equal points, so depth is capped
+        for value in 0..100 {
+            tree.insert([1.0, 1.0], value).unwrap();
+        }
+
+        assert_eq!(tree.query(&Rect::new([1.0, 1.0], [1.0, 1.0])).len(), 100);
+        assert!(tree.query(&Rect::new([0.0, 0.0], [0.9, 0.9])).is_empty());
+
+        tree.clear();
+        assert!(tree.is_empty());
+    }
+}
@@ -1,4 +1,18 @@
-use std::fmt::Display;
+use std::{
+    fmt::{self, Debug, Display},
+    io, mem,
+};
+
+#[cfg(feature = "stats")]
+use crate::stats::Stats;
+use crate::{
+    allocator::{self, Allocator, Global},
+    drop_guard,
+    heap_size::{self, HeapSize},
+    stats::Counters,
+    traits,
+    try_reserve_error::TryReserveError,
+};
 
 struct Node<T> {
     value: T,
@@ -11,13 +25,41 @@ impl<T> Node<T> {
     }
 }
 
-pub struct Queue<T> {
+/// Creates a [`Queue`] containing the arguments, the first one at the front.
+///
+/// # Examples
+///
+/// ```rust
+/// use collections_rust::queue;
+///
+/// let mut queue = queue!["a", "b"];
+///
+/// assert_eq!(queue.pop(), Some("a"));
+/// assert_eq!(queue.len(), 1);
+/// ```
+#[macro_export]
+macro_rules! queue {
+    ($($value:expr),* $(,)?) => {{
+        // Not mutated when the macro is called without values
+        #[allow(unused_mut)]
+        let mut queue = $crate::Queue::new();
+        $(queue.append($value);)*
+        queue
+    }};
+}
+
+pub struct Queue<T, A: Allocator = Global> {
     head: *mut Node<T>,
     tail: *mut Node<T>,
     len: u32,
+    alloc: A,
+    stats: Counters,
 }
 
-pub struct IntoIter<T>(Queue<T>);
+// The queue owns its nodes, so it can move to another thread with its values.
+unsafe impl<T: Send, A: Allocator + Send> Send for Queue<T, A> {}
+
+pub struct IntoIter<T, A: Allocator = Global>(Queue<T, A>);
 
 pub struct Iter<'a, T> {
     next: Option<&'a Node<T>>,
@@ -28,14 +70,61 @@ pub struct IterMut<'a, T> {
 }
 
 impl<T> Queue<T> {
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
+        Self::new_in(Global)
+    }
+}
+
+impl<T, A: Allocator> Queue<T, A> {
+    /// Creates an empty queue whose nodes are allocated by `alloc`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::{Global, Queue};
+    ///
+    /// let mut queue = Queue::new_in(Global);
+    /// queue.append(1);
+    ///
+    /// assert_eq!(queue.peek(), Some(&1));
+    /// ```
+    pub const fn new_in(alloc: A) -> Self {
         Queue {
             head: std::ptr::null_mut(),
             tail: std::ptr::null_mut(),
             len: 0,
+            alloc,
+            stats: Counters::new(),
         }
     }
 
+    /// Returns the allocator of the queue.
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    /// Returns the counters of the queue, see [`Stats`]. Queues don't have
+    /// positional access, so `pointer_walks` stays at 0.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Queue;
+    ///
+    /// let mut queue = Queue::new();
+    /// queue.append(1);
+    /// queue.append(2);
+    /// queue.pop();
+    /// queue.append(3);
+    ///
+    /// let stats = queue.stats();
+    /// assert_eq!((stats.allocations, stats.peak_len), (3, 2));
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+
     pub fn len(&self) -> u32 {
         self.len
     }
@@ -45,8 +134,40 @@ impl<T> Queue<T> {
     }
 
     pub fn append(&mut self, value: T) {
-        let node = Box::into_raw(Box::new(Node::new(value, std::ptr::null_mut())));
+        let node = allocator::new_in(Node::new(value, std::ptr::null_mut()), &self.alloc);
+        self.stats.record_allocation();
+
+        self.link_back(node.as_ptr());
+    }
+
+    /// Like [`Queue::append`], but returns an error instead of aborting if
+    /// the node can't be allocated. `value` is dropped in that case.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryReserveError`] if the allocator fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Queue;
+    ///
+    /// let mut queue = Queue::new();
+    ///
+    /// assert_eq!(queue.try_append(1), Ok(()));
+    /// assert_eq!(queue.pop(), Some(1));
+    /// ```
+    pub fn try_append(&mut self, value: T) -> Result<(), TryReserveError> {
+        let node = allocator::try_new_in(Node::new(value, std::ptr::null_mut()), &self.alloc)?;
+        self.stats.record_allocation();
+
+        self.link_back(node.as_ptr());
+
+        Ok(())
+    }
 
+    /// Makes the detached `node` the new tail.
+    fn link_back(&mut self, node: *mut Node<T>) {
         if !self.tail.is_null() {
             unsafe {
                 (*self.tail).next = node;
@@ -58,6 +179,7 @@ impl<T> Queue<T> {
         self.tail = node;
 
         self.len += 1;
+        self.stats.record_len(self.len as usize);
     }
 
     pub fn pop(&mut self) -> Option<T> {
@@ -66,7 +188,7 @@ impl<T> Queue<T> {
         }
 
         unsafe {
-            let head = Box::from_raw(self.head);
+            let head = allocator::take_in(std::ptr::NonNull::new_unchecked(self.head), &self.alloc);
 
             self.head = head.next;
 
@@ -103,11 +225,54 @@ impl<T> Queue<T> {
             }
         }
     }
+
+    /// Appends the values of `iter` until it yields an error. The values
+    /// before the error stay in the queue.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error yielded by `iter`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Queue;
+    ///
+    /// let mut queue = Queue::new();
+    ///
+    /// let result = queue.try_extend(["1", "2", "x", "4"].map(str::parse::<i32>));
+    ///
+    /// assert!(result.is_err());
+    /// assert!(queue.iter().eq(&[1, 2]));
+    /// ```
+    pub fn try_extend<E, I>(&mut self, iter: I) -> Result<(), E>
+    where
+        I: IntoIterator<Item = Result<T, E>>,
+    {
+        for value in iter {
+            self.append(value?);
+        }
+
+        Ok(())
+    }
+
+    /// Calls `f` on every value from front to back until it returns an
+    /// error. The values before the error keep the changes made by `f`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by `f`.
+    pub fn try_for_each_mut<E, F>(&mut self, f: F) -> Result<(), E>
+    where
+        F: FnMut(&mut T) -> Result<(), E>,
+    {
+        self.iter_mut().try_for_each(f)
+    }
 }
 
-impl<T> IntoIterator for Queue<T> {
+impl<T, A: Allocator> IntoIterator for Queue<T, A> {
     type Item = T;
-    type IntoIter = IntoIter<T>;
+    type IntoIter = IntoIter<T, A>;
 
     fn into_iter(self) -> Self::IntoIter {
         IntoIter(self)
@@ -120,13 +285,112 @@ impl<T> Default for Queue<T> {
     }
 }
 
-impl<T> Drop for Queue<T> {
+impl<T, A: Allocator> Extend<T> for Queue<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.append(value);
+        }
+    }
+}
+
+/// Copies the values, so slices can be appended without `.copied()`.
+impl<'a, T: Copy + 'a, A: Allocator> Extend<&'a T> for Queue<T, A> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+/// Appends the bytes at the back, so the queue can buffer a stream
+/// until it's read back with [`io::Read`].
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::{Read, Write};
+///
+/// use collections_rust::Queue;
+///
+/// let mut buffer = Queue::new();
+/// write!(buffer, "{} {}", 1, 2).unwrap();
+///
+/// let mut text = String::new();
+/// buffer.read_to_string(&mut text).unwrap();
+///
+/// assert_eq!(text, "1 2");
+/// ```
+impl<A: Allocator> io::Write for Queue<u8, A> {
+    /// Stops at the first byte that can't be allocated. That error is only
+    /// returned if no bytes were written.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for (written, &byte) in buf.iter().enumerate() {
+            if let Err(err) = self.try_append(byte) {
+                if written == 0 {
+                    return Err(io::Error::new(io::ErrorKind::OutOfMemory, err));
+                }
+
+                return Ok(written);
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Consumes bytes from the front. Reading an empty queue returns `Ok(0)`,
+/// which readers take as the end of the stream.
+impl<A: Allocator> io::Read for Queue<u8, A> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut read = 0;
+
+        while read < buf.len() {
+            match self.pop() {
+                Some(byte) => buf[read] = byte,
+                None => break,
+            }
+            read += 1;
+        }
+
+        Ok(read)
+    }
+}
+
+impl<T, A: Allocator> traits::Collection for Queue<T, A> {
+    fn len(&self) -> usize {
+        Queue::len(self) as usize
+    }
+}
+
+impl<T, A: Allocator> traits::FifoQueue<T> for Queue<T, A> {
+    fn enqueue(&mut self, value: T) {
+        self.append(value);
+    }
+
+    fn dequeue(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn front(&self) -> Option<&T> {
+        self.peek()
+    }
+}
+
+impl<T: HeapSize, A: Allocator> HeapSize for Queue<T, A> {
+    fn heap_size_of_children(&self) -> usize {
+        self.len as usize * mem::size_of::<Node<T>>() + heap_size::children(self.iter())
+    }
+}
+
+impl<T, A: Allocator> Drop for Queue<T, A> {
     fn drop(&mut self) {
-        while self.pop().is_some() {}
+        drop_guard::drop_each(&mut || self.pop());
     }
 }
 
-impl<T> Iterator for IntoIter<T> {
+impl<T, A: Allocator> Iterator for IntoIter<T, A> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -160,7 +424,60 @@ impl<'a, T> Iterator for IterMut<'a, T> {
     }
 }
 
-impl<T: Display> Display for Queue<T> {
+impl<T: Debug, A: Allocator> Debug for Queue<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Debug, A: Allocator> Queue<T, A> {
+    /// Writes the address of the head and tail nodes, then one line per node
+    /// from head to tail with its address, value and next node. The last
+    /// node points to `0x0`. Meant for seeing how operations rewire the
+    /// nodes, the format may change at any time.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `w` if writing fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Queue;
+    ///
+    /// let mut queue = Queue::new();
+    /// queue.append("a");
+    /// queue.append("b");
+    ///
+    /// let mut layout = String::new();
+    /// queue.dump_layout(&mut layout).unwrap();
+    ///
+    /// // Something like:
+    /// // head 0x5581e0a31b10, tail 0x5581e0a31b30, len 2
+    /// // 0x5581e0a31b10: "a", next 0x5581e0a31b30
+    /// // 0x5581e0a31b30: "b", next 0x0
+    /// assert_eq!(layout.lines().count(), 3);
+    /// assert!(layout.ends_with(": \"b\", next 0x0\n"));
+    /// ```
+    pub fn dump_layout(&self, mut w: impl fmt::Write) -> fmt::Result {
+        writeln!(
+            w,
+            "head {:p}, tail {:p}, len {}",
+            self.head, self.tail, self.len
+        )?;
+
+        let mut current = self.head;
+
+        while let Some(node) = unsafe { current.as_ref() } {
+            writeln!(w, "{:p}: {:?}, next {:p}", node, node.value, node.next)?;
+            current = node.next;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Display, A: Allocator> Display for Queue<T, A> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if self.is_empty() {
             return write!(f, "[]");
@@ -179,9 +496,51 @@ impl<T: Display> Display for Queue<T> {
     }
 }
 
+#[cfg(feature = "proptest")]
+impl<T: Debug> Queue<T> {
+    /// Returns a [proptest](https://docs.rs/proptest) strategy that
+    /// generates queues of values from `element` with a length in `size`.
+    /// Failing cases shrink like `Vec`s do, dropping values first and then
+    /// shrinking the ones left.
+    pub fn strategy<S: proptest::strategy::Strategy<Value = T>>(
+        element: S,
+        size: impl Into<proptest::collection::SizeRange>,
+    ) -> impl proptest::strategy::Strategy<Value = Self> {
+        use proptest::strategy::Strategy;
+
+        proptest::collection::vec(element, size).prop_map(Self::from_vec)
+    }
+
+    fn from_vec(values: Vec<T>) -> Self {
+        let mut queue = Self::new();
+
+        for value in values {
+            queue.append(value);
+        }
+
+        queue
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary> proptest::arbitrary::Arbitrary for Queue<T> {
+    type Parameters = <Vec<T> as proptest::arbitrary::Arbitrary>::Parameters;
+    type Strategy = proptest::strategy::Map<
+        <Vec<T> as proptest::arbitrary::Arbitrary>::Strategy,
+        fn(Vec<T>) -> Self,
+    >;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+
+        proptest::arbitrary::any_with::<Vec<T>>(args).prop_map(Self::from_vec)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Queue;
+    use std::{sync::Mutex, thread};
 
     #[test]
     fn basics() {
@@ -271,6 +630,78 @@ mod tests {
         assert_eq!(iter.next(), None);
     }
 
+    #[test]
+    fn extend() {
+        let mut queue = Queue::new();
+        queue.extend(vec![1, 2]);
+        queue.extend(&[3, 4]);
+
+        assert_eq!(queue.len(), 4);
+        assert!(queue.into_iter().eq(1..=4));
+    }
+
+    #[test]
+    fn macros() {
+        let queue: Queue<u8> = queue![];
+        assert!(queue.is_empty());
+
+        let queue = queue![String::from("a"), String::from("b")];
+        assert!(queue.into_iter().eq(["a", "b"]));
+    }
+
+    #[test]
+    fn statics() {
+        static PENDING: Mutex<Queue<Box<u32>>> = Mutex::new(Queue::new());
+
+        thread::scope(|s| {
+            for i in 0..4 {
+                s.spawn(move || PENDING.lock().unwrap().append(Box::new(i)));
+            }
+        });
+
+        let mut pending = PENDING.lock().unwrap();
+        let mut values: Vec<_> = std::iter::from_fn(|| pending.pop()).map(|v| *v).collect();
+        values.sort();
+        assert_eq!(values, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn try_for_each_mut() {
+        let mut queue = Queue::new();
+
+        assert_eq!(queue.try_extend([Ok(1), Ok(2), Err(3), Ok(4)]), Err(3));
+        assert_eq!(queue.try_extend(Vec::<Result<_, ()>>::new()), Ok(()));
+
+        let result = queue.try_for_each_mut(|value| match *value {
+            2 => Err("two"),
+            _ => {
+                *value *= 10;
+                Ok(())
+            }
+        });
+
+        assert_eq!(result, Err("two"));
+        assert!(queue.into_iter().eq([10, 2]));
+    }
+
+    #[test]
+    fn io() {
+        use std::io::{self, Read, Write};
+
+        let mut queue = Queue::new();
+        let mut buf = [0; 3];
+
+        queue.write_all(b"hello").unwrap();
+        assert_eq!(queue.read(&mut buf).unwrap(), 3);
+        assert_eq!(&buf, b"hel");
+
+        // Pipe the rest into another queue
+        let mut sink = Queue::new();
+        assert_eq!(io::copy(&mut queue, &mut sink).unwrap(), 2);
+        assert_eq!(queue.read(&mut buf).unwrap(), 0);
+        assert!(sink.iter().eq(b"lo"));
+    }
+
     #[test]
     fn display() {
         let mut queue = Queue::<i32>::new();
@@ -290,6 +721,9 @@ mod tests {
     }
 
     #[test]
+    // Kept as written in "Learning Rust With Entirely Too Many Linked
+    // Lists", where this test comes from
+    #[allow(clippy::option_map_unit_fn)]
     fn miri_food() {
         let mut queue = Queue::new();
 
@@ -326,4 +760,25 @@ mod tests {
 
         // Drop it on the ground and let the dtor exercise itself
     }
+
+    #[cfg(feature = "proptest")]
+    #[test]
+    fn proptest_arbitrary() {
+        use proptest::{
+            arbitrary::any,
+            prop_assert,
+            test_runner::{TestError, TestRunner},
+        };
+
+        let mut runner = TestRunner::deterministic();
+        let result = runner.run(&any::<Queue<u8>>(), |queue| {
+            prop_assert!(queue.len() < 3);
+            Ok(())
+        });
+
+        let Err(TestError::Fail(_, queue)) = result else {
+            panic!("expected a failing queue, got {result:?}");
+        };
+        assert_eq!(queue.to_string(), "[0, 0, 0]");
+    }
 }
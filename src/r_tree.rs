@@ -0,0 +1,512 @@
+use std::mem;
+
+use crate::{heap_size::HeapSize, quad_tree::Rect};
+
+/// Maximum number of entries of a node before it's split in two.
+const MAX_ENTRIES: usize = 8;
+
+/// Minimum number of entries of every node except the root. Nodes that drop
+/// below it on removal are dissolved and their values inserted again.
+const MIN_ENTRIES: usize = 3;
+
+/// Entries of a node, each paired with its bounding box.
+type Entries<X> = Vec<(Rect, X)>;
+
+/// The bounding box of a branch entry is the smallest rectangle that
+/// contains everything below it.
+enum Node<V> {
+    Branch(Vec<(Rect, Node<V>)>),
+    Leaf(Vec<(Rect, V)>),
+}
+
+/// Returns the smallest rectangle that contains every entry.
+fn bounds<X>(entries: &[(Rect, X)]) -> Rect {
+    entries[1..]
+        .iter()
+        .fold(entries[0].0, |bounds, (rect, _)| bounds.union(rect))
+}
+
+/// Returns how much `bounds` would grow to contain `rect`.
+fn enlargement(bounds: &Rect, rect: &Rect) -> f64 {
+    bounds.union(rect).area() - bounds.area()
+}
+
+/// Splits an overflowing node in two with the quadratic split of Guttman:
+/// the two entries that would waste the most area together start separate
+/// groups, and every other entry joins the group that grows the least.
+fn split<X>(mut entries: Entries<X>) -> (Entries<X>, Entries<X>) {
+    let mut seeds = (0, 1);
+    let mut worst = f64::NEG_INFINITY;
+
+    for i in 0..entries.len() {
+        for j in i + 1..entries.len() {
+            let (a, b) = (&entries[i].0, &entries[j].0);
+            let waste = a.union(b).area() - a.area() - b.area();
+
+            if waste > worst {
+                worst = waste;
+                seeds = (i, j);
+            }
+        }
+    }
+
+    // Remove the later seed first so the index of the other stays valid
+    let second = entries.swap_remove(seeds.1);
+    let first = entries.swap_remove(seeds.0);
+
+    let mut groups = (vec![first], vec![second]);
+    let mut bounds = (groups.0[0].0, groups.1[0].0);
+
+    while let Some(entry) = entries.pop() {
+        // Give every remaining entry to a group that would end up too small
+        let remaining = entries.len() + 1;
+        let to_first = if groups.0.len() + remaining <= MIN_ENTRIES {
+            true
+        } else if groups.1.len() + remaining <= MIN_ENTRIES {
+            false
+        } else {
+            enlargement(&bounds.0, &entry.0) <= enlargement(&bounds.1, &entry.0)
+        };
+
+        if to_first {
+            bounds.0 = bounds.0.union(&entry.0);
+            groups.0.push(entry);
+        } else {
+            bounds.1 = bounds.1.union(&entry.0);
+            groups.1.push(entry);
+        }
+    }
+
+    groups
+}
+
+impl<V> Node<V> {
+    fn len(&self) -> usize {
+        match self {
+            Self::Branch(children) => children.len(),
+            Self::Leaf(entries) => entries.len(),
+        }
+    }
+
+    fn bounds(&self) -> Rect {
+        match self {
+            Self::Branch(children) => bounds(children),
+            Self::Leaf(entries) => bounds(entries),
+        }
+    }
+
+    /// Inserts the entry in the leaf whose bounding box grows the least and
+    /// returns the new sibling of this node if it had to be split.
+    fn insert(&mut self, rect: Rect, value: V) -> Option<Node<V>> {
+        match self {
+            Self::Leaf(entries) => {
+                entries.push((rect, value));
+
+                if entries.len() > MAX_ENTRIES {
+                    let (left, right) = split(mem::take(entries));
+                    *entries = left;
+
+                    return Some(Self::Leaf(right));
+                }
+            }
+            Self::Branch(children) => {
+                let (bounds, child) = children
+                    .iter_mut()
+                    .min_by(|(a, _), (b, _)| {
+                        enlargement(a, &rect)
+                            .total_cmp(&enlargement(b, &rect))
+                            .then(a.area().total_cmp(&b.area()))
+                    })
+                    .unwrap();
+
+                *bounds = bounds.union(&rect);
+
+                if let Some(sibling) = child.insert(rect, value) {
+                    *bounds = child.bounds();
+                    children.push((sibling.bounds(), sibling));
+
+                    if children.len() > MAX_ENTRIES {
+                        let (left, right) = split(mem::take(children));
+                        *children = left;
+
+                        return Some(Self::Branch(right));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Removes an entry whose bounding box equals `rect`. Children left
+    /// with too few entries are dissolved and their values are moved to
+    /// `orphans` to be inserted again.
+    fn remove(&mut self, rect: &Rect, orphans: &mut Vec<(Rect, V)>) -> Option<V> {
+        match self {
+            Self::Leaf(entries) => {
+                let index = entries.iter().position(|(other, _)| other == rect)?;
+                Some(entries.swap_remove(index).1)
+            }
+            Self::Branch(children) => {
+                for index in 0..children.len() {
+                    if !children[index].0.contains(rect) {
+                        continue;
+                    }
+
+                    let Some(value) = children[index].1.remove(rect, orphans) else {
+                        continue;
+                    };
+
+                    if children[index].1.len() < MIN_ENTRIES {
+                        children.swap_remove(index).1.drain_into(orphans);
+                    } else {
+                        children[index].0 = children[index].1.bounds();
+                    }
+
+                    return Some(value);
+                }
+
+                None
+            }
+        }
+    }
+
+    /// Moves every value below this node to `entries`.
+    fn drain_into(self, entries: &mut Vec<(Rect, V)>) {
+        match self {
+            Self::Branch(children) => {
+                for (_, child) in children {
+                    child.drain_into(entries);
+                }
+            }
+            Self::Leaf(leaf) => entries.extend(leaf),
+        }
+    }
+
+    /// Visits every entry whose bounding box intersects `area` and collects
+    /// the ones that satisfy `filter`.
+    fn search<'a, F: Fn(&Rect) -> bool>(
+        &'a self,
+        area: &Rect,
+        filter: &F,
+        found: &mut Vec<(&'a Rect, &'a V)>,
+    ) {
+        match self {
+            Self::Branch(children) => {
+                for (bounds, child) in children {
+                    if bounds.intersects(area) {
+                        child.search(area, filter, found);
+                    }
+                }
+            }
+            Self::Leaf(entries) => found.extend(
+                entries
+                    .iter()
+                    .filter(|(rect, _)| filter(rect))
+                    .map(|(rect, value)| (rect, value)),
+            ),
+        }
+    }
+}
+
+/// R-tree that indexes values by their axis-aligned bounding box, for
+/// objects that have extent rather than being points, like the roads and
+/// parcels of a map. Nodes group nearby boxes under a common bounding box,
+/// so queries skip every group that doesn't touch the queried area.
+///
+/// All leaves are at the same depth. Nodes hold up to 8 entries and are
+/// split with the quadratic algorithm of Guttman when they overflow, and
+/// nodes left with fewer than 3 entries after a removal are dissolved and
+/// their values inserted again.
+///
+/// # Examples
+///
+/// ```rust
+/// use collections_rust::{RTree, Rect};
+///
+/// let mut parcels = RTree::new();
+///
+/// parcels.insert(Rect::new([0.0, 0.0], [10.0, 10.0]), "park");
+/// parcels.insert(Rect::new([8.0, 8.0], [12.0, 20.0]), "school");
+/// parcels.insert(Rect::new([30.0, 30.0], [40.0, 35.0]), "lake");
+///
+/// let mut touched: Vec<&str> = parcels
+///     .intersecting(&Rect::new([9.0, 9.0], [9.5, 9.5]))
+///     .into_iter()
+///     .map(|(_, value)| *value)
+///     .collect();
+/// touched.sort();
+///
+/// assert_eq!(touched, ["park", "school"]);
+/// ```
+pub struct RTree<V> {
+    root: Node<V>,
+    len: usize,
+}
+
+impl<V> RTree<V> {
+    /// Creates an empty tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::RTree;
+    ///
+    /// let tree: RTree<u32> = RTree::new();
+    ///
+    /// assert!(tree.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            root: Node::Leaf(Vec::new()),
+            len: 0,
+        }
+    }
+
+    /// Returns the number of values in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all the values.
+    pub fn clear(&mut self) {
+        self.root = Node::Leaf(Vec::new());
+        self.len = 0;
+    }
+
+    /// Inserts `value` with the bounding box `rect`. Many values can share
+    /// the same box.
+    pub fn insert(&mut self, rect: Rect, value: V) {
+        self.insert_entry(rect, value);
+        self.len += 1;
+    }
+
+    /// Inserts an entry and grows the tree by one level if the root splits.
+    fn insert_entry(&mut self, rect: Rect, value: V) {
+        if let Some(sibling) = self.root.insert(rect, value) {
+            let root = mem::replace(&mut self.root, Node::Leaf(Vec::new()));
+            self.root = Node::Branch(vec![(root.bounds(), root), (sibling.bounds(), sibling)]);
+        }
+    }
+
+    /// Removes and returns a value whose bounding box is exactly `rect`, or
+    /// `None` if there's no such value. When many values share the box any
+    /// of them may be returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::{RTree, Rect};
+    ///
+    /// let mut tree = RTree::new();
+    /// let rect = Rect::new([0.0, 0.0], [1.0, 2.0]);
+    /// tree.insert(rect, 'a');
+    ///
+    /// assert_eq!(tree.remove(&Rect::new([0.0, 0.0], [1.0, 1.0])), None);
+    /// assert_eq!(tree.remove(&rect), Some('a'));
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn remove(&mut self, rect: &Rect) -> Option<V> {
+        let mut orphans = Vec::new();
+        let value = self.root.remove(rect, &mut orphans)?;
+        self.len -= 1;
+
+        // Drop the roots left with a single child, or none at all
+        while let Node::Branch(children) = &mut self.root {
+            match children.len() {
+                0 => self.root = Node::Leaf(Vec::new()),
+                1 => self.root = children.pop().unwrap().1,
+                _ => break,
+            }
+        }
+
+        for (rect, value) in orphans {
+            self.insert_entry(rect, value);
+        }
+
+        Some(value)
+    }
+
+    /// Returns every value whose bounding box shares at least one point with
+    /// `area`, in no particular order.
+    pub fn intersecting(&self, area: &Rect) -> Vec<(&Rect, &V)> {
+        let mut found = Vec::new();
+        self.root
+            .search(area, &|rect| rect.intersects(area), &mut found);
+
+        found
+    }
+
+    /// Returns every value whose bounding box is completely inside `area`,
+    /// in no particular order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::{RTree, Rect};
+    ///
+    /// let mut tree = RTree::new();
+    /// tree.insert(Rect::new([1.0, 1.0], [2.0, 2.0]), "inside");
+    /// tree.insert(Rect::new([4.0, 4.0], [6.0, 6.0]), "crossing");
+    ///
+    /// let area = Rect::new([0.0, 0.0], [5.0, 5.0]);
+    ///
+    /// assert_eq!(tree.intersecting(&area).len(), 2);
+    /// assert_eq!(tree.contained_in(&area)[0].1, &"inside");
+    /// assert_eq!(tree.contained_in(&area).len(), 1);
+    /// ```
+    pub fn contained_in(&self, area: &Rect) -> Vec<(&Rect, &V)> {
+        let mut found = Vec::new();
+        self.root
+            .search(area, &|rect| area.contains(rect), &mut found);
+
+        found
+    }
+}
+
+impl<V> Default for RTree<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> Extend<(Rect, V)> for RTree<V> {
+    fn extend<I: IntoIterator<Item = (Rect, V)>>(&mut self, iter: I) {
+        for (rect, value) in iter {
+            self.insert(rect, value);
+        }
+    }
+}
+
+impl<V> FromIterator<(Rect, V)> for RTree<V> {
+    fn from_iter<I: IntoIterator<Item = (Rect, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+
+        tree
+    }
+}
+
+impl<V: HeapSize> HeapSize for Node<V> {
+    fn heap_size_of_children(&self) -> usize {
+        match self {
+            Node::Branch(children) => children.heap_size_of_children(),
+            Node::Leaf(entries) => entries.heap_size_of_children(),
+        }
+    }
+}
+
+impl<V: HeapSize> HeapSize for RTree<V> {
+    fn heap_size_of_children(&self) -> usize {
+        self.root.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Node, RTree, Rect, MAX_ENTRIES, MIN_ENTRIES};
+
+    /// Checks that every bounding box is tight, that node sizes are within
+    /// bounds and that all leaves are at the same depth, which is returned.
+    fn check<V>(node: &Node<V>, is_root: bool) -> usize {
+        assert!(node.len() <= MAX_ENTRIES);
+        assert!(is_root || node.len() >= MIN_ENTRIES);
+
+        match node {
+            Node::Leaf(_) => 0,
+            Node::Branch(children) => {
+                let depths: Vec<usize> = children
+                    .iter()
+                    .map(|(bounds, child)| {
+                        assert_eq!(*bounds, child.bounds());
+                        check(child, false)
+                    })
+                    .collect();
+
+                assert!(depths.iter().all(|depth| *depth == depths[0]));
+                depths[0] + 1
+            }
+        }
+    }
+
+    fn rects(count: usize) -> Vec<Rect> {
+        let mut seed: u64 = 0x2545_F491_4F6C_DD1D;
+
+        (0..count)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+
+                let (x, y) = ((seed % 1_000) as f64, (seed / 1_000 % 1_000) as f64);
+                let (width, height) = ((seed >> 40) % 20, (seed >> 50) % 20);
+                Rect::new([x, y], [x + width as f64, y + height as f64])
+            })
+            .collect()
+    }
+
+    fn sorted(found: Vec<(&Rect, &usize)>) -> Vec<usize> {
+        let mut found: Vec<usize> = found.into_iter().map(|(_, index)| *index).collect();
+        found.sort();
+
+        found
+    }
+
+    #[test]
+    fn test_queries() {
+        let rects = rects(3_000);
+        let tree: RTree<usize> = rects.iter().copied().zip(0..).collect();
+
+        assert_eq!(tree.len(), 3_000);
+        assert!(check(&tree.root, true) >= 3);
+
+        for area in [
+            Rect::new([100.0, 100.0], [200.0, 150.0]),
+            Rect::new([500.0, 0.0], [500.0, 1_000.0]),
+            Rect::new([0.0, 0.0], [1_000.0, 1_000.0]),
+            Rect::new([2_000.0, 2_000.0], [3_000.0, 3_000.0]),
+        ] {
+            let intersecting: Vec<usize> = (0..rects.len())
+                .filter(|&index| rects[index].intersects(&area))
+                .collect();
+            let contained: Vec<usize> = (0..rects.len())
+                .filter(|&index| area.contains(&rects[index]))
+                .collect();
+
+            assert_eq!(sorted(tree.intersecting(&area)), intersecting);
+            assert_eq!(sorted(tree.contained_in(&area)), contained);
+        }
+    }
+
+    #[test]
+    fn test_remove() {
+        let rects = rects(1_000);
+        let mut tree: RTree<usize> = rects.iter().copied().zip(0..).collect();
+
+        for rect in rects.iter().step_by(3) {
+            assert!(tree.remove(rect).is_some());
+            check(&tree.root, true);
+        }
+
+        assert_eq!(tree.len(), 666);
+        assert_eq!(tree.remove(&Rect::new([-1.0, -1.0], [0.0, 0.0])), None);
+
+        let everything = Rect::new([0.0, 0.0], [2_000.0, 2_000.0]);
+        assert_eq!(tree.intersecting(&everything).len(), 666);
+
+        for rect in rects.iter().skip(1).step_by(3) {
+            assert!(tree.remove(rect).is_some());
+        }
+        for rect in rects.iter().skip(2).step_by(3) {
+            assert!(tree.remove(rect).is_some());
+        }
+
+        assert!(tree.is_empty());
+        assert!(matches!(&tree.root, Node::Leaf(entries) if entries.is_empty()));
+    }
+}
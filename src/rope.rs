@@ -0,0 +1,658 @@
+use std::{
+    fmt::{self, Display},
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+    str,
+};
+
+use crate::heap_size::HeapSize;
+
+/// Maximum number of bytes of a chunk. Text is split in chunks this size when
+/// inserted, and short insertions are copied into an existing chunk while it
+/// has room, so typing one character at a time doesn't allocate a node for
+/// each of them.
+const MAX_CHUNK: usize = 512;
+
+type Link = Option<Box<Node>>;
+
+struct Node {
+    chunk: String,
+    /// Number of chars in `chunk`.
+    chunk_chars: usize,
+    /// Number of chars in the subtree rooted at this node.
+    chars: usize,
+    /// Number of bytes in the subtree rooted at this node.
+    bytes: usize,
+    /// Random priority, every node has a greater priority than its children.
+    priority: u64,
+    left: Link,
+    right: Link,
+}
+
+impl Node {
+    fn new(chunk: String, priority: u64) -> Box<Self> {
+        let chars = chunk.chars().count();
+
+        Box::new(Self {
+            bytes: chunk.len(),
+            chunk,
+            chunk_chars: chars,
+            chars,
+            priority,
+            left: None,
+            right: None,
+        })
+    }
+
+    fn update(&mut self) {
+        self.chars = self.chunk_chars + chars(&self.left) + chars(&self.right);
+        self.bytes = self.chunk.len() + bytes(&self.left) + bytes(&self.right);
+    }
+}
+
+/// Returns the number of chars in the subtree at `link`.
+fn chars(link: &Link) -> usize {
+    link.as_ref().map_or(0, |node| node.chars)
+}
+
+/// Returns the number of bytes in the subtree at `link`.
+fn bytes(link: &Link) -> usize {
+    link.as_ref().map_or(0, |node| node.bytes)
+}
+
+/// Returns the byte offset of the char at `index` in `text`, or the length
+/// of `text` if `index` is the number of chars.
+fn byte_offset(text: &str, index: usize) -> usize {
+    text.char_indices()
+        .nth(index)
+        .map_or(text.len(), |(offset, _)| offset)
+}
+
+/// Returns the next value of the xorshift generator at `seed`.
+fn next_priority(seed: &mut u64) -> u64 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 7;
+    *seed ^= *seed << 17;
+
+    *seed
+}
+
+/// Splits the subtree at `link` in the first `at` chars and the rest, which
+/// splits the chunk where the boundary falls in two if necessary.
+fn split(link: Link, at: usize, seed: &mut u64) -> (Link, Link) {
+    let Some(mut node) = link else {
+        return (None, None);
+    };
+
+    let left_chars = chars(&node.left);
+
+    if at <= left_chars {
+        let (left, right) = split(node.left.take(), at, seed);
+        node.left = right;
+        node.update();
+
+        (left, Some(node))
+    } else if at >= left_chars + node.chunk_chars {
+        let (left, right) = split(node.right.take(), at - left_chars - node.chunk_chars, seed);
+        node.right = left;
+        node.update();
+
+        (Some(node), right)
+    } else {
+        let offset = byte_offset(&node.chunk, at - left_chars);
+        let tail = Node::new(node.chunk.split_off(offset), next_priority(seed));
+
+        node.chunk_chars = at - left_chars;
+        let right = merge(Some(tail), node.right.take());
+        node.update();
+
+        (Some(node), right)
+    }
+}
+
+/// Joins two subtrees, the text of `left` going before the text of `right`.
+fn merge(left: Link, right: Link) -> Link {
+    match (left, right) {
+        (None, right) => right,
+        (left, None) => left,
+
+        (Some(mut left), Some(mut right)) => {
+            if left.priority > right.priority {
+                left.right = merge(left.right.take(), Some(right));
+                left.update();
+
+                Some(left)
+            } else {
+                right.left = merge(Some(left), right.left.take());
+                right.update();
+
+                Some(right)
+            }
+        }
+    }
+}
+
+/// Inserts `text` at char `at` of the subtree at `link` if the chunk where it
+/// goes has room for it. Returns `false` without modifying anything if not.
+fn insert_in_chunk(link: &mut Link, at: usize, text: &str, text_chars: usize) -> bool {
+    let Some(node) = link else {
+        return false;
+    };
+
+    let left_chars = chars(&node.left);
+
+    let inserted = if at < left_chars {
+        insert_in_chunk(&mut node.left, at, text, text_chars)
+    } else if at <= left_chars + node.chunk_chars {
+        if node.chunk.len() + text.len() > MAX_CHUNK {
+            return false;
+        }
+
+        let offset = byte_offset(&node.chunk, at - left_chars);
+        node.chunk.insert_str(offset, text);
+        node.chunk_chars += text_chars;
+
+        true
+    } else {
+        let at = at - left_chars - node.chunk_chars;
+        insert_in_chunk(&mut node.right, at, text, text_chars)
+    };
+
+    if inserted {
+        node.chars += text_chars;
+        node.bytes += text.len();
+    }
+
+    inserted
+}
+
+/// Appends the chars in `start..end` of the subtree at `link` to `out`.
+fn collect(link: &Link, start: usize, end: usize, out: &mut String) {
+    let Some(node) = link else {
+        return;
+    };
+
+    let left_chars = chars(&node.left);
+    let chunk_end = left_chars + node.chunk_chars;
+
+    if start < left_chars {
+        collect(&node.left, start, end.min(left_chars), out);
+    }
+
+    if start < chunk_end && end > left_chars {
+        let from = byte_offset(&node.chunk, start.saturating_sub(left_chars));
+        let to = byte_offset(&node.chunk, end.min(chunk_end) - left_chars);
+        out.push_str(&node.chunk[from..to]);
+    }
+
+    if end > chunk_end {
+        collect(
+            &node.right,
+            start.saturating_sub(chunk_end),
+            end - chunk_end,
+            out,
+        );
+    }
+}
+
+/// Text stored as a balanced tree of string chunks, which makes inserting
+/// and removing text at any position take O(log n) instead of moving every
+/// byte after it like a contiguous buffer does. Positions are counted in
+/// chars, not bytes.
+///
+/// The chunks are kept in a treap ordered by position: every node stores the
+/// number of chars in its subtree, and edits are done by splitting the tree
+/// at the edited positions and merging the pieces back together.
+pub struct Rope {
+    root: Link,
+    /// State of the xorshift generator used for priorities.
+    seed: u64,
+}
+
+impl Rope {
+    /// Creates an empty rope.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Rope;
+    ///
+    /// let rope = Rope::new();
+    ///
+    /// assert!(rope.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            root: None,
+            seed: 0x2545_F491_4F6C_DD1D,
+        }
+    }
+
+    /// Returns the number of chars.
+    pub fn len_chars(&self) -> usize {
+        chars(&self.root)
+    }
+
+    /// Returns the number of bytes of the text encoded as UTF-8.
+    pub fn len_bytes(&self) -> usize {
+        bytes(&self.root)
+    }
+
+    /// Returns `true` if the rope contains no text.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Removes all the text.
+    pub fn clear(&mut self) {
+        self.root = None;
+    }
+
+    /// Returns the start and end of `range` in chars after checking its
+    /// bounds.
+    fn bounds<R: RangeBounds<usize>>(&self, range: R) -> (usize, usize) {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len_chars(),
+        };
+
+        assert!(
+            start <= end && end <= self.len_chars(),
+            "Range out of bounds"
+        );
+
+        (start, end)
+    }
+
+    /// Builds a subtree holding `text` split in chunks.
+    fn build(&mut self, mut text: &str) -> Link {
+        let mut link = None;
+
+        while !text.is_empty() {
+            let mut end = text.len().min(MAX_CHUNK);
+
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+
+            let node = Node::new(text[..end].to_owned(), next_priority(&mut self.seed));
+            link = merge(link, Some(node));
+            text = &text[end..];
+        }
+
+        link
+    }
+
+    /// Inserts `text` before the char at `index`, in O(log n + m) for a text
+    /// of m bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than [`Rope::len_chars`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Rope;
+    ///
+    /// let mut rope = Rope::from("hello world");
+    /// rope.insert(5, ",");
+    /// rope.insert(rope.len_chars(), "!");
+    ///
+    /// assert_eq!(rope.to_string(), "hello, world!");
+    /// ```
+    pub fn insert(&mut self, index: usize, text: &str) {
+        assert!(index <= self.len_chars(), "Index out of bounds");
+
+        let text_chars = text.chars().count();
+
+        if text_chars == 0 || insert_in_chunk(&mut self.root, index, text, text_chars) {
+            return;
+        }
+
+        let (left, right) = split(self.root.take(), index, &mut self.seed);
+        let middle = self.build(text);
+
+        self.root = merge(merge(left, middle), right);
+    }
+
+    /// Removes the chars in `range`, in O(log n).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Rope;
+    ///
+    /// let mut rope = Rope::from("hello, cruel world");
+    /// rope.remove(5..12);
+    ///
+    /// assert_eq!(rope.to_string(), "hello world");
+    /// ```
+    pub fn remove<R: RangeBounds<usize>>(&mut self, range: R) {
+        let (start, end) = self.bounds(range);
+
+        if start == end {
+            return;
+        }
+
+        let (rest, right) = split(self.root.take(), end, &mut self.seed);
+        let (left, _) = split(rest, start, &mut self.seed);
+
+        self.root = merge(left, right);
+    }
+
+    /// Returns the chars in `range` as a string, in O(log n + m) for a slice
+    /// of m bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Rope;
+    ///
+    /// let rope = Rope::from("¡hola, mundo!");
+    ///
+    /// assert_eq!(rope.slice(1..5), "hola");
+    /// assert_eq!(rope.slice(7..), "mundo!");
+    /// ```
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> String {
+        let (start, end) = self.bounds(range);
+        let mut out = String::new();
+
+        if start < end {
+            collect(&self.root, start, end, &mut out);
+        }
+
+        out
+    }
+
+    /// Returns the char at `index`, or `None` if it's out of bounds.
+    pub fn char_at(&self, mut index: usize) -> Option<char> {
+        let mut link = &self.root;
+
+        while let Some(node) = link {
+            let left_chars = chars(&node.left);
+
+            if index < left_chars {
+                link = &node.left;
+            } else if index < left_chars + node.chunk_chars {
+                return node.chunk.chars().nth(index - left_chars);
+            } else {
+                index -= left_chars + node.chunk_chars;
+                link = &node.right;
+            }
+        }
+
+        None
+    }
+
+    /// Moves the chars from `index` onwards to a new rope and returns it, in
+    /// O(log n).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than [`Rope::len_chars`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Rope;
+    ///
+    /// let mut rope = Rope::from("key=value");
+    /// let value = rope.split_off(4);
+    ///
+    /// assert_eq!(rope.to_string(), "key=");
+    /// assert_eq!(value.to_string(), "value");
+    /// ```
+    pub fn split_off(&mut self, index: usize) -> Self {
+        assert!(index <= self.len_chars(), "Index out of bounds");
+
+        let (left, right) = split(self.root.take(), index, &mut self.seed);
+        self.root = left;
+
+        Self {
+            root: right,
+            seed: next_priority(&mut self.seed),
+        }
+    }
+
+    /// Moves all the text of `other` to the end of this rope, in O(log n).
+    pub fn append(&mut self, mut other: Self) {
+        self.root = merge(self.root.take(), other.root.take());
+    }
+
+    /// Returns an iterator over the chunks of text in order. Concatenated,
+    /// they give back the whole text.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Rope;
+    ///
+    /// let text = "lorem ipsum ".repeat(1000);
+    /// let rope = Rope::from(text.as_str());
+    ///
+    /// assert!(rope.chunks().count() > 1);
+    /// assert_eq!(rope.chunks().collect::<String>(), text);
+    /// ```
+    pub fn chunks(&self) -> Chunks<'_> {
+        let mut chunks = Chunks {
+            stack: Vec::new(),
+            marker: PhantomData,
+        };
+
+        chunks.push_left_spine(self.root.as_deref());
+
+        chunks
+    }
+
+    /// Returns an iterator over the chars in order.
+    pub fn chars(&self) -> Chars<'_> {
+        Chars {
+            chunks: self.chunks(),
+            current: "".chars(),
+        }
+    }
+}
+
+impl Default for Rope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<&str> for Rope {
+    fn from(text: &str) -> Self {
+        let mut rope = Self::new();
+        rope.root = rope.build(text);
+
+        rope
+    }
+}
+
+impl Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for chunk in self.chunks() {
+            f.write_str(chunk)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Iterator over the chunks of a [`Rope`].
+pub struct Chunks<'a> {
+    /// Nodes whose chunk and right subtree haven't been visited yet.
+    stack: Vec<&'a Node>,
+    marker: PhantomData<&'a str>,
+}
+
+impl<'a> Chunks<'a> {
+    fn push_left_spine(&mut self, mut node: Option<&'a Node>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(node.right.as_deref());
+
+        Some(&node.chunk)
+    }
+}
+
+/// Iterator over the chars of a [`Rope`].
+pub struct Chars<'a> {
+    chunks: Chunks<'a>,
+    current: str::Chars<'a>,
+}
+
+impl<'a> Iterator for Chars<'a> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(ch) = self.current.next() {
+                return Some(ch);
+            }
+
+            self.current = self.chunks.next()?.chars();
+        }
+    }
+}
+
+impl HeapSize for Node {
+    fn heap_size_of_children(&self) -> usize {
+        self.chunk.heap_size_of_children()
+            + self.left.heap_size_of_children()
+            + self.right.heap_size_of_children()
+    }
+}
+
+impl HeapSize for Rope {
+    fn heap_size_of_children(&self) -> usize {
+        self.root.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Link, Rope, MAX_CHUNK};
+
+    /// Checks the heap, size and chunk invariants.
+    fn check(rope: &Rope) {
+        fn check_node(link: &Link) -> (usize, usize) {
+            let Some(node) = link else {
+                return (0, 0);
+            };
+
+            assert!(!node.chunk.is_empty() && node.chunk.len() <= MAX_CHUNK);
+            assert_eq!(node.chunk_chars, node.chunk.chars().count());
+
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                assert!(child.priority <= node.priority);
+            }
+
+            let (left_chars, left_bytes) = check_node(&node.left);
+            let (right_chars, right_bytes) = check_node(&node.right);
+
+            assert_eq!(node.chars, node.chunk_chars + left_chars + right_chars);
+            assert_eq!(node.bytes, node.chunk.len() + left_bytes + right_bytes);
+
+            (node.chars, node.bytes)
+        }
+
+        check_node(&rope.root);
+    }
+
+    #[test]
+    fn test_edits() {
+        let mut rope = Rope::new();
+        let mut expected: Vec<char> = Vec::new();
+        let words = ["añadir", " ", "日本語", "text ", "x", &"long ".repeat(300)];
+
+        for step in 0..500usize {
+            let index = (step * 31) % (expected.len() + 1);
+
+            if step % 3 == 2 {
+                let end = (index + step % 50).min(expected.len());
+                rope.remove(index..end);
+                expected.drain(index..end);
+            } else {
+                let word = words[step % words.len()];
+                rope.insert(index, word);
+                expected.splice(index..index, word.chars());
+            }
+
+            if step % 50 == 0 {
+                check(&rope);
+            }
+
+            assert_eq!(rope.len_chars(), expected.len());
+        }
+
+        check(&rope);
+
+        let text: String = expected.iter().collect();
+        assert_eq!(rope.to_string(), text);
+        assert_eq!(rope.len_bytes(), text.len());
+        assert!(rope.chars().eq(expected.iter().copied()));
+
+        for index in (0..expected.len()).step_by(97) {
+            assert_eq!(rope.char_at(index), Some(expected[index]));
+
+            let end = (index + 700).min(expected.len());
+            let slice: String = expected[index..end].iter().collect();
+            assert_eq!(rope.slice(index..end), slice);
+        }
+
+        assert_eq!(rope.char_at(expected.len()), None);
+
+        rope.remove(..);
+        assert!(rope.is_empty());
+    }
+
+    #[test]
+    fn test_split_append() {
+        let text = "abcdé".repeat(500);
+        let mut rope = Rope::from(text.as_str());
+
+        let tail = rope.split_off(1234);
+        check(&rope);
+        check(&tail);
+
+        let chars: Vec<char> = text.chars().collect();
+        assert!(rope.chars().eq(chars[..1234].iter().copied()));
+        assert!(tail.chars().eq(chars[1234..].iter().copied()));
+
+        rope.append(tail);
+        check(&rope);
+        assert_eq!(rope.to_string(), text);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_insert_out_of_bounds() {
+        Rope::from("abc").insert(4, "d");
+    }
+}
@@ -0,0 +1,467 @@
+use std::{cmp::Ordering, marker::PhantomData};
+
+use crate::heap_size::HeapSize;
+
+type Link<T> = Option<Box<Node<T>>>;
+
+/// Nodes only store the value and the children, the balance is maintained
+/// from the size of the whole tree alone.
+struct Node<T> {
+    value: T,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+/// Result of inserting into a subtree.
+enum Inserted {
+    /// The value was already present.
+    Duplicate,
+    /// The value was inserted and the tree needs no more changes.
+    Done,
+    /// The value was inserted too deep, and the subtree that contains it,
+    /// with the given size, is not unbalanced enough to be rebuilt.
+    TooDeep(usize),
+}
+
+/// Returns the number of nodes in the subtree at `link`.
+fn count<T>(link: &Link<T>) -> usize {
+    let mut stack: Vec<&Node<T>> = link.as_deref().into_iter().collect();
+    let mut count = 0;
+
+    while let Some(node) = stack.pop() {
+        count += 1;
+        stack.extend(node.left.as_deref());
+        stack.extend(node.right.as_deref());
+    }
+
+    count
+}
+
+/// Rebuilds the subtree at `link`, which has `size` nodes, into a perfectly
+/// balanced one reusing its nodes.
+fn rebuild<T>(link: &mut Link<T>, size: usize) {
+    let mut nodes = Vec::with_capacity(size);
+    let mut stack = Vec::new();
+    let mut current = link.take();
+
+    loop {
+        while let Some(mut node) = current {
+            current = node.left.take();
+            stack.push(node);
+        }
+
+        let Some(mut node) = stack.pop() else {
+            break;
+        };
+
+        current = node.right.take();
+        nodes.push(node);
+    }
+
+    fn build<T>(nodes: &mut std::vec::IntoIter<Box<Node<T>>>, size: usize) -> Link<T> {
+        if size == 0 {
+            return None;
+        }
+
+        let left = build(nodes, size / 2);
+        let mut node = nodes.next().unwrap();
+        node.left = left;
+        node.right = build(nodes, size - size / 2 - 1);
+
+        Some(node)
+    }
+
+    *link = build(&mut nodes.into_iter(), size);
+}
+
+/// Self-balancing binary search tree that keeps no balance information in
+/// the nodes, which makes it the most memory efficient balanced tree of the
+/// crate. When an insertion ends up deeper than `log(n)` in base 3/2, the
+/// tree walks back up to the first ancestor whose subtree is unbalanced (the
+/// scapegoat) and rebuilds that subtree perfectly balanced. Removals rebuild
+/// the whole tree once it shrinks to 2/3 of its size since the last rebuild.
+/// Lookups are O(log n) worst case, updates O(log n) amortized.
+pub struct ScapegoatTree<T> {
+    root: Link<T>,
+    len: usize,
+    /// Greatest `len` since the whole tree was last rebuilt.
+    max_len: usize,
+}
+
+impl<T> ScapegoatTree<T> {
+    /// Creates an empty tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::ScapegoatTree;
+    ///
+    /// let tree: ScapegoatTree<i32> = ScapegoatTree::new();
+    ///
+    /// assert!(tree.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            root: None,
+            len: 0,
+            max_len: 0,
+        }
+    }
+
+    /// Returns the number of values in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all the values from the tree.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Returns an iterator over the values in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::ScapegoatTree;
+    ///
+    /// let tree: ScapegoatTree<i32> = [3, 1, 2].into_iter().collect();
+    ///
+    /// assert!(tree.iter().eq([1, 2, 3].iter()));
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            len: self.len,
+            marker: PhantomData,
+        };
+
+        iter.push_left_spine(self.root.as_deref());
+
+        iter
+    }
+
+    /// Returns the maximum depth allowed for a tree of `len` nodes, which is
+    /// `log(len)` in base 3/2.
+    fn max_depth(len: usize) -> usize {
+        ((len as f64).ln() / 1.5f64.ln()).floor() as usize
+    }
+}
+
+impl<T: Ord> ScapegoatTree<T> {
+    /// Returns `true` if the tree contains `value`.
+    pub fn contains(&self, value: &T) -> bool {
+        let mut link = &self.root;
+
+        while let Some(node) = link {
+            link = match value.cmp(&node.value) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return true,
+            };
+        }
+
+        false
+    }
+
+    /// Inserts `value` and returns `true` unless an equal value is already
+    /// present, in which case the tree is not modified.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::ScapegoatTree;
+    ///
+    /// let mut tree = ScapegoatTree::new();
+    ///
+    /// assert!(tree.insert(1));
+    /// assert!(!tree.insert(1));
+    /// assert_eq!(tree.len(), 1);
+    /// ```
+    pub fn insert(&mut self, value: T) -> bool {
+        let max_depth = Self::max_depth(self.len + 1);
+
+        match Self::insert_into(&mut self.root, value, 0, max_depth) {
+            Inserted::Duplicate => return false,
+            Inserted::Done => {}
+            // Can't happen, the root subtree is always unbalanced enough if
+            // the insertion was too deep, but rebuilding is always correct.
+            Inserted::TooDeep(size) => rebuild(&mut self.root, size),
+        }
+
+        self.len += 1;
+        self.max_len = self.max_len.max(self.len);
+
+        true
+    }
+
+    /// Inserts `value` into the subtree at `link`, which is at `depth`, and
+    /// rebuilds the scapegoat on the way back if needed.
+    fn insert_into(link: &mut Link<T>, value: T, depth: usize, max_depth: usize) -> Inserted {
+        let Some(node) = link else {
+            *link = Some(Box::new(Node {
+                value,
+                left: None,
+                right: None,
+            }));
+
+            return if depth > max_depth {
+                Inserted::TooDeep(1)
+            } else {
+                Inserted::Done
+            };
+        };
+
+        let (child, sibling) = match value.cmp(&node.value) {
+            Ordering::Less => (&mut node.left, &node.right),
+            Ordering::Greater => (&mut node.right, &node.left),
+            Ordering::Equal => return Inserted::Duplicate,
+        };
+
+        match Self::insert_into(child, value, depth + 1, max_depth) {
+            Inserted::TooDeep(child_size) => {
+                let size = child_size + count(sibling) + 1;
+
+                // The child holds more than 2/3 of the subtree
+                if 3 * child_size > 2 * size {
+                    rebuild(link, size);
+                    Inserted::Done
+                } else {
+                    Inserted::TooDeep(size)
+                }
+            }
+
+            inserted => inserted,
+        }
+    }
+
+    /// Removes `value` and returns `true` unless it's not present.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::ScapegoatTree;
+    ///
+    /// let mut tree: ScapegoatTree<i32> = [1, 2].into_iter().collect();
+    ///
+    /// assert!(tree.remove(&1));
+    /// assert!(!tree.remove(&1));
+    /// assert!(!tree.contains(&1));
+    /// ```
+    pub fn remove(&mut self, value: &T) -> bool {
+        let mut link = &mut self.root;
+
+        loop {
+            let Some(node) = link else {
+                return false;
+            };
+
+            match value.cmp(&node.value) {
+                Ordering::Less => link = &mut link.as_mut().unwrap().left,
+                Ordering::Greater => link = &mut link.as_mut().unwrap().right,
+                Ordering::Equal => break,
+            }
+        }
+
+        let mut node = link.take().unwrap();
+
+        *link = match (node.left.take(), node.right.take()) {
+            (None, right) => right,
+            (left, None) => left,
+            (left, Some(right)) => {
+                // Replace the node with the minimum of its right subtree
+                let mut right = Some(right);
+                let mut min = &mut right;
+
+                while min.as_ref().unwrap().left.is_some() {
+                    min = &mut min.as_mut().unwrap().left;
+                }
+
+                let mut successor = min.take().unwrap();
+                *min = successor.right.take();
+                successor.left = left;
+                successor.right = right;
+
+                Some(successor)
+            }
+        };
+
+        self.len -= 1;
+
+        if 3 * self.len < 2 * self.max_len {
+            rebuild(&mut self.root, self.len);
+            self.max_len = self.len;
+        }
+
+        true
+    }
+}
+
+impl<T> Default for ScapegoatTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for ScapegoatTree<T> {
+    /// Drops the nodes iteratively, the height is bounded but an explicit
+    /// stack is as cheap as recursion.
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<Node<T>>> = self.root.take().into_iter().collect();
+
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+impl<T: Ord> Extend<T> for ScapegoatTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for ScapegoatTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+
+        tree
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ScapegoatTree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the values of a [`ScapegoatTree`] in ascending order.
+pub struct Iter<'a, T> {
+    /// Nodes whose value and right subtree haven't been visited yet.
+    stack: Vec<&'a Node<T>>,
+    len: usize,
+    marker: PhantomData<&'a T>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn push_left_spine(&mut self, mut node: Option<&'a Node<T>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(node.right.as_deref());
+        self.len -= 1;
+
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<T: HeapSize> HeapSize for Node<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.value.heap_size_of_children()
+            + self.left.heap_size_of_children()
+            + self.right.heap_size_of_children()
+    }
+}
+
+impl<T: HeapSize> HeapSize for ScapegoatTree<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.root.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count, Link, ScapegoatTree};
+
+    fn height<T>(link: &Link<T>) -> usize {
+        link.as_ref()
+            .map_or(0, |node| 1 + height(&node.left).max(height(&node.right)))
+    }
+
+    /// Checks that the height is within the bound the tree guarantees.
+    fn check_height<T>(tree: &ScapegoatTree<T>) {
+        assert_eq!(count(&tree.root), tree.len());
+
+        if tree.max_len > 0 {
+            assert!(height(&tree.root) <= ScapegoatTree::<T>::max_depth(tree.max_len) + 1);
+        }
+    }
+
+    #[test]
+    fn test_sorted_insertions() {
+        let mut tree = ScapegoatTree::new();
+
+        for value in 0..10_000 {
+            assert!(tree.insert(value));
+
+            if value % 100 == 0 {
+                check_height(&tree);
+            }
+        }
+
+        check_height(&tree);
+
+        assert!(!tree.insert(500));
+        assert_eq!(tree.len(), 10_000);
+        assert!(tree.iter().copied().eq(0..10_000));
+        assert!(tree.contains(&9_999));
+        assert!(!tree.contains(&10_000));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut tree: ScapegoatTree<i32> = (0..1_000).rev().collect();
+
+        for value in (0..1_000).step_by(3) {
+            assert!(tree.remove(&value));
+            check_height(&tree);
+        }
+
+        assert!(!tree.remove(&0));
+        assert_eq!(tree.iter().len(), 666);
+        assert!(tree
+            .iter()
+            .copied()
+            .eq((0..1_000).filter(|value| value % 3 != 0)));
+
+        for value in 0..1_000 {
+            tree.remove(&value);
+            check_height(&tree);
+        }
+
+        assert!(tree.is_empty());
+        assert!(tree.root.is_none());
+
+        tree.extend([2, 1]);
+        assert!((&tree).into_iter().eq([1, 2].iter()));
+        tree.clear();
+        assert!(tree.is_empty());
+    }
+}
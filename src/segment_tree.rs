@@ -0,0 +1,647 @@
+use std::{
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+};
+
+use crate::{heap_size::HeapSize, vector::Vector};
+
+/// Associative operation with an identity value, used by [`SegmentTree`] to
+/// combine ranges of values. Implemented by [`Sum`], [`Min`] and [`Max`] for
+/// the primitive integer types, and by [`Gcd`] for the unsigned ones.
+///
+/// `combine` must be associative and `identity` must leave any value
+/// unchanged when combined with it, otherwise queries return meaningless
+/// results.
+pub trait Monoid {
+    /// Type of the values being combined.
+    type Value: Clone;
+
+    /// Returns the value that leaves any other value unchanged.
+    fn identity(&self) -> Self::Value;
+
+    /// Combines `a` with `b`, where `a` comes first.
+    fn combine(&self, a: &Self::Value, b: &Self::Value) -> Self::Value;
+}
+
+/// Marker for monoids where combining a value with itself gives back the
+/// same value, so overlapping ranges can be combined without counting any
+/// value twice. Required by [`crate::SparseTable`].
+pub trait Idempotent: Monoid {}
+
+/// Update that can be applied to a whole range of a [`SegmentTree`] at once,
+/// without visiting every value of the range. Implemented by [`RangeAdd`]
+/// for [`Sum`], [`Min`] and [`Max`].
+pub trait Action<M: Monoid>: Clone {
+    /// Returns the result of applying the action to every one of the `len`
+    /// values whose combination is `value`.
+    fn apply(&self, monoid: &M, value: &M::Value, len: usize) -> M::Value;
+
+    /// Returns the action equivalent to applying `earlier` and then `self`.
+    fn compose(&self, earlier: &Self) -> Self;
+}
+
+/// Action of trees that don't support range updates. It has no values, so
+/// [`SegmentTree::update_range`] can't be called on them.
+#[derive(Clone, Copy, Debug)]
+pub enum NoUpdate {}
+
+impl<M: Monoid> Action<M> for NoUpdate {
+    fn apply(&self, _: &M, _: &M::Value, _: usize) -> M::Value {
+        match *self {}
+    }
+
+    fn compose(&self, _: &Self) -> Self {
+        match *self {}
+    }
+}
+
+/// Monoid that adds values.
+pub struct Sum<T>(PhantomData<T>);
+
+/// Monoid that keeps the smallest value.
+pub struct Min<T>(PhantomData<T>);
+
+/// Monoid that keeps the greatest value.
+pub struct Max<T>(PhantomData<T>);
+
+/// Monoid that computes the greatest common divisor, where zero is the
+/// identity.
+pub struct Gcd<T>(PhantomData<T>);
+
+/// Action that adds the same amount to every value of a range.
+///
+/// With [`Min`] and [`Max`] values equal to the identity of the monoid are
+/// left as they are, since they stand for missing values and adding to them
+/// would overflow.
+#[derive(Clone, Copy, Debug)]
+pub struct RangeAdd<T>(pub T);
+
+macro_rules! impl_monoid_default {
+    ($($monoid:ident)*) => {$(
+        impl<T> Default for $monoid<T> {
+            fn default() -> Self {
+                Self(PhantomData)
+            }
+        }
+    )*};
+}
+
+impl_monoid_default!(Sum Min Max Gcd);
+
+macro_rules! impl_integer_monoids {
+    ($($t:ty)*) => {$(
+        impl Monoid for Sum<$t> {
+            type Value = $t;
+
+            fn identity(&self) -> $t {
+                0
+            }
+
+            fn combine(&self, a: &$t, b: &$t) -> $t {
+                a + b
+            }
+        }
+
+        impl Monoid for Min<$t> {
+            type Value = $t;
+
+            fn identity(&self) -> $t {
+                <$t>::MAX
+            }
+
+            fn combine(&self, a: &$t, b: &$t) -> $t {
+                *a.min(b)
+            }
+        }
+
+        impl Monoid for Max<$t> {
+            type Value = $t;
+
+            fn identity(&self) -> $t {
+                <$t>::MIN
+            }
+
+            fn combine(&self, a: &$t, b: &$t) -> $t {
+                *a.max(b)
+            }
+        }
+
+        impl Idempotent for Min<$t> {}
+
+        impl Idempotent for Max<$t> {}
+
+        impl Action<Sum<$t>> for RangeAdd<$t> {
+            fn apply(&self, _: &Sum<$t>, value: &$t, len: usize) -> $t {
+                value + self.0 * len as $t
+            }
+
+            fn compose(&self, earlier: &Self) -> Self {
+                RangeAdd(self.0 + earlier.0)
+            }
+        }
+
+        impl Action<Min<$t>> for RangeAdd<$t> {
+            fn apply(&self, monoid: &Min<$t>, value: &$t, _: usize) -> $t {
+                if *value == monoid.identity() {
+                    *value
+                } else {
+                    value + self.0
+                }
+            }
+
+            fn compose(&self, earlier: &Self) -> Self {
+                RangeAdd(self.0 + earlier.0)
+            }
+        }
+
+        impl Action<Max<$t>> for RangeAdd<$t> {
+            fn apply(&self, monoid: &Max<$t>, value: &$t, _: usize) -> $t {
+                if *value == monoid.identity() {
+                    *value
+                } else {
+                    value + self.0
+                }
+            }
+
+            fn compose(&self, earlier: &Self) -> Self {
+                RangeAdd(self.0 + earlier.0)
+            }
+        }
+    )*};
+}
+
+impl_integer_monoids!(i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize);
+
+macro_rules! impl_unsigned_monoids {
+    ($($t:ty)*) => {$(
+        impl Monoid for Gcd<$t> {
+            type Value = $t;
+
+            fn identity(&self) -> $t {
+                0
+            }
+
+            fn combine(&self, a: &$t, b: &$t) -> $t {
+                let (mut a, mut b) = (*a, *b);
+
+                while b != 0 {
+                    (a, b) = (b, a % b);
+                }
+
+                a
+            }
+        }
+
+        impl Idempotent for Gcd<$t> {}
+    )*};
+}
+
+impl_unsigned_monoids!(u8 u16 u32 u64 u128 usize);
+
+/// Fixed-length sequence of values that answers the combination of any range
+/// of them under a [`Monoid`] in O(log n), while still allowing values to be
+/// changed in O(log n). With an [`Action`] other than [`NoUpdate`], whole
+/// ranges can be updated in O(log n) too: updates are stored lazily in the
+/// nodes covering the range and only pushed down when needed.
+///
+/// The tree is stored implicitly in a [`Vector`] with the length rounded up to
+/// a power of two. Node `1` is the root and node `i` has children `2i` and
+/// `2i + 1`.
+pub struct SegmentTree<M: Monoid, A = NoUpdate> {
+    monoid: M,
+    len: usize,
+    /// Combination of the values covered by every node, with the pending
+    /// actions of the node itself already applied.
+    tree: Vector<M::Value>,
+    /// Actions not yet applied to the children of every node.
+    lazy: Vector<Option<A>>,
+}
+
+impl<M: Monoid, A: Action<M>> SegmentTree<M, A> {
+    /// Creates a tree with `len` values equal to the identity of `monoid`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::{Max, SegmentTree};
+    ///
+    /// let tree: SegmentTree<Max<i32>> = SegmentTree::new(Max::default(), 10);
+    ///
+    /// assert_eq!(tree.len(), 10);
+    /// assert_eq!(tree.query(..), i32::MIN);
+    /// ```
+    pub fn new(monoid: M, len: usize) -> Self {
+        let identity = monoid.identity();
+
+        Self::from_values(monoid, (0..len).map(|_| identity.clone()))
+    }
+
+    /// Creates a tree holding `values`, combined with `monoid`, in O(n).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::{Min, SegmentTree};
+    ///
+    /// let tree: SegmentTree<Min<u32>> = SegmentTree::from_values(Min::default(), [5, 3, 8, 1]);
+    ///
+    /// assert_eq!(tree.query(..3), 3);
+    /// ```
+    pub fn from_values<I: IntoIterator<Item = M::Value>>(monoid: M, values: I) -> Self {
+        let values: Vec<M::Value> = values.into_iter().collect();
+        let size = values.len().next_power_of_two();
+
+        let mut tree = Vector::new();
+        let mut lazy = Vector::new();
+
+        for _ in 0..size {
+            tree.push(monoid.identity());
+        }
+
+        let len = values.len();
+
+        for value in values {
+            tree.push(value);
+        }
+
+        for _ in len..size {
+            tree.push(monoid.identity());
+        }
+
+        for _ in 0..2 * size {
+            lazy.push(None);
+        }
+
+        for node in (1..size).rev() {
+            tree[node] = monoid.combine(&tree[2 * node], &tree[2 * node + 1]);
+        }
+
+        Self {
+            monoid,
+            len,
+            tree,
+            lazy,
+        }
+    }
+
+    /// Returns the number of values.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of leaves, a power of two.
+    fn size(&self) -> usize {
+        self.tree.len() / 2
+    }
+
+    /// Returns the start and end of `range` after checking its bounds.
+    fn bounds<R: RangeBounds<usize>>(&self, range: R) -> (usize, usize) {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len,
+        };
+
+        assert!(start <= end && end <= self.len, "Range out of bounds");
+
+        (start, end)
+    }
+
+    /// Returns the value at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> M::Value {
+        self.query(index..=index)
+    }
+
+    /// Returns the combination of the values in `range`, or the identity if
+    /// it's empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::{SegmentTree, Sum};
+    ///
+    /// let tree: SegmentTree<Sum<u64>> = (1..=10).collect();
+    ///
+    /// assert_eq!(tree.query(..), 55);
+    /// assert_eq!(tree.query(2..5), 3 + 4 + 5);
+    /// assert_eq!(tree.query(4..4), 0);
+    /// ```
+    pub fn query<R: RangeBounds<usize>>(&self, range: R) -> M::Value {
+        let (start, end) = self.bounds(range);
+
+        if start == end {
+            return self.monoid.identity();
+        }
+
+        self.query_node(1, 0, self.size(), start, end)
+    }
+
+    /// Returns the combination of the values in `start..end` covered by
+    /// `node`, which covers `low..high`.
+    fn query_node(
+        &self,
+        node: usize,
+        low: usize,
+        high: usize,
+        start: usize,
+        end: usize,
+    ) -> M::Value {
+        if end <= low || high <= start {
+            return self.monoid.identity();
+        }
+
+        if start <= low && high <= end {
+            return self.tree[node].clone();
+        }
+
+        let mid = (low + high) / 2;
+        let value = self.monoid.combine(
+            &self.query_node(2 * node, low, mid, start, end),
+            &self.query_node(2 * node + 1, mid, high, start, end),
+        );
+
+        // Pending actions of this node haven't reached the children yet
+        match &self.lazy[node] {
+            Some(action) => {
+                let len = end.min(high) - start.max(low);
+                action.apply(&self.monoid, &value, len)
+            }
+            None => value,
+        }
+    }
+
+    /// Replaces the value at `index` with `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::{SegmentTree, Sum};
+    ///
+    /// let mut tree: SegmentTree<Sum<i32>> = [1, 2, 3].into_iter().collect();
+    /// tree.set(1, 10);
+    ///
+    /// assert_eq!(tree.query(..), 14);
+    /// ```
+    pub fn set(&mut self, index: usize, value: M::Value) {
+        assert!(index < self.len, "Index out of bounds");
+
+        self.set_node(1, 0, self.size(), index, value);
+    }
+
+    fn set_node(&mut self, node: usize, low: usize, high: usize, index: usize, value: M::Value) {
+        if high - low == 1 {
+            self.tree[node] = value;
+            return;
+        }
+
+        self.push_down(node, low, high);
+
+        let mid = (low + high) / 2;
+
+        if index < mid {
+            self.set_node(2 * node, low, mid, index, value);
+        } else {
+            self.set_node(2 * node + 1, mid, high, index, value);
+        }
+
+        self.pull_up(node);
+    }
+
+    /// Applies `action` to every value in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::{RangeAdd, SegmentTree, Sum};
+    ///
+    /// let mut tree: SegmentTree<Sum<i64>, RangeAdd<i64>> = [0; 8].into_iter().collect();
+    ///
+    /// tree.update_range(2..6, RangeAdd(5));
+    /// tree.update_range(4.., RangeAdd(1));
+    ///
+    /// assert_eq!(tree.query(..), 4 * 5 + 4);
+    /// assert_eq!(tree.get(5), 6);
+    /// ```
+    pub fn update_range<R: RangeBounds<usize>>(&mut self, range: R, action: A) {
+        let (start, end) = self.bounds(range);
+
+        self.update_node(1, 0, self.size(), start, end, &action);
+    }
+
+    fn update_node(
+        &mut self,
+        node: usize,
+        low: usize,
+        high: usize,
+        start: usize,
+        end: usize,
+        action: &A,
+    ) {
+        if end <= low || high <= start {
+            return;
+        }
+
+        if start <= low && high <= end {
+            self.apply(node, low, high, action);
+            return;
+        }
+
+        self.push_down(node, low, high);
+
+        let mid = (low + high) / 2;
+        self.update_node(2 * node, low, mid, start, end, action);
+        self.update_node(2 * node + 1, mid, high, start, end, action);
+
+        self.pull_up(node);
+    }
+
+    /// Applies `action` to `node`, which covers `low..high`, and stores it to
+    /// be pushed down to its children later.
+    fn apply(&mut self, node: usize, low: usize, high: usize, action: &A) {
+        // Padding leaves past the length stay equal to the identity
+        let len = high.min(self.len).saturating_sub(low);
+
+        if len == 0 {
+            return;
+        }
+
+        self.tree[node] = action.apply(&self.monoid, &self.tree[node], len);
+
+        if high - low > 1 {
+            self.lazy[node] = Some(match self.lazy[node].take() {
+                Some(earlier) => action.compose(&earlier),
+                None => action.clone(),
+            });
+        }
+    }
+
+    /// Moves the pending action of `node` to its children.
+    fn push_down(&mut self, node: usize, low: usize, high: usize) {
+        if let Some(action) = self.lazy[node].take() {
+            let mid = (low + high) / 2;
+
+            self.apply(2 * node, low, mid, &action);
+            self.apply(2 * node + 1, mid, high, &action);
+        }
+    }
+
+    /// Recomputes the value of `node` from its children.
+    fn pull_up(&mut self, node: usize) {
+        self.tree[node] = self
+            .monoid
+            .combine(&self.tree[2 * node], &self.tree[2 * node + 1]);
+    }
+}
+
+impl<M: Monoid + Default, A: Action<M>> FromIterator<M::Value> for SegmentTree<M, A> {
+    fn from_iter<I: IntoIterator<Item = M::Value>>(iter: I) -> Self {
+        Self::from_values(M::default(), iter)
+    }
+}
+
+impl<M: Monoid, A: HeapSize> HeapSize for SegmentTree<M, A>
+where
+    M::Value: HeapSize,
+{
+    fn heap_size_of_children(&self) -> usize {
+        self.tree.heap_size_of_children() + self.lazy.heap_size_of_children()
+    }
+}
+
+impl HeapSize for NoUpdate {
+    fn heap_size_of_children(&self) -> usize {
+        match *self {}
+    }
+}
+
+impl<T: HeapSize> HeapSize for RangeAdd<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.0.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Max, Min, RangeAdd, SegmentTree, Sum};
+
+    #[test]
+    fn test_query_set() {
+        let mut values: Vec<i32> = (0..100).map(|i| (i * 37) % 101 - 50).collect();
+        let mut sums: SegmentTree<Sum<i32>> = values.iter().copied().collect();
+        let mut mins: SegmentTree<Min<i32>> = values.iter().copied().collect();
+
+        for step in 0..100 {
+            let index = (step * 13) % 100;
+            values[index] = step as i32;
+            sums.set(index, step as i32);
+            mins.set(index, step as i32);
+
+            let (start, end) = ((step * 7) % 100, (step * 7) % 100 + step % 10);
+            let end = end.min(100);
+            let range = &values[start..end];
+
+            assert_eq!(sums.query(start..end), range.iter().sum::<i32>());
+            assert_eq!(
+                mins.query(start..end),
+                range.iter().copied().min().unwrap_or(i32::MAX)
+            );
+        }
+
+        assert_eq!(sums.query(..), values.iter().sum::<i32>());
+        assert!((0..100).all(|index| sums.get(index) == values[index]));
+
+        let empty: SegmentTree<Sum<i32>> = SegmentTree::new(Sum::default(), 0);
+        assert_eq!(empty.query(..), 0);
+    }
+
+    #[test]
+    fn test_lazy_updates() {
+        let mut values = vec![0i64; 37];
+        let mut sums: SegmentTree<Sum<i64>, RangeAdd<i64>> = values.iter().copied().collect();
+        let mut maxs: SegmentTree<Max<i64>, RangeAdd<i64>> = values.iter().copied().collect();
+
+        for step in 0..200 {
+            let start = (step * 11) % 37;
+            let end = (start + step % 17).min(37);
+            let amount = step as i64 % 7 - 3;
+
+            if step % 5 == 0 {
+                values[start] = amount;
+                sums.set(start, amount);
+                maxs.set(start, amount);
+            } else {
+                values[start..end]
+                    .iter_mut()
+                    .for_each(|value| *value += amount);
+                sums.update_range(start..end, RangeAdd(amount));
+                maxs.update_range(start..end, RangeAdd(amount));
+            }
+
+            let (low, high) = ((step * 3) % 37, ((step * 3) % 37 + step % 23).min(37));
+            let range = &values[low..high];
+
+            assert_eq!(sums.query(low..high), range.iter().sum::<i64>());
+            assert_eq!(
+                maxs.query(low..high),
+                range.iter().copied().max().unwrap_or(i64::MIN)
+            );
+        }
+
+        assert!((0..37).all(|index| sums.get(index) == values[index]));
+    }
+
+    #[test]
+    fn test_lazy_updates_identity() {
+        let mut mins: SegmentTree<Min<i32>, RangeAdd<i32>> = SegmentTree::new(Min::default(), 5);
+        mins.update_range(.., RangeAdd(1));
+        assert_eq!(mins.query(..), i32::MAX);
+
+        mins.set(2, 4);
+        mins.update_range(1.., RangeAdd(1));
+        assert_eq!(mins.query(..), 5);
+        assert_eq!(mins.get(3), i32::MAX);
+
+        let mut maxs: SegmentTree<Max<i32>, RangeAdd<i32>> = SegmentTree::new(Max::default(), 5);
+        maxs.update_range(.., RangeAdd(-1));
+        assert_eq!(maxs.query(..), i32::MIN);
+
+        maxs.set(0, -4);
+        maxs.update_range(..3, RangeAdd(-1));
+        assert_eq!(maxs.query(..), -5);
+        assert_eq!(maxs.get(1), i32::MIN);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_out_of_bounds() {
+        let tree: SegmentTree<Sum<u8>> = [1, 2, 3].into_iter().collect();
+        tree.query(1..4);
+    }
+}
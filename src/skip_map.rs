@@ -0,0 +1,500 @@
+use std::{cmp::Ordering, marker::PhantomData, mem, ptr::NonNull};
+
+use crate::heap_size::HeapSize;
+
+/// Maximum number of levels of the list, enough for 2^32 entries.
+const MAX_HEIGHT: usize = 32;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    /// Next node at every level this node takes part in, level 0 first.
+    next: Vec<Link<K, V>>,
+}
+
+type Link<K, V> = Option<NonNull<Node<K, V>>>;
+
+/// Sorted map implemented as a skip list. All the entries are linked in
+/// order at level 0, and every level above links a random half of the nodes
+/// of the level below, so searches can skip over most of the nodes by
+/// starting at the top level and going down. Operations take O(log n)
+/// expected time without any rebalancing.
+pub struct SkipMap<K, V> {
+    /// First node at every level, the length is the current height.
+    head: Vec<Link<K, V>>,
+    len: usize,
+    /// State of the xorshift generator used to choose node heights.
+    seed: u64,
+    marker: PhantomData<Box<Node<K, V>>>,
+}
+
+impl<K, V> SkipMap<K, V> {
+    /// Creates an empty map.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SkipMap;
+    ///
+    /// let map: SkipMap<i32, &str> = SkipMap::new();
+    ///
+    /// assert!(map.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            head: Vec::new(),
+            len: 0,
+            seed: 0x9E37_79B9_7F4A_7C15,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all the entries from the map.
+    pub fn clear(&mut self) {
+        let mut link = self.head.first().copied().flatten();
+
+        while let Some(node) = link {
+            unsafe {
+                let node = Box::from_raw(node.as_ptr());
+                link = node.next[0];
+            }
+        }
+
+        self.head.clear();
+        self.len = 0;
+    }
+
+    /// Returns an iterator over the entries in ascending key order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SkipMap;
+    ///
+    /// let map: SkipMap<_, _> = [(2, 'b'), (1, 'a')].into_iter().collect();
+    ///
+    /// assert!(map.iter().eq([(&1, &'a'), (&2, &'b')]));
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            next: self.head.first().copied().flatten(),
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over the keys in ascending order.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys(self.iter())
+    }
+
+    /// Returns an iterator over the values ordered by their keys.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values(self.iter())
+    }
+
+    /// Returns the entry with the smallest key.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.iter().next()
+    }
+
+    /// Returns a random height between 1 and [`MAX_HEIGHT`], where every
+    /// height is half as likely as the previous one.
+    fn random_height(&mut self) -> usize {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+
+        (self.seed.trailing_ones() as usize + 1).min(MAX_HEIGHT)
+    }
+}
+
+impl<K: Ord, V> SkipMap<K, V> {
+    /// Returns the link slots that lead to the first node with a key not
+    /// less than `key` at every level, top level first.
+    ///
+    /// # Safety
+    ///
+    /// The pointers are only valid until the map is modified other than by
+    /// writing through them.
+    unsafe fn predecessors(&mut self, key: &K) -> Vec<*mut Link<K, V>> {
+        let mut slots = self.head.as_mut_ptr();
+        let mut predecessors = Vec::with_capacity(self.head.len());
+
+        for level in (0..self.head.len()).rev() {
+            let mut slot = slots.add(level);
+
+            while let Some(next) = *slot {
+                let next = &mut *next.as_ptr();
+
+                if next.key >= *key {
+                    break;
+                }
+
+                slots = next.next.as_mut_ptr();
+                slot = slots.add(level);
+            }
+
+            predecessors.push(slot);
+        }
+
+        predecessors
+    }
+
+    /// Returns the node with `key`, if present.
+    fn find(&self, key: &K) -> Link<K, V> {
+        let mut slots = &self.head;
+
+        for level in (0..self.head.len()).rev() {
+            while let Some(next) = slots[level] {
+                unsafe {
+                    match (*next.as_ptr()).key.cmp(key) {
+                        Ordering::Less => slots = &(*next.as_ptr()).next,
+                        Ordering::Equal => return Some(next),
+                        Ordering::Greater => break,
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns a reference to the value of `key`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SkipMap;
+    ///
+    /// let map: SkipMap<_, _> = [(1, "one"), (2, "two")].into_iter().collect();
+    ///
+    /// assert_eq!(map.get(&2), Some(&"two"));
+    /// assert_eq!(map.get(&3), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.find(key)
+            .map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    /// Returns a mutable reference to the value of `key`.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.find(key)
+            .map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    /// Returns `true` if the map contains `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Inserts `value` with `key` and returns the previous value of the key,
+    /// if any.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    ///
+    /// assert_eq!(map.insert(1, "a"), None);
+    /// assert_eq!(map.insert(1, "b"), Some("a"));
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(node) = self.find(&key) {
+            return unsafe { Some(mem::replace(&mut (*node.as_ptr()).value, value)) };
+        }
+
+        // Grow the head first, the slots returned by `predecessors` point
+        // into it.
+        let height = self.random_height();
+
+        while self.head.len() < height {
+            self.head.push(None);
+        }
+
+        unsafe {
+            let predecessors = self.predecessors(&key);
+            let levels = predecessors.iter().rev().take(height);
+            let next = levels.clone().map(|&slot| *slot).collect();
+            let node = NonNull::from(Box::leak(Box::new(Node { key, value, next })));
+
+            for &slot in levels {
+                *slot = Some(node);
+            }
+        }
+
+        self.len += 1;
+
+        None
+    }
+
+    /// Removes `key` and returns its value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SkipMap;
+    ///
+    /// let mut map: SkipMap<_, _> = [(1, "a"), (2, "b")].into_iter().collect();
+    ///
+    /// assert_eq!(map.remove(&1), Some("a"));
+    /// assert_eq!(map.remove(&1), None);
+    /// assert!(map.keys().eq([2].iter()));
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        unsafe {
+            let predecessors = self.predecessors(key);
+            let node = (*predecessors[predecessors.len().checked_sub(1)?])?;
+
+            if (*node.as_ptr()).key != *key {
+                return None;
+            }
+
+            let node = Box::from_raw(node.as_ptr());
+
+            for (level, &slot) in predecessors.iter().rev().take(node.next.len()).enumerate() {
+                *slot = node.next[level];
+            }
+
+            while self.head.last() == Some(&None) {
+                self.head.pop();
+            }
+
+            self.len -= 1;
+
+            Some(node.value)
+        }
+    }
+}
+
+impl<K, V> Drop for SkipMap<K, V> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<K, V> Default for SkipMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for SkipMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SkipMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+
+        map
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SkipMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the entries of a [`SkipMap`] in ascending key order.
+pub struct Iter<'a, K, V> {
+    next: Link<K, V>,
+    len: usize,
+    marker: PhantomData<&'a Node<K, V>>,
+}
+
+/// Iterator over the keys of a [`SkipMap`] in ascending order.
+pub struct Keys<'a, K, V>(Iter<'a, K, V>);
+
+/// Iterator over the values of a [`SkipMap`], ordered by their keys.
+pub struct Values<'a, K, V>(Iter<'a, K, V>);
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| unsafe {
+            let node = &*node.as_ptr();
+            self.next = node.next[0];
+            self.len -= 1;
+
+            (&node.key, &node.value)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Keys<'a, K, V> {}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Values<'a, K, V> {}
+
+impl<K: HeapSize, V: HeapSize> HeapSize for SkipMap<K, V> {
+    fn heap_size_of_children(&self) -> usize {
+        let link = mem::size_of::<Link<K, V>>();
+        let mut size = self.head.capacity() * link;
+        let mut next = self.head.first().copied().flatten();
+
+        while let Some(node) = next {
+            let node = unsafe { node.as_ref() };
+
+            size += mem::size_of::<Node<K, V>>()
+                + node.next.capacity() * link
+                + node.key.heap_size_of_children()
+                + node.value.heap_size_of_children();
+
+            next = node.next[0];
+        }
+
+        size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SkipMap;
+
+    /// Checks that every level is sorted and only links nodes that are also
+    /// in the level below.
+    fn check_levels<K: Ord, V>(map: &SkipMap<K, V>) {
+        unsafe {
+            for level in 0..map.head.len() {
+                let mut link = map.head[level];
+                let mut below = map.head[level.saturating_sub(1)];
+                let mut count = 0;
+
+                while let Some(node) = link {
+                    let node = &*node.as_ptr();
+
+                    while let Some(other) = below {
+                        if std::ptr::eq(other.as_ptr(), node) {
+                            break;
+                        }
+
+                        below = (&*other.as_ptr()).next[level.saturating_sub(1)];
+                    }
+
+                    assert!(below.is_some());
+
+                    if let Some(next) = node.next[level] {
+                        assert!(node.key < (*next.as_ptr()).key);
+                    }
+
+                    link = node.next[level];
+                    count += 1;
+                }
+
+                assert!(count > 0);
+
+                if level == 0 {
+                    assert_eq!(count, map.len());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut map = SkipMap::new();
+
+        for key in (0..2_000).map(|key| (key * 7_919) % 1_000) {
+            map.insert(key, key * 2);
+        }
+
+        check_levels(&map);
+        assert_eq!(map.len(), 1_000);
+        assert!(map.keys().copied().eq(0..1_000));
+        assert!(map.values().copied().eq((0..1_000).map(|key| key * 2)));
+        assert!(map.head.len() > 5);
+
+        for key in (0..1_000).step_by(2) {
+            assert_eq!(map.remove(&key), Some(key * 2));
+            assert_eq!(map.get(&key), None);
+        }
+
+        check_levels(&map);
+        assert_eq!(map.remove(&0), None);
+        assert_eq!(map.remove(&1_000), None);
+        assert_eq!(map.first(), Some((&1, &2)));
+        assert!(map.contains_key(&999));
+        assert_eq!(map.iter().len(), 500);
+
+        *map.get_mut(&1).unwrap() = 0;
+        assert_eq!(map.get(&1), Some(&0));
+
+        for key in (1..1_000).step_by(2) {
+            map.remove(&key);
+        }
+
+        assert!(map.is_empty());
+        assert!(map.head.is_empty());
+        assert_eq!(map.iter().next(), None);
+    }
+
+    #[test]
+    fn test_owned_values() {
+        let mut map: SkipMap<String, Vec<i32>> = ["b", "c", "a"]
+            .into_iter()
+            .map(|key| (String::from(key), vec![1, 2, 3]))
+            .collect();
+
+        map.get_mut(&String::from("a")).unwrap().push(4);
+        assert_eq!(map.remove(&String::from("c")).unwrap().len(), 3);
+        assert!(map.keys().eq(["a", "b"]));
+        assert_eq!(map.first().unwrap().1.len(), 4);
+
+        map.clear();
+        assert!(map.is_empty());
+        map.insert(String::from("z"), Vec::new());
+    }
+}
@@ -0,0 +1,397 @@
+use std::ops::{Index, IndexMut};
+
+use crate::{heap_size::HeapSize, vector::Vector};
+
+/// Key returned by [`SlotMap::insert`]. It stores the index of the slot
+/// along with the generation of the slot at insertion time, so a key stops
+/// matching as soon as its value is removed, even if the slot is reused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SlotKey {
+    index: usize,
+    generation: u32,
+}
+
+struct Slot<T> {
+    /// Incremented every time the value of the slot is removed.
+    generation: u32,
+    value: Option<T>,
+}
+
+/// Arena that stores values in a [`Vector`] of slots and hands out a
+/// [`SlotKey`] for each of them. Insertion, lookup and removal take O(1), and the keys
+/// are plain copyable data, so graphs and trees can refer to their nodes by
+/// key instead of raw pointers or reference counting.
+///
+/// Removed slots are reused by later insertions, but every slot has a
+/// generation that is incremented on removal, so the keys of removed values
+/// never give access to newer values stored in the same slot.
+pub struct SlotMap<T> {
+    slots: Vector<Slot<T>>,
+    /// Indices of the empty slots that can be reused.
+    free: Vector<usize>,
+    len: usize,
+}
+
+impl<T> SlotMap<T> {
+    /// Creates an empty slot map.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SlotMap;
+    ///
+    /// let map: SlotMap<&str> = SlotMap::new();
+    ///
+    /// assert!(map.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            slots: Vector::new(),
+            free: Vector::new(),
+            len: 0,
+        }
+    }
+
+    /// Returns the number of values in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the map contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts `value` and returns its key.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SlotMap;
+    ///
+    /// let mut map = SlotMap::new();
+    /// let key = map.insert("node");
+    ///
+    /// assert_eq!(map.get(key), Some(&"node"));
+    /// ```
+    pub fn insert(&mut self, value: T) -> SlotKey {
+        self.insert_with_key(|_| value)
+    }
+
+    /// Inserts the value returned by `f`, which receives the key the value
+    /// will have, and returns that key. Useful for values that store their
+    /// own key.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::{SlotKey, SlotMap};
+    ///
+    /// struct Node {
+    ///     id: SlotKey,
+    ///     parent: Option<SlotKey>,
+    /// }
+    ///
+    /// let mut nodes = SlotMap::new();
+    /// let root = nodes.insert_with_key(|id| Node { id, parent: None });
+    /// let child = nodes.insert_with_key(|id| Node { id, parent: Some(root) });
+    ///
+    /// assert_eq!(nodes[child].id, child);
+    /// assert_eq!(nodes[child].parent, Some(nodes[root].id));
+    /// ```
+    pub fn insert_with_key<F: FnOnce(SlotKey) -> T>(&mut self, f: F) -> SlotKey {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    value: None,
+                });
+
+                self.slots.len() - 1
+            }
+        };
+
+        let slot = &mut self.slots[index];
+        let key = SlotKey {
+            index,
+            generation: slot.generation,
+        };
+
+        slot.value = Some(f(key));
+        self.len += 1;
+
+        key
+    }
+
+    /// Returns the slot of `key` if it still holds the value of `key`.
+    fn slot(&self, key: SlotKey) -> Option<&Slot<T>> {
+        self.slots
+            .get(key.index)
+            .filter(|slot| slot.generation == key.generation)
+    }
+
+    /// Returns the value of `key`, or `None` if it has been removed.
+    pub fn get(&self, key: SlotKey) -> Option<&T> {
+        self.slot(key)?.value.as_ref()
+    }
+
+    /// Returns a mutable reference to the value of `key`, or `None` if it
+    /// has been removed.
+    pub fn get_mut(&mut self, key: SlotKey) -> Option<&mut T> {
+        self.slots
+            .get_mut(key.index)
+            .filter(|slot| slot.generation == key.generation)?
+            .value
+            .as_mut()
+    }
+
+    /// Returns `true` if the value of `key` hasn't been removed.
+    pub fn contains_key(&self, key: SlotKey) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Removes and returns the value of `key`. From then on `key` doesn't
+    /// match any value, even after its slot is reused.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SlotMap;
+    ///
+    /// let mut map = SlotMap::new();
+    /// let old = map.insert(1);
+    ///
+    /// assert_eq!(map.remove(old), Some(1));
+    ///
+    /// let new = map.insert(2);
+    ///
+    /// assert_eq!(map.get(old), None);
+    /// assert_eq!(map.get(new), Some(&2));
+    /// ```
+    pub fn remove(&mut self, key: SlotKey) -> Option<T> {
+        self.slot(key)?;
+
+        let slot = &mut self.slots[key.index];
+        let value = slot.value.take()?;
+
+        // A slot whose generation would wrap around is never reused, so old
+        // keys can't match it again
+        if let Some(generation) = slot.generation.checked_add(1) {
+            slot.generation = generation;
+            self.free.push(key.index);
+        }
+
+        self.len -= 1;
+
+        Some(value)
+    }
+
+    /// Removes all the values, invalidating all the keys.
+    pub fn clear(&mut self) {
+        for index in 0..self.slots.len() {
+            let key = SlotKey {
+                index,
+                generation: self.slots[index].generation,
+            };
+
+            self.remove(key);
+        }
+    }
+
+    /// Returns an iterator over the keys and values in slot order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            slots: self.slots.iter().enumerate(),
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over the keys and mutable references to the
+    /// values in slot order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            slots: self.slots.iter_mut().enumerate(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for SlotMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Index<SlotKey> for SlotMap<T> {
+    type Output = T;
+
+    /// Returns the value of `key`, panicking if it has been removed.
+    fn index(&self, key: SlotKey) -> &T {
+        self.get(key).expect("Key of a removed value")
+    }
+}
+
+impl<T> IndexMut<SlotKey> for SlotMap<T> {
+    fn index_mut(&mut self, key: SlotKey) -> &mut T {
+        self.get_mut(key).expect("Key of a removed value")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SlotMap<T> {
+    type Item = (SlotKey, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the keys and values of a [`SlotMap`].
+pub struct Iter<'a, T> {
+    slots: std::iter::Enumerate<std::slice::Iter<'a, Slot<T>>>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (SlotKey, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot) in self.slots.by_ref() {
+            if let Some(value) = &slot.value {
+                self.len -= 1;
+
+                let key = SlotKey {
+                    index,
+                    generation: slot.generation,
+                };
+
+                return Some((key, value));
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+/// Iterator over the keys and mutable references to the values of a
+/// [`SlotMap`].
+pub struct IterMut<'a, T> {
+    slots: std::iter::Enumerate<std::slice::IterMut<'a, Slot<T>>>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (SlotKey, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot) in self.slots.by_ref() {
+            if let Some(value) = &mut slot.value {
+                self.len -= 1;
+
+                let key = SlotKey {
+                    index,
+                    generation: slot.generation,
+                };
+
+                return Some((key, value));
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<T: HeapSize> HeapSize for Slot<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.value.heap_size_of_children()
+    }
+}
+
+impl<T: HeapSize> HeapSize for SlotMap<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.slots.heap_size_of_children() + self.free.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SlotKey, SlotMap};
+
+    #[test]
+    fn test_stale_keys() {
+        let mut map = SlotMap::new();
+        let keys: Vec<SlotKey> = (0..100).map(|value| map.insert(value)).collect();
+
+        for key in keys.iter().step_by(2) {
+            assert!(map.remove(*key).is_some());
+            assert!(map.remove(*key).is_none());
+        }
+
+        // Reuses the removed slots with new generations
+        let new_keys: Vec<SlotKey> = (100..150).map(|value| map.insert(value)).collect();
+
+        assert_eq!(map.len(), 100);
+        assert!(keys.iter().step_by(2).all(|key| !map.contains_key(*key)));
+        assert!(keys
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .all(|key| map.contains_key(*key)));
+        assert!(new_keys
+            .iter()
+            .zip(100..)
+            .all(|(key, value)| map[*key] == value));
+
+        for (_, value) in map.iter_mut() {
+            *value *= 2;
+        }
+
+        let mut values: Vec<i32> = map
+            .iter()
+            .map(|(key, value)| {
+                assert_eq!(map.get(key), Some(value));
+                *value
+            })
+            .collect();
+        values.sort();
+        assert_eq!(values.len(), 100);
+        assert_eq!(values[0], 2);
+
+        map.clear();
+        assert!(map.is_empty());
+        assert!(new_keys.iter().all(|key| map.get(*key).is_none()));
+        assert_eq!(map.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_generation_exhausted() {
+        let mut map = SlotMap::new();
+        let key = map.insert(0);
+        map.slots[key.index].generation = u32::MAX;
+
+        let last = SlotKey {
+            index: key.index,
+            generation: u32::MAX,
+        };
+
+        assert_eq!(map.remove(last), Some(0));
+
+        // The exhausted slot is retired instead of reused
+        let new = map.insert(1);
+        assert_ne!(new.index, key.index);
+        assert!(map.get(last).is_none());
+    }
+}
@@ -0,0 +1,281 @@
+use std::ops::{Bound, Deref, RangeBounds};
+
+use crate::{heap_size::HeapSize, vector::Vector};
+
+/// Values kept in ascending order in a contiguous [`Vector`]. Lookups are
+/// binary searches over memory laid out sequentially, which makes them faster
+/// in practice than following the pointers of a [`crate::BinaryTree`], at the
+/// cost of insertions and removals shifting the values after them in O(n).
+/// It's the better choice for sets that are built once and read many times.
+///
+/// Equal values are allowed and kept in insertion order, use
+/// [`SortedVec::insert_unique`] or [`SortedVec::dedup`] to avoid them. The
+/// values are available as a sorted slice through [`Deref`].
+pub struct SortedVec<T> {
+    values: Vector<T>,
+}
+
+impl<T> SortedVec<T> {
+    /// Creates an empty sorted vector.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SortedVec;
+    ///
+    /// let values: SortedVec<i32> = SortedVec::new();
+    ///
+    /// assert!(values.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            values: Vector::new(),
+        }
+    }
+
+    /// Removes all the values.
+    pub fn clear(&mut self) {
+        while self.values.pop().is_some() {}
+    }
+
+    /// Returns the values as a sorted slice.
+    pub fn as_slice(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Removes and returns the value at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove_index(&mut self, index: usize) -> T {
+        self.values.remove(index)
+    }
+
+    /// Removes and returns the greatest value.
+    pub fn pop(&mut self) -> Option<T> {
+        self.values.pop()
+    }
+}
+
+impl<T: Ord> SortedVec<T> {
+    /// Inserts `value` after any equal values and returns its index.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SortedVec;
+    ///
+    /// let mut values = SortedVec::new();
+    ///
+    /// assert_eq!(values.insert(5), 0);
+    /// assert_eq!(values.insert(1), 0);
+    /// assert_eq!(values.insert(5), 2);
+    ///
+    /// assert_eq!(*values, [1, 5, 5]);
+    /// ```
+    pub fn insert(&mut self, value: T) -> usize {
+        let index = self.values.partition_point(|other| *other <= value);
+        self.values.insert(index, value);
+
+        index
+    }
+
+    /// Inserts `value` and returns `true` unless an equal value is already
+    /// present, in which case the vector is not modified.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SortedVec;
+    ///
+    /// let mut values = SortedVec::new();
+    ///
+    /// assert!(values.insert_unique(1));
+    /// assert!(!values.insert_unique(1));
+    /// assert_eq!(values.len(), 1);
+    /// ```
+    pub fn insert_unique(&mut self, value: T) -> bool {
+        match self.values.binary_search(&value) {
+            Ok(_) => false,
+            Err(index) => {
+                self.values.insert(index, value);
+                true
+            }
+        }
+    }
+
+    /// Removes and returns one value equal to `value`, if present.
+    pub fn remove(&mut self, value: &T) -> Option<T> {
+        let index = self.values.binary_search(value).ok()?;
+
+        Some(self.values.remove(index))
+    }
+
+    /// Returns `true` if a value equal to `value` is present, in O(log n).
+    pub fn contains(&self, value: &T) -> bool {
+        self.values.binary_search(value).is_ok()
+    }
+
+    /// Returns the values within `range` as a slice, in O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SortedVec;
+    ///
+    /// let values: SortedVec<i32> = [8, 1, 5, 3, 9, 5].into_iter().collect();
+    ///
+    /// assert_eq!(values.range(3..=5), [3, 5, 5]);
+    /// assert_eq!(values.range(6..), [8, 9]);
+    /// assert!(values.range(10..).is_empty());
+    /// ```
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> &[T] {
+        let start = match range.start_bound() {
+            Bound::Included(start) => self.values.partition_point(|value| value < start),
+            Bound::Excluded(start) => self.values.partition_point(|value| value <= start),
+            Bound::Unbounded => 0,
+        };
+
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.values.partition_point(|value| value <= end),
+            Bound::Excluded(end) => self.values.partition_point(|value| value < end),
+            Bound::Unbounded => self.values.len(),
+        };
+
+        &self.values[start..end.max(start)]
+    }
+
+    /// Removes every value equal to the value before it, leaving each value
+    /// only once.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SortedVec;
+    ///
+    /// let mut values: SortedVec<i32> = [3, 1, 3, 2, 1, 3].into_iter().collect();
+    /// values.dedup();
+    ///
+    /// assert_eq!(*values, [1, 2, 3]);
+    /// ```
+    pub fn dedup(&mut self) {
+        let mut kept = 0;
+
+        for index in 0..self.values.len() {
+            if kept == 0 || self.values[index] != self.values[kept - 1] {
+                self.values.swap(index, kept);
+                kept += 1;
+            }
+        }
+
+        while self.values.len() > kept {
+            self.values.pop();
+        }
+    }
+}
+
+impl<T> Deref for SortedVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.values
+    }
+}
+
+impl<T> Default for SortedVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> Extend<T> for SortedVec<T> {
+    /// Appends all the values and sorts them again, which takes O(n + m log m)
+    /// for m new values since the existing ones are already sorted.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.values.push(value);
+        }
+
+        self.values.sort();
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SortedVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut values = Self::new();
+        values.extend(iter);
+
+        values
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SortedVec<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.iter()
+    }
+}
+
+impl<T: HeapSize> HeapSize for SortedVec<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.values.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SortedVec;
+
+    #[test]
+    fn test_insert_remove() {
+        let mut values = SortedVec::new();
+        let mut expected = Vec::new();
+
+        for step in 0..500 {
+            let value = (step * 7919) % 101;
+
+            if step % 4 == 3 {
+                let removed = values.remove(&value);
+                let position = expected.iter().position(|other| *other == value);
+                assert_eq!(removed, position.map(|index| expected.remove(index)));
+            } else {
+                values.insert(value);
+                expected.push(value);
+                expected.sort();
+            }
+
+            assert_eq!(*values, *expected);
+        }
+
+        assert!(values.contains(&expected[0]));
+        assert!(!values.contains(&101));
+        assert_eq!(values.pop(), expected.pop());
+        assert_eq!(values.remove_index(0), expected.remove(0));
+
+        values.dedup();
+        expected.dedup();
+        assert_eq!(*values, *expected);
+
+        values.clear();
+        assert!(values.is_empty());
+    }
+
+    #[test]
+    fn test_range() {
+        let mut values: SortedVec<i32> = (0..100).step_by(2).rev().collect();
+        values.extend([1, 3, 5]);
+
+        assert_eq!(values.first(), Some(&0));
+        assert_eq!(values.range(..6), [0, 1, 2, 3, 4, 5]);
+        assert_eq!(
+            values.range((std::ops::Bound::Excluded(96), std::ops::Bound::Unbounded)),
+            [98]
+        );
+        assert!(values.range(50..50).is_empty());
+        assert_eq!(values.range(..).len(), 53);
+        assert!((&values).into_iter().is_sorted());
+    }
+}
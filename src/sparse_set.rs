@@ -0,0 +1,214 @@
+use std::slice;
+
+use crate::{heap_size::HeapSize, vector::Vector};
+
+/// Set of small integers with O(1) insertion, removal and membership tests
+/// that iterates over its members as a contiguous slice, the building block
+/// entity component systems use to track which entities have a component.
+///
+/// Members are stored packed in a dense [`Vector`] in no particular order,
+/// and a sparse [`Vector`] indexed by value stores the position of every
+/// member in the dense one. Memory use is proportional to the greatest value
+/// ever inserted, so values should be dense integers like indices or ids.
+pub struct SparseSet {
+    dense: Vector<usize>,
+    /// `sparse[value]` is the position of `value` in `dense` if it's a
+    /// member, and meaningless otherwise.
+    sparse: Vector<usize>,
+}
+
+impl SparseSet {
+    /// Creates an empty set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SparseSet;
+    ///
+    /// let set = SparseSet::new();
+    ///
+    /// assert!(set.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            dense: Vector::new(),
+            sparse: Vector::new(),
+        }
+    }
+
+    /// Returns the number of members.
+    pub fn len(&self) -> usize {
+        self.dense.len()
+    }
+
+    /// Returns `true` if the set contains no members.
+    pub fn is_empty(&self) -> bool {
+        self.dense.is_empty()
+    }
+
+    /// Removes all the members. The sparse index is kept, so values up to
+    /// the greatest one inserted before can be inserted again without
+    /// allocating.
+    pub fn clear(&mut self) {
+        while self.dense.pop().is_some() {}
+    }
+
+    /// Returns `true` if `value` is a member.
+    pub fn contains(&self, value: usize) -> bool {
+        self.sparse
+            .get(value)
+            .is_some_and(|&index| self.dense.get(index) == Some(&value))
+    }
+
+    /// Adds `value` to the set and returns `true` unless it was already a
+    /// member.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SparseSet;
+    ///
+    /// let mut set = SparseSet::new();
+    ///
+    /// assert!(set.insert(42));
+    /// assert!(!set.insert(42));
+    /// assert!(set.contains(42));
+    /// ```
+    pub fn insert(&mut self, value: usize) -> bool {
+        if self.contains(value) {
+            return false;
+        }
+
+        while self.sparse.len() <= value {
+            self.sparse.push(0);
+        }
+
+        self.sparse[value] = self.dense.len();
+        self.dense.push(value);
+
+        true
+    }
+
+    /// Removes `value` from the set and returns `true` unless it wasn't a
+    /// member. The last member in iteration order takes its place.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SparseSet;
+    ///
+    /// let mut set: SparseSet = [1, 2, 3].into_iter().collect();
+    ///
+    /// assert!(set.remove(1));
+    /// assert!(!set.remove(1));
+    /// assert_eq!(set.as_slice(), [3, 2]);
+    /// ```
+    pub fn remove(&mut self, value: usize) -> bool {
+        if !self.contains(value) {
+            return false;
+        }
+
+        let index = self.sparse[value];
+        let last = self.dense.pop().unwrap();
+
+        if index < self.dense.len() {
+            self.dense[index] = last;
+            self.sparse[last] = index;
+        }
+
+        true
+    }
+
+    /// Returns the members as a slice, in insertion order unless members
+    /// have been removed.
+    pub fn as_slice(&self) -> &[usize] {
+        &self.dense
+    }
+
+    /// Returns an iterator over the members, in the order of
+    /// [`SparseSet::as_slice`].
+    pub fn iter(&self) -> slice::Iter<'_, usize> {
+        self.dense.iter()
+    }
+}
+
+impl Default for SparseSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Extend<usize> for SparseSet {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl FromIterator<usize> for SparseSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+
+        set
+    }
+}
+
+impl<'a> IntoIterator for &'a SparseSet {
+    type Item = &'a usize;
+    type IntoIter = slice::Iter<'a, usize>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl HeapSize for SparseSet {
+    fn heap_size_of_children(&self) -> usize {
+        self.dense.heap_size_of_children() + self.sparse.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::SparseSet;
+
+    #[test]
+    fn test_insert_remove() {
+        let mut set = SparseSet::new();
+        let mut expected = HashSet::new();
+
+        for step in 0..2_000 {
+            let value = (step * 7919) % 503;
+
+            if step % 3 == 0 {
+                assert_eq!(set.remove(value), expected.remove(&value));
+            } else {
+                assert_eq!(set.insert(value), expected.insert(value));
+            }
+
+            assert_eq!(set.len(), expected.len());
+        }
+
+        assert!((0..600).all(|value| set.contains(value) == expected.contains(&value)));
+        assert_eq!(set.iter().copied().collect::<HashSet<_>>(), expected);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut set: SparseSet = (0..100).collect();
+
+        set.clear();
+        assert!(set.is_empty());
+        assert!(!set.contains(5));
+
+        // Stale entries of the sparse index must not look like members
+        set.insert(99);
+        assert!(!set.contains(0));
+        assert!(set.contains(99));
+        assert_eq!(set.as_slice(), [99]);
+    }
+}
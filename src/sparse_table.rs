@@ -0,0 +1,186 @@
+use std::ops::{Bound, RangeBounds};
+
+use crate::{heap_size::HeapSize, segment_tree::Idempotent, vector::Vector};
+
+/// Immutable table that answers range queries under an [`Idempotent`]
+/// monoid, like the minimum, maximum or greatest common divisor of a range,
+/// in O(1) after an O(n log n) build.
+///
+/// Level `k` holds the combination of every run of `2^k` consecutive values.
+/// Any range is covered by two runs of the same level that may overlap,
+/// which is harmless since combining a value twice doesn't change the
+/// result. Unlike [`crate::SegmentTree`], the values can't be changed after
+/// the build.
+pub struct SparseTable<M: Idempotent> {
+    monoid: M,
+    /// `levels[k][i]` combines the values in `i..i + 2^k`.
+    levels: Vector<Vector<M::Value>>,
+}
+
+impl<M: Idempotent> SparseTable<M> {
+    /// Builds a table over `values` in O(n log n).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::{Max, SparseTable};
+    ///
+    /// let table = SparseTable::new(Max::<i32>::default(), &[3, 9, 2, 7]);
+    ///
+    /// assert_eq!(table.len(), 4);
+    /// assert_eq!(table.query(2..), 7);
+    /// ```
+    pub fn new(monoid: M, values: &[M::Value]) -> Self {
+        let mut levels = Vector::new();
+        let mut first = Vector::new();
+
+        for value in values {
+            first.push(value.clone());
+        }
+
+        levels.push(first);
+
+        let mut width = 1;
+
+        while 2 * width <= values.len() {
+            let previous: &Vector<M::Value> = &levels[levels.len() - 1];
+            let mut level = Vector::new();
+
+            for start in 0..=values.len() - 2 * width {
+                level.push(monoid.combine(&previous[start], &previous[start + width]));
+            }
+
+            levels.push(level);
+            width *= 2;
+        }
+
+        Self { monoid, levels }
+    }
+
+    /// Returns the number of values.
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Returns `true` if the table contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the value at `index`, if present.
+    pub fn get(&self, index: usize) -> Option<&M::Value> {
+        self.levels[0].get(index)
+    }
+
+    /// Returns the combination of the values in `range` in O(1), or the
+    /// identity if it's empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::{Gcd, Min, SparseTable};
+    ///
+    /// let prices = [31, 27, 45, 12, 19, 40];
+    /// let table = SparseTable::new(Min::<u32>::default(), &prices);
+    ///
+    /// assert_eq!(table.query(..3), 27);
+    /// assert_eq!(table.query(1..=4), 12);
+    ///
+    /// let table = SparseTable::new(Gcd::<u32>::default(), &[12, 18, 30, 7]);
+    ///
+    /// assert_eq!(table.query(..3), 6);
+    /// assert_eq!(table.query(..), 1);
+    /// ```
+    pub fn query<R: RangeBounds<usize>>(&self, range: R) -> M::Value {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len(),
+        };
+
+        assert!(start <= end && end <= self.len(), "Range out of bounds");
+
+        if start == end {
+            return self.monoid.identity();
+        }
+
+        let level = (end - start).ilog2() as usize;
+        let values = &self.levels[level];
+
+        self.monoid
+            .combine(&values[start], &values[end - (1 << level)])
+    }
+}
+
+impl<M: Idempotent + Default> FromIterator<M::Value> for SparseTable<M> {
+    fn from_iter<I: IntoIterator<Item = M::Value>>(iter: I) -> Self {
+        let values: Vec<M::Value> = iter.into_iter().collect();
+
+        Self::new(M::default(), &values)
+    }
+}
+
+impl<M: Idempotent> HeapSize for SparseTable<M>
+where
+    M::Value: HeapSize,
+{
+    fn heap_size_of_children(&self) -> usize {
+        self.levels.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SparseTable;
+    use crate::{Gcd, Max, Min};
+
+    #[test]
+    fn test_queries() {
+        let values: Vec<i64> = (0..300).map(|i| (i * 7919) % 1009 - 500).collect();
+        let mins = SparseTable::new(Min::<i64>::default(), &values);
+        let maxs: SparseTable<Max<i64>> = values.iter().copied().collect();
+
+        for start in (0..300).step_by(7) {
+            for end in start..=300 {
+                let range = &values[start..end];
+
+                assert_eq!(
+                    mins.query(start..end),
+                    range.iter().copied().min().unwrap_or(i64::MAX)
+                );
+                assert_eq!(
+                    maxs.query(start..end),
+                    range.iter().copied().max().unwrap_or(i64::MIN)
+                );
+            }
+        }
+
+        assert_eq!(mins.get(3), Some(&values[3]));
+        assert_eq!(mins.get(300), None);
+
+        let gcds = SparseTable::new(Gcd::<u64>::default(), &[0, 24, 36, 60, 0, 45]);
+        assert_eq!(gcds.query(..4), 12);
+        assert_eq!(gcds.query(3..), 15);
+        assert_eq!(gcds.query(4..5), 0);
+
+        let empty = SparseTable::new(Min::<u8>::default(), &[]);
+        assert!(empty.is_empty());
+        assert_eq!(empty.query(..), u8::MAX);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_out_of_bounds() {
+        SparseTable::new(Max::<i32>::default(), &[1, 2, 3]).query(2..5);
+    }
+}
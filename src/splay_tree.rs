@@ -0,0 +1,311 @@
+use crate::{
+    binary_tree::{self, BinaryTree},
+    compare::{Compare, Natural},
+    heap_size::HeapSize,
+};
+
+/// Self-adjusting binary search tree. Every value inserted or searched is
+/// moved to the root with splay rotations, so values accessed recently stay
+/// close to the root and repeated lookups of the same few values are very
+/// fast. Any sequence of operations runs in amortized O(log n) per operation,
+/// although a single operation can take O(n).
+///
+/// Since lookups restructure the tree they need `&mut self`. Read-only
+/// operations that don't adjust the tree are available through
+/// [`SplayTree::as_tree`].
+pub struct SplayTree<T, C = Natural> {
+    tree: BinaryTree<T, C>,
+}
+
+impl<T> SplayTree<T> {
+    /// Creates an empty splay tree ordered by [`Ord`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SplayTree;
+    ///
+    /// let tree: SplayTree<i32> = SplayTree::new();
+    ///
+    /// assert!(tree.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            tree: BinaryTree::new(),
+        }
+    }
+}
+
+impl<T, C> SplayTree<T, C> {
+    /// Creates an empty splay tree that orders its values with `comparator`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SplayTree;
+    ///
+    /// let mut tree = SplayTree::new_with_comparator(|a: &i32, b: &i32| b.cmp(a));
+    /// tree.extend([1, 3, 2]);
+    ///
+    /// assert_eq!(tree.iter().collect::<Vec<_>>(), vec![&3, &2, &1]);
+    /// ```
+    pub fn new_with_comparator(comparator: C) -> Self {
+        Self {
+            tree: BinaryTree::new_with_comparator(comparator),
+        }
+    }
+
+    /// Returns the number of values in the tree.
+    pub fn size(&self) -> usize {
+        self.tree.size()
+    }
+
+    /// Returns `true` if the tree contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Removes all the values from the tree.
+    pub fn clear(&mut self) {
+        self.tree.clear();
+    }
+
+    /// Returns the value at the root, which is the value accessed last
+    /// unless it has been removed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SplayTree;
+    ///
+    /// let mut tree: SplayTree<i32> = (1..=10).collect();
+    ///
+    /// tree.contains(&4);
+    /// assert_eq!(tree.root(), Some(&4));
+    /// ```
+    pub fn root(&self) -> Option<&T> {
+        self.tree.root_value()
+    }
+
+    /// Returns an iterator over the values in ascending order. Iterating
+    /// doesn't restructure the tree.
+    pub fn iter(&self) -> binary_tree::Iter<'_, T> {
+        self.tree.iter()
+    }
+
+    /// Returns the underlying [`BinaryTree`], which gives access to read-only
+    /// operations like ranges, cursors or set operations. Lookups performed
+    /// through it don't move the values to the root.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SplayTree;
+    ///
+    /// let tree: SplayTree<i32> = (1..=10).collect();
+    ///
+    /// assert_eq!(tree.as_tree().range(3..6).count(), 3);
+    /// ```
+    pub fn as_tree(&self) -> &BinaryTree<T, C> {
+        &self.tree
+    }
+}
+
+impl<T, C: Compare<T>> SplayTree<T, C> {
+    /// Inserts `value` and moves it to the root. Returns `false` if an equal
+    /// value was already present, in which case that value is moved to the
+    /// root instead and `value` is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SplayTree;
+    ///
+    /// let mut tree = SplayTree::new();
+    ///
+    /// assert!(tree.insert(2));
+    /// assert!(tree.insert(1));
+    /// assert!(!tree.insert(2));
+    /// assert_eq!(tree.root(), Some(&2));
+    /// ```
+    pub fn insert(&mut self, value: T) -> bool {
+        self.tree.insert_and_splay(value)
+    }
+
+    /// Returns `true` if the tree contains `value`. The value is moved to the
+    /// root, or the closest value visited by the search if it's not present.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SplayTree;
+    ///
+    /// let mut tree: SplayTree<i32> = [1, 2, 3].into_iter().collect();
+    ///
+    /// assert!(tree.contains(&1));
+    /// assert!(!tree.contains(&4));
+    /// ```
+    pub fn contains(&mut self, value: &T) -> bool {
+        self.tree.splay(value)
+    }
+
+    /// Returns a reference to the value equal to `value` after moving it to
+    /// the root.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SplayTree;
+    ///
+    /// let mut tree = SplayTree::new_with_comparator(|a: &(u32, &str), b: &(u32, &str)| {
+    ///     a.0.cmp(&b.0)
+    /// });
+    /// tree.insert((1, "one"));
+    /// tree.insert((2, "two"));
+    ///
+    /// assert_eq!(tree.get(&(1, "")), Some(&(1, "one")));
+    /// assert_eq!(tree.get(&(3, "")), None);
+    /// ```
+    pub fn get(&mut self, value: &T) -> Option<&T> {
+        if self.tree.splay(value) {
+            self.tree.root_value()
+        } else {
+            None
+        }
+    }
+
+    /// Removes `value` from the tree and returns `true` unless it's not
+    /// present. The value is moved to the root before removing it, so its
+    /// neighbours end up close to the root.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::SplayTree;
+    ///
+    /// let mut tree: SplayTree<i32> = [1, 2, 3].into_iter().collect();
+    ///
+    /// assert!(tree.remove(&2));
+    /// assert!(!tree.remove(&2));
+    /// assert_eq!(tree.iter().collect::<Vec<_>>(), vec![&1, &3]);
+    /// ```
+    pub fn remove(&mut self, value: &T) -> bool {
+        // The value is at the root after splaying, so removing it doesn't
+        // descend again.
+        self.tree.splay(value) && self.tree.remove(value)
+    }
+}
+
+impl<T, C: Default> Default for SplayTree<T, C> {
+    fn default() -> Self {
+        Self {
+            tree: BinaryTree::default(),
+        }
+    }
+}
+
+impl<T, C: Compare<T>> Extend<T> for SplayTree<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T, C: Compare<T> + Default> FromIterator<T> for SplayTree<T, C> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::default();
+        tree.extend(iter);
+
+        tree
+    }
+}
+
+impl<'a, T, C> IntoIterator for &'a SplayTree<T, C> {
+    type Item = &'a T;
+    type IntoIter = binary_tree::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: HeapSize, C> HeapSize for SplayTree<T, C> {
+    fn heap_size_of_children(&self) -> usize {
+        self.tree.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SplayTree;
+
+    #[test]
+    fn test_accessed_values_move_to_root() {
+        let mut tree: SplayTree<i32> = (0..100).collect();
+
+        // Sorted insertions leave a path, the first lookup of the deepest
+        // value flattens it
+        assert_eq!(tree.as_tree().height(), 100);
+        assert!(tree.contains(&0));
+        assert_eq!(tree.root(), Some(&0));
+        assert!(tree.as_tree().height() < 60);
+
+        for value in [42, 7, 42, 99] {
+            assert_eq!(tree.get(&value), Some(&value));
+            assert_eq!(tree.as_tree().depth_of(&value), Some(0));
+        }
+
+        // Missing values bring a neighbour to the root
+        assert!(!tree.contains(&150));
+        assert_eq!(tree.root(), Some(&99));
+
+        assert_eq!(tree.size(), 100);
+        assert!(tree.iter().copied().eq(0..100));
+        assert_eq!(tree.as_tree().range(10..20).count(), 10);
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut tree = SplayTree::new();
+
+        for value in [50, 20, 80, 10, 30, 70, 90, 60] {
+            assert!(tree.insert(value));
+            assert_eq!(tree.root(), Some(&value));
+        }
+
+        assert!(!tree.insert(30));
+        assert_eq!(tree.root(), Some(&30));
+
+        for value in [50, 10, 90, 50] {
+            tree.remove(&value);
+            assert!(!tree.contains(&value));
+        }
+
+        assert_eq!(
+            tree.iter().copied().collect::<Vec<_>>(),
+            vec![20, 30, 60, 70, 80]
+        );
+        assert_eq!(tree.size(), 5);
+
+        tree.clear();
+        assert!(tree.is_empty());
+        assert_eq!(tree.root(), None);
+    }
+
+    #[test]
+    fn test_owned_values() {
+        let mut tree = SplayTree::new();
+
+        for word in ["delta", "alpha", "charlie", "bravo"] {
+            tree.insert(String::from(word));
+        }
+
+        assert!(tree.remove(&String::from("charlie")));
+        assert_eq!(tree.get(&String::from("alpha")).unwrap(), "alpha");
+        assert_eq!(
+            tree.iter().cloned().collect::<Vec<_>>(),
+            vec!["alpha", "bravo", "delta"]
+        );
+    }
+}
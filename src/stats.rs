@@ -0,0 +1,195 @@
+//! Instrumentation of the core collections. With the `stats` feature
+//! [`crate::Vector`], [`crate::Dequeue`], [`crate::Queue`] and
+//! [`crate::BinaryTree`] count what they do and return it from their `stats`
+//! method, so performance investigations don't need an external profiler.
+//! Without the feature the counters are zero sized and updating them
+//! compiles to nothing.
+
+#[cfg(all(
+    feature = "stats",
+    any(
+        feature = "binary_tree",
+        feature = "deque",
+        feature = "queue",
+        feature = "vector"
+    )
+))]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counters of a collection since it was created, returned by its `stats`
+/// method.
+///
+/// # Examples
+///
+/// ```rust
+/// use collections_rust::{Stats, Vector};
+///
+/// let mut vector = Vector::new();
+///
+/// for value in 0..5 {
+///     vector.push(value);
+/// }
+///
+/// vector.pop();
+///
+/// // The buffer grows to 1, 2, 4 and 8 values
+/// assert_eq!(
+///     vector.stats(),
+///     Stats {
+///         allocations: 1,
+///         reallocations: 3,
+///         peak_len: 5,
+///         pointer_walks: 0,
+///     }
+/// );
+/// ```
+#[cfg(feature = "stats")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Number of allocations: one per node in linked collections, one per
+    /// new buffer in [`crate::Vector`].
+    pub allocations: usize,
+    /// Number of times a buffer was moved to a bigger one.
+    pub reallocations: usize,
+    /// Largest number of values held at once.
+    pub peak_len: usize,
+    /// Number of links followed to reach a value by its position, or by its
+    /// value in [`crate::BinaryTree`].
+    pub pointer_walks: usize,
+}
+
+/// Counters updated by a collection.
+#[cfg(all(
+    feature = "stats",
+    any(
+        feature = "binary_tree",
+        feature = "deque",
+        feature = "queue",
+        feature = "vector"
+    )
+))]
+#[derive(Debug)]
+pub(crate) struct Counters {
+    allocations: AtomicUsize,
+    reallocations: AtomicUsize,
+    peak_len: AtomicUsize,
+    pointer_walks: AtomicUsize,
+}
+
+#[cfg(all(
+    feature = "stats",
+    any(
+        feature = "binary_tree",
+        feature = "deque",
+        feature = "queue",
+        feature = "vector"
+    )
+))]
+impl Counters {
+    pub(crate) const fn new() -> Self {
+        Self {
+            allocations: AtomicUsize::new(0),
+            reallocations: AtomicUsize::new(0),
+            peak_len: AtomicUsize::new(0),
+            pointer_walks: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn record_allocation(&self) {
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[cfg(feature = "vector")]
+    pub(crate) fn record_reallocation(&self) {
+        self.reallocations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Raises the peak length to `len` if it's higher.
+    pub(crate) fn record_len(&self, len: usize) {
+        self.peak_len.fetch_max(len, Ordering::Relaxed);
+    }
+
+    #[cfg(any(feature = "binary_tree", feature = "deque"))]
+    pub(crate) fn record_walk(&self, links: usize) {
+        self.pointer_walks.fetch_add(links, Ordering::Relaxed);
+    }
+
+    /// Returns the current value of every counter.
+    pub(crate) fn get(&self) -> Stats {
+        Stats {
+            allocations: self.allocations.load(Ordering::Relaxed),
+            reallocations: self.reallocations.load(Ordering::Relaxed),
+            peak_len: self.peak_len.load(Ordering::Relaxed),
+            pointer_walks: self.pointer_walks.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Counters that record nothing, without the `stats` feature.
+#[cfg(all(
+    not(feature = "stats"),
+    any(
+        feature = "binary_tree",
+        feature = "deque",
+        feature = "queue",
+        feature = "vector"
+    )
+))]
+#[derive(Debug)]
+pub(crate) struct Counters;
+
+#[cfg(all(
+    not(feature = "stats"),
+    any(
+        feature = "binary_tree",
+        feature = "deque",
+        feature = "queue",
+        feature = "vector"
+    )
+))]
+impl Counters {
+    pub(crate) const fn new() -> Self {
+        Self
+    }
+
+    #[inline(always)]
+    pub(crate) fn record_allocation(&self) {}
+
+    #[cfg(feature = "vector")]
+    #[inline(always)]
+    pub(crate) fn record_reallocation(&self) {}
+
+    #[inline(always)]
+    pub(crate) fn record_len(&self, _len: usize) {}
+
+    #[cfg(any(feature = "binary_tree", feature = "deque"))]
+    #[inline(always)]
+    pub(crate) fn record_walk(&self, _links: usize) {}
+}
+
+#[cfg(all(test, feature = "stats", feature = "deque", feature = "vector"))]
+mod tests {
+    use super::{Counters, Stats};
+
+    #[test]
+    fn test_counters() {
+        let counters = Counters::new();
+
+        counters.record_allocation();
+        counters.record_reallocation();
+        counters.record_len(3);
+        counters.record_len(1);
+        counters.record_walk(4);
+        counters.record_walk(2);
+
+        assert_eq!(
+            counters.get(),
+            Stats {
+                allocations: 1,
+                reallocations: 1,
+                peak_len: 3,
+                pointer_walks: 6,
+            }
+        );
+    }
+}
@@ -0,0 +1,625 @@
+use std::{cmp::Ordering, mem};
+
+use crate::heap_size::HeapSize;
+
+type Link<V> = Option<Box<Node<V>>>;
+
+struct Node<V> {
+    ch: char,
+    value: Option<V>,
+    /// Nodes for the same position of the key with smaller characters.
+    left: Link<V>,
+    /// Nodes for the next position of keys that contain `ch` here.
+    mid: Link<V>,
+    /// Nodes for the same position of the key with greater characters.
+    right: Link<V>,
+}
+
+impl<V> Node<V> {
+    fn new(ch: char) -> Self {
+        Self {
+            ch,
+            value: None,
+            left: None,
+            mid: None,
+            right: None,
+        }
+    }
+}
+
+/// Map with string keys stored as a ternary search tree. Every node holds
+/// one character and three children: the left and right ones form a binary
+/// search tree over the alternatives for the current position of the key,
+/// and the middle one continues with the next position. Uses much less
+/// memory than a trie with one child slot per possible character, while
+/// still supporting lookups by prefix and approximate matching. Use `()` as
+/// the value type for a set of strings.
+pub struct TernarySearchTree<V> {
+    root: Link<V>,
+    /// The empty string has no characters, so it doesn't have a node.
+    empty: Option<V>,
+    len: usize,
+}
+
+impl<V> TernarySearchTree<V> {
+    /// Creates an empty tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TernarySearchTree;
+    ///
+    /// let tree: TernarySearchTree<i32> = TernarySearchTree::new();
+    ///
+    /// assert!(tree.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            root: None,
+            empty: None,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of keys in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree contains no keys.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all the keys from the tree.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Returns the node of the last character of `key`, if the tree has a
+    /// path for it. The key must not be empty.
+    fn find_node(&self, key: &str) -> Option<&Node<V>> {
+        let mut chars = key.chars();
+        let mut ch = chars.next()?;
+        let mut link = &self.root;
+
+        while let Some(node) = link {
+            match ch.cmp(&node.ch) {
+                Ordering::Less => link = &node.left,
+                Ordering::Greater => link = &node.right,
+                Ordering::Equal => match chars.next() {
+                    Some(next) => {
+                        ch = next;
+                        link = &node.mid;
+                    }
+                    None => return Some(node),
+                },
+            }
+        }
+
+        None
+    }
+
+    /// Returns a reference to the value of `key`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TernarySearchTree;
+    ///
+    /// let mut tree = TernarySearchTree::new();
+    /// tree.insert("car", 1);
+    ///
+    /// assert_eq!(tree.get("car"), Some(&1));
+    /// assert_eq!(tree.get("ca"), None);
+    /// ```
+    pub fn get(&self, key: &str) -> Option<&V> {
+        if key.is_empty() {
+            return self.empty.as_ref();
+        }
+
+        self.find_node(key)?.value.as_ref()
+    }
+
+    /// Returns a mutable reference to the value of `key`.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        let mut chars = key.chars();
+
+        let Some(mut ch) = chars.next() else {
+            return self.empty.as_mut();
+        };
+
+        let mut link = &mut self.root;
+
+        while let Some(node) = link {
+            match ch.cmp(&node.ch) {
+                Ordering::Less => link = &mut node.left,
+                Ordering::Greater => link = &mut node.right,
+                Ordering::Equal => match chars.next() {
+                    Some(next) => {
+                        ch = next;
+                        link = &mut node.mid;
+                    }
+                    None => return node.value.as_mut(),
+                },
+            }
+        }
+
+        None
+    }
+
+    /// Returns `true` if the tree contains `key`.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Inserts `value` with `key` and returns the previous value of the key,
+    /// if any.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TernarySearchTree;
+    ///
+    /// let mut tree = TernarySearchTree::new();
+    ///
+    /// assert_eq!(tree.insert("cat", 1), None);
+    /// assert_eq!(tree.insert("cat", 2), Some(1));
+    /// assert_eq!(tree.len(), 1);
+    /// ```
+    pub fn insert(&mut self, key: &str, value: V) -> Option<V> {
+        let mut chars = key.chars();
+
+        let slot = match chars.next() {
+            None => &mut self.empty,
+
+            Some(mut ch) => {
+                let mut link = &mut self.root;
+
+                loop {
+                    let node = link.get_or_insert_with(|| Box::new(Node::new(ch)));
+
+                    match ch.cmp(&node.ch) {
+                        Ordering::Less => link = &mut node.left,
+                        Ordering::Greater => link = &mut node.right,
+                        Ordering::Equal => match chars.next() {
+                            Some(next) => {
+                                ch = next;
+                                link = &mut node.mid;
+                            }
+                            None => break &mut node.value,
+                        },
+                    }
+                }
+            }
+        };
+
+        let old = slot.replace(value);
+
+        if old.is_none() {
+            self.len += 1;
+        }
+
+        old
+    }
+
+    /// Removes `key` and returns its value. Nodes that no longer lead to any
+    /// key are freed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TernarySearchTree;
+    ///
+    /// let mut tree: TernarySearchTree<_> = [("cat", 1), ("cart", 2)].into_iter().collect();
+    ///
+    /// assert_eq!(tree.remove("cat"), Some(1));
+    /// assert_eq!(tree.remove("cat"), None);
+    /// assert_eq!(tree.get("cart"), Some(&2));
+    /// ```
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let mut chars = key.chars();
+
+        let value = match chars.next() {
+            None => self.empty.take(),
+            Some(ch) => Self::remove_from(&mut self.root, ch, chars),
+        };
+
+        if value.is_some() {
+            self.len -= 1;
+        }
+
+        value
+    }
+
+    /// Removes the rest of the key, which starts with `ch`, from the subtree
+    /// at `link`, pruning the nodes left without value and middle child.
+    fn remove_from(link: &mut Link<V>, ch: char, mut chars: std::str::Chars) -> Option<V> {
+        let node = link.as_mut()?;
+
+        let value = match ch.cmp(&node.ch) {
+            Ordering::Less => Self::remove_from(&mut node.left, ch, chars),
+            Ordering::Greater => Self::remove_from(&mut node.right, ch, chars),
+            Ordering::Equal => match chars.next() {
+                Some(next) => Self::remove_from(&mut node.mid, next, chars),
+                None => node.value.take(),
+            },
+        };
+
+        if node.value.is_none() && node.mid.is_none() {
+            // Nothing goes through this node anymore, replace it with the
+            // merge of its siblings.
+            let node = link.take().unwrap();
+
+            *link = match (node.left, node.right) {
+                (None, right) => right,
+                (left, None) => left,
+                (Some(mut left), right) => {
+                    let mut rightmost = &mut left;
+
+                    while rightmost.right.is_some() {
+                        rightmost = rightmost.right.as_mut().unwrap();
+                    }
+
+                    rightmost.right = right;
+
+                    Some(left)
+                }
+            };
+        }
+
+        value
+    }
+
+    /// Returns an iterator over the keys and values in ascending key order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TernarySearchTree;
+    ///
+    /// let tree: TernarySearchTree<_> = [("b", 2), ("a", 1), ("ab", 3)].into_iter().collect();
+    ///
+    /// let entries: Vec<_> = tree.iter().collect();
+    ///
+    /// assert_eq!(entries, [("a".to_string(), &1), ("ab".to_string(), &3), ("b".to_string(), &2)]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter {
+            stack: self
+                .root
+                .as_deref()
+                .map(|root| (root, 0, false))
+                .into_iter()
+                .collect(),
+            key: String::new(),
+            pending: self.empty.as_ref(),
+        }
+    }
+
+    /// Returns an iterator over the keys that start with `prefix` and their
+    /// values in ascending key order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TernarySearchTree;
+    ///
+    /// let tree: TernarySearchTree<()> = ["car", "cart", "cat", "dog"].into_iter().map(|key| (key, ())).collect();
+    ///
+    /// let keys: Vec<_> = tree.prefix_iter("car").map(|(key, _)| key).collect();
+    ///
+    /// assert_eq!(keys, ["car", "cart"]);
+    /// ```
+    pub fn prefix_iter(&self, prefix: &str) -> Iter<'_, V> {
+        if prefix.is_empty() {
+            return self.iter();
+        }
+
+        let node = self.find_node(prefix);
+
+        Iter {
+            stack: node
+                .and_then(|node| node.mid.as_deref())
+                .map(|mid| (mid, prefix.len(), false))
+                .into_iter()
+                .collect(),
+            key: String::from(prefix),
+            pending: node.and_then(|node| node.value.as_ref()),
+        }
+    }
+
+    /// Returns the keys within `max_edits` insertions, deletions or
+    /// substitutions of `key` (Levenshtein distance) and their values in
+    /// ascending key order. Subtrees are skipped as soon as every prefix of
+    /// `key` is too far from them, so small distances only visit a small part
+    /// of the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TernarySearchTree;
+    ///
+    /// let words = ["cat", "cart", "cut", "at", "dog", "act"];
+    /// let tree: TernarySearchTree<()> = words.into_iter().map(|word| (word, ())).collect();
+    ///
+    /// let near: Vec<_> = tree.near("cat", 1).into_iter().map(|(key, _)| key).collect();
+    ///
+    /// assert_eq!(near, ["at", "cart", "cat", "cut"]);
+    /// ```
+    pub fn near(&self, key: &str, max_edits: usize) -> Vec<(String, &V)> {
+        let key: Vec<char> = key.chars().collect();
+        let row: Vec<usize> = (0..=key.len()).collect();
+        let mut found = Vec::new();
+
+        if let Some(value) = &self.empty {
+            if key.len() <= max_edits {
+                found.push((String::new(), value));
+            }
+        }
+
+        if let Some(root) = &self.root {
+            Self::near_from(root, &key, &row, max_edits, &mut String::new(), &mut found);
+        }
+
+        found
+    }
+
+    /// Visits `node` and its subtrees for [`TernarySearchTree::near`]. `row`
+    /// holds the edit distances between `prefix` and every prefix of `key`.
+    fn near_from<'a>(
+        node: &'a Node<V>,
+        key: &[char],
+        row: &[usize],
+        max_edits: usize,
+        prefix: &mut String,
+        found: &mut Vec<(String, &'a V)>,
+    ) {
+        if let Some(left) = &node.left {
+            Self::near_from(left, key, row, max_edits, prefix, found);
+        }
+
+        let mut next_row = Vec::with_capacity(row.len());
+        next_row.push(row[0] + 1);
+
+        for (index, &ch) in key.iter().enumerate() {
+            let substitution = row[index] + usize::from(ch != node.ch);
+            let insertion = row[index + 1] + 1;
+            let deletion = next_row[index] + 1;
+
+            next_row.push(substitution.min(insertion).min(deletion));
+        }
+
+        prefix.push(node.ch);
+
+        if let Some(value) = &node.value {
+            if next_row[key.len()] <= max_edits {
+                found.push((prefix.clone(), value));
+            }
+        }
+
+        if let Some(mid) = &node.mid {
+            if next_row.iter().min().is_some_and(|&min| min <= max_edits) {
+                Self::near_from(mid, key, &next_row, max_edits, prefix, found);
+            }
+        }
+
+        prefix.pop();
+
+        if let Some(right) = &node.right {
+            Self::near_from(right, key, row, max_edits, prefix, found);
+        }
+    }
+}
+
+impl<V> Default for TernarySearchTree<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'k, V> Extend<(&'k str, V)> for TernarySearchTree<V> {
+    fn extend<I: IntoIterator<Item = (&'k str, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'k, V> FromIterator<(&'k str, V)> for TernarySearchTree<V> {
+    fn from_iter<I: IntoIterator<Item = (&'k str, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+
+        tree
+    }
+}
+
+impl<'a, V> IntoIterator for &'a TernarySearchTree<V> {
+    type Item = (String, &'a V);
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the entries of a [`TernarySearchTree`] in ascending key
+/// order. See [`TernarySearchTree::iter`] and
+/// [`TernarySearchTree::prefix_iter`].
+pub struct Iter<'a, V> {
+    /// Nodes left to visit with the length in bytes of the key before them,
+    /// and whether their left and right subtrees were already scheduled.
+    stack: Vec<(&'a Node<V>, usize, bool)>,
+    /// Key of the last visited node.
+    key: String,
+    /// Value of the key the iteration starts at, returned first.
+    pending: Option<&'a V>,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.pending.take() {
+            return Some((self.key.clone(), value));
+        }
+
+        while let Some((node, depth, expanded)) = self.stack.pop() {
+            if !expanded {
+                if let Some(right) = &node.right {
+                    self.stack.push((right, depth, false));
+                }
+
+                self.stack.push((node, depth, true));
+
+                if let Some(left) = &node.left {
+                    self.stack.push((left, depth, false));
+                }
+
+                continue;
+            }
+
+            self.key.truncate(depth);
+            self.key.push(node.ch);
+
+            if let Some(mid) = &node.mid {
+                self.stack.push((mid, self.key.len(), false));
+            }
+
+            if let Some(value) = &node.value {
+                return Some((self.key.clone(), value));
+            }
+        }
+
+        None
+    }
+}
+
+impl<V> Drop for TernarySearchTree<V> {
+    /// Drops the nodes iteratively, long keys would otherwise overflow the
+    /// stack with one recursive call per character.
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<Node<V>>> = self.root.take().into_iter().collect();
+
+        while let Some(mut node) = stack.pop() {
+            for link in [&mut node.left, &mut node.mid, &mut node.right] {
+                stack.extend(mem::take(link));
+            }
+        }
+    }
+}
+
+impl<V: HeapSize> HeapSize for Node<V> {
+    fn heap_size_of_children(&self) -> usize {
+        self.value.heap_size_of_children()
+            + self.left.heap_size_of_children()
+            + self.mid.heap_size_of_children()
+            + self.right.heap_size_of_children()
+    }
+}
+
+impl<V: HeapSize> HeapSize for TernarySearchTree<V> {
+    fn heap_size_of_children(&self) -> usize {
+        self.root.heap_size_of_children() + self.empty.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TernarySearchTree;
+
+    fn words() -> Vec<&'static str> {
+        vec![
+            "she", "sells", "sea", "shells", "by", "the", "sea", "shore", "shell", "s", "", "über",
+            "übel",
+        ]
+    }
+
+    #[test]
+    fn test_insert_get_iter() {
+        let mut tree = TernarySearchTree::new();
+
+        for (index, word) in words().into_iter().enumerate() {
+            tree.insert(word, index);
+        }
+
+        let mut expected = words();
+        expected.sort();
+        expected.dedup();
+
+        assert_eq!(tree.len(), expected.len());
+        assert!(tree.iter().map(|(key, _)| key).eq(expected.iter().copied()));
+        assert_eq!(tree.get("sea"), Some(&6));
+        assert_eq!(tree.get(""), Some(&10));
+        assert_eq!(tree.get("sh"), None);
+        assert_eq!(tree.get("shells!"), None);
+        assert!(tree.contains_key("über"));
+
+        *tree.get_mut("by").unwrap() = 100;
+        assert_eq!(tree.get("by"), Some(&100));
+        assert_eq!(tree.get_mut("b"), None);
+
+        let shells: Vec<_> = tree.prefix_iter("shel").map(|(key, _)| key).collect();
+        assert_eq!(shells, ["shell", "shells"]);
+        assert_eq!(tree.prefix_iter("ü").count(), 2);
+        assert_eq!(tree.prefix_iter("x").count(), 0);
+        assert_eq!(tree.prefix_iter("").count(), expected.len());
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut tree: TernarySearchTree<_> = words().into_iter().map(|word| (word, ())).collect();
+        let mut expected = words();
+        expected.sort();
+        expected.dedup();
+
+        for word in ["shell", "s", "", "by", "über"] {
+            assert_eq!(tree.remove(word), Some(()));
+            assert_eq!(tree.remove(word), None);
+            expected.retain(|key| *key != word);
+
+            assert_eq!(tree.len(), expected.len());
+            assert!(tree.iter().map(|(key, _)| key).eq(expected.iter().copied()));
+        }
+
+        assert!(tree.contains_key("shells"));
+        assert!(tree.contains_key("übel"));
+
+        for word in words() {
+            tree.remove(word);
+        }
+
+        assert!(tree.is_empty());
+        assert!(tree.root.is_none());
+
+        tree.insert("again", ());
+        tree.clear();
+        assert_eq!(tree.iter().next(), None);
+    }
+
+    #[test]
+    fn test_near() {
+        let tree: TernarySearchTree<_> = words().into_iter().map(|word| (word, ())).collect();
+        let near = |key, max_edits| -> Vec<String> {
+            tree.near(key, max_edits)
+                .into_iter()
+                .map(|(key, _)| key)
+                .collect()
+        };
+
+        assert_eq!(near("shell", 0), ["shell"]);
+        assert_eq!(near("shell", 1), ["shell", "shells"]);
+        assert_eq!(
+            near("sel", 2),
+            ["s", "sea", "sells", "she", "shell", "übel"]
+        );
+        assert_eq!(near("ubel", 1), ["übel"]);
+        assert_eq!(near("", 1), ["", "s"]);
+        assert!(near("xyz", 1).is_empty());
+
+        let mut long = TernarySearchTree::new();
+        long.insert(&"a".repeat(100_000), ());
+        assert_eq!(long.near("b", 1).len(), 0);
+    }
+}
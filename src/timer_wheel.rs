@@ -0,0 +1,248 @@
+use std::mem;
+
+use crate::{
+    dequeue::{self, Dequeue},
+    heap_size::HeapSize,
+};
+
+/// Number of bits of the tick that select a slot in each level.
+const SLOT_BITS: u32 = 6;
+
+/// Number of slots per level.
+const SLOTS: usize = 1 << SLOT_BITS;
+
+/// Number of levels, enough for the wheel to cover every `u64` tick.
+const LEVELS: usize = u64::BITS.div_ceil(SLOT_BITS) as usize;
+
+/// Hierarchical timing wheel, the timer queue of network stacks and
+/// runtimes that schedule lots of timeouts. Items are scheduled at a future
+/// tick and come out of [`TimerWheel::tick`] when the wheel reaches it.
+///
+/// The wheel has several levels of 64 slots each, where a slot of level `n`
+/// spans 64^n ticks. An item goes into the slot of the lowest level that
+/// can tell its tick apart from the current one, so scheduling is O(1).
+/// When the current tick enters the span of a slot in a higher level, the
+/// items of that slot cascade down to lower levels, and those in the slot
+/// of the current tick at the lowest level expire. Every item cascades at
+/// most once per level. Slots are [`Dequeue`]s, so moving items around
+/// never reallocates.
+///
+/// # Examples
+///
+/// ```rust
+/// use collections_rust::TimerWheel;
+///
+/// let mut timers = TimerWheel::new();
+///
+/// timers.insert(3, "retransmit");
+/// timers.insert(1, "ack");
+/// timers.insert(200, "keepalive");
+///
+/// assert_eq!(timers.tick().collect::<Vec<_>>(), ["ack"]);
+/// assert_eq!(timers.tick().count(), 0);
+/// assert_eq!(timers.tick().collect::<Vec<_>>(), ["retransmit"]);
+///
+/// while timers.now() < 199 {
+///     assert_eq!(timers.tick().count(), 0);
+/// }
+///
+/// assert_eq!(timers.tick().collect::<Vec<_>>(), ["keepalive"]);
+/// assert!(timers.is_empty());
+/// ```
+pub struct TimerWheel<T> {
+    /// Slots of every level one after the other, lowest level first. Items
+    /// are stored with their tick.
+    slots: Box<[Dequeue<(u64, T)>]>,
+    now: u64,
+    len: usize,
+}
+
+impl<T> TimerWheel<T> {
+    /// Creates an empty wheel at tick 0.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TimerWheel;
+    ///
+    /// let timers: TimerWheel<&str> = TimerWheel::new();
+    ///
+    /// assert_eq!(timers.now(), 0);
+    /// assert!(timers.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            slots: (0..LEVELS * SLOTS).map(|_| Dequeue::new()).collect(),
+            now: 0,
+            len: 0,
+        }
+    }
+
+    /// Returns the current tick.
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    /// Returns the number of scheduled items.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no items are scheduled.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all the scheduled items without changing the current tick.
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(Dequeue::clear);
+        self.len = 0;
+    }
+
+    /// Returns the index in `slots` of the slot for `deadline`.
+    fn slot_of(&self, deadline: u64) -> usize {
+        // The highest bit where the deadline differs from the current tick
+        // decides the level, and its bits in that level the slot
+        let level = match deadline ^ self.now {
+            0 => 0,
+            diff => ((u64::BITS - 1 - diff.leading_zeros()) / SLOT_BITS) as usize,
+        };
+        let slot = (deadline >> (level as u32 * SLOT_BITS)) as usize % SLOTS;
+
+        level * SLOTS + slot
+    }
+
+    /// Schedules `item` to expire at tick `deadline` in O(1). Items whose
+    /// deadline isn't in the future expire on the next tick.
+    pub fn insert(&mut self, deadline: u64, item: T) {
+        let deadline = deadline.max(self.now + 1);
+        let slot = self.slot_of(deadline);
+
+        self.slots[slot].push_back((deadline, item));
+        self.len += 1;
+    }
+
+    /// Advances the wheel one tick and returns an iterator over the items
+    /// that expire on it, in no particular order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TimerWheel;
+    ///
+    /// let mut timers = TimerWheel::new();
+    /// timers.insert(1, 'a');
+    /// timers.insert(1, 'b');
+    ///
+    /// assert_eq!(timers.tick().collect::<String>(), "ab");
+    /// assert_eq!(timers.now(), 1);
+    /// ```
+    pub fn tick(&mut self) -> Expired<T> {
+        self.now += 1;
+
+        // Higher levels cascade first, since their items may land in the
+        // slots that lower levels cascade next
+        for level in (1..LEVELS).rev() {
+            let shift = level as u32 * SLOT_BITS;
+
+            if self.now & ((1 << shift) - 1) != 0 {
+                continue;
+            }
+
+            let slot = level * SLOTS + (self.now >> shift) as usize % SLOTS;
+
+            for (deadline, item) in mem::take(&mut self.slots[slot]) {
+                let lower = self.slot_of(deadline);
+                self.slots[lower].push_back((deadline, item));
+            }
+        }
+
+        let expired = mem::take(&mut self.slots[self.now as usize % SLOTS]);
+        self.len -= expired.len();
+
+        Expired(expired.into_iter())
+    }
+}
+
+impl<T> Default for TimerWheel<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Iterator over the items that expire on a tick of a [`TimerWheel`]. See
+/// [`TimerWheel::tick`].
+pub struct Expired<T>(dequeue::IntoIter<(u64, T)>);
+
+impl<T> Iterator for Expired<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, item)| item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for Expired<T> {}
+
+impl<T: HeapSize> HeapSize for TimerWheel<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.slots.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TimerWheel;
+
+    #[test]
+    fn test_random_deadlines() {
+        let mut wheel = TimerWheel::new();
+        let mut expected = Vec::new();
+        let mut seed: u64 = 0x2545_F491_4F6C_DD1D;
+
+        // Deadlines spread over several levels, scheduled while ticking
+        for tick in 0..20_000u64 {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+
+            if seed.is_multiple_of(4) {
+                let deadline = tick + 1 + (seed >> 8) % (1 << ((seed >> 4) % 15));
+                wheel.insert(deadline, (deadline, expected.len()));
+                expected.push(deadline);
+            }
+
+            for (deadline, _) in wheel.tick() {
+                assert_eq!(deadline, wheel.now());
+            }
+        }
+
+        let pending = expected.iter().filter(|&&deadline| deadline > 20_000);
+        assert_eq!(wheel.len(), pending.count());
+    }
+
+    #[test]
+    fn test_past_and_far_deadlines() {
+        let mut wheel = TimerWheel::new();
+
+        for _ in 0..100 {
+            wheel.tick();
+        }
+
+        wheel.insert(0, "past");
+        wheel.insert(100, "now");
+        wheel.insert(u64::MAX, "far");
+        assert_eq!(wheel.len(), 3);
+
+        assert_eq!(wheel.tick().collect::<Vec<_>>(), ["past", "now"]);
+        assert_eq!(wheel.len(), 1);
+
+        wheel.clear();
+        assert!(wheel.is_empty());
+        assert_eq!(wheel.now(), 101);
+    }
+}
@@ -0,0 +1,215 @@
+//! Traits shared by the collections of the crate, so code can be written
+//! once against an abstract container and run with whichever
+//! implementation fits best, or with all of them to compare. They live in
+//! their own module because [`crate::List`] is already the name of the
+//! persistent list.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use collections_rust::{traits::Stack, Dequeue, Vector};
+//!
+//! fn balanced<S: Stack<char> + Default>(text: &str) -> bool {
+//!     let mut open = S::default();
+//!
+//!     for c in text.chars() {
+//!         match c {
+//!             '(' => open.push(c),
+//!             ')' if open.pop().is_none() => return false,
+//!             _ => {}
+//!         }
+//!     }
+//!
+//!     open.is_empty()
+//! }
+//!
+//! assert!(balanced::<Vector<_>>("(a (b) c)"));
+//! assert!(!balanced::<Dequeue<_>>("(a))"));
+//! ```
+
+/// Anything that holds a number of values. Supertrait of all the other
+/// traits in this module.
+pub trait Collection {
+    /// Returns the number of values in the collection.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the collection contains no values.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Sequence of values addressed by their position, starting at 0.
+pub trait List<T>: Collection {
+    /// Returns the value at `index`, or `None` if it's out of bounds.
+    fn get(&self, index: usize) -> Option<&T>;
+
+    /// Inserts `value` at `index`, shifting the values after it one
+    /// position towards the end.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    fn insert(&mut self, index: usize, value: T);
+
+    /// Removes and returns the value at `index`, shifting the values after
+    /// it one position towards the start.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index >= len`.
+    fn remove(&mut self, index: usize) -> T;
+}
+
+/// Last in, first out collection.
+pub trait Stack<T>: Collection {
+    /// Pushes `value` on top of the stack.
+    fn push(&mut self, value: T);
+
+    /// Removes and returns the value on top of the stack.
+    fn pop(&mut self) -> Option<T>;
+
+    /// Returns the value on top of the stack without removing it.
+    fn peek(&self) -> Option<&T>;
+}
+
+/// First in, first out collection.
+///
+/// # Examples
+///
+/// ```rust
+/// use collections_rust::{traits::FifoQueue, Dequeue, Queue};
+///
+/// fn round_robin<Q: FifoQueue<(char, u32)>>(jobs: &mut Q) -> String {
+///     let mut order = String::new();
+///
+///     while let Some((name, left)) = jobs.dequeue() {
+///         order.push(name);
+///
+///         if left > 1 {
+///             jobs.enqueue((name, left - 1));
+///         }
+///     }
+///
+///     order
+/// }
+///
+/// let mut queue = Queue::new();
+/// queue.append(('a', 2));
+/// queue.append(('b', 1));
+///
+/// let mut dequeue: Dequeue<_> = [('a', 2), ('b', 1)].into_iter().collect();
+///
+/// assert_eq!(round_robin(&mut queue), "aba");
+/// assert_eq!(round_robin(&mut dequeue), "aba");
+/// ```
+pub trait FifoQueue<T>: Collection {
+    /// Adds `value` at the back of the queue.
+    fn enqueue(&mut self, value: T);
+
+    /// Removes and returns the value at the front of the queue.
+    fn dequeue(&mut self) -> Option<T>;
+
+    /// Returns the value at the front of the queue without removing it.
+    fn front(&self) -> Option<&T>;
+}
+
+/// Collection of unique values.
+pub trait Set<T>: Collection {
+    /// Adds `value` to the set. Returns `false` and leaves the set
+    /// untouched if it was already there.
+    fn insert(&mut self, value: T) -> bool;
+
+    /// Returns `true` if the set contains `value`.
+    fn contains(&self, value: &T) -> bool;
+
+    /// Removes `value` from the set. Returns `true` if it was there.
+    fn remove(&mut self, value: &T) -> bool;
+}
+
+/// Set that keeps its values in order.
+pub trait SortedSet<T>: Set<T> {
+    /// Returns the smallest value in the set.
+    fn first(&self) -> Option<&T>;
+
+    /// Returns the largest value in the set.
+    fn last(&self) -> Option<&T>;
+}
+
+#[cfg(all(
+    test,
+    feature = "binary_tree",
+    feature = "deque",
+    feature = "queue",
+    feature = "vector"
+))]
+mod tests {
+    use super::{FifoQueue, List, SortedSet, Stack};
+    use crate::{BinaryTree, Dequeue, Queue, Vector};
+
+    fn check_list<L: List<i32> + Stack<i32> + Default>() {
+        let mut list = L::default();
+
+        for value in [1, 3, 5] {
+            list.push(value);
+        }
+
+        list.insert(0, 0);
+        list.insert(2, 2);
+        list.insert(5, 6);
+        assert_eq!(list.len(), 6);
+        let values: Vec<_> = (0..6).map(|index| *list.get(index).unwrap()).collect();
+        assert_eq!(values, [0, 1, 2, 3, 5, 6]);
+
+        assert_eq!(list.remove(3), 3);
+        assert_eq!(list.get(3), Some(&5));
+        assert_eq!(list.get(5), None);
+
+        assert_eq!(list.peek(), Some(&6));
+        assert_eq!(list.pop(), Some(6));
+        assert_eq!(list.len(), 4);
+    }
+
+    fn check_fifo_queue<Q: FifoQueue<i32> + Default>() {
+        let mut queue = Q::default();
+        assert!(queue.is_empty());
+
+        for value in 0..5 {
+            queue.enqueue(value);
+        }
+
+        assert_eq!(queue.front(), Some(&0));
+        assert!((0..5).all(|value| queue.dequeue() == Some(value)));
+        assert_eq!(queue.dequeue(), None);
+    }
+
+    fn check_sorted_set<S: SortedSet<i32> + Default>() {
+        let mut set = S::default();
+
+        for value in [5, 1, 9, 3, 5] {
+            set.insert(value);
+        }
+
+        assert_eq!(set.len(), 4);
+        assert!(!set.insert(9));
+        assert!(set.contains(&3));
+        assert_eq!((set.first(), set.last()), (Some(&1), Some(&9)));
+
+        assert!(set.remove(&1));
+        assert!(!set.remove(&1));
+        assert_eq!(set.first(), Some(&3));
+    }
+
+    #[test]
+    fn test_lists() {
+        check_list::<Vector<_>>();
+        check_list::<Dequeue<_>>();
+    }
+
+    #[test]
+    fn test_queues_and_sets() {
+        check_fifo_queue::<Dequeue<_>>();
+        check_fifo_queue::<Queue<_>>();
+        check_sorted_set::<BinaryTree<_>>();
+    }
+}
@@ -0,0 +1,468 @@
+use std::{cmp::Ordering, marker::PhantomData};
+
+use crate::heap_size::HeapSize;
+
+type Link<T> = Option<Box<Node<T>>>;
+
+struct Node<T> {
+    value: T,
+    /// Random priority, every node has a greater priority than its children.
+    priority: u64,
+    /// Number of nodes in the subtree rooted at this node.
+    size: usize,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+/// Returns the number of nodes in the subtree at `link`.
+fn size<T>(link: &Link<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+impl<T> Node<T> {
+    fn update_size(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+/// Splits the subtree at `link` in two: the values for which `goes_left`
+/// returns `true` and the rest. `goes_left` must return `true` for a prefix
+/// of the values in ascending order.
+fn split<T, F: Fn(&T) -> bool>(link: Link<T>, goes_left: &F) -> (Link<T>, Link<T>) {
+    let Some(mut node) = link else {
+        return (None, None);
+    };
+
+    if goes_left(&node.value) {
+        let (left, right) = split(node.right.take(), goes_left);
+        node.right = left;
+        node.update_size();
+
+        (Some(node), right)
+    } else {
+        let (left, right) = split(node.left.take(), goes_left);
+        node.left = right;
+        node.update_size();
+
+        (left, Some(node))
+    }
+}
+
+/// Joins two subtrees where every value of `left` is smaller than every
+/// value of `right`, keeping the node with the greatest priority as root.
+fn merge<T>(left: Link<T>, right: Link<T>) -> Link<T> {
+    match (left, right) {
+        (None, right) => right,
+        (left, None) => left,
+
+        (Some(mut left), Some(mut right)) => {
+            if left.priority > right.priority {
+                left.right = merge(left.right.take(), Some(right));
+                left.update_size();
+
+                Some(left)
+            } else {
+                right.left = merge(Some(left), right.left.take());
+                right.update_size();
+
+                Some(right)
+            }
+        }
+    }
+}
+
+/// Randomized binary search tree. Every node gets a random priority and the
+/// tree is a binary search tree by value and a max-heap by priority, which
+/// keeps its expected height in O(log n) without any rebalancing rules. All
+/// the operations are built on top of splitting a treap by value and merging
+/// two treaps whose values don't overlap, which are public as well.
+pub struct Treap<T> {
+    root: Link<T>,
+    /// State of the xorshift generator used for priorities.
+    seed: u64,
+}
+
+impl<T> Treap<T> {
+    /// Creates an empty treap.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Treap;
+    ///
+    /// let treap: Treap<i32> = Treap::new();
+    ///
+    /// assert!(treap.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            root: None,
+            seed: 0x2545_F491_4F6C_DD1D,
+        }
+    }
+
+    /// Returns the number of values in the treap.
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Returns `true` if the treap contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Removes all the values from the treap.
+    pub fn clear(&mut self) {
+        self.root = None;
+    }
+
+    /// Returns the smallest value.
+    pub fn first(&self) -> Option<&T> {
+        let mut node = self.root.as_ref()?;
+
+        while let Some(left) = &node.left {
+            node = left;
+        }
+
+        Some(&node.value)
+    }
+
+    /// Returns the greatest value.
+    pub fn last(&self) -> Option<&T> {
+        let mut node = self.root.as_ref()?;
+
+        while let Some(right) = &node.right {
+            node = right;
+        }
+
+        Some(&node.value)
+    }
+
+    /// Returns an iterator over the values in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Treap;
+    ///
+    /// let treap: Treap<i32> = [3, 1, 2].into_iter().collect();
+    ///
+    /// assert!(treap.iter().eq([1, 2, 3].iter()));
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            len: self.len(),
+            marker: PhantomData,
+        };
+
+        iter.push_left_spine(self.root.as_deref());
+
+        iter
+    }
+
+    /// Returns the next random priority.
+    fn next_priority(&mut self) -> u64 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+
+        self.seed
+    }
+}
+
+impl<T: Ord> Treap<T> {
+    /// Returns `true` if the treap contains `value`.
+    pub fn contains(&self, value: &T) -> bool {
+        let mut link = &self.root;
+
+        while let Some(node) = link {
+            link = match value.cmp(&node.value) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return true,
+            };
+        }
+
+        false
+    }
+
+    /// Inserts `value` and returns `true` unless an equal value is already
+    /// present, in which case the treap is not modified.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Treap;
+    ///
+    /// let mut treap = Treap::new();
+    ///
+    /// assert!(treap.insert(1));
+    /// assert!(!treap.insert(1));
+    /// assert_eq!(treap.len(), 1);
+    /// ```
+    pub fn insert(&mut self, value: T) -> bool {
+        if self.contains(&value) {
+            return false;
+        }
+
+        let node = Box::new(Node {
+            priority: self.next_priority(),
+            size: 1,
+            left: None,
+            right: None,
+            value,
+        });
+
+        let (left, right) = split(self.root.take(), &|other| *other < node.value);
+        self.root = merge(merge(left, Some(node)), right);
+
+        true
+    }
+
+    /// Removes `value` and returns `true` unless it's not present.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Treap;
+    ///
+    /// let mut treap: Treap<i32> = [1, 2].into_iter().collect();
+    ///
+    /// assert!(treap.remove(&1));
+    /// assert!(!treap.remove(&1));
+    /// assert!(!treap.contains(&1));
+    /// ```
+    pub fn remove(&mut self, value: &T) -> bool {
+        let (left, rest) = split(self.root.take(), &|other| other < value);
+        let (middle, right) = split(rest, &|other| other <= value);
+        self.root = merge(left, right);
+
+        middle.is_some()
+    }
+
+    /// Moves the values greater than or equal to `value` to a new treap and
+    /// returns it, in O(log n) expected time.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Treap;
+    ///
+    /// let mut treap: Treap<i32> = (0..10).collect();
+    /// let upper = treap.split_off(&6);
+    ///
+    /// assert!(treap.iter().copied().eq(0..6));
+    /// assert!(upper.iter().copied().eq(6..10));
+    /// ```
+    pub fn split_off(&mut self, value: &T) -> Self {
+        let (left, right) = split(self.root.take(), &|other| other < value);
+        self.root = left;
+
+        Self {
+            root: right,
+            seed: self.next_priority(),
+        }
+    }
+
+    /// Moves all the values of `other` into this treap in O(log n) expected
+    /// time. It's the inverse of [`Treap::split_off`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if some value of `other` is not greater than every value of
+    /// this treap.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Treap;
+    ///
+    /// let mut low: Treap<i32> = (0..5).collect();
+    /// let high: Treap<i32> = (5..10).collect();
+    ///
+    /// low.merge(high);
+    ///
+    /// assert!(low.iter().copied().eq(0..10));
+    /// ```
+    pub fn merge(&mut self, mut other: Self) {
+        if let (Some(last), Some(first)) = (self.last(), other.first()) {
+            assert!(last < first, "merged treaps must not overlap");
+        }
+
+        self.root = merge(self.root.take(), other.root.take());
+    }
+}
+
+impl<T> Default for Treap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> Extend<T> for Treap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for Treap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut treap = Self::new();
+        treap.extend(iter);
+
+        treap
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Treap<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the values of a [`Treap`] in ascending order.
+pub struct Iter<'a, T> {
+    /// Nodes whose value and right subtree haven't been visited yet.
+    stack: Vec<&'a Node<T>>,
+    len: usize,
+    marker: PhantomData<&'a T>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn push_left_spine(&mut self, mut node: Option<&'a Node<T>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(node.right.as_deref());
+        self.len -= 1;
+
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<T: HeapSize> HeapSize for Node<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.value.heap_size_of_children()
+            + self.left.heap_size_of_children()
+            + self.right.heap_size_of_children()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Treap<T> {
+    fn heap_size_of_children(&self) -> usize {
+        self.root.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Link, Treap};
+
+    /// Checks the order, heap and size invariants and returns the height.
+    fn check<T: Ord>(treap: &Treap<T>) -> usize {
+        fn check_node<T: Ord>(link: &Link<T>, low: Option<&T>, high: Option<&T>) -> (usize, usize) {
+            let Some(node) = link else {
+                return (0, 0);
+            };
+
+            assert!(low.is_none_or(|low| *low < node.value));
+            assert!(high.is_none_or(|high| node.value < *high));
+
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                assert!(child.priority <= node.priority);
+            }
+
+            let (left_size, left_height) = check_node(&node.left, low, Some(&node.value));
+            let (right_size, right_height) = check_node(&node.right, Some(&node.value), high);
+
+            assert_eq!(node.size, 1 + left_size + right_size);
+
+            (node.size, 1 + left_height.max(right_height))
+        }
+
+        check_node(&treap.root, None, None).1
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        // Sorted insertions would make an unbalanced BST degenerate
+        let mut treap: Treap<i32> = (0..10_000).collect();
+
+        assert_eq!(treap.len(), 10_000);
+        assert!(check(&treap) < 60);
+        assert!(treap.iter().copied().eq(0..10_000));
+        assert!(!treap.insert(5));
+
+        for value in (0..10_000).step_by(2) {
+            assert!(treap.remove(&value));
+        }
+
+        assert!(!treap.remove(&0));
+        assert!(check(&treap) < 60);
+        assert_eq!(treap.first(), Some(&1));
+        assert_eq!(treap.last(), Some(&9_999));
+        assert!(treap.contains(&1));
+        assert!(!treap.contains(&2));
+        assert_eq!(treap.iter().len(), 5_000);
+
+        treap.clear();
+        assert!(treap.is_empty());
+        assert_eq!(treap.first(), None);
+    }
+
+    #[test]
+    fn test_split_merge() {
+        let mut treap: Treap<i32> = (0..1_000).map(|value| value * 2).collect();
+
+        let mut upper = treap.split_off(&501);
+        check(&treap);
+        check(&upper);
+        assert!(treap.iter().copied().eq((0..251).map(|value| value * 2)));
+        assert!(upper
+            .iter()
+            .copied()
+            .eq((251..1_000).map(|value| value * 2)));
+
+        let rest = upper.split_off(&10_000);
+        assert!(rest.is_empty());
+
+        treap.merge(upper);
+        treap.merge(Treap::new());
+        check(&treap);
+        assert_eq!(treap.len(), 1_000);
+        assert!(treap.iter().copied().eq((0..1_000).map(|value| value * 2)));
+
+        let mut empty = Treap::new();
+        empty.merge(treap.split_off(&0));
+        assert!(treap.is_empty());
+        assert_eq!(empty.len(), 1_000);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_merge_overlapping() {
+        let mut low: Treap<i32> = (0..5).collect();
+        low.merge((4..10).collect());
+    }
+}
@@ -0,0 +1,655 @@
+use std::ops::RangeBounds;
+
+use crate::{
+    binary_tree::{self, BinaryTree, OccupiedSlot, Slot, VacantSlot},
+    compare::Natural,
+    heap_size::HeapSize,
+};
+
+/// Sorted map backed by a [`BinaryTree`] of key-value pairs. The pairs are
+/// ordered by key only, so values don't need to be comparable.
+pub struct TreeMap<K, V> {
+    tree: BinaryTree<(K, V)>,
+}
+
+impl<K, V> TreeMap<K, V> {
+    /// Creates an empty map.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TreeMap;
+    ///
+    /// let map: TreeMap<i32, &str> = TreeMap::new();
+    ///
+    /// assert!(map.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            tree: BinaryTree::new(),
+        }
+    }
+
+    /// Returns the number of entries in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TreeMap;
+    ///
+    /// let mut map = TreeMap::new();
+    ///
+    /// map.insert(1, "a");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.tree.size()
+    }
+
+    /// Returns `true` if the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Removes all the entries from the map.
+    pub fn clear(&mut self) {
+        self.tree.clear();
+    }
+
+    /// Returns an iterator over the entries of the map in ascending key
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TreeMap;
+    ///
+    /// let mut map = TreeMap::new();
+    ///
+    /// map.insert(2, "b");
+    /// map.insert(1, "a");
+    ///
+    /// assert_eq!(map.iter().collect::<Vec<_>>(), vec![(&1, &"a"), (&2, &"b")]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter(self.tree.iter())
+    }
+
+    /// Returns an iterator over the keys of the map in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TreeMap;
+    ///
+    /// let mut map = TreeMap::new();
+    ///
+    /// map.insert(2, "b");
+    /// map.insert(1, "a");
+    ///
+    /// assert!(map.keys().eq([1, 2].iter()));
+    /// ```
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys(self.iter())
+    }
+
+    /// Returns an iterator over the values of the map, ordered by their keys.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TreeMap;
+    ///
+    /// let mut map = TreeMap::new();
+    ///
+    /// map.insert(2, "b");
+    /// map.insert(1, "a");
+    ///
+    /// assert!(map.values().eq(["a", "b"].iter()));
+    /// ```
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values(self.iter())
+    }
+}
+
+impl<K: Ord, V> TreeMap<K, V> {
+    /// Inserts `value` under `key`. If the key was already present its value
+    /// is replaced and the old one is returned, the key itself is not
+    /// updated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TreeMap;
+    ///
+    /// let mut map = TreeMap::new();
+    ///
+    /// assert_eq!(map.insert(1, "a"), None);
+    /// assert_eq!(map.insert(1, "b"), Some("a"));
+    /// assert_eq!(map.get(&1), Some(&"b"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut value = Some(value);
+
+        let entry = self.tree.get_or_insert_with_by(
+            key,
+            |key, (node_key, _)| key.cmp(node_key),
+            |key| (key, value.take().unwrap()),
+        );
+
+        // The value is still here only if the key was found.
+        value.map(|value| std::mem::replace(&mut entry.1, value))
+    }
+
+    /// Returns a reference to the value stored under `key`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TreeMap;
+    ///
+    /// let mut map = TreeMap::new();
+    ///
+    /// map.insert(1, "a");
+    ///
+    /// assert_eq!(map.get(&1), Some(&"a"));
+    /// assert_eq!(map.get(&2), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.tree
+            .get_by(|(node_key, _)| key.cmp(node_key))
+            .map(|(_, value)| value)
+    }
+
+    /// Returns a mutable reference to the value stored under `key`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TreeMap;
+    ///
+    /// let mut map = TreeMap::new();
+    ///
+    /// map.insert(1, 10);
+    ///
+    /// if let Some(value) = map.get_mut(&1) {
+    ///     *value += 1;
+    /// }
+    ///
+    /// assert_eq!(map.get(&1), Some(&11));
+    /// ```
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.tree
+            .get_mut_by(|(node_key, _)| key.cmp(node_key))
+            .map(|(_, value)| value)
+    }
+
+    /// Returns the entry for `key`, which can be inspected and modified or
+    /// filled in place. The tree is descended only once, no matter what is
+    /// done with the entry afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TreeMap;
+    ///
+    /// let mut counts = TreeMap::new();
+    ///
+    /// for word in ["a", "b", "a"] {
+    ///     *counts.entry(word).or_insert(0) += 1;
+    /// }
+    ///
+    /// assert_eq!(counts.get(&"a"), Some(&2));
+    /// assert_eq!(counts.get(&"b"), Some(&1));
+    /// ```
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        match self.tree.slot_by(|(node_key, _)| key.cmp(node_key)) {
+            Slot::Occupied(slot) => Entry::Occupied(OccupiedEntry { slot }),
+            Slot::Vacant(slot) => Entry::Vacant(VacantEntry { key, slot }),
+        }
+    }
+
+    /// Returns `true` if the map contains an entry for `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Removes the entry for `key` and returns its value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TreeMap;
+    ///
+    /// let mut map = TreeMap::new();
+    ///
+    /// map.insert(1, "a");
+    ///
+    /// assert_eq!(map.remove(&1), Some("a"));
+    /// assert_eq!(map.remove(&1), None);
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.tree
+            .remove_by(|(node_key, _)| key.cmp(node_key))
+            .map(|(_, value)| value)
+    }
+
+    /// Returns an iterator over the entries whose keys fall within `range`,
+    /// in ascending key order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TreeMap;
+    ///
+    /// let mut map = TreeMap::new();
+    ///
+    /// for (key, value) in [(1, "a"), (2, "b"), (3, "c"), (4, "d")] {
+    ///     map.insert(key, value);
+    /// }
+    ///
+    /// let middle: Vec<_> = map.range(2..=3).collect();
+    /// assert_eq!(middle, vec![(&2, &"b"), (&3, &"c")]);
+    /// ```
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V> {
+        Range(
+            self.tree
+                .range_by(range, |key, (node_key, _)| key.cmp(node_key)),
+        )
+    }
+}
+
+impl<K, V> Default for TreeMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for TreeMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for TreeMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+
+        map
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a TreeMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// View into a single entry of a [`TreeMap`], which may be occupied or
+/// vacant. See [`TreeMap::entry`].
+pub enum Entry<'a, K, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+/// Entry whose key is present in the map.
+pub struct OccupiedEntry<'a, K, V> {
+    slot: OccupiedSlot<'a, (K, V), Natural>,
+}
+
+/// Entry whose key is not present in the map. Knows where the key belongs,
+/// so inserting doesn't search again.
+pub struct VacantEntry<'a, K, V> {
+    key: K,
+    slot: VacantSlot<'a, (K, V), Natural>,
+}
+
+impl<'a, K, V> Entry<'a, K, V> {
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Inserts `default` if the entry is vacant and returns a mutable
+    /// reference to the value of the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TreeMap;
+    ///
+    /// let mut map = TreeMap::new();
+    ///
+    /// map.entry(1).or_insert(10);
+    /// *map.entry(1).or_insert(20) += 1;
+    ///
+    /// assert_eq!(map.get(&1), Some(&11));
+    /// ```
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// Inserts the value returned by `default` if the entry is vacant and
+    /// returns a mutable reference to the value of the entry. `default` is
+    /// only called when the entry is vacant.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TreeMap;
+    ///
+    /// let mut map = TreeMap::new();
+    ///
+    /// map.entry("key").or_insert_with(Vec::new).push(1);
+    /// map.entry("key").or_insert_with(Vec::new).push(2);
+    ///
+    /// assert_eq!(map.get(&"key"), Some(&vec![1, 2]));
+    /// ```
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Inserts `V::default()` if the entry is vacant and returns a mutable
+    /// reference to the value of the entry.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// Calls `modify` with the value of the entry if it's occupied. Returns
+    /// the entry so that it can be chained with the `or_insert` methods.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TreeMap;
+    ///
+    /// let mut map = TreeMap::new();
+    ///
+    /// map.entry(1).and_modify(|value| *value += 1).or_insert(0);
+    /// assert_eq!(map.get(&1), Some(&0));
+    ///
+    /// map.entry(1).and_modify(|value| *value += 1).or_insert(0);
+    /// assert_eq!(map.get(&1), Some(&1));
+    /// ```
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, modify: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            modify(entry.get_mut());
+        }
+
+        self
+    }
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &K {
+        &self.slot.get().0
+    }
+
+    /// Returns a reference to the value of the entry.
+    pub fn get(&self) -> &V {
+        &self.slot.get().1
+    }
+
+    /// Returns a mutable reference to the value of the entry.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.slot.get_mut().1
+    }
+
+    /// Converts the entry into a mutable reference to its value that lives as
+    /// long as the borrow of the map.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.slot.into_mut().1
+    }
+
+    /// Replaces the value of the entry and returns the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the entry from the map and returns its value.
+    pub fn remove(self) -> V {
+        self.slot.remove().1
+    }
+}
+
+impl<'a, K, V> VacantEntry<'a, K, V> {
+    /// Returns the key that would be inserted.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Takes back ownership of the key without inserting anything.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Inserts `value` under the key of the entry and returns a mutable
+    /// reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        &mut self.slot.insert((self.key, value)).1
+    }
+}
+
+/// Iterator over the entries of a [`TreeMap`] in ascending key order.
+pub struct Iter<'a, K, V>(binary_tree::Iter<'a, (K, V)>);
+
+/// Iterator over the keys of a [`TreeMap`] in ascending order.
+pub struct Keys<'a, K, V>(Iter<'a, K, V>);
+
+/// Iterator over the values of a [`TreeMap`], ordered by their keys.
+pub struct Values<'a, K, V>(Iter<'a, K, V>);
+
+/// Iterator over the entries of a [`TreeMap`] within a range of keys. See
+/// [`TreeMap::range`].
+pub struct Range<'a, K, V>(binary_tree::Range<'a, (K, V)>);
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, value)| (key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(key, value)| (key, value))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, value)| (key, value))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Range<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(key, value)| (key, value))
+    }
+}
+
+impl<K: HeapSize, V: HeapSize> HeapSize for TreeMap<K, V> {
+    fn heap_size_of_children(&self) -> usize {
+        self.tree.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Entry, TreeMap};
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut map = TreeMap::new();
+
+        for (key, value) in [(20, "a"), (10, "b"), (30, "c"), (5, "d"), (15, "e")] {
+            assert_eq!(map.insert(key, value), None);
+        }
+
+        assert_eq!(map.len(), 5);
+        assert_eq!(map.insert(10, "f"), Some("b"));
+        assert_eq!(map.len(), 5);
+
+        assert_eq!(map.get(&10), Some(&"f"));
+        assert_eq!(map.get(&11), None);
+        assert!(map.contains_key(&5));
+
+        *map.get_mut(&5).unwrap() = "g";
+        assert_eq!(map.get(&5), Some(&"g"));
+
+        assert_eq!(map.remove(&20), Some("a"));
+        assert_eq!(map.remove(&20), None);
+        assert_eq!(map.len(), 4);
+
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.iter().next(), None);
+    }
+
+    #[test]
+    fn test_iter() {
+        let map: TreeMap<_, _> = [(3, 'c'), (1, 'a'), (2, 'b')].into_iter().collect();
+
+        assert!(map.iter().eq([(&1, &'a'), (&2, &'b'), (&3, &'c')]));
+        assert!(map.iter().rev().eq([(&3, &'c'), (&2, &'b'), (&1, &'a')]));
+        assert!(map.keys().eq([1, 2, 3].iter()));
+        assert!(map.values().eq(['a', 'b', 'c'].iter()));
+        assert_eq!(map.iter().len(), 3);
+        assert_eq!((&map).into_iter().count(), 3);
+    }
+
+    #[test]
+    fn test_range() {
+        let map: TreeMap<_, _> = (0..20).map(|key| (key * 2, key)).collect();
+
+        let keys =
+            |range: super::Range<'_, i32, i32>| range.map(|(key, _)| *key).collect::<Vec<_>>();
+
+        assert_eq!(keys(map.range(10..16)), [10, 12, 14]);
+        assert_eq!(keys(map.range(9..=16)), [10, 12, 14, 16]);
+        assert_eq!(keys(map.range(..5)), [0, 2, 4]);
+        assert_eq!(keys(map.range(35..)), [36, 38]);
+        assert!(keys(map.range(11..12)).is_empty());
+        assert!(keys(map.range(100..)).is_empty());
+        assert_eq!(keys(map.range(..)).len(), 20);
+        assert!(map
+            .range(10..16)
+            .rev()
+            .map(|(key, _)| key)
+            .eq([14, 12, 10].iter()));
+
+        let mut range = map.range(10..=14);
+        assert_eq!(range.next(), Some((&10, &5)));
+        assert_eq!(range.next_back(), Some((&14, &7)));
+        assert_eq!(range.next(), Some((&12, &6)));
+        assert_eq!(range.next_back(), None);
+        assert_eq!(range.next(), None);
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut map = TreeMap::new();
+
+        for word in "the quick fox jumps over the lazy dog the end".split(' ') {
+            map.entry(word).and_modify(|count| *count += 1).or_insert(1);
+        }
+
+        assert_eq!(map.get(&"the"), Some(&3));
+        assert_eq!(map.get(&"fox"), Some(&1));
+        assert_eq!(map.len(), 8);
+
+        match map.entry("fox") {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(entry.key(), &"fox");
+                assert_eq!(entry.insert(10), 1);
+                assert_eq!(entry.get(), &10);
+                assert_eq!(entry.remove(), 10);
+            }
+            Entry::Vacant(_) => panic!("fox should be present"),
+        }
+
+        assert!(!map.contains_key(&"fox"));
+
+        match map.entry("cat") {
+            Entry::Occupied(_) => panic!("cat should not be present"),
+            Entry::Vacant(entry) => assert_eq!(entry.into_key(), "cat"),
+        }
+
+        assert!(!map.contains_key(&"cat"));
+        assert_eq!(*map.entry("cat").or_default(), 0);
+        assert_eq!(map.entry("cat").key(), &"cat");
+        assert_eq!(map.len(), 8);
+        assert!(map
+            .keys()
+            .eq(["cat", "dog", "end", "jumps", "lazy", "over", "quick", "the"].iter()));
+    }
+
+    #[test]
+    fn test_owned_values() {
+        let mut map = TreeMap::new();
+
+        for word in ["b", "a", "c"] {
+            map.insert(String::from(word), word.repeat(2));
+        }
+
+        assert_eq!(
+            map.insert(String::from("a"), String::from("z")),
+            Some(String::from("aa"))
+        );
+        assert_eq!(map.remove(&String::from("b")), Some(String::from("bb")));
+        assert!(map.values().eq(["z", "cc"]));
+    }
+}
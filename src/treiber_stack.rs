@@ -0,0 +1,366 @@
+use std::{
+    mem::{self, ManuallyDrop},
+    ptr,
+    sync::atomic::{AtomicPtr, AtomicUsize, Ordering},
+};
+
+use crate::heap_size::HeapSize;
+
+struct Node<T> {
+    /// Moved out by the thread that pops the node, so freeing the node
+    /// never drops it.
+    value: ManuallyDrop<T>,
+    /// Next node of the stack, or of the retired list once popped. Threads
+    /// that lost the race to pop the node may still read it.
+    next: AtomicPtr<Node<T>>,
+}
+
+/// Lock-free stack for many threads, the classic design of R. Kent
+/// Treiber: the stack is a linked list and both [`TreiberStack::push`] and
+/// [`TreiberStack::pop`] replace the head with a compare-and-swap, retrying
+/// if another thread changed it first.
+///
+/// A popped node can't be freed right away, because other threads that
+/// read the same head may still be following its `next` pointer. Popped
+/// nodes are retired to a list instead, and the list is freed whenever a
+/// thread finds itself alone inside `pop`. Since nodes are never freed
+/// while a pop that could have seen them is running, their addresses can't
+/// be reused under it either, which rules out the ABA problem. Under
+/// constant contention from many poppers the retired list can grow until
+/// the next quiet moment.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::thread;
+///
+/// use collections_rust::TreiberStack;
+///
+/// let stack = TreiberStack::new();
+///
+/// thread::scope(|scope| {
+///     for thread in 0..4 {
+///         let stack = &stack;
+///         scope.spawn(move || {
+///             for value in 0..100 {
+///                 stack.push(thread * 100 + value);
+///             }
+///         });
+///     }
+/// });
+///
+/// let mut values = Vec::new();
+/// while let Some(value) = stack.pop() {
+///     values.push(value);
+/// }
+/// values.sort();
+///
+/// assert!(values.into_iter().eq(0..400));
+/// ```
+pub struct TreiberStack<T> {
+    head: AtomicPtr<Node<T>>,
+    /// Number of threads currently inside `pop`.
+    poppers: AtomicUsize,
+    /// Popped nodes waiting until no thread can be reading them.
+    retired: AtomicPtr<Node<T>>,
+}
+
+// Values are moved between threads, and nodes are only freed once no other
+// thread can access them.
+unsafe impl<T: Send> Send for TreiberStack<T> {}
+unsafe impl<T: Send> Sync for TreiberStack<T> {}
+
+impl<T> TreiberStack<T> {
+    /// Creates an empty stack.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TreiberStack;
+    ///
+    /// let stack: TreiberStack<i32> = TreiberStack::new();
+    ///
+    /// assert!(stack.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            head: AtomicPtr::new(ptr::null_mut()),
+            poppers: AtomicUsize::new(0),
+            retired: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    /// Returns `true` if the stack contains no values. Other threads may
+    /// change it right after it's read, so it's only a hint.
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire).is_null()
+    }
+
+    /// Pushes `value` on top of the stack.
+    pub fn push(&self, value: T) {
+        let node = Box::into_raw(Box::new(Node {
+            value: ManuallyDrop::new(value),
+            next: AtomicPtr::new(ptr::null_mut()),
+        }));
+
+        let mut head = self.head.load(Ordering::Relaxed);
+
+        loop {
+            // The node isn't shared until the swap succeeds
+            unsafe { (*node).next.store(head, Ordering::Relaxed) };
+
+            match self
+                .head
+                .compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed)
+            {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// Removes and returns the value on top of the stack, or `None` if it's
+    /// empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::TreiberStack;
+    ///
+    /// let stack = TreiberStack::new();
+    /// stack.push(1);
+    /// stack.push(2);
+    ///
+    /// assert_eq!(stack.pop(), Some(2));
+    /// assert_eq!(stack.pop(), Some(1));
+    /// assert_eq!(stack.pop(), None);
+    /// ```
+    pub fn pop(&self) -> Option<T> {
+        self.poppers.fetch_add(1, Ordering::SeqCst);
+
+        let mut head = self.head.load(Ordering::Acquire);
+
+        while !head.is_null() {
+            // The node can't be freed while this thread counts as a popper
+            let next = unsafe { (*head).next.load(Ordering::Relaxed) };
+
+            match self
+                .head
+                .compare_exchange_weak(head, next, Ordering::Acquire, Ordering::Acquire)
+            {
+                Ok(_) => break,
+                Err(current) => head = current,
+            }
+        }
+
+        if head.is_null() {
+            self.poppers.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+
+        // Winning the swap gives this thread sole ownership of the value
+        let value = unsafe { ManuallyDrop::take(&mut (*head).value) };
+        self.retire(head);
+
+        Some(value)
+    }
+
+    /// Frees `node` and every retired node if this thread is the only one
+    /// popping, or adds `node` to the retired list otherwise. Leaves the
+    /// popper count decremented either way.
+    fn retire(&self, node: *mut Node<T>) {
+        if self.poppers.load(Ordering::SeqCst) > 1 {
+            self.chain_retired(node, node);
+            self.poppers.fetch_sub(1, Ordering::SeqCst);
+            return;
+        }
+
+        // Other threads that enter pop from now on can't reach the retired
+        // nodes, but the ones that entered before taking the list may
+        let retired = self.retired.swap(ptr::null_mut(), Ordering::SeqCst);
+
+        if self.poppers.fetch_sub(1, Ordering::SeqCst) == 1 {
+            unsafe { Self::free_list(retired) };
+        } else if !retired.is_null() {
+            let mut last = retired;
+
+            loop {
+                let next = unsafe { (*last).next.load(Ordering::Relaxed) };
+
+                if next.is_null() {
+                    break;
+                }
+
+                last = next;
+            }
+
+            self.chain_retired(retired, last);
+        }
+
+        // No thread was popping when `node` was already off the stack
+        unsafe { drop(Box::from_raw(node)) };
+    }
+
+    /// Adds the list of nodes from `first` to `last` to the retired list.
+    fn chain_retired(&self, first: *mut Node<T>, last: *mut Node<T>) {
+        let mut retired = self.retired.load(Ordering::SeqCst);
+
+        loop {
+            unsafe { (*last).next.store(retired, Ordering::Relaxed) };
+
+            match self.retired.compare_exchange_weak(
+                retired,
+                first,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return,
+                Err(current) => retired = current,
+            }
+        }
+    }
+
+    /// Frees a list of retired nodes, whose values were already moved out.
+    ///
+    /// # Safety
+    ///
+    /// No other thread may access any node of the list.
+    unsafe fn free_list(mut node: *mut Node<T>) {
+        while !node.is_null() {
+            let next = (*node).next.load(Ordering::Relaxed);
+            drop(Box::from_raw(node));
+            node = next;
+        }
+    }
+}
+
+impl<T> Default for TreiberStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for TreiberStack<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+
+        unsafe { Self::free_list(*self.retired.get_mut()) };
+    }
+}
+
+/// Values aren't visited, since other threads may be popping them at the
+/// same time. Nodes already popped but not freed yet may be counted.
+impl<T> HeapSize for TreiberStack<T> {
+    fn heap_size_of_children(&self) -> usize {
+        // Nodes can't be freed while this thread counts as a popper. A node
+        // popped while visiting it is retired and may lead to other retired
+        // nodes, which aren't freed either.
+        self.poppers.fetch_add(1, Ordering::SeqCst);
+
+        let mut nodes = 0;
+        let mut node = self.head.load(Ordering::Acquire);
+
+        while !node.is_null() {
+            nodes += 1;
+            node = unsafe { (*node).next.load(Ordering::Acquire) };
+        }
+
+        self.poppers.fetch_sub(1, Ordering::SeqCst);
+
+        nodes * mem::size_of::<Node<T>>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{mem, rc::Rc, sync::Mutex, thread};
+
+    use super::{Node, TreiberStack};
+    use crate::HeapSize;
+
+    #[test]
+    fn test_threads() {
+        let stack = TreiberStack::new();
+        let popped = Mutex::new(Vec::new());
+        let (threads, per_thread) = (4, 5_000);
+
+        thread::scope(|scope| {
+            for thread in 0..threads {
+                let (stack, popped) = (&stack, &popped);
+
+                scope.spawn(move || {
+                    let mut local = Vec::new();
+
+                    // Push and pop at the same time to exercise retirement
+                    for value in 0..per_thread {
+                        stack.push(thread * per_thread + value);
+
+                        if value % 2 == 1 {
+                            local.extend(stack.pop());
+                            local.extend(stack.pop());
+                        }
+                    }
+
+                    popped.lock().unwrap().append(&mut local);
+                });
+            }
+        });
+
+        let mut popped = popped.into_inner().unwrap();
+        while let Some(value) = stack.pop() {
+            popped.push(value);
+        }
+
+        popped.sort();
+        assert!(popped.into_iter().eq(0..threads * per_thread));
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn test_static() {
+        static STACK: TreiberStack<usize> = TreiberStack::new();
+
+        thread::scope(|scope| {
+            for value in 0..4 {
+                scope.spawn(move || STACK.push(value));
+            }
+        });
+
+        let mut values: Vec<_> = (0..4).filter_map(|_| STACK.pop()).collect();
+        values.sort();
+        assert_eq!(values, [0, 1, 2, 3]);
+        assert!(STACK.is_empty());
+    }
+
+    #[test]
+    fn test_drop() {
+        let value = Rc::new(());
+        let stack = TreiberStack::new();
+
+        for _ in 0..10 {
+            stack.push(value.clone());
+        }
+
+        assert!(stack.pop().is_some());
+        assert_eq!(Rc::strong_count(&value), 10);
+
+        drop(stack);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn test_heap_size() {
+        let stack = TreiberStack::new();
+        assert_eq!(stack.heap_size_of_children(), 0);
+
+        for value in 0..5 {
+            stack.push(value.to_string());
+        }
+
+        stack.pop();
+        assert_eq!(
+            stack.heap_size_of_children(),
+            4 * mem::size_of::<Node<String>>()
+        );
+    }
+}
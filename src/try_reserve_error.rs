@@ -0,0 +1,39 @@
+#[cfg(feature = "binary_tree")]
+use std::ptr;
+use std::{alloc, error::Error, fmt};
+
+/// Error returned by the fallible methods of the collections, like
+/// [`crate::Vector::try_reserve`] or [`crate::Dequeue::try_push_back`],
+/// when they can't get the memory they need. Programs that must survive
+/// running out of memory use those methods instead of the ones that abort.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TryReserveError {
+    /// The requested capacity doesn't fit in memory addresses, so it was
+    /// never asked to the allocator.
+    CapacityOverflow,
+    /// The allocator returned an error for `layout`.
+    AllocError { layout: alloc::Layout },
+}
+
+impl fmt::Display for TryReserveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CapacityOverflow => write!(f, "capacity overflow"),
+            Self::AllocError { layout } => {
+                write!(f, "failed to allocate {} bytes", layout.size())
+            }
+        }
+    }
+}
+
+impl Error for TryReserveError {}
+
+/// Allocates memory for `layout`, which may have zero size.
+#[cfg(feature = "binary_tree")]
+pub(crate) fn try_alloc(layout: alloc::Layout) -> Result<ptr::NonNull<u8>, TryReserveError> {
+    if layout.size() == 0 {
+        return Ok(ptr::NonNull::new(layout.align() as *mut u8).unwrap());
+    }
+
+    ptr::NonNull::new(unsafe { alloc::alloc(layout) }).ok_or(TryReserveError::AllocError { layout })
+}
@@ -1,22 +1,80 @@
 use std::{
-    alloc, marker, mem,
-    ops::{Deref, DerefMut},
+    alloc, cmp,
+    convert::Infallible,
+    fmt,
+    hash::{Hash, Hasher},
+    marker, mem,
+    ops::{Bound, Deref, DerefMut, RangeBounds},
     ptr,
+    sync::Arc,
+};
+
+#[cfg(feature = "stats")]
+use crate::stats::Stats;
+use crate::{
+    allocator::{Allocator, Global},
+    drop_guard,
+    heap_size::{self, HeapSize},
+    stats::Counters,
+    traits,
+    try_reserve_error::TryReserveError,
 };
 
 /// Buffer of fixed capacity that stores the values.
-struct Buffer<T> {
+///
+/// Unless `cap` is zero or `T` is zero sized, `ptr` points to memory of
+/// `alloc` with the layout of an array of `cap` values, owned by the buffer
+/// alone and freed when it's dropped. Otherwise `ptr` is only non-null and
+/// aligned, and zero sized values have a capacity of `usize::MAX`. The
+/// buffer doesn't track which slots hold values.
+pub(crate) struct Buffer<T, A: Allocator = Global> {
     ptr: ptr::NonNull<T>,
     cap: usize,
+    alloc: A,
     _marker: marker::PhantomData<T>,
 }
 
-// unsafe impl<T: Send> Send for RawVec<T> {}
-// unsafe impl<T: Sync> Sync for RawVec<T> {}
+// The buffer owns its values like a `Box<[T]>`, so it can cross threads
+// whenever they and the allocator can.
+unsafe impl<T: Send, A: Allocator + Send> Send for Buffer<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for Buffer<T, A> {}
 
 impl<T> Buffer<T> {
     /// Creates a new `RawVec` with zero capacity.
-    pub fn new() -> Self {
+    pub(crate) const fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    /// Takes over `cap` slots at `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` and `cap` must uphold the contract of [`Buffer`] for the global
+    /// allocator, except that the capacity of zero sized values is ignored.
+    pub(crate) unsafe fn from_raw_parts(ptr: *mut T, cap: usize) -> Self {
+        let mut buf = Self::new();
+
+        if mem::size_of::<T>() != 0 {
+            buf.cap = cap;
+        }
+
+        buf.ptr = ptr::NonNull::new_unchecked(ptr);
+        buf
+    }
+
+    /// Gives up the slots without freeing them, returning the pointer and
+    /// the capacity.
+    pub(crate) fn into_raw_parts(self) -> (*mut T, usize) {
+        let buf = mem::ManuallyDrop::new(self);
+
+        (buf.ptr(), buf.cap)
+    }
+}
+
+impl<T, A: Allocator> Buffer<T, A> {
+    /// Creates a buffer with zero capacity whose memory will come from
+    /// `alloc`.
+    pub(crate) const fn new_in(alloc: A) -> Self {
         let cap = if mem::size_of::<T>() == 0 {
             usize::MAX
         } else {
@@ -26,71 +84,282 @@ impl<T> Buffer<T> {
         Self {
             ptr: ptr::NonNull::dangling(),
             cap,
+            alloc,
             _marker: marker::PhantomData,
         }
     }
 
+    /// Returns the pointer to the first slot of the buffer.
+    pub(crate) fn ptr(&self) -> *mut T {
+        self.ptr.as_ptr()
+    }
+
+    /// Returns the number of slots of the buffer.
+    #[cfg(feature = "array_deque")]
+    pub(crate) fn cap(&self) -> usize {
+        self.cap
+    }
+
+    /// Returns the layout of the current allocation.
+    fn layout(&self) -> alloc::Layout {
+        alloc::Layout::array::<T>(self.cap).unwrap()
+    }
+
     /// Allocates a new buffer if the capacity is zero, otherwise it doubles
-    /// the size of the buffer and reallocates it.
-    fn grow(&mut self) {
-        // We shouldn't get to this point if `T` is zero sized.
-        assert!(mem::size_of::<T>() != 0, "Capacity overflow");
+    /// the size of the buffer and reallocates it. Goes through
+    /// [`Buffer::try_grow_to`], panicking or aborting where it would fail.
+    pub(crate) fn grow(&mut self) {
+        // Zero sized values are already at the max capacity, so they overflow
+        let grown = match self.cap.checked_add(1) {
+            Some(min_cap) => self.try_grow_to(min_cap),
+            None => Err(TryReserveError::CapacityOverflow),
+        };
 
-        let (new_cap, new_layout, new_ptr) = if self.cap == 0 {
-            let new_layout = alloc::Layout::array::<T>(1).unwrap();
-            let new_ptr = unsafe { alloc::alloc(new_layout) };
+        handle_reserve(grown);
+    }
 
-            (1, new_layout, new_ptr)
-        } else {
-            let new_cap = self.cap * 2;
-            let new_layout = alloc::Layout::array::<T>(new_cap).unwrap();
+    /// Grows the buffer to at least `min_cap` slots, and at least doubles its
+    /// capacity if it grows at all. Leaves the buffer unchanged if the memory
+    /// can't be allocated.
+    pub(crate) fn try_grow_to(&mut self, min_cap: usize) -> Result<(), TryReserveError> {
+        // Zero sized values never get past this, their capacity is the max.
+        if min_cap <= self.cap {
+            return Ok(());
+        }
 
-            assert!(
-                new_layout.size() <= isize::MAX as usize,
-                "Allocation too large"
-            );
+        self.try_grow_exact_to(min_cap.max(self.cap * 2))
+    }
 
-            let new_ptr = unsafe {
-                alloc::realloc(
-                    self.ptr.as_ptr() as *mut u8,
-                    alloc::Layout::array::<T>(self.cap).unwrap(),
-                    new_layout.size(),
-                )
-            };
+    /// Grows the buffer to exactly `new_cap` slots if it has fewer. Leaves
+    /// the buffer unchanged if the memory can't be allocated.
+    pub(crate) fn try_grow_exact_to(&mut self, new_cap: usize) -> Result<(), TryReserveError> {
+        if new_cap <= self.cap {
+            return Ok(());
+        }
 
-            (new_cap, new_layout, new_ptr)
-        };
+        let new_layout =
+            alloc::Layout::array::<T>(new_cap).map_err(|_| TryReserveError::CapacityOverflow)?;
 
-        self.ptr = match ptr::NonNull::new(new_ptr as *mut T) {
-            Some(ptr) => ptr,
-            None => alloc::handle_alloc_error(new_layout),
+        let new_ptr = unsafe {
+            if self.cap == 0 {
+                self.alloc.allocate(new_layout)
+            } else {
+                self.alloc
+                    .reallocate(self.ptr.cast(), self.layout(), new_layout)
+            }
         };
 
+        self.ptr = new_ptr
+            .ok_or(TryReserveError::AllocError { layout: new_layout })?
+            .cast();
+        self.cap = new_cap;
+
+        Ok(())
+    }
+
+    /// Reallocates the buffer down to `new_cap` slots if it has more, or
+    /// frees it if `new_cap` is zero. The values in the slots that are
+    /// cut off must have been moved out or dropped already.
+    pub(crate) fn shrink_to(&mut self, new_cap: usize) {
+        // Zero sized values never own memory, their capacity stays the max.
+        if new_cap >= self.cap || mem::size_of::<T>() == 0 {
+            return;
+        }
+
+        if new_cap == 0 {
+            unsafe { self.alloc.deallocate(self.ptr.cast(), self.layout()) };
+            self.ptr = ptr::NonNull::dangling();
+        } else {
+            let new_layout = alloc::Layout::array::<T>(new_cap).unwrap();
+            let new_ptr = unsafe {
+                self.alloc
+                    .reallocate(self.ptr.cast(), self.layout(), new_layout)
+            };
+
+            self.ptr = match new_ptr {
+                Some(ptr) => ptr.cast(),
+                None => alloc::handle_alloc_error(new_layout),
+            };
+        }
+
         self.cap = new_cap;
     }
 }
 
-impl<T> Drop for Buffer<T> {
+impl<T, A: Allocator> Drop for Buffer<T, A> {
     fn drop(&mut self) {
         if self.cap != 0 && mem::size_of::<T>() != 0 {
-            unsafe {
-                alloc::dealloc(
-                    self.ptr.as_ptr() as *mut u8,
-                    alloc::Layout::array::<T>(self.cap).unwrap(),
-                );
-            }
+            unsafe { self.alloc.deallocate(self.ptr.cast(), self.layout()) };
         }
     }
 }
 
+/// Panics or aborts like [`Vector::push`] if a reservation failed.
+fn handle_reserve(result: Result<(), TryReserveError>) {
+    match result {
+        Ok(()) => {}
+        Err(TryReserveError::CapacityOverflow) => panic!("Capacity overflow"),
+        Err(TryReserveError::AllocError { layout }) => alloc::handle_alloc_error(layout),
+    }
+}
+
+/// Creates a [`Vector`] containing the arguments, like [`vec!`] does. The
+/// buffer is allocated once with room for every value.
+///
+/// # Examples
+///
+/// ```rust
+/// use collections_rust::vector;
+///
+/// let numbers = vector![1, 2, 3];
+/// let zeros = vector![0u8; 4];
+///
+/// assert_eq!(&*numbers, [1, 2, 3]);
+/// assert_eq!(&*zeros, [0, 0, 0, 0]);
+/// ```
+#[macro_export]
+macro_rules! vector {
+    ($value:expr; $len:expr) => {{
+        let mut vector = $crate::Vector::new();
+        ::core::iter::Extend::extend(&mut vector, ::core::iter::repeat_n($value, $len));
+        vector
+    }};
+    ($($value:expr),* $(,)?) => {{
+        let mut vector = $crate::Vector::new();
+        ::core::iter::Extend::extend(&mut vector, [$($value),*]);
+        vector
+    }};
+}
+
 /// List data structure stored as an array that grow's automatically when it's
 /// necessary.
-pub struct Vector<T> {
-    buf: Buffer<T>,
+pub struct Vector<T, A: Allocator = Global> {
+    buf: Buffer<T, A>,
     len: usize,
+    stats: Counters,
 }
 
 impl<T> Vector<T> {
+    /// Creates and returns a new `Vec` with zero length. Doesn't allocate,
+    /// so it can initialize `static` items.
+    pub const fn new() -> Self {
+        Self {
+            buf: Buffer::new(),
+            len: 0,
+            stats: Counters::new(),
+        }
+    }
+
+    /// Creates a vector from its parts, taking ownership of the buffer
+    /// without copying it, for example memory handed over through FFI.
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must be non-null and aligned for `T`.
+    /// - Unless `cap` is zero or `T` is zero sized, `ptr` must have been
+    ///   allocated by the global allocator with the layout of an array of
+    ///   `cap` values of `T`, and nothing else may use or free it anymore.
+    /// - `len` must not exceed `cap`, and the first `len` slots must hold
+    ///   initialized values.
+    ///
+    /// The parts returned by [`Vector::into_raw_parts`] and by std's `Vec`
+    /// meet these requirements. The capacity of zero sized values is
+    /// ignored, it's always `usize::MAX`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::{vector, Vector};
+    ///
+    /// let (ptr, len, cap) = vector![1, 2, 3].into_raw_parts();
+    ///
+    /// // Hand the buffer to C and get it back later
+    /// let vector = unsafe { Vector::from_raw_parts(ptr, len, cap) };
+    ///
+    /// assert_eq!(vector, [1, 2, 3]);
+    /// ```
+    pub unsafe fn from_raw_parts(ptr: *mut T, len: usize, cap: usize) -> Self {
+        let vector = Self {
+            buf: Buffer::from_raw_parts(ptr, cap),
+            len,
+            stats: Counters::new(),
+        };
+
+        vector.stats.record_len(len);
+        vector
+    }
+
+    /// Returns the pointer to the buffer, the length and the capacity of the
+    /// vector without dropping the values or freeing the buffer, which
+    /// becomes the caller's responsibility. [`Vector::from_raw_parts`] can
+    /// take them back.
+    pub fn into_raw_parts(self) -> (*mut T, usize, usize) {
+        let vector = mem::ManuallyDrop::new(self);
+        let (ptr, cap) = unsafe { ptr::read(&vector.buf) }.into_raw_parts();
+
+        (ptr, vector.len, cap)
+    }
+
+    /// Creates an empty vector that can hold `capacity` values before it
+    /// needs to grow.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer would be bigger than `isize::MAX` bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Vector;
+    ///
+    /// let mut vector = Vector::with_capacity(10);
+    ///
+    /// for value in 0..10 {
+    ///     vector.push(value);
+    /// }
+    ///
+    /// assert_eq!(vector.capacity(), 10);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut vector = Self::new();
+        vector.reserve_exact(capacity);
+
+        vector
+    }
+
+    /// Turns the vector into an immutable snapshot that any number of
+    /// threads can read at once. Cloning the snapshot shares the values
+    /// instead of copying them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Vector;
+    /// use std::thread;
+    ///
+    /// let mut vector = Vector::new();
+    /// vector.extend([1, 2, 3]);
+    ///
+    /// let frozen = vector.freeze();
+    ///
+    /// thread::scope(|s| {
+    ///     for _ in 0..4 {
+    ///         let frozen = frozen.clone();
+    ///         s.spawn(move || assert_eq!(frozen.iter().sum::<i32>(), 6));
+    ///     }
+    /// });
+    ///
+    /// let mut vector = frozen.thaw().unwrap();
+    /// vector.push(4);
+    /// ```
+    pub fn freeze(self) -> FrozenVector<T> {
+        FrozenVector {
+            inner: Arc::new(self),
+        }
+    }
+}
+
+impl<T, A: Allocator> Vector<T, A> {
     /// Returns the underlying buffer pointer.
     fn ptr(&self) -> *mut T {
         self.buf.ptr.as_ptr()
@@ -101,24 +370,77 @@ impl<T> Vector<T> {
         self.buf.cap
     }
 
+    /// Returns the number of values the vector can hold without growing its
+    /// buffer. Zero sized values never need to grow it, so their capacity is
+    /// `usize::MAX`.
+    pub fn capacity(&self) -> usize {
+        self.cap()
+    }
+
+    /// Grows the buffer, see [`Buffer::grow`].
+    fn grow(&mut self) {
+        let cap = self.cap();
+        self.buf.grow();
+        self.record_growth(cap);
+    }
+
+    /// Counts the allocation or reallocation of the buffer if it grew from
+    /// `old_cap` slots.
+    fn record_growth(&self, old_cap: usize) {
+        match old_cap {
+            _ if self.cap() == old_cap => {}
+            0 => self.stats.record_allocation(),
+            _ => self.stats.record_reallocation(),
+        }
+    }
+
     pub fn len(&self) -> usize {
         self.len
     }
 
-    /// Creates and returns a new `Vec` with zero length.
-    pub fn new() -> Self {
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Creates an empty vector whose buffer is allocated by `alloc`. See
+    /// [`Allocator`] for an example.
+    pub const fn new_in(alloc: A) -> Self {
         Self {
-            buf: Buffer::new(),
+            buf: Buffer::new_in(alloc),
             len: 0,
+            stats: Counters::new(),
         }
     }
 
+    /// Like [`Vector::with_capacity`], with the buffer allocated by `alloc`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer would be bigger than `isize::MAX` bytes.
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        let mut vector = Self::new_in(alloc);
+        vector.reserve_exact(capacity);
+
+        vector
+    }
+
+    /// Returns the allocator of the buffer.
+    pub fn allocator(&self) -> &A {
+        &self.buf.alloc
+    }
+
+    /// Returns the counters of the vector, see [`Stats`].
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+
     /// Adds a new value to the vector. If necessary, the capacity of the
     /// underlying buffer will grow to fit in the new value. Each time it needs
     /// to grow it will double in size.
     pub fn push(&mut self, value: T) {
         if self.len == self.cap() {
-            self.buf.grow();
+            self.grow();
         }
 
         unsafe {
@@ -126,6 +448,173 @@ impl<T> Vector<T> {
         }
 
         self.len += 1;
+        self.stats.record_len(self.len);
+    }
+
+    /// Makes room for at least `additional` more values without aborting if
+    /// the memory can't be allocated.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryReserveError`] if the new capacity overflows or the
+    /// allocator fails, and leaves the vector unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::{TryReserveError, Vector};
+    ///
+    /// let mut vector: Vector<u64> = Vector::new();
+    ///
+    /// assert_eq!(vector.try_reserve(10), Ok(()));
+    /// assert_eq!(vector.try_reserve(usize::MAX), Err(TryReserveError::CapacityOverflow));
+    /// ```
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let required = self
+            .len
+            .checked_add(additional)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+
+        let cap = self.cap();
+        self.buf.try_grow_to(required)?;
+        self.record_growth(cap);
+
+        Ok(())
+    }
+
+    /// Like [`Vector::try_reserve`], but doesn't grow the buffer beyond the
+    /// `additional` values, so it's meant for callers that know the final
+    /// length. Prefer [`Vector::try_reserve`] if more values may follow.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryReserveError`] if the new capacity overflows or the
+    /// allocator fails, and leaves the vector unchanged.
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let required = self
+            .len
+            .checked_add(additional)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+
+        let cap = self.cap();
+        self.buf.try_grow_exact_to(required)?;
+        self.record_growth(cap);
+
+        Ok(())
+    }
+
+    /// Makes room for at least `additional` more values, so a bulk insert
+    /// reallocates at most once. Like [`Vector::push`], the buffer at least
+    /// doubles when it grows.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows, and aborts like
+    /// [`Vector::push`] if the memory can't be allocated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Vector;
+    ///
+    /// let mut vector = Vector::new();
+    /// vector.push(1);
+    /// vector.reserve(10);
+    ///
+    /// assert!(vector.capacity() >= 11);
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        handle_reserve(self.try_reserve(additional));
+    }
+
+    /// Makes room for exactly `additional` more values if the buffer can't
+    /// hold them yet. See [`Vector::try_reserve_exact`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows, and aborts like
+    /// [`Vector::push`] if the memory can't be allocated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Vector;
+    ///
+    /// let mut vector = Vector::new();
+    /// vector.push(1);
+    /// vector.reserve_exact(10);
+    ///
+    /// assert_eq!(vector.capacity(), 11);
+    /// ```
+    pub fn reserve_exact(&mut self, additional: usize) {
+        handle_reserve(self.try_reserve_exact(additional));
+    }
+
+    /// Releases the memory the buffer doesn't need to hold the current
+    /// values, freeing it altogether if the vector is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Vector;
+    ///
+    /// let mut vector = Vector::with_capacity(10);
+    /// vector.extend([1, 2, 3]);
+    /// vector.shrink_to_fit();
+    ///
+    /// assert_eq!(vector.capacity(), 3);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(0);
+    }
+
+    /// Shrinks the buffer to the largest of `min_cap` and the length of the
+    /// vector. Does nothing if the capacity is already lower, and zero sized
+    /// values keep their `usize::MAX` capacity since they never need memory.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Vector;
+    ///
+    /// let mut vector = Vector::with_capacity(10);
+    /// vector.extend([1, 2, 3]);
+    ///
+    /// vector.shrink_to(5);
+    /// assert_eq!(vector.capacity(), 5);
+    ///
+    /// vector.shrink_to(0);
+    /// assert_eq!(vector.capacity(), 3);
+    /// ```
+    pub fn shrink_to(&mut self, min_cap: usize) {
+        self.buf.shrink_to(min_cap.max(self.len));
+    }
+
+    /// Like [`Vector::push`], but gives `value` back instead of aborting if
+    /// the buffer can't grow.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err(value)` if the buffer is full and can't grow.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Vector;
+    ///
+    /// let mut vector = Vector::new();
+    ///
+    /// assert_eq!(vector.try_push(1), Ok(()));
+    /// assert_eq!(*vector, [1]);
+    /// ```
+    pub fn try_push(&mut self, value: T) -> Result<(), T> {
+        if self.try_reserve(1).is_err() {
+            return Err(value);
+        }
+
+        self.push(value);
+
+        Ok(())
     }
 
     /// Removes and returns the last element of the vector.
@@ -145,7 +634,7 @@ impl<T> Vector<T> {
         assert!(index <= self.len, "Index out of bounds");
 
         if self.cap() == self.len {
-            self.buf.grow();
+            self.grow();
         }
 
         unsafe {
@@ -159,6 +648,8 @@ impl<T> Vector<T> {
 
             self.len += 1;
         }
+
+        self.stats.record_len(self.len);
     }
 
     /// Removes and returns the value at the specified `index`.
@@ -168,6 +659,8 @@ impl<T> Vector<T> {
         unsafe {
             let value = ptr::read(self.ptr().add(index));
 
+            self.len -= 1;
+
             ptr::copy(
                 self.ptr().add(index + 1),
                 self.ptr().add(index),
@@ -178,42 +671,513 @@ impl<T> Vector<T> {
         }
     }
 
-    pub fn drain(&mut self) -> Drain<T> {
+    /// Removes and returns the value at the specified `index`, moving the
+    /// last value into its place. Doesn't preserve the order, but takes
+    /// constant time instead of shifting every value after `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::vector;
+    ///
+    /// let mut entities = vector!["a", "b", "c", "d"];
+    ///
+    /// assert_eq!(entities.swap_remove(1), "b");
+    /// assert_eq!(&*entities, ["a", "d", "c"]);
+    /// ```
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "Index out of bounds");
+
         unsafe {
-            let iter = RawIter::new(&self);
+            let value = ptr::read(self.ptr().add(index));
+
+            self.len -= 1;
+
+            // Copying the last value onto itself is fine when it's the one
+            // removed, the length already excludes it.
+            ptr::copy(self.ptr().add(self.len), self.ptr().add(index), 1);
+
+            value
+        }
+    }
 
-            self.len = 0;
+    /// Removes the values in `range` and returns them in an iterator. The
+    /// values after `range` are moved back into place when the iterator is
+    /// dropped, and the values it didn't return are dropped with it.
+    ///
+    /// If the iterator is leaked instead of dropped, the vector keeps the
+    /// values before `range` only, and the rest are leaked too.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::vector;
+    ///
+    /// let mut vector = vector![1, 2, 3, 4, 5];
+    ///
+    /// let drained: Vec<_> = vector.drain(1..4).collect();
+    ///
+    /// assert_eq!(drained, [2, 3, 4]);
+    /// assert_eq!(&*vector, [1, 5]);
+    ///
+    /// vector.drain(..);
+    /// assert!(vector.is_empty());
+    /// ```
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T, A> {
+        let (start, end) = self.bounds(range);
+        let tail_len = self.len - end;
+
+        unsafe {
+            let iter = RawIter::new(&self[start..end]);
+
+            // Until the iterator is dropped the vector ends before `range`,
+            // so leaking it can't expose moved values.
+            self.len = start;
 
             Drain {
+                vector: self,
                 iter,
-                vec: marker::PhantomData,
+                tail_start: end,
+                tail_len,
             }
         }
     }
+
+    /// Removes the values in `range` and inserts the values of `replace_with`
+    /// in their place. The removed values are returned by the iterator, and
+    /// the replacement happens when it's dropped, whether it was consumed
+    /// or not. The values after `range` are moved at most twice.
+    ///
+    /// If the iterator is leaked instead of dropped, the vector keeps the
+    /// values before `range` only, and the rest are leaked too.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::vector;
+    ///
+    /// let mut vector = vector![1, 2, 3, 4];
+    ///
+    /// let removed: Vec<_> = vector.splice(1..3, [7, 8, 9]).collect();
+    ///
+    /// assert_eq!(removed, [2, 3]);
+    /// assert_eq!(&*vector, [1, 7, 8, 9, 4]);
+    /// ```
+    pub fn splice<R, I>(&mut self, range: R, replace_with: I) -> Splice<'_, I::IntoIter, A>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = T>,
+    {
+        Splice {
+            drain: self.drain(range),
+            replace_with: replace_with.into_iter(),
+        }
+    }
+
+    /// Returns the start and end of `range` after checking its bounds.
+    fn bounds<R: RangeBounds<usize>>(&self, range: R) -> (usize, usize) {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start
+                .checked_add(1)
+                .expect("attempted to index slice from after maximum usize"),
+            Bound::Unbounded => 0,
+        };
+
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end
+                .checked_add(1)
+                .expect("attempted to index slice up to maximum usize"),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len,
+        };
+
+        assert!(start <= end && end <= self.len, "Range out of bounds");
+
+        (start, end)
+    }
+
+    /// Returns an iterator that removes and returns the values for which
+    /// `extract` returns `true`, checking them lazily as it advances. The
+    /// values kept are moved down to close the gaps along the way, so both
+    /// sides keep their original order in a single pass.
+    ///
+    /// Dropping the iterator early keeps the values it didn't check. If it
+    /// is leaked instead, the vector is left empty and its values leaked.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::{vector, Dequeue};
+    ///
+    /// let mut numbers = vector![1, 2, 3, 4, 5, 6];
+    ///
+    /// let evens: Dequeue<_> = numbers.extract_if(|n| *n % 2 == 0).collect();
+    ///
+    /// assert_eq!(evens, [2, 4, 6]);
+    /// assert_eq!(numbers, [1, 3, 5]);
+    /// ```
+    pub fn extract_if<F: FnMut(&mut T) -> bool>(&mut self, extract: F) -> ExtractIf<'_, T, F, A> {
+        let len = self.len;
+
+        // Until the iterator is dropped the gaps would expose moved values
+        self.len = 0;
+
+        ExtractIf {
+            vector: self,
+            len,
+            checked: 0,
+            extracted: 0,
+            extract,
+        }
+    }
+
+    /// Retains only the values for which `keep` returns `true`, in their
+    /// original order.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut keep: F) {
+        let Ok(()) = self.try_retain(|value| Ok::<_, Infallible>(keep(value)));
+    }
+
+    /// Like [`Vector::retain`], but stops at the first error returned by
+    /// `keep`. The values checked before it are removed or kept as `keep`
+    /// said, the value that failed and the ones after it are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by `keep`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Vector;
+    ///
+    /// let mut vector = Vector::new();
+    /// vector.extend([1, 2, 3, 4, 5]);
+    ///
+    /// let result = vector.try_retain(|&value| match value {
+    ///     4 => Err("too big"),
+    ///     _ => Ok(value % 2 == 1),
+    /// });
+    ///
+    /// assert_eq!(result, Err("too big"));
+    /// assert_eq!(&*vector, [1, 3, 4, 5]);
+    /// ```
+    pub fn try_retain<E, F>(&mut self, mut keep: F) -> Result<(), E>
+    where
+        F: FnMut(&T) -> Result<bool, E>,
+    {
+        /// Moves the values that weren't checked over the removed ones and
+        /// fixes the length, also when `keep` or a drop panics.
+        struct Guard<'a, T, A: Allocator> {
+            vector: &'a mut Vector<T, A>,
+            len: usize,
+            checked: usize,
+            removed: usize,
+        }
+
+        impl<T, A: Allocator> Drop for Guard<'_, T, A> {
+            fn drop(&mut self) {
+                unsafe {
+                    let ptr = self.vector.ptr();
+
+                    ptr::copy(
+                        ptr.add(self.checked),
+                        ptr.add(self.checked - self.removed),
+                        self.len - self.checked,
+                    );
+                }
+
+                self.vector.len = self.len - self.removed;
+            }
+        }
+
+        let len = self.len;
+
+        // Until the guard is dropped the removed slots hold values that were
+        // already dropped, so nothing else may see them
+        self.len = 0;
+
+        let mut guard = Guard {
+            vector: self,
+            len,
+            checked: 0,
+            removed: 0,
+        };
+
+        while guard.checked < guard.len {
+            unsafe {
+                let current = guard.vector.ptr().add(guard.checked);
+
+                if keep(&*current)? {
+                    if guard.removed > 0 {
+                        ptr::copy_nonoverlapping(current, current.sub(guard.removed), 1);
+                    }
+                    guard.checked += 1;
+                } else {
+                    guard.checked += 1;
+                    guard.removed += 1;
+                    ptr::drop_in_place(current);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Pushes the values of `iter` until it yields an error. The values
+    /// before the error stay in the vector.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error yielded by `iter`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Vector;
+    ///
+    /// let mut vector = Vector::new();
+    ///
+    /// let result = vector.try_extend(["1", "2", "x", "4"].map(str::parse::<i32>));
+    ///
+    /// assert!(result.is_err());
+    /// assert_eq!(&*vector, [1, 2]);
+    /// ```
+    pub fn try_extend<E, I>(&mut self, iter: I) -> Result<(), E>
+    where
+        I: IntoIterator<Item = Result<T, E>>,
+    {
+        for value in iter {
+            self.push(value?);
+        }
+
+        Ok(())
+    }
+
+    /// Moves every value of `other` to the end of the vector, leaving `other`
+    /// empty. The values are copied at once after growing the buffer at most
+    /// once, and `other` keeps its buffer for reuse.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::vector;
+    ///
+    /// let mut a = vector![1, 2];
+    /// let mut b = vector![3, 4];
+    ///
+    /// a.append(&mut b);
+    ///
+    /// assert_eq!(a, [1, 2, 3, 4]);
+    /// assert!(b.is_empty());
+    /// ```
+    pub fn append(&mut self, other: &mut Self) {
+        self.reserve(other.len);
+
+        unsafe {
+            ptr::copy_nonoverlapping(other.ptr(), self.ptr().add(self.len), other.len);
+        }
+
+        self.len += other.len;
+        other.len = 0;
+        self.stats.record_len(self.len);
+    }
+
+    /// Clones the values of `other` at the end of the vector, growing the
+    /// buffer at most once. For `Copy` values, [`Extend`] does the same from
+    /// any iterator of references.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::vector;
+    ///
+    /// let mut names = vector![String::from("a")];
+    /// names.extend_from_slice(&[String::from("b"), String::from("c")]);
+    ///
+    /// assert_eq!(&*names, ["a", "b", "c"]);
+    /// ```
+    pub fn extend_from_slice(&mut self, other: &[T])
+    where
+        T: Clone,
+    {
+        self.extend(other.iter().cloned());
+    }
 }
 
-impl<T> Drop for Vector<T> {
+impl<T> Default for Vector<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone> Clone for Vector<T, A> {
+    fn clone(&self) -> Self {
+        let mut vector = Self::new_in(self.allocator().clone());
+        vector.extend_from_slice(self);
+
+        vector
+    }
+}
+
+/// Makes room for the values the iterator is sure to yield before pushing
+/// them, so the buffer grows at most once for iterators of known length.
+impl<T, A: Allocator> Extend<T> for Vector<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+/// Copies the values, so slices can be appended without `.copied()`.
+impl<'a, T: Copy + 'a, A: Allocator> Extend<&'a T> for Vector<T, A> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+/// Allocates room for the values the iterator is sure to yield up front,
+/// see [`Extend`].
+impl<T> FromIterator<T> for Vector<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vector = Self::new();
+        vector.extend(iter);
+
+        vector
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for Vector<T> {
+    fn from(values: [T; N]) -> Self {
+        values.into_iter().collect()
+    }
+}
+
+impl<T: Clone> From<&[T]> for Vector<T> {
+    fn from(values: &[T]) -> Self {
+        let mut vector = Self::new();
+        vector.extend_from_slice(values);
+
+        vector
+    }
+}
+
+/// Takes over the allocation of the box, without moving the values.
+impl<T> From<Box<[T]>> for Vector<T> {
+    fn from(values: Box<[T]>) -> Self {
+        let len = values.len();
+        let ptr = Box::into_raw(values) as *mut T;
+
+        unsafe { Self::from_raw_parts(ptr, len, len) }
+    }
+}
+
+impl<T, A: Allocator> traits::Collection for Vector<T, A> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<T, A: Allocator> traits::List<T> for Vector<T, A> {
+    fn get(&self, index: usize) -> Option<&T> {
+        self[..].get(index)
+    }
+
+    fn insert(&mut self, index: usize, value: T) {
+        Vector::insert(self, index, value);
+    }
+
+    fn remove(&mut self, index: usize) -> T {
+        Vector::remove(self, index)
+    }
+}
+
+impl<T, A: Allocator> traits::Stack<T> for Vector<T, A> {
+    fn push(&mut self, value: T) {
+        Vector::push(self, value);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        Vector::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.last()
+    }
+}
+
+impl<T: HeapSize, A: Allocator> HeapSize for Vector<T, A> {
+    fn heap_size_of_children(&self) -> usize {
+        self.cap() * mem::size_of::<T>() + heap_size::children(self.iter())
+    }
+}
+
+impl<T, A: Allocator> Drop for Vector<T, A> {
     fn drop(&mut self) {
-        while let Some(_) = self.pop() {}
+        drop_guard::drop_each(&mut || self.pop());
     }
 }
 
-impl<T> Deref for Vector<T> {
+impl<T, A: Allocator> Deref for Vector<T, A> {
     type Target = [T];
     fn deref(&self) -> &Self::Target {
         unsafe { std::slice::from_raw_parts(self.ptr(), self.len) }
     }
 }
 
-impl<T> DerefMut for Vector<T> {
+impl<T, A: Allocator> DerefMut for Vector<T, A> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         unsafe { std::slice::from_raw_parts_mut(self.ptr(), self.len) }
     }
 }
 
-impl<T> IntoIterator for Vector<T> {
+impl<T: fmt::Debug, A: Allocator> fmt::Debug for Vector<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Eq, A: Allocator> Eq for Vector<T, A> {}
+
+/// Compares the values in order, like slices do.
+impl<T: PartialOrd, A: Allocator> PartialOrd for Vector<T, A> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<T: Ord, A: Allocator> Ord for Vector<T, A> {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+/// Hashes like the slice of its values, so it agrees with `Vec<T>`.
+impl<T: Hash, A: Allocator> Hash for Vector<T, A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+impl<T, A: Allocator> IntoIterator for Vector<T, A> {
     type Item = T;
-    type IntoIter = IntoIter<T>;
+    type IntoIter = IntoIter<T, A>;
 
     fn into_iter(self) -> Self::IntoIter {
         unsafe {
@@ -240,7 +1204,7 @@ impl<T> RawIter<T> {
             start: slice.as_ptr(),
             end: if mem::size_of::<T>() == 0 {
                 (slice.as_ptr() as usize + slice.len()) as *const _
-            } else if slice.len() == 0 {
+            } else if slice.is_empty() {
                 slice.as_ptr()
             } else {
                 slice.as_ptr().add(slice.len())
@@ -270,13 +1234,9 @@ impl<T> Iterator for RawIter<T> {
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let type_size = mem::size_of::<T>();
+        let diff = self.end as usize - self.start as usize;
 
-        let mut len = self.end as usize - self.start as usize;
-
-        if type_size != 0 {
-            len /= type_size;
-        }
+        let len = diff.checked_div(mem::size_of::<T>()).unwrap_or(diff);
 
         (len, Some(len))
     }
@@ -301,12 +1261,12 @@ impl<T> DoubleEndedIterator for RawIter<T> {
 }
 
 /// Struct used for iteration traits.
-pub struct IntoIter<T> {
-    _buf: Buffer<T>,
+pub struct IntoIter<T, A: Allocator = Global> {
+    _buf: Buffer<T, A>,
     iter: RawIter<T>,
 }
 
-impl<T> Iterator for IntoIter<T> {
+impl<T, A: Allocator> Iterator for IntoIter<T, A> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -317,25 +1277,27 @@ impl<T> Iterator for IntoIter<T> {
         self.iter.size_hint()
     }
 }
-impl<T> DoubleEndedIterator for IntoIter<T> {
+impl<T, A: Allocator> DoubleEndedIterator for IntoIter<T, A> {
     fn next_back(&mut self) -> Option<Self::Item> {
         self.iter.next_back()
     }
 }
 
-impl<T> Drop for IntoIter<T> {
+impl<T, A: Allocator> Drop for IntoIter<T, A> {
     fn drop(&mut self) {
-        for _ in &mut *self {}
+        drop_guard::drop_each(&mut || self.next());
     }
 }
 
-/// Struct used for implementing drain iterators.
-pub struct Drain<'a, T: 'a> {
-    vec: marker::PhantomData<&'a mut Vector<T>>,
+/// Iterator over the values removed by [`Vector::drain`].
+pub struct Drain<'a, T: 'a, A: Allocator = Global> {
+    vector: &'a mut Vector<T, A>,
     iter: RawIter<T>,
+    tail_start: usize,
+    tail_len: usize,
 }
 
-impl<'a, T> Iterator for Drain<'a, T> {
+impl<'a, T, A: Allocator> Iterator for Drain<'a, T, A> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -347,21 +1309,288 @@ impl<'a, T> Iterator for Drain<'a, T> {
     }
 }
 
-impl<'a, T> DoubleEndedIterator for Drain<'a, T> {
+impl<'a, T, A: Allocator> DoubleEndedIterator for Drain<'a, T, A> {
     fn next_back(&mut self) -> Option<T> {
         self.iter.next_back()
     }
 }
 
-impl<'a, T> Drop for Drain<'a, T> {
+impl<'a, T, A: Allocator> Drop for Drain<'a, T, A> {
     fn drop(&mut self) {
-        for _ in &mut *self {}
+        /// Moves the tail back next to the values before it, even if
+        /// dropping one of the values left panics.
+        struct Guard<'a, 'b, T, A: Allocator>(&'a mut Drain<'b, T, A>);
+
+        impl<T, A: Allocator> Drop for Guard<'_, '_, T, A> {
+            fn drop(&mut self) {
+                let drain = &mut *self.0;
+
+                unsafe {
+                    let ptr = drain.vector.ptr();
+                    ptr::copy(
+                        ptr.add(drain.tail_start),
+                        ptr.add(drain.vector.len),
+                        drain.tail_len,
+                    );
+                }
+
+                drain.vector.len += drain.tail_len;
+                drain.vector.stats.record_len(drain.vector.len);
+            }
+        }
+
+        let guard = Guard(self);
+        let iter = &mut guard.0.iter;
+        drop_guard::drop_each(&mut || iter.next());
+    }
+}
+
+/// Iterator over the values removed by [`Vector::extract_if`].
+pub struct ExtractIf<'a, T, F: FnMut(&mut T) -> bool, A: Allocator = Global> {
+    vector: &'a mut Vector<T, A>,
+    len: usize,
+    checked: usize,
+    extracted: usize,
+    extract: F,
+}
+
+impl<T, F: FnMut(&mut T) -> bool, A: Allocator> Iterator for ExtractIf<'_, T, F, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        while self.checked < self.len {
+            unsafe {
+                let current = self.vector.ptr().add(self.checked);
+                let extract = (self.extract)(&mut *current);
+
+                // Only counted once `extract` returns, so a panic keeps the
+                // value in place
+                self.checked += 1;
+
+                if extract {
+                    self.extracted += 1;
+                    return Some(ptr::read(current));
+                }
+
+                if self.extracted > 0 {
+                    ptr::copy_nonoverlapping(current, current.sub(self.extracted), 1);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.len - self.checked))
+    }
+}
+
+impl<T, F: FnMut(&mut T) -> bool, A: Allocator> Drop for ExtractIf<'_, T, F, A> {
+    /// Moves the values that weren't checked down to close the gap.
+    fn drop(&mut self) {
+        unsafe {
+            let ptr = self.vector.ptr();
+            ptr::copy(
+                ptr.add(self.checked),
+                ptr.add(self.checked - self.extracted),
+                self.len - self.checked,
+            );
+        }
+
+        self.vector.len = self.len - self.extracted;
+    }
+}
+
+/// Iterator over the values removed by [`Vector::splice`].
+pub struct Splice<'a, I: Iterator + 'a, A: Allocator = Global> {
+    drain: Drain<'a, I::Item, A>,
+    replace_with: I,
+}
+
+impl<I: Iterator, A: Allocator> Iterator for Splice<'_, I, A> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.drain.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.drain.size_hint()
+    }
+}
+
+impl<I: Iterator, A: Allocator> DoubleEndedIterator for Splice<'_, I, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.drain.next_back()
+    }
+}
+
+impl<I: Iterator, A: Allocator> Drop for Splice<'_, I, A> {
+    /// Fills the gap left by the removed values, and leaves moving the tail
+    /// back to [`Drain`], which also does it if a replacement panics.
+    fn drop(&mut self) {
+        let drain = &mut self.drain;
+        drop_guard::drop_each(&mut || drain.next());
+
+        // Fill the gap left by the removed values first
+        let vector = &mut *drain.vector;
+        while vector.len < drain.tail_start {
+            match self.replace_with.next() {
+                Some(value) => unsafe {
+                    ptr::write(vector.ptr().add(vector.len), value);
+                    vector.len += 1;
+                },
+                None => return,
+            }
+        }
+
+        // Then move the tail once to make room for the rest
+        let mut rest = Vector::new();
+        rest.extend(&mut self.replace_with);
+
+        if rest.is_empty() {
+            return;
+        }
+
+        vector.reserve(drain.tail_len + rest.len());
+
+        unsafe {
+            let ptr = vector.ptr();
+            let tail_start = drain.tail_start + rest.len();
+            ptr::copy(
+                ptr.add(drain.tail_start),
+                ptr.add(tail_start),
+                drain.tail_len,
+            );
+            ptr::copy_nonoverlapping(rest.ptr(), ptr.add(drain.tail_start), rest.len());
+
+            drain.tail_start = tail_start;
+            vector.len = tail_start;
+            rest.len = 0;
+        }
+    }
+}
+
+/// Immutable [`Vector`] shared between its clones, returned by
+/// [`Vector::freeze`]. It can be sent to and read from other threads whenever
+/// the values can.
+pub struct FrozenVector<T> {
+    inner: Arc<Vector<T>>,
+}
+
+impl<T> FrozenVector<T> {
+    /// Returns the vector back without copying it if no other clone is alive,
+    /// otherwise returns the snapshot unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns `self` if other clones share the values.
+    pub fn thaw(self) -> Result<Vector<T>, Self> {
+        Arc::try_unwrap(self.inner).map_err(|inner| Self { inner })
+    }
+
+    /// Returns `true` if both snapshots share the same values.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl<T> Clone for FrozenVector<T> {
+    /// Shares the values with the new snapshot, without copying any of them.
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T> Deref for FrozenVector<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.inner
+    }
+}
+
+impl<T: PartialEq> PartialEq for FrozenVector<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || **self == **other
+    }
+}
+
+impl<T: Eq> Eq for FrozenVector<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for FrozenVector<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: fmt::Debug> Vector<T> {
+    /// Returns a [proptest](https://docs.rs/proptest) strategy that
+    /// generates vectors of values from `element` with a length in `size`.
+    /// Failing cases shrink like `Vec`s do, dropping values first and then
+    /// shrinking the ones left.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::Vector;
+    /// use proptest::{prop_assert, test_runner::TestRunner};
+    ///
+    /// let mut runner = TestRunner::default();
+    ///
+    /// let result = runner.run(&Vector::strategy(0..10u8, 1..5), |vector| {
+    ///     prop_assert!((1..5).contains(&vector.len()));
+    ///     prop_assert!(vector.iter().all(|value| *value < 10));
+    ///     Ok(())
+    /// });
+    ///
+    /// assert!(result.is_ok());
+    /// ```
+    pub fn strategy<S: proptest::strategy::Strategy<Value = T>>(
+        element: S,
+        size: impl Into<proptest::collection::SizeRange>,
+    ) -> impl proptest::strategy::Strategy<Value = Self> {
+        use proptest::strategy::Strategy;
+
+        proptest::collection::vec(element, size).prop_map(Self::from_vec)
+    }
+
+    fn from_vec(values: Vec<T>) -> Self {
+        let mut vector = Self::new();
+
+        for value in values {
+            vector.push(value);
+        }
+
+        vector
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary> proptest::arbitrary::Arbitrary for Vector<T> {
+    type Parameters = <Vec<T> as proptest::arbitrary::Arbitrary>::Parameters;
+    type Strategy = proptest::strategy::Map<
+        <Vec<T> as proptest::arbitrary::Arbitrary>::Strategy,
+        fn(Vec<T>) -> Self,
+    >;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+
+        proptest::arbitrary::any_with::<Vec<T>>(args).prop_map(Self::from_vec)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{alloc::Layout, cell::Cell, mem, ptr};
+
     use super::Vector;
+    use crate::{Allocator, Global};
 
     #[test]
     fn basics() {
@@ -380,4 +1609,556 @@ mod tests {
 
         assert_eq!(l.len(), 2);
     }
+
+    #[test]
+    fn std_traits() {
+        use std::{
+            collections::{BTreeSet, HashSet},
+            hash::{BuildHasher, RandomState},
+        };
+
+        let a = vector![1, 2, 3];
+        let b = vector![1, 2, 4];
+
+        assert!(a < b);
+        assert_eq!(a.cmp(&a.clone()), std::cmp::Ordering::Equal);
+        assert!(vector![1, 2] < a);
+        assert_eq!(vector![0.5].partial_cmp(&vector![f64::NAN]), None);
+
+        // Usable as keys, and hashed like `Vec` so lookups agree
+        let sorted: BTreeSet<_> = [b.clone(), a.clone()].into_iter().collect();
+        assert!(sorted.into_iter().eq([a.clone(), b]));
+
+        let state = RandomState::new();
+        assert_eq!(state.hash_one(&a), state.hash_one(vec![1, 2, 3]));
+
+        let keys: HashSet<_> = [a.clone(), a.clone()].into_iter().collect();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(format!("{a:?}"), "[1, 2, 3]");
+        assert!(a == vec![1, 2, 3] && vec![1, 2, 3] == a);
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mut l = Vector::<i32>::new();
+
+        for i in 0..5 {
+            l.insert(0, i);
+        }
+
+        assert_eq!(*l, [4, 3, 2, 1, 0]);
+        assert_eq!(l.remove(1), 3);
+        assert_eq!(l.remove(3), 0);
+        assert_eq!(*l, [4, 2, 1]);
+        assert_eq!(l.len(), 3);
+
+        l.push(5);
+        assert_eq!(l.swap_remove(0), 4);
+        assert_eq!(*l, [5, 2, 1]);
+        assert_eq!(l.swap_remove(2), 1);
+        assert_eq!(l.swap_remove(1), 2);
+        assert_eq!(l.swap_remove(0), 5);
+        assert!(l.is_empty());
+    }
+
+    #[test]
+    fn drain() {
+        let mut l = Vector::new();
+        l.extend((0..10).map(|i| i.to_string()));
+
+        assert!(l.drain(2..5).eq(["2", "3", "4"]));
+        assert_eq!(&*l, ["0", "1", "5", "6", "7", "8", "9"]);
+
+        // The values left in the iterator are dropped with it
+        let mut drain = l.drain(1..=4);
+        assert_eq!(drain.next().as_deref(), Some("1"));
+        assert_eq!(drain.next_back().as_deref(), Some("7"));
+        drop(drain);
+        assert_eq!(&*l, ["0", "8", "9"]);
+
+        assert_eq!(l.drain(3..).count(), 0);
+        assert_eq!(l.drain(..1).count(), 1);
+        assert_eq!(&*l, ["8", "9"]);
+
+        // Leaking the iterator leaks the values from the range on
+        let mut numbers = vector![1, 2, 3];
+        std::mem::forget(numbers.drain(1..1));
+        assert_eq!(*numbers, [1]);
+
+        let mut units = vector![(); 5];
+        assert_eq!(units.drain(1..3).count(), 2);
+        assert_eq!(units.len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Range out of bounds")]
+    fn drain_out_of_bounds() {
+        vector![1, 2, 3].drain(2..4);
+    }
+
+    #[test]
+    #[should_panic(expected = "attempted to index slice up to maximum usize")]
+    fn drain_inclusive_end_overflow() {
+        vector![1, 2, 3].drain(2..=usize::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "attempted to index slice from after maximum usize")]
+    fn drain_exclusive_start_overflow() {
+        use std::ops::Bound;
+
+        vector![1, 2, 3].drain((Bound::Excluded(usize::MAX), Bound::Unbounded));
+    }
+
+    #[test]
+    fn allocator() {
+        /// Allocator that hands out a limited number of blocks, moving
+        /// them with the default `reallocate`.
+        struct Limited(Cell<usize>);
+
+        unsafe impl Allocator for Limited {
+            fn allocate(&self, layout: Layout) -> Option<ptr::NonNull<u8>> {
+                self.0.set(self.0.get().checked_sub(1)?);
+                Global.allocate(layout)
+            }
+
+            unsafe fn deallocate(&self, ptr: ptr::NonNull<u8>, layout: Layout) {
+                self.0.set(self.0.get() + 1);
+                Global.deallocate(ptr, layout);
+            }
+        }
+
+        let limited = Limited(Cell::new(3));
+        let mut l = Vector::with_capacity_in(2, &limited);
+        l.extend(["a", "b", "c"].map(String::from));
+        assert_eq!(l, ["a", "b", "c"]);
+        assert_eq!(l, vector!["a", "b", "c"]);
+        assert_eq!(limited.0.get(), 2);
+
+        // Every reallocation needs a new block before freeing the old one
+        l.shrink_to_fit();
+        assert_eq!(l.capacity(), 3);
+        limited.0.set(0);
+        assert!(l.try_reserve(1).is_err());
+        assert_eq!(l.try_push(String::from("d")), Err(String::from("d")));
+        assert_eq!(l.capacity(), 3);
+        limited.0.set(2);
+
+        let mut copy = l.clone();
+        copy.extend(l.drain(..2));
+        assert_eq!(copy, ["a", "b", "c", "a", "b"]);
+        assert!(l > copy);
+        assert_eq!(limited.0.get(), 1);
+
+        drop(l);
+        drop(copy);
+        assert_eq!(limited.0.get(), 3);
+    }
+
+    #[test]
+    fn raw_parts() {
+        let mut l = Vector::with_capacity(4);
+        l.extend(["a", "b"].map(String::from));
+
+        let (ptr, len, cap) = l.into_raw_parts();
+        assert_eq!((len, cap), (2, 4));
+
+        let mut l = unsafe { Vector::from_raw_parts(ptr, len, cap) };
+        l.push(String::from("c"));
+        assert_eq!(l, ["a", "b", "c"]);
+        assert_eq!((l.as_ptr(), l.capacity()), (ptr as *const _, 4));
+
+        // Buffers of std's `Vec` have the same layout
+        let mut std = std::mem::ManuallyDrop::new(vec![1u64, 2]);
+        let l = unsafe { Vector::from_raw_parts(std.as_mut_ptr(), std.len(), std.capacity()) };
+        assert_eq!(l, [1, 2]);
+
+        let (ptr, len, cap) = Vector::<()>::new().into_raw_parts();
+        assert_eq!((len, cap), (0, usize::MAX));
+        let units = unsafe { Vector::<()>::from_raw_parts(ptr, 5, 0) };
+        assert_eq!((units.len(), units.capacity()), (5, usize::MAX));
+    }
+
+    #[test]
+    fn extract_if() {
+        let mut l: Vector<_> = (0..10).map(|i| i.to_string()).collect();
+
+        let mut odds = l.extract_if(|value| value.parse::<u8>().unwrap() % 2 == 1);
+        assert_eq!(odds.next().as_deref(), Some("1"));
+        assert_eq!(odds.next().as_deref(), Some("3"));
+        drop(odds);
+        assert_eq!(l, ["0", "2", "4", "5", "6", "7", "8", "9"]);
+
+        // The values can be modified while checking them
+        let mut big = l.extract_if(|value| {
+            value.push('!');
+            value.len() > 2
+        });
+        assert!(big.next().is_none());
+        drop(big);
+        assert_eq!(l, ["0!", "2!", "4!", "5!", "6!", "7!", "8!", "9!"]);
+
+        assert_eq!(l.extract_if(|_| true).count(), 8);
+        assert!(l.is_empty());
+
+        let mut units = vector![(); 4];
+        let mut first = true;
+        assert_eq!(units.extract_if(|_| std::mem::take(&mut first)).count(), 1);
+        assert_eq!(units.len(), 3);
+    }
+
+    #[test]
+    fn splice() {
+        let mut l = vector![0, 1, 2, 3, 4, 5];
+
+        // Fewer values than removed
+        assert!(l.splice(1..4, [7]).eq([1, 2, 3]));
+        assert_eq!(*l, [0, 7, 4, 5]);
+
+        // More values than removed, without consuming the iterator
+        drop(l.splice(..=1, 10..14));
+        assert_eq!(*l, [10, 11, 12, 13, 4, 5]);
+
+        let mut splice = l.splice(4.., [20, 21]);
+        assert_eq!(splice.next_back(), Some(5));
+        drop(splice);
+        assert_eq!(*l, [10, 11, 12, 13, 20, 21]);
+
+        assert_eq!(l.splice(2..2, 0..3).count(), 0);
+        assert_eq!(*l, [10, 11, 0, 1, 2, 12, 13, 20, 21]);
+
+        // Leaking the iterator leaks the values from the range on
+        std::mem::forget(l.splice(3.., []));
+        assert_eq!(*l, [10, 11, 0]);
+
+        let mut units = vector![(); 4];
+        assert_eq!(units.splice(1..3, [(); 5]).count(), 2);
+        assert_eq!(units.len(), 7);
+    }
+
+    #[test]
+    fn extend() {
+        let mut l = Vector::new();
+
+        l.extend([1, 2]);
+        l.extend(&[3, 4]);
+        l.extend([5, 6].iter());
+
+        assert_eq!(*l, [1, 2, 3, 4, 5, 6]);
+
+        // Appending moves the values and keeps the other buffer
+        let mut other = vector![7, 8, 9];
+        let mut l = vector![6];
+        l.append(&mut other);
+        assert_eq!(l, [6, 7, 8, 9]);
+        assert_eq!((other.len(), other.cap()), (0, 3));
+        l.append(&mut other);
+        assert_eq!(l.len(), 4);
+
+        // Conversions allocate once
+        assert_eq!(Vector::from([1, 2, 3]), [1, 2, 3]);
+        assert_eq!(Vector::from(&["a", "b"][..]).cap(), 2);
+        assert_eq!((0..4).filter(|i| i % 2 == 0).collect::<Vector<_>>(), [0, 2]);
+        assert_eq!((0..4).collect::<Vector<_>>().cap(), 4);
+
+        let boxed: Box<[String]> = Box::new([String::from("a"), String::from("b")]);
+        let ptr = boxed.as_ptr();
+        let mut l = Vector::from(boxed);
+        assert_eq!((l.as_ptr(), l.cap()), (ptr, 2));
+        l.push(String::from("c"));
+        assert_eq!(l, ["a", "b", "c"]);
+
+        let units: Box<[()]> = Box::new([(); 3]);
+        assert_eq!(Vector::from(units).len(), 3);
+        assert!(Vector::from(Box::<[u8]>::default()).is_empty());
+
+        // Slices grow the buffer once
+        let mut l = vector![String::from("a")];
+        l.extend_from_slice(&[]);
+        assert_eq!(l.cap(), 1);
+        l.extend_from_slice(&[String::from("b"), String::from("c")]);
+        assert_eq!(&*l, ["a", "b", "c"]);
+        assert_eq!(l.cap(), 3);
+        assert_eq!(&*l.clone(), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn try_reserve() {
+        let mut l = Vector::<i32>::new();
+
+        for i in 0..100 {
+            assert_eq!(l.try_push(i), Ok(()));
+        }
+
+        assert!(l.iter().copied().eq(0..100));
+        assert!(l.try_reserve(isize::MAX as usize).is_err());
+        assert_eq!(l.len(), 100);
+
+        // Zero sized values only fail when the length would overflow
+        let mut units = Vector::<()>::new();
+        assert_eq!(units.try_push(()), Ok(()));
+        assert!(units.try_reserve(usize::MAX - 1).is_ok());
+        assert!(units.try_reserve(usize::MAX).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Capacity overflow")]
+    fn push_overflow() {
+        // Dropping that many values would take too long
+        let mut units = mem::ManuallyDrop::new(unsafe {
+            Vector::from_raw_parts(ptr::NonNull::dangling().as_ptr(), usize::MAX, 0)
+        });
+
+        assert_eq!(units.try_push(()), Err(()));
+        units.push(());
+    }
+
+    #[test]
+    fn reserve() {
+        let mut l = Vector::with_capacity(5);
+        assert_eq!(l.capacity(), 5);
+        assert!(Vector::<u8>::with_capacity(0).is_empty());
+
+        l.extend(0..5);
+        l.reserve_exact(3);
+        assert_eq!(l.capacity(), 8);
+
+        // Reserving what fits already leaves the buffer alone
+        let ptr = l.as_ptr();
+        l.reserve(3);
+        l.reserve_exact(2);
+        assert_eq!((l.as_ptr(), l.capacity()), (ptr, 8));
+
+        // Growing at least doubles the capacity
+        l.reserve(4);
+        assert_eq!(l.capacity(), 16);
+        l.extend(5..9);
+        assert!(l.iter().copied().eq(0..9));
+
+        assert!(l.try_reserve_exact(usize::MAX).is_err());
+        assert_eq!(Vector::<()>::with_capacity(10).capacity(), usize::MAX);
+    }
+
+    #[test]
+    fn shrink() {
+        let mut l = Vector::with_capacity(10);
+        l.extend((0..4).map(|i| i.to_string()));
+
+        l.shrink_to(12);
+        assert_eq!(l.capacity(), 10);
+        l.shrink_to(6);
+        assert_eq!(l.capacity(), 6);
+        l.shrink_to_fit();
+        assert_eq!(l.capacity(), 4);
+        assert_eq!(&*l, ["0", "1", "2", "3"]);
+
+        // The buffer is freed once empty and can grow again afterwards
+        l.drain(..);
+        l.shrink_to_fit();
+        assert_eq!(l.capacity(), 0);
+        l.push(String::from("a"));
+        assert_eq!(&*l, ["a"]);
+
+        let mut empty = Vector::<u8>::new();
+        empty.shrink_to_fit();
+        assert_eq!(empty.capacity(), 0);
+
+        let mut units = vector![(); 3];
+        units.shrink_to_fit();
+        assert_eq!((units.len(), units.capacity()), (3, usize::MAX));
+    }
+
+    #[test]
+    fn macros() {
+        let l = vector![String::from("a"), String::from("b")];
+        assert_eq!(&*l, ["a", "b"]);
+        assert_eq!(l.cap(), 2);
+
+        let l = vector![7u16; 5];
+        assert_eq!(&*l, [7; 5]);
+        assert_eq!(l.cap(), 5);
+
+        let l: Vector<()> = vector![(); 3];
+        assert_eq!(l.len(), 3);
+        assert!(vector![0; 0].is_empty());
+        assert!(Vector::<u8>::new().is_empty());
+
+        // Extending with a known length grows the buffer once
+        let mut l = vector![1];
+        l.extend(2..=9);
+        assert_eq!(l.cap(), 9);
+    }
+
+    #[test]
+    fn const_new() {
+        const EMPTY: Vector<String> = Vector::new();
+        static SHARED: std::sync::Mutex<Vector<u32>> = std::sync::Mutex::new(Vector::new());
+
+        let mut l = EMPTY;
+        l.push(String::from("a"));
+        assert_eq!(&*l, ["a"]);
+
+        std::thread::scope(|s| {
+            for i in 0..4 {
+                s.spawn(move || SHARED.lock().unwrap().push(i));
+            }
+        });
+        assert_eq!(SHARED.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn freeze() {
+        let frozen = vector![String::from("a"), String::from("b")].freeze();
+        let ptr = frozen.as_ptr();
+
+        let readers: Vec<_> = (0..3)
+            .map(|_| {
+                let frozen = frozen.clone();
+                std::thread::spawn(move || frozen.concat())
+            })
+            .collect();
+
+        // Readers still hold clones until they are joined
+        let frozen = frozen.thaw().unwrap_err();
+
+        for reader in readers {
+            assert_eq!(reader.join().unwrap(), "ab");
+        }
+
+        let mut l = frozen.thaw().unwrap();
+        assert_eq!(l.as_ptr(), ptr);
+        l.push(String::from("c"));
+        assert_eq!(&*l, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn try_retain() {
+        let mut l = Vector::new();
+        l.extend(0..10);
+
+        l.retain(|value| value % 3 != 0);
+        assert_eq!(&*l, [1, 2, 4, 5, 7, 8]);
+
+        let mut calls = 0;
+        let result = l.try_retain(|&value| {
+            calls += 1;
+            if value == 5 {
+                return Err(value);
+            }
+            Ok(value > 2)
+        });
+
+        // 5 and everything after it are kept without calling the closure
+        assert_eq!(result, Err(5));
+        assert_eq!(calls, 4);
+        assert_eq!(&*l, [4, 5, 7, 8]);
+
+        assert_eq!(l.try_extend([Ok(9), Err(0), Ok(10)]), Err(0));
+        assert_eq!(&*l, [4, 5, 7, 8, 9]);
+    }
+
+    #[test]
+    fn panic_safety() {
+        use std::{
+            cell::Cell,
+            panic::{self, AssertUnwindSafe},
+        };
+
+        /// Counts its drops and panics when dropped if it's 3.
+        struct Fragile<'a>(i32, &'a Cell<usize>);
+
+        impl Drop for Fragile<'_> {
+            fn drop(&mut self) {
+                self.1.set(self.1.get() + 1);
+                assert_ne!(self.0, 3, "dropping 3");
+            }
+        }
+
+        let drops = Cell::new(0);
+        let mut l = Vector::new();
+
+        for i in 0..10 {
+            l.push(Fragile(i, &drops));
+        }
+
+        // Every value is dropped even if one of them panics
+        let drained = panic::catch_unwind(AssertUnwindSafe(|| drop(l.drain(..))));
+        assert!(drained.is_err());
+        assert_eq!(drops.get(), 10);
+        assert!(l.is_empty());
+
+        for i in 0..10 {
+            l.push(Fragile(i, &drops));
+        }
+
+        // The values after the one that panicked are still kept
+        let retained = panic::catch_unwind(AssertUnwindSafe(|| l.retain(|value| value.0 < 3)));
+        assert!(retained.is_err());
+        assert_eq!(drops.get(), 11);
+        assert!(l
+            .iter()
+            .map(|value| value.0)
+            .eq([0, 1, 2, 4, 5, 6, 7, 8, 9]));
+
+        // The tail is moved back if pulling a replacement panics
+        let spliced = panic::catch_unwind(AssertUnwindSafe(|| {
+            let replace_with = (10..15).map(|i| {
+                assert_ne!(i, 12, "pulling 12");
+                Fragile(i, &drops)
+            });
+            drop(l.splice(..2, replace_with));
+        }));
+        assert!(spliced.is_err());
+        assert_eq!(drops.get(), 13);
+        assert!(l
+            .iter()
+            .map(|value| value.0)
+            .eq([10, 11, 2, 4, 5, 6, 7, 8, 9]));
+
+        // A panic in the predicate keeps the value it was checking
+        let extracted = panic::catch_unwind(AssertUnwindSafe(|| {
+            l.extract_if(|value| {
+                assert_ne!(value.0, 5, "checking 5");
+                value.0 % 2 == 1
+            })
+            .for_each(drop)
+        }));
+        assert!(extracted.is_err());
+        assert_eq!(drops.get(), 14);
+        assert!(l.iter().map(|value| value.0).eq([10, 2, 4, 5, 6, 7, 8, 9]));
+
+        // The tail is moved back even if dropping a drained value panics
+        l.insert(3, Fragile(3, &drops));
+        let drained = panic::catch_unwind(AssertUnwindSafe(|| drop(l.drain(2..5))));
+        assert!(drained.is_err());
+        assert_eq!(drops.get(), 17);
+        assert!(l.iter().map(|value| value.0).eq([10, 2, 6, 7, 8, 9]));
+
+        l.push(Fragile(3, &drops));
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| drop(l))).is_err());
+        assert_eq!(drops.get(), 24);
+    }
+
+    #[cfg(feature = "proptest")]
+    #[test]
+    fn proptest_shrinking() {
+        use proptest::{
+            prop_assert,
+            test_runner::{TestError, TestRunner},
+        };
+
+        let mut runner = TestRunner::deterministic();
+        let result = runner.run(&Vector::strategy(0..100u32, 0..50), |vector| {
+            prop_assert!(vector.iter().sum::<u32>() < 100);
+            Ok(())
+        });
+
+        // The smallest failing vector is a single value of 99 or two values
+        // adding up to at least 100
+        let Err(TestError::Fail(_, vector)) = result else {
+            panic!("expected a failing vector, got {result:?}");
+        };
+        assert!(vector.len() <= 2);
+        assert!(vector.iter().sum::<u32>() >= 100);
+    }
 }
@@ -0,0 +1,231 @@
+use std::{
+    hash::Hash,
+    mem,
+    rc::{Rc, Weak},
+};
+
+use crate::{heap_size::HeapSize, index_map::IndexMap};
+
+/// Number of entries below which [`WeakValueMap::insert`] never prunes.
+const MIN_PRUNE_LEN: usize = 16;
+
+/// Map that holds its values through [`Weak`] references, for caches of
+/// large objects that should go away as soon as nothing else uses them,
+/// like decoded images or parsed files shared between views. The map never
+/// keeps a value alive on its own.
+///
+/// Entries whose value was dropped are removed lazily: lookups that find
+/// one remove it, and insertions prune the whole map whenever it doubles in
+/// size since the last pruning, so dead entries never take more than about
+/// half the map. [`WeakValueMap::prune`] removes them all at once.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::rc::Rc;
+///
+/// use collections_rust::WeakValueMap;
+///
+/// let mut textures = WeakValueMap::new();
+///
+/// let grass = Rc::new(vec![0u8; 1024]);
+/// textures.insert("grass", &grass);
+///
+/// assert_eq!(textures.get(&"grass").map(|texture| texture.len()), Some(1024));
+///
+/// drop(grass);
+/// assert_eq!(textures.get(&"grass"), None);
+/// assert!(textures.is_empty());
+/// ```
+pub struct WeakValueMap<K, V> {
+    map: IndexMap<K, Weak<V>>,
+    /// Number of entries at which the next insertion prunes the map.
+    prune_at: usize,
+}
+
+impl<K, V> WeakValueMap<K, V> {
+    /// Creates an empty map.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use collections_rust::WeakValueMap;
+    ///
+    /// let map: WeakValueMap<&str, String> = WeakValueMap::new();
+    ///
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            map: IndexMap::new(),
+            prune_at: MIN_PRUNE_LEN,
+        }
+    }
+
+    /// Returns the number of entries, including the ones whose value was
+    /// dropped but weren't removed yet.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Removes all the entries from the map.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.prune_at = MIN_PRUNE_LEN;
+    }
+
+    /// Returns an iterator over the keys and values still alive, in
+    /// insertion order except for removals.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, Rc<V>)> {
+        self.map
+            .iter()
+            .filter_map(|(key, value)| Some((key, value.upgrade()?)))
+    }
+}
+
+impl<K: Hash + Eq, V> WeakValueMap<K, V> {
+    /// Returns the value of `key` if it's still alive. Removes the entry if
+    /// the value was dropped.
+    pub fn get(&mut self, key: &K) -> Option<Rc<V>> {
+        let value = self.map.get(key)?.upgrade();
+
+        if value.is_none() {
+            self.map.swap_remove(key);
+        }
+
+        value
+    }
+
+    /// Returns `true` if the map contains `key` and its value is alive.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map
+            .get(key)
+            .is_some_and(|value| value.strong_count() > 0)
+    }
+
+    /// Inserts a weak reference to `value` under `key` and returns the
+    /// previous value if it was still alive.
+    pub fn insert(&mut self, key: K, value: &Rc<V>) -> Option<Rc<V>> {
+        let previous = self.map.insert(key, Rc::downgrade(value));
+
+        if self.map.len() >= self.prune_at {
+            self.prune();
+        }
+
+        previous?.upgrade()
+    }
+
+    /// Returns the value of `key` if it's still alive, or creates it with
+    /// `f`, inserts it and returns it otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::rc::Rc;
+    ///
+    /// use collections_rust::WeakValueMap;
+    ///
+    /// let mut map = WeakValueMap::new();
+    ///
+    /// let first = map.get_or_insert_with(1, || String::from("one"));
+    /// let second = map.get_or_insert_with(1, || String::from("uno"));
+    ///
+    /// assert!(Rc::ptr_eq(&first, &second));
+    ///
+    /// drop((first, second));
+    /// assert_eq!(*map.get_or_insert_with(1, || String::from("uno")), "uno");
+    /// ```
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, key: K, f: F) -> Rc<V> {
+        if let Some(value) = self.map.get(&key).and_then(Weak::upgrade) {
+            return value;
+        }
+
+        let value = Rc::new(f());
+        self.insert(key, &value);
+
+        value
+    }
+
+    /// Removes `key` and returns its value if it was still alive.
+    pub fn remove(&mut self, key: &K) -> Option<Rc<V>> {
+        self.map.swap_remove(key)?.upgrade()
+    }
+
+    /// Removes every entry whose value was dropped.
+    pub fn prune(&mut self) {
+        self.map = mem::take(&mut self.map)
+            .into_iter()
+            .filter(|(_, value)| value.strong_count() > 0)
+            .collect();
+
+        self.prune_at = MIN_PRUNE_LEN.max(2 * self.map.len());
+    }
+}
+
+impl<K, V> Default for WeakValueMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Values are owned by their strong references, so only the keys and the
+/// table are counted.
+impl<K: HeapSize, V> HeapSize for WeakValueMap<K, V> {
+    fn heap_size_of_children(&self) -> usize {
+        self.map.heap_size_of_children()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::WeakValueMap;
+
+    #[test]
+    fn test_lazy_removal() {
+        let mut map = WeakValueMap::new();
+        let values: Vec<Rc<usize>> = (0..10).map(Rc::new).collect();
+
+        for (key, value) in values.iter().enumerate() {
+            assert_eq!(map.insert(key, value), None);
+        }
+
+        let replacement = Rc::new(100);
+        assert_eq!(map.insert(3, &replacement), Some(values[3].clone()));
+        drop(values);
+
+        // Dead entries stay until something looks at them
+        assert_eq!(map.len(), 10);
+        assert!(!map.contains_key(&0));
+        assert_eq!(map.get(&0), None);
+        assert_eq!(map.len(), 9);
+
+        assert!(map.iter().eq([(&3, replacement.clone())]));
+
+        map.prune();
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.remove(&3), Some(replacement));
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_insert_prunes() {
+        let mut map = WeakValueMap::new();
+        let alive = Rc::new(0);
+        map.insert(0, &alive);
+
+        // Values dropped right away never pile up
+        for key in 1..1_000 {
+            map.insert(key, &Rc::new(key));
+            assert!(map.len() <= 16);
+        }
+
+        assert_eq!(map.get(&0), Some(alive));
+    }
+}